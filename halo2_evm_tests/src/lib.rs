@@ -0,0 +1,94 @@
+//! Helpers for testing the Solidity verifiers generated by
+//! [`halo2_proofs::plonk::evm`] on the EVM.
+//!
+//! The contracts are compiled with the `solc` binary on the `PATH`, and executed
+//! with [`revm`].
+
+#![deny(missing_debug_implementations)]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use revm::{
+    primitives::{CreateScheme, ExecutionResult, Output, TransactTo, TxEnv},
+    InMemoryDB, EVM,
+};
+
+/// Compiles `source` with the `solc` binary on the `PATH`, returning the
+/// creation bytecode of the contract.
+pub fn compile_solidity(source: &str) -> Vec<u8> {
+    let mut solc = Command::new("solc")
+        .args(["--bin", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("solc must be installed to test the generated verifiers");
+    solc.stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .unwrap();
+    let output = solc.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "solc failed to compile the verifier"
+    );
+
+    let output = String::from_utf8(output.stdout).unwrap();
+    let bytecode = output.split_whitespace().last().unwrap();
+    (0..bytecode.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&bytecode[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// Deploys `bytecode` to a fresh EVM and returns whether `verifyProof` returns
+/// `true` for each of `calls`. A call that reverts is reported as `false`.
+pub fn evm_verify(bytecode: Vec<u8>, calls: Vec<Vec<u8>>) -> Vec<bool> {
+    let mut evm = EVM {
+        env: Default::default(),
+        db: Some(InMemoryDB::default()),
+    };
+    evm.env.cfg.limit_contract_code_size = Some(usize::MAX);
+
+    evm.env.tx = TxEnv {
+        gas_limit: u64::MAX,
+        transact_to: TransactTo::Create(CreateScheme::Create),
+        data: bytecode.into(),
+        ..Default::default()
+    };
+    let contract = match evm.transact_commit().unwrap() {
+        ExecutionResult::Success {
+            output: Output::Create(_, Some(contract)),
+            ..
+        } => contract,
+        result => panic!("deploying the verifier failed: {:?}", result),
+    };
+
+    calls
+        .into_iter()
+        .map(|calldata| {
+            evm.env.tx = TxEnv {
+                gas_limit: u64::MAX,
+                transact_to: TransactTo::Call(contract),
+                data: calldata.into(),
+                ..Default::default()
+            };
+            match evm.transact_commit().unwrap() {
+                ExecutionResult::Success {
+                    output: Output::Call(output),
+                    ..
+                } => {
+                    assert_eq!(output.len(), 32);
+                    assert!(output[..31].iter().all(|byte| *byte == 0));
+                    assert!(output[31] <= 1);
+                    output[31] == 1
+                }
+                ExecutionResult::Revert { .. } => false,
+                result => panic!("calling the verifier failed: {:?}", result),
+            }
+        })
+        .collect()
+}
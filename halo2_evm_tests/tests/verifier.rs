@@ -0,0 +1,173 @@
+//! Checks that the generated Solidity verifiers accept valid proofs and reject
+//! invalid ones when they are compiled and executed on the EVM.
+
+use halo2_evm_tests::{compile_solidity, evm_verify};
+use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::plonk::evm::{MultiopenScheme, SolidityGenerator};
+use halo2_proofs::plonk::{
+    create_proof, keygen_pk, keygen_vk, Advice, Circuit, Column, ConstraintSystem, Error, Instance,
+    Selector, TableColumn,
+};
+use halo2_proofs::poly::commitment::ParamsProver;
+use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+use halo2_proofs::poly::kzg::multiopen::{ProverGWC, ProverSHPLONK};
+use halo2_proofs::poly::Rotation;
+use halo2_proofs::transcript::{Challenge255, Keccak256Write, TranscriptWriterBuffer};
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use rand_core::OsRng;
+
+#[derive(Clone, Debug)]
+struct TestConfig {
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+    s: Selector,
+    table: TableColumn,
+    instance: Column<Instance>,
+}
+
+/// Proves that `a * b = c` for small `a` and `b`, exposing `c`.
+#[derive(Clone, Default)]
+struct TestCircuit {
+    a: Value<Fr>,
+    b: Value<Fr>,
+}
+
+impl Circuit<Fr> for TestCircuit {
+    type Config = TestConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> TestConfig {
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let c = meta.advice_column();
+        let s = meta.selector();
+        let table = meta.lookup_table_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(c);
+        meta.enable_equality(instance);
+
+        meta.create_gate("mul", |meta| {
+            let s = meta.query_selector(s);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let c = meta.query_advice(c, Rotation::cur());
+            vec![s * (a * b - c)]
+        });
+        meta.lookup("a is small", |meta| {
+            vec![(meta.query_advice(a, Rotation::cur()), table)]
+        });
+        meta.lookup_logup("b is small", |meta| {
+            (
+                vec![vec![meta.query_advice(b, Rotation::cur())]],
+                vec![table],
+            )
+        });
+
+        TestConfig {
+            a,
+            b,
+            c,
+            s,
+            table,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: TestConfig, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "table",
+            |mut table| {
+                for i in 0..16 {
+                    table.assign_cell(
+                        || "value",
+                        config.table,
+                        i,
+                        || Value::known(Fr::from(i as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )?;
+
+        let c = layouter.assign_region(
+            || "mul",
+            |mut region| {
+                config.s.enable(&mut region, 0)?;
+                region.assign_advice(|| "a", config.a, 0, || self.a)?;
+                region.assign_advice(|| "b", config.b, 0, || self.b)?;
+                region.assign_advice(|| "c", config.c, 0, || self.a * self.b)
+            },
+        )?;
+
+        layouter.constrain_instance(c.cell(), config.instance, 0)
+    }
+}
+
+fn check(scheme: MultiopenScheme) {
+    const K: u32 = 5;
+    let params = ParamsKZG::<Bn256>::new(K);
+    let circuit = TestCircuit {
+        a: Value::known(Fr::from(3)),
+        b: Value::known(Fr::from(5)),
+    };
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    let pk = keygen_pk(&params, vk, &circuit).unwrap();
+    let instances = [Fr::from(15)];
+
+    let mut transcript = Keccak256Write::<_, G1Affine, Challenge255<_>>::init(vec![]);
+    match scheme {
+        MultiopenScheme::Gwc => {
+            create_proof::<KZGCommitmentScheme<Bn256>, ProverGWC<_>, _, _, _, _>(
+                &params,
+                &pk,
+                &[circuit],
+                &[&[&instances]],
+                OsRng,
+                &mut transcript,
+            )
+        }
+        MultiopenScheme::Shplonk => {
+            create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+                &params,
+                &pk,
+                &[circuit],
+                &[&[&instances]],
+                OsRng,
+                &mut transcript,
+            )
+        }
+    }
+    .unwrap();
+    let proof = transcript.finalize();
+
+    let generator = SolidityGenerator::new(&params, pk.get_vk(), scheme, &[1]).unwrap();
+    let calldata = generator.encode_calldata(&[&instances], &proof).unwrap();
+    let wrong_instances = generator
+        .encode_calldata(&[&[Fr::from(16)]], &proof)
+        .unwrap();
+    // Flip the last bit of the proof, which is part of the last point it contains.
+    let mut tampered = calldata.clone();
+    let last_proof_byte = tampered.len() - 32 * instances.len() - 1;
+    tampered[last_proof_byte] ^= 1;
+
+    let bytecode = compile_solidity(&generator.render());
+    assert_eq!(
+        evm_verify(bytecode, vec![calldata, wrong_instances, tampered]),
+        vec![true, false, false]
+    );
+}
+
+#[test]
+fn gwc_verifier() {
+    check(MultiopenScheme::Gwc);
+}
+
+#[test]
+fn shplonk_verifier() {
+    check(MultiopenScheme::Shplonk);
+}
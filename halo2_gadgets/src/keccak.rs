@@ -0,0 +1,131 @@
+//! The [Keccak-256] hash function.
+//!
+//! This is the hash function used by Ethereum, which differs from the SHA3-256 of
+//! [FIPS 202] only in its padding rule.
+//!
+//! [Keccak-256]: https://keccak.team/keccak_specs_summary.html
+//! [FIPS 202]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter},
+    plonk::Error,
+};
+
+mod chip;
+
+pub use chip::{KeccakChip, KeccakConfig, KeccakState};
+
+/// The size of a Keccak-256 block, in bytes.
+pub const RATE_BYTES: usize = 136;
+/// The size of a Keccak-256 digest, in bytes.
+pub const DIGEST_BYTES: usize = 32;
+
+/// The set of circuit instructions required to use the [`Keccak256`] gadget.
+pub trait KeccakInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a byte, a message length or a flag.
+    type Var: Clone + fmt::Debug;
+    /// Variable representing the Keccak-f[1600] state.
+    type State: Clone + fmt::Debug;
+
+    /// Places the all-zero initial state in the circuit.
+    fn initial_state(&self, layouter: &mut impl Layouter<F>) -> Result<Self::State, Error>;
+
+    /// Pads the first `len` bytes of `message`, returning as many blocks as a message of
+    /// `message.len()` bytes needs. Each block comes with a flag that is set only on the
+    /// final block of the first `len` bytes.
+    ///
+    /// The remaining bytes of `message` are ignored. The circuit is not satisfied if `len`
+    /// is greater than `message.len()`.
+    #[allow(clippy::type_complexity)]
+    fn pad(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        message: &[Self::Var],
+        len: &Self::Var,
+    ) -> Result<Vec<([Self::Var; RATE_BYTES], Self::Var)>, Error>;
+
+    /// XORs a padded block into the given state.
+    fn absorb(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        state: &Self::State,
+        block: &[Self::Var; RATE_BYTES],
+    ) -> Result<Self::State, Error>;
+
+    /// Applies the Keccak-f[1600] permutation to the given state.
+    fn permute(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        state: &Self::State,
+    ) -> Result<Self::State, Error>;
+
+    /// Returns the digest of the one state, out of the given states, whose flag is set.
+    fn squeeze(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        states: &[(Self::Var, Self::State)],
+    ) -> Result<[Self::Var; DIGEST_BYTES], Error>;
+}
+
+/// The output of a Keccak-256 circuit invocation.
+#[derive(Debug)]
+pub struct Keccak256Digest<Var>([Var; DIGEST_BYTES]);
+
+impl<Var> Keccak256Digest<Var> {
+    /// Returns the bytes of the digest.
+    pub fn bytes(&self) -> &[Var; DIGEST_BYTES] {
+        &self.0
+    }
+}
+
+/// A gadget that constrains a Keccak-256 invocation on a message of witnessed length.
+#[derive(Debug)]
+pub struct Keccak256<F: FieldExt, KeccakChip: KeccakInstructions<F>> {
+    chip: KeccakChip,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, KeccakChip: KeccakInstructions<F>> Keccak256<F, KeccakChip> {
+    /// Create a new hasher instance.
+    pub fn new(chip: KeccakChip) -> Self {
+        Keccak256 {
+            chip,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Computes the digest of the first `len` bytes of `message`.
+    ///
+    /// The circuit has the same shape for every `len` up to `message.len()`: all of the
+    /// blocks that a message of `message.len()` bytes needs are permuted, and the state
+    /// after the final block of the first `len` bytes is selected in-circuit.
+    pub fn digest(
+        &self,
+        mut layouter: impl Layouter<F>,
+        message: &[KeccakChip::Var],
+        len: &KeccakChip::Var,
+    ) -> Result<Keccak256Digest<KeccakChip::Var>, Error> {
+        let blocks = self
+            .chip
+            .pad(&mut layouter.namespace(|| "pad"), message, len)?;
+
+        let mut state = self
+            .chip
+            .initial_state(&mut layouter.namespace(|| "init"))?;
+        let mut states = Vec::with_capacity(blocks.len());
+        for (i, (block, is_final)) in blocks.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("block {}", i));
+            state = self.chip.absorb(&mut layouter, &state, block)?;
+            state = self.chip.permute(&mut layouter, &state)?;
+            states.push((is_final.clone(), state.clone()));
+        }
+
+        self.chip
+            .squeeze(&mut layouter.namespace(|| "squeeze"), &states)
+            .map(Keccak256Digest)
+    }
+}
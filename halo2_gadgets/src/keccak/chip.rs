@@ -0,0 +1,1141 @@
+use std::convert::TryInto;
+use std::marker::PhantomData;
+
+use ff::PrimeField;
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::{AssignedCell, Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use super::{KeccakInstructions, DIGEST_BYTES, RATE_BYTES};
+use crate::utilities::bool_check;
+
+mod table;
+
+use table::{Normalize, TableConfig, TAG_BYTE};
+
+const ROUNDS: usize = 24;
+
+/// The largest number of digits in a looked-up chunk of a lane.
+const CHUNK_LEN: usize = 4;
+/// The number of chunks in a lane. A lane that is rotated by a multiple of `CHUNK_LEN`
+/// needs one fewer.
+const NUM_CHUNKS: usize = 64 / CHUNK_LEN + 1;
+
+/// The number of linear combinations of lanes that the chi step needs.
+const NUM_OPERANDS: usize = 5;
+
+const ROUND_CONSTANTS: [u64; ROUNDS] = [
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_8082,
+    0x8000_0000_0000_808a,
+    0x8000_0000_8000_8000,
+    0x0000_0000_0000_808b,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8009,
+    0x0000_0000_0000_008a,
+    0x0000_0000_0000_0088,
+    0x0000_0000_8000_8009,
+    0x0000_0000_8000_000a,
+    0x0000_0000_8000_808b,
+    0x8000_0000_0000_008b,
+    0x8000_0000_0000_8089,
+    0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002,
+    0x8000_0000_0000_0080,
+    0x0000_0000_0000_800a,
+    0x8000_0000_8000_000a,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8080,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8008,
+];
+
+/// The rotation offsets of the rho step, indexed by `[x][y]`.
+const RHO_OFFSETS: [[u32; 5]; 5] = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+];
+
+fn lane_index(x: usize, y: usize) -> usize {
+    x + 5 * y
+}
+
+/// Returns $8^{exp}$.
+fn pow8<F: FieldExt>(exp: usize) -> F {
+    F::from(8u64).pow_vartime(&[exp as u64])
+}
+
+/// Returns $\sum_i digit(i) \cdot 8^i$ over the 64 digits of a lane.
+fn spread_digits<F: FieldExt>(digit: impl Fn(usize) -> u64) -> F {
+    (0..64)
+        .rev()
+        .fold(F::zero(), |acc, i| acc * F::from(8u64) + F::from(digit(i)))
+}
+
+/// Returns the spread form of a lane, which places bit $i$ at $8^i$.
+fn spread<F: FieldExt>(bits: u64) -> F {
+    spread_digits(|i| (bits >> i) & 1)
+}
+
+fn signed<F: FieldExt>(value: i64) -> F {
+    if value < 0 {
+        -F::from(value.unsigned_abs())
+    } else {
+        F::from(value as u64)
+    }
+}
+
+/// Splits the 64 digits of a lane into `(position, length)` chunks of at most
+/// `CHUNK_LEN` digits, such that no chunk straddles digit `64 - rotation`. The chunks
+/// can then be recombined into the lane rotated left by `rotation`.
+fn chunks(rotation: u32) -> Vec<(usize, usize)> {
+    let boundary = 64 - rotation as usize;
+    let mut chunks = Vec::with_capacity(NUM_CHUNKS);
+    for (start, end) in [(0, boundary), (boundary, 64)] {
+        let mut position = start;
+        while position < end {
+            let len = std::cmp::min(CHUNK_LEN, end - position);
+            chunks.push((position, len));
+            position += len;
+        }
+    }
+    chunks
+}
+
+/// A lane of the Keccak-f[1600] state, assigned in spread form.
+#[derive(Clone, Debug)]
+struct Lane<F: FieldExt> {
+    cell: AssignedCell<F, F>,
+    bits: Value<u64>,
+}
+
+/// The Keccak-f[1600] state, as 25 lanes indexed by `x + 5y`.
+#[derive(Clone, Debug)]
+pub struct KeccakState<F: FieldExt>([Lane<F>; 25]);
+
+/// The columns of a looked-up chunk of a lane.
+#[derive(Clone, Copy, Debug)]
+struct ChunkColumns {
+    tag: Column<Fixed>,
+    /// The weight of the chunk in the lane.
+    coeff: Column<Fixed>,
+    /// The weight of the chunk in the rotated lane.
+    coeff_rotated: Column<Fixed>,
+    input: Column<Advice>,
+    output: Column<Advice>,
+}
+
+impl ChunkColumns {
+    fn assign_fixed<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        tag: u64,
+        coeff: F,
+        coeff_rotated: F,
+    ) -> Result<(), Error> {
+        region.assign_fixed(|| "tag", self.tag, offset, || Value::known(F::from(tag)))?;
+        region.assign_fixed(|| "coeff", self.coeff, offset, || Value::known(coeff))?;
+        region.assign_fixed(
+            || "rotated coeff",
+            self.coeff_rotated,
+            offset,
+            || Value::known(coeff_rotated),
+        )?;
+        Ok(())
+    }
+
+    fn assign_unused<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.assign_fixed(region, offset, 0, F::zero(), F::zero())?;
+        region.assign_advice(|| "unused", self.input, offset, || Value::known(F::zero()))?;
+        region.assign_advice(|| "unused", self.output, offset, || Value::known(F::zero()))?;
+        Ok(())
+    }
+}
+
+/// Configuration for a [`KeccakChip`].
+#[derive(Clone, Debug)]
+pub struct KeccakConfig {
+    q_decompose: Selector,
+    q_compose: Selector,
+    q_select: Selector,
+    q_pad: Selector,
+    q_pad_byte: Selector,
+    q_pad_end: Selector,
+    operands: [Column<Advice>; NUM_OPERANDS],
+    operand_coeffs: [Column<Fixed>; NUM_OPERANDS],
+    constant: Column<Fixed>,
+    output: Column<Advice>,
+    output_rotated: Column<Advice>,
+    chunks: [ChunkColumns; NUM_CHUNKS],
+    table: TableConfig,
+}
+
+/// A chip implementing Keccak-256 with lookups on lanes in spread form.
+///
+/// A lane of 64 bits is represented by its spread form $\sum_i b_i \cdot 8^i$, so that
+/// up to seven lanes can be added without carries between digits. Each step of a round
+/// is then a single row that computes a linear combination of spread lanes, splits it
+/// into chunks of at most `CHUNK_LEN` digits, and looks up the chunks to map each digit
+/// through a function:
+///
+/// - theta sums the five lanes of each column, and then adds two of these sums to each
+///   lane, reducing the digits modulo 2;
+/// - rho and pi are free: the chunks of the theta output are recombined with rotated
+///   weights, which is why a lane is split at its rotation offset;
+/// - chi maps the digits of $3 - 2a + b - c$ to $a \oplus (\neg b \wedge c)$;
+/// - iota adds the round constant to a lane, reducing the digits modulo 2.
+///
+/// A round takes 56 rows, and the lookup table has 2591 rows.
+///
+/// Messages are padded in-circuit. Bytes are packed into lanes with a lookup that maps
+/// each byte to its spread form, and the digest is unpacked the same way.
+#[derive(Clone, Debug)]
+pub struct KeccakChip<F: FieldExt> {
+    config: KeccakConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for KeccakChip<F> {
+    type Config = KeccakConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> KeccakChip<F> {
+    /// Configures a circuit to include this chip.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `F` has 192 bits or fewer, as a lane in spread form would not fit.
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> KeccakConfig {
+        assert!(
+            F::NUM_BITS > 3 * 64,
+            "lanes in spread form do not fit in the field"
+        );
+
+        let operands = [(); NUM_OPERANDS].map(|_| meta.advice_column());
+        let operand_coeffs = [(); NUM_OPERANDS].map(|_| meta.fixed_column());
+        let constant = meta.fixed_column();
+        let output = meta.advice_column();
+        let output_rotated = meta.advice_column();
+        let chunks = [(); NUM_CHUNKS].map(|_| ChunkColumns {
+            tag: meta.fixed_column(),
+            coeff: meta.fixed_column(),
+            coeff_rotated: meta.fixed_column(),
+            input: meta.advice_column(),
+            output: meta.advice_column(),
+        });
+        let table = TableConfig::configure(meta);
+
+        for column in operands {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(output);
+        meta.enable_equality(output_rotated);
+        for chunk in chunks.iter() {
+            meta.enable_equality(chunk.input);
+        }
+
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+
+        let config = KeccakConfig {
+            q_decompose: meta.selector(),
+            q_compose: meta.selector(),
+            q_select: meta.selector(),
+            q_pad: meta.selector(),
+            q_pad_byte: meta.selector(),
+            q_pad_end: meta.selector(),
+            operands,
+            operand_coeffs,
+            constant,
+            output,
+            output_rotated,
+            chunks,
+            table,
+        };
+
+        for chunk in config.chunks.iter() {
+            meta.lookup("keccak chunk", |meta| {
+                let tag = meta.query_fixed(chunk.tag, Rotation::cur());
+                let input = meta.query_advice(chunk.input, Rotation::cur());
+                let output = meta.query_advice(chunk.output, Rotation::cur());
+
+                vec![
+                    (tag, config.table.tag),
+                    (input, config.table.input),
+                    (output, config.table.output),
+                ]
+            });
+        }
+
+        // The linear combination constant + Σ coeff_k ⋅ operand_k has the digits of the
+        // input chunks.
+        meta.create_gate("keccak decomposition", |meta| {
+            let q_decompose = meta.query_selector(config.q_decompose);
+
+            let mut combination = meta.query_fixed(config.constant, Rotation::cur());
+            for (operand, coeff) in config.operands.iter().zip(config.operand_coeffs.iter()) {
+                combination = combination
+                    + meta.query_fixed(*coeff, Rotation::cur())
+                        * meta.query_advice(*operand, Rotation::cur());
+            }
+
+            let mut chunks = Expression::Constant(F::zero());
+            for chunk in config.chunks.iter() {
+                chunks = chunks
+                    + meta.query_fixed(chunk.coeff, Rotation::cur())
+                        * meta.query_advice(chunk.input, Rotation::cur());
+            }
+
+            Constraints::with_selector(q_decompose, Some(("decomposition", combination - chunks)))
+        });
+
+        // The output chunks, recombined with unrotated and rotated weights.
+        meta.create_gate("keccak composition", |meta| {
+            let q_compose = meta.query_selector(config.q_compose);
+
+            let mut output = meta.query_advice(config.output, Rotation::cur());
+            let mut output_rotated = meta.query_advice(config.output_rotated, Rotation::cur());
+            for chunk in config.chunks.iter() {
+                let chunk_output = meta.query_advice(chunk.output, Rotation::cur());
+                output =
+                    output - meta.query_fixed(chunk.coeff, Rotation::cur()) * chunk_output.clone();
+                output_rotated = output_rotated
+                    - meta.query_fixed(chunk.coeff_rotated, Rotation::cur()) * chunk_output;
+            }
+
+            Constraints::with_selector(
+                q_compose,
+                [("output", output), ("rotated output", output_rotated)],
+            )
+        });
+
+        // Accumulates flag ⋅ lane over the rows of a selection.
+        meta.create_gate("keccak selection", |meta| {
+            let q_select = meta.query_selector(config.q_select);
+
+            let flag = meta.query_advice(config.operands[0], Rotation::cur());
+            let lane = meta.query_advice(config.operands[1], Rotation::cur());
+            let acc_prev = meta.query_advice(config.output, Rotation::prev());
+            let acc = meta.query_advice(config.output, Rotation::cur());
+
+            Constraints::with_selector(q_select, Some(("selection", acc_prev + flag * lane - acc)))
+        });
+
+        // The padding uses the operand columns for the message byte, a flag that is set
+        // on the first `len` bytes, the padded byte, the running count of set flags, and
+        // the final-block flag.
+        let [message, flag, word, count, last] = config.operands;
+
+        meta.create_gate("keccak padding", |meta| {
+            let q_pad = meta.query_selector(config.q_pad);
+
+            let flag_prev = meta.query_advice(flag, Rotation::prev());
+            let flag = meta.query_advice(flag, Rotation::cur());
+            let count_prev = meta.query_advice(count, Rotation::prev());
+            let count = meta.query_advice(count, Rotation::cur());
+
+            let one = Expression::Constant(F::one());
+
+            Constraints::with_selector(
+                q_pad,
+                [
+                    ("flag is bool", bool_check(flag.clone())),
+                    (
+                        "flag is not set after being unset",
+                        flag.clone() * (one - flag_prev),
+                    ),
+                    ("running count", count_prev + flag - count),
+                ],
+            )
+        });
+
+        // The first byte after the message is 0x01.
+        meta.create_gate("keccak padded byte", |meta| {
+            let q_pad_byte = meta.query_selector(config.q_pad_byte);
+
+            let message = meta.query_advice(message, Rotation::cur());
+            let flag_prev = meta.query_advice(flag, Rotation::prev());
+            let flag = meta.query_advice(flag, Rotation::cur());
+            let word = meta.query_advice(word, Rotation::cur());
+
+            Constraints::with_selector(
+                q_pad_byte,
+                Some((
+                    "padded byte",
+                    flag.clone() * message + flag_prev - flag - word,
+                )),
+            )
+        });
+
+        // The last byte of the final block also has its top bit set. The final block is
+        // the one that the flags become unset in (or right after).
+        meta.create_gate("keccak padded block end", |meta| {
+            let q_pad_end = meta.query_selector(config.q_pad_end);
+
+            let message = meta.query_advice(message, Rotation::cur());
+            let flag_block_start = meta.query_advice(flag, Rotation(-(RATE_BYTES as i32)));
+            let flag_prev = meta.query_advice(flag, Rotation::prev());
+            let flag = meta.query_advice(flag, Rotation::cur());
+            let word = meta.query_advice(word, Rotation::cur());
+            let last = meta.query_advice(last, Rotation::cur());
+
+            Constraints::with_selector(
+                q_pad_end,
+                [
+                    (
+                        "final block",
+                        flag_block_start - flag.clone() - last.clone(),
+                    ),
+                    (
+                        "padded byte",
+                        flag.clone() * message + flag_prev - flag
+                            + last * Expression::Constant(F::from(0x80u64))
+                            - word,
+                    ),
+                ],
+            )
+        });
+
+        config
+    }
+
+    /// Constructs a [`KeccakChip`] given a [`KeccakConfig`].
+    pub fn construct(config: KeccakConfig) -> Self {
+        KeccakChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loads the lookup table required by this chip into the circuit.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.config.table.load(layouter)
+    }
+}
+
+impl KeccakConfig {
+    /// Assigns a row that maps each digit of `constant + Σ coeff ⋅ operand` through
+    /// `normalize`, where `constant` is given as `(multiple, bits)`. Returns the result,
+    /// and the result rotated left by `rotation`.
+    fn normalize<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        operands: &[(&Lane<F>, i64)],
+        constant: (u64, u64),
+        normalize: Normalize,
+        rotation: u32,
+    ) -> Result<(Lane<F>, Lane<F>), Error> {
+        assert!(operands.len() <= NUM_OPERANDS);
+        self.q_decompose.enable(region, offset)?;
+
+        for (i, (column, coeff_column)) in self
+            .operands
+            .iter()
+            .zip(self.operand_coeffs.iter())
+            .enumerate()
+        {
+            let coeff = match operands.get(i) {
+                Some((operand, coeff)) => {
+                    operand.cell.copy_advice(
+                        || format!("operand_{}", i),
+                        region,
+                        *column,
+                        offset,
+                    )?;
+                    *coeff
+                }
+                None => {
+                    region.assign_advice(
+                        || format!("operand_{}", i),
+                        *column,
+                        offset,
+                        || Value::known(F::zero()),
+                    )?;
+                    0
+                }
+            };
+            region.assign_fixed(
+                || format!("coeff_{}", i),
+                *coeff_column,
+                offset,
+                || Value::known(signed::<F>(coeff)),
+            )?;
+        }
+
+        let (multiple, constant_bits) = constant;
+        region.assign_fixed(
+            || "constant",
+            self.constant,
+            offset,
+            || {
+                Value::known(spread_digits::<F>(|i| {
+                    multiple * ((constant_bits >> i) & 1)
+                }))
+            },
+        )?;
+
+        // The digits of the linear combination.
+        let mut constant_digits = [0i64; 64];
+        for (i, digit) in constant_digits.iter_mut().enumerate() {
+            *digit = (multiple * ((constant_bits >> i) & 1)) as i64;
+        }
+        let digits = operands
+            .iter()
+            .fold(Value::known(constant_digits), |digits, (operand, coeff)| {
+                digits.zip(operand.bits).map(|(mut digits, bits)| {
+                    for (i, digit) in digits.iter_mut().enumerate() {
+                        *digit += coeff * ((bits >> i) & 1) as i64;
+                    }
+                    digits
+                })
+            })
+            .map(|digits| digits.map(|digit| digit as u64));
+
+        let chunks = chunks(rotation);
+        for (j, columns) in self.chunks.iter().enumerate() {
+            match chunks.get(j) {
+                Some(&(position, len)) => {
+                    columns.assign_fixed(
+                        region,
+                        offset,
+                        normalize.tag(len),
+                        pow8(position),
+                        pow8((position + rotation as usize) % 64),
+                    )?;
+
+                    let digits = digits.map(|digits| {
+                        let mut chunk = [0; CHUNK_LEN];
+                        chunk[..len].copy_from_slice(&digits[position..position + len]);
+                        chunk
+                    });
+                    let input =
+                        digits.map(|digits| digits.iter().rev().fold(0, |acc, d| acc * 8 + d));
+                    let output = digits.map(|digits| {
+                        digits
+                            .iter()
+                            .rev()
+                            .fold(0, |acc, d| acc * 8 + normalize.apply(*d))
+                    });
+                    region.assign_advice(
+                        || format!("chunk_{}", j),
+                        columns.input,
+                        offset,
+                        || input.map(F::from),
+                    )?;
+                    region.assign_advice(
+                        || format!("normalized chunk_{}", j),
+                        columns.output,
+                        offset,
+                        || output.map(F::from),
+                    )?;
+                }
+                None => columns.assign_unused(region, offset)?,
+            }
+        }
+
+        let bits = digits.map(|digits| {
+            digits
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (i, digit)| acc | normalize.apply(*digit) << i)
+        });
+        self.assign_output(region, offset, bits, rotation)
+    }
+
+    /// Assigns the output of a row, unrotated and rotated left by `rotation`.
+    fn assign_output<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        bits: Value<u64>,
+        rotation: u32,
+    ) -> Result<(Lane<F>, Lane<F>), Error> {
+        self.q_compose.enable(region, offset)?;
+
+        let cell =
+            region.assign_advice(|| "output", self.output, offset, || bits.map(spread::<F>))?;
+
+        let bits_rotated = bits.map(|bits| bits.rotate_left(rotation));
+        let cell_rotated = region.assign_advice(
+            || "rotated output",
+            self.output_rotated,
+            offset,
+            || bits_rotated.map(spread::<F>),
+        )?;
+
+        Ok((
+            Lane { cell, bits },
+            Lane {
+                cell: cell_rotated,
+                bits: bits_rotated,
+            },
+        ))
+    }
+
+    /// Assigns a row that packs 8 bytes, little-endian, into a lane in spread form.
+    fn pack_lane<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        bytes: &[AssignedCell<F, F>],
+    ) -> Result<Lane<F>, Error> {
+        let mut bits = Value::known(0);
+        for (j, columns) in self.chunks.iter().enumerate() {
+            match bytes.get(j) {
+                Some(byte) => {
+                    columns.assign_fixed(region, offset, TAG_BYTE, pow8(8 * j), pow8(8 * j))?;
+                    byte.copy_advice(|| format!("byte_{}", j), region, columns.input, offset)?;
+
+                    let value = byte.value().map(|byte| byte.get_lower_128() as u64);
+                    region.assign_advice(
+                        || format!("spread byte_{}", j),
+                        columns.output,
+                        offset,
+                        || value.map(spread::<F>),
+                    )?;
+                    bits = bits.zip(value).map(|(bits, byte)| bits | byte << (8 * j));
+                }
+                None => columns.assign_unused(region, offset)?,
+            }
+        }
+
+        self.assign_output(region, offset, bits, 0)
+            .map(|(lane, _)| lane)
+    }
+
+    /// Assigns a row that unpacks a lane in spread form into its 8 bytes, little-endian.
+    fn unpack_lane<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lane: &Lane<F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        self.q_compose.enable(region, offset)?;
+
+        let mut bytes = Vec::with_capacity(8);
+        for (j, columns) in self.chunks.iter().enumerate() {
+            if j < 8 {
+                columns.assign_fixed(region, offset, TAG_BYTE, pow8(8 * j), pow8(8 * j))?;
+
+                let value = lane.bits.map(|bits| (bits >> (8 * j)) & 0xff);
+                bytes.push(region.assign_advice(
+                    || format!("byte_{}", j),
+                    columns.input,
+                    offset,
+                    || value.map(F::from),
+                )?);
+                region.assign_advice(
+                    || format!("spread byte_{}", j),
+                    columns.output,
+                    offset,
+                    || value.map(spread::<F>),
+                )?;
+            } else {
+                columns.assign_unused(region, offset)?;
+            }
+        }
+
+        lane.cell
+            .copy_advice(|| "lane", region, self.output, offset)?;
+        region.assign_advice(
+            || "lane",
+            self.output_rotated,
+            offset,
+            || lane.bits.map(spread::<F>),
+        )?;
+
+        Ok(bytes)
+    }
+
+    /// Assigns the 56 rows of a round of Keccak-f[1600].
+    fn round<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        state: &KeccakState<F>,
+        round_constant: u64,
+    ) -> Result<KeccakState<F>, Error> {
+        let a = &state.0;
+        let mut offset = 0;
+
+        // Theta: the column sums, and the column sums rotated by one.
+        let mut c = Vec::with_capacity(5);
+        for x in 0..5 {
+            let operands: Vec<(&Lane<F>, i64)> =
+                (0..5).map(|y| (&a[lane_index(x, y)], 1)).collect();
+            c.push(self.normalize(region, offset, &operands, (0, 0), Normalize::Xor, 1)?);
+            offset += 1;
+        }
+
+        // Theta, rho and pi.
+        let mut b = vec![None; 25];
+        for (x, offsets) in RHO_OFFSETS.iter().enumerate() {
+            for (y, rotation) in offsets.iter().enumerate() {
+                let (_, rotated) = self.normalize(
+                    region,
+                    offset,
+                    &[
+                        (&a[lane_index(x, y)], 1),
+                        (&c[(x + 4) % 5].0, 1),
+                        (&c[(x + 1) % 5].1, 1),
+                    ],
+                    (0, 0),
+                    Normalize::Xor,
+                    *rotation,
+                )?;
+                b[lane_index(y, (2 * x + 3 * y) % 5)] = Some(rotated);
+                offset += 1;
+            }
+        }
+        let b: Vec<Lane<F>> = b
+            .into_iter()
+            .map(|lane| lane.expect("pi is a permutation"))
+            .collect();
+
+        // Chi.
+        let mut next = Vec::with_capacity(25);
+        for y in 0..5 {
+            for x in 0..5 {
+                let (lane, _) = self.normalize(
+                    region,
+                    offset,
+                    &[
+                        (&b[lane_index(x, y)], -2),
+                        (&b[lane_index((x + 1) % 5, y)], 1),
+                        (&b[lane_index((x + 2) % 5, y)], -1),
+                    ],
+                    (3, u64::MAX),
+                    Normalize::Chi,
+                    0,
+                )?;
+                next.push(lane);
+                offset += 1;
+            }
+        }
+
+        // Iota.
+        let (lane, _) = self.normalize(
+            region,
+            offset,
+            &[(&next[0], 1)],
+            (1, round_constant),
+            Normalize::Xor,
+            0,
+        )?;
+        next[0] = lane;
+
+        Ok(KeccakState(next.try_into().unwrap()))
+    }
+}
+
+impl<F: FieldExt> KeccakInstructions<F> for KeccakChip<F> {
+    type Var = AssignedCell<F, F>;
+    type State = KeccakState<F>;
+
+    fn initial_state(&self, layouter: &mut impl Layouter<F>) -> Result<Self::State, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "initial state",
+            |mut region| {
+                let zero =
+                    region.assign_advice_from_constant(|| "zero", config.output, 0, F::zero())?;
+                let lane = Lane {
+                    cell: zero,
+                    bits: Value::known(0),
+                };
+                Ok(KeccakState(vec![lane; 25].try_into().unwrap()))
+            },
+        )
+    }
+
+    fn pad(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        message: &[Self::Var],
+        len: &Self::Var,
+    ) -> Result<Vec<([Self::Var; RATE_BYTES], Self::Var)>, Error> {
+        let config = self.config();
+        let [message_column, flag_column, word_column, count_column, last_column] = config.operands;
+        let num_blocks = message.len() / RATE_BYTES + 1;
+
+        // The length as an integer, if it is at most message.len().
+        let len_value = len.value().map(|len| {
+            (0..=message.len())
+                .find(|i| F::from(*i as u64) == *len)
+                .unwrap_or(usize::MAX)
+        });
+
+        layouter.assign_region(
+            || "keccak padding",
+            |mut region| {
+                region.assign_advice_from_constant(|| "initial flag", flag_column, 0, F::one())?;
+                region.assign_advice_from_constant(
+                    || "initial count",
+                    count_column,
+                    0,
+                    F::zero(),
+                )?;
+
+                let mut words = Vec::with_capacity(num_blocks * RATE_BYTES);
+                let mut flags = Vec::with_capacity(num_blocks);
+                for i in 0..num_blocks * RATE_BYTES {
+                    let offset = i + 1;
+                    let block = i / RATE_BYTES;
+                    let is_block_end = i % RATE_BYTES == RATE_BYTES - 1;
+
+                    config.q_pad.enable(&mut region, offset)?;
+                    if is_block_end {
+                        config.q_pad_end.enable(&mut region, offset)?;
+                    } else {
+                        config.q_pad_byte.enable(&mut region, offset)?;
+                    }
+
+                    let message_byte = match message.get(i) {
+                        Some(byte) => byte.copy_advice(
+                            || format!("message_{}", i),
+                            &mut region,
+                            message_column,
+                            offset,
+                        )?,
+                        // Past the end of the message, the flags are unset.
+                        None => region.assign_advice(
+                            || "no message",
+                            message_column,
+                            offset,
+                            || Value::known(F::zero()),
+                        )?,
+                    };
+
+                    if i == message.len() {
+                        region.assign_advice_from_constant(
+                            || "final flag",
+                            flag_column,
+                            offset,
+                            F::zero(),
+                        )?;
+                    } else {
+                        region.assign_advice(
+                            || format!("flag_{}", i),
+                            flag_column,
+                            offset,
+                            || len_value.map(|len| F::from((i < len) as u64)),
+                        )?;
+                    }
+
+                    let count = region.assign_advice(
+                        || format!("count_{}", i),
+                        count_column,
+                        offset,
+                        || len_value.map(|len| F::from(std::cmp::min(i + 1, len) as u64)),
+                    )?;
+                    if i == num_blocks * RATE_BYTES - 1 {
+                        region.constrain_equal(count.cell(), len.cell())?;
+                    }
+
+                    let is_final = len_value.map(|len| is_block_end && block == len / RATE_BYTES);
+                    words.push(region.assign_advice(
+                        || format!("word_{}", i),
+                        word_column,
+                        offset,
+                        || {
+                            message_byte.value().zip(len_value).zip(is_final).map(
+                                |((message, len), is_final)| {
+                                    let word = if i < len {
+                                        *message
+                                    } else if i == len {
+                                        F::one()
+                                    } else {
+                                        F::zero()
+                                    };
+                                    if is_final {
+                                        word + F::from(0x80u64)
+                                    } else {
+                                        word
+                                    }
+                                },
+                            )
+                        },
+                    )?);
+
+                    if is_block_end {
+                        flags.push(region.assign_advice(
+                            || format!("final_{}", block),
+                            last_column,
+                            offset,
+                            || is_final.map(|is_final| F::from(is_final as u64)),
+                        )?);
+                    }
+                }
+
+                Ok(words
+                    .chunks(RATE_BYTES)
+                    .map(|block| block.to_vec().try_into().unwrap())
+                    .zip(flags)
+                    .collect())
+            },
+        )
+    }
+
+    fn absorb(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        state: &Self::State,
+        block: &[Self::Var; RATE_BYTES],
+    ) -> Result<Self::State, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "absorb",
+            |mut region| {
+                let mut lanes = state.0.clone();
+                for (i, bytes) in block.chunks(8).enumerate() {
+                    let message = config.pack_lane(&mut region, 2 * i, bytes)?;
+                    let (lane, _) = config.normalize(
+                        &mut region,
+                        2 * i + 1,
+                        &[(&lanes[i], 1), (&message, 1)],
+                        (0, 0),
+                        Normalize::Xor,
+                        0,
+                    )?;
+                    lanes[i] = lane;
+                }
+                Ok(KeccakState(lanes))
+            },
+        )
+    }
+
+    fn permute(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        state: &Self::State,
+    ) -> Result<Self::State, Error> {
+        let config = self.config();
+        let mut state = state.clone();
+        for (round, round_constant) in ROUND_CONSTANTS.iter().enumerate() {
+            state = layouter.assign_region(
+                || format!("round {}", round),
+                |mut region| config.round(&mut region, &state, *round_constant),
+            )?;
+        }
+        Ok(state)
+    }
+
+    fn squeeze(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        states: &[(Self::Var, Self::State)],
+    ) -> Result<[Self::Var; DIGEST_BYTES], Error> {
+        let config = self.config();
+        let mut digest = Vec::with_capacity(DIGEST_BYTES);
+        for i in 0..DIGEST_BYTES / 8 {
+            let lane = layouter.assign_region(
+                || format!("select lane {}", i),
+                |mut region| {
+                    let mut acc = region.assign_advice_from_constant(
+                        || "initial",
+                        config.output,
+                        0,
+                        F::zero(),
+                    )?;
+                    let mut bits = Value::known(0);
+                    for (k, (flag, state)) in states.iter().enumerate() {
+                        let offset = k + 1;
+                        config.q_select.enable(&mut region, offset)?;
+
+                        let lane = &state.0[lane_index(i, 0)];
+                        flag.copy_advice(|| "flag", &mut region, config.operands[0], offset)?;
+                        lane.cell.copy_advice(
+                            || "lane",
+                            &mut region,
+                            config.operands[1],
+                            offset,
+                        )?;
+
+                        let value = acc
+                            .value()
+                            .zip(flag.value())
+                            .zip(lane.cell.value())
+                            .map(|((acc, flag), lane)| *acc + *flag * lane);
+                        acc =
+                            region.assign_advice(|| "selected", config.output, offset, || value)?;
+                        bits = bits
+                            .zip(flag.value())
+                            .zip(lane.bits)
+                            .map(
+                                |((bits, flag), lane)| if *flag == F::one() { lane } else { bits },
+                            );
+                    }
+                    Ok(Lane { cell: acc, bits })
+                },
+            )?;
+
+            digest.extend(layouter.assign_region(
+                || format!("unpack lane {}", i),
+                |mut region| config.unpack_lane(&mut region, 0, &lane),
+            )?);
+        }
+
+        Ok(digest.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use halo2curves::{bn256, pasta::pallas};
+    use sha3::{Digest, Keccak256 as Keccak256Reference};
+
+    use super::{chunks, KeccakChip, KeccakConfig, CHUNK_LEN, NUM_CHUNKS};
+    use crate::keccak::Keccak256;
+
+    const K: u32 = 12;
+    const MAX: usize = 200;
+
+    struct HashCircuit<F: FieldExt> {
+        message: Value<Vec<u8>>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for HashCircuit<F> {
+        type Config = (KeccakConfig, Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            HashCircuit {
+                message: Value::unknown(),
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let input = meta.advice_column();
+            meta.enable_equality(input);
+            let digest = meta.instance_column();
+            meta.enable_equality(digest);
+
+            (KeccakChip::configure(meta), input, digest)
+        }
+
+        fn synthesize(
+            &self,
+            (config, input, digest_column): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = KeccakChip::construct(config);
+            chip.load(&mut layouter)?;
+
+            let (message, len) = layouter.assign_region(
+                || "message",
+                |mut region| {
+                    let message = (0..MAX)
+                        .map(|i| {
+                            region.assign_advice(
+                                || format!("byte_{}", i),
+                                input,
+                                i,
+                                || {
+                                    self.message
+                                        .as_ref()
+                                        .map(|m| F::from(m.get(i).copied().unwrap_or(0) as u64))
+                                },
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let len = region.assign_advice(
+                        || "len",
+                        input,
+                        MAX,
+                        || self.message.as_ref().map(|m| F::from(m.len() as u64)),
+                    )?;
+                    Ok((message, len))
+                },
+            )?;
+
+            let hasher = Keccak256::new(chip);
+            let digest = hasher.digest(layouter.namespace(|| "keccak256"), &message, &len)?;
+            for (i, byte) in digest.bytes().iter().enumerate() {
+                layouter.constrain_instance(byte.cell(), digest_column, i)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    fn prover<F: FieldExt>(message: &[u8]) -> MockProver<F> {
+        let digest = Keccak256Reference::digest(message)
+            .iter()
+            .map(|byte| F::from(*byte as u64))
+            .collect();
+
+        let circuit = HashCircuit::<F> {
+            message: Value::known(message.to_vec()),
+            _marker: PhantomData,
+        };
+        MockProver::run(K, &circuit, vec![digest]).unwrap()
+    }
+
+    fn message(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 + 7) as u8).collect()
+    }
+
+    #[test]
+    fn lane_chunks() {
+        for rotation in 0..64 {
+            let chunks = chunks(rotation);
+            assert!(chunks.len() <= NUM_CHUNKS);
+            assert_eq!(chunks.iter().map(|(_, len)| len).sum::<usize>(), 64);
+            assert!(chunks.iter().all(|(_, len)| *len <= CHUNK_LEN));
+            assert!(chunks
+                .iter()
+                .all(|(position, len)| *position >= 64 - rotation as usize
+                    || position + len <= 64 - rotation as usize));
+        }
+    }
+
+    #[test]
+    fn keccak256_bn256() {
+        // Lengths on either side of the block boundaries, and the case where the first
+        // and last padding bytes coincide.
+        for len in [0, 1, 135, 136, 137, MAX] {
+            assert_eq!(prover::<bn256::Fr>(&message(len)).verify(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn keccak256_pallas() {
+        assert_eq!(prover::<pallas::Base>(b"abc").verify(), Ok(()));
+    }
+
+    #[test]
+    fn keccak256_length_exceeds_max() {
+        assert!(prover::<bn256::Fr>(&message(MAX + 1)).verify().is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+
+use super::CHUNK_LEN;
+
+/// The tag of the rows that map a byte to its spread form.
+pub(super) const TAG_BYTE: u64 = 1;
+
+/// A function that the lookup table applies to each digit of a chunk in spread form.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Normalize {
+    /// Reduces a sum of bits modulo 2, for the theta and iota steps and for absorbing.
+    Xor,
+    /// Maps $3 - 2a + b - c$ to $a \oplus (\neg b \wedge c)$, for the chi step.
+    Chi,
+}
+
+impl Normalize {
+    /// The largest digit of the input chunks in the table.
+    fn max_digit(self) -> u64 {
+        match self {
+            // A column sum of the theta step.
+            Normalize::Xor => 5,
+            Normalize::Chi => 4,
+        }
+    }
+
+    pub(super) fn apply(self, digit: u64) -> u64 {
+        match self {
+            Normalize::Xor => digit & 1,
+            Normalize::Chi => [0, 1, 1, 0, 0][digit as usize],
+        }
+    }
+
+    /// The tag of the rows for chunks of `len` digits.
+    pub(super) fn tag(self, len: usize) -> u64 {
+        let offset = match self {
+            Normalize::Xor => TAG_BYTE,
+            Normalize::Chi => TAG_BYTE + CHUNK_LEN as u64,
+        };
+        offset + len as u64
+    }
+}
+
+/// The lookup table of the Keccak chip, with rows `(tag, input, output)`.
+///
+/// Besides the all-zero row, which unused chunks look up, it holds:
+/// - the rows with tag [`TAG_BYTE`], mapping each byte to its spread form;
+/// - the rows with tag [`Normalize::tag`]`(len)`, mapping each chunk of `len` digits in
+///   spread form to its image under the [`Normalize`] function. The tag of a chunk
+///   shorter than `CHUNK_LEN` range-checks it to `len` digits.
+#[derive(Clone, Debug)]
+pub(super) struct TableConfig {
+    pub(super) tag: TableColumn,
+    pub(super) input: TableColumn,
+    pub(super) output: TableColumn,
+}
+
+impl TableConfig {
+    pub(super) fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        TableConfig {
+            tag: meta.lookup_table_column(),
+            input: meta.lookup_table_column(),
+            output: meta.lookup_table_column(),
+        }
+    }
+
+    pub(super) fn load<F: FieldExt>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "keccak table",
+            |mut table| {
+                for (index, (tag, input, output)) in Self::rows().into_iter().enumerate() {
+                    table.assign_cell(|| "tag", self.tag, index, || Value::known(F::from(tag)))?;
+                    table.assign_cell(
+                        || "input",
+                        self.input,
+                        index,
+                        || Value::known(F::from(input)),
+                    )?;
+                    table.assign_cell(
+                        || "output",
+                        self.output,
+                        index,
+                        || Value::known(F::from(output)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn rows() -> Vec<(u64, u64, u64)> {
+        let mut rows = vec![(0, 0, 0)];
+
+        for byte in 0..256 {
+            let spread = (0..8).fold(0, |acc, i| acc | ((byte >> i) & 1) << (3 * i));
+            rows.push((TAG_BYTE, byte, spread));
+        }
+
+        for normalize in [Normalize::Xor, Normalize::Chi] {
+            let base = normalize.max_digit() + 1;
+            for len in 1..=CHUNK_LEN {
+                for index in 0..base.pow(len as u32) {
+                    let (mut input, mut output) = (0, 0);
+                    for i in (0..len as u32).rev() {
+                        let digit = (index / base.pow(i)) % base;
+                        input = input * 8 + digit;
+                        output = output * 8 + normalize.apply(digit);
+                    }
+                    rows.push((normalize.tag(len), input, output));
+                }
+            }
+        }
+
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Normalize, TableConfig, TAG_BYTE};
+
+    #[test]
+    fn chi() {
+        for (a, b, c) in (0..8).map(|i| (i & 1, (i >> 1) & 1, (i >> 2) & 1)) {
+            assert_eq!(Normalize::Chi.apply(3 - 2 * a + b - c), a ^ (!b & 1 & c));
+        }
+    }
+
+    #[test]
+    fn tags_are_distinct() {
+        let rows = TableConfig::rows();
+        let mut entries: Vec<_> = rows.iter().map(|(tag, input, _)| (*tag, *input)).collect();
+        entries.sort_unstable();
+        entries.dedup();
+        assert_eq!(entries.len(), rows.len());
+
+        // The spread form of a byte places bit i at 8^i.
+        assert!(rows.contains(&(TAG_BYTE, 0b1011, 0o1011)));
+    }
+}
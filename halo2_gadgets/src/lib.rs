@@ -24,9 +24,11 @@
 #![deny(unsafe_code)]
 
 pub mod ecc;
+pub mod keccak;
 pub mod poseidon;
-#[cfg(feature = "unstable")]
-#[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
 pub mod sha256;
 pub mod sinsemilla;
 pub mod utilities;
+#[cfg(feature = "unstable")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
+pub mod verifier;
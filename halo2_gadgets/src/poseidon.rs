@@ -11,11 +11,20 @@ use halo2_proofs::{
     plonk::Error,
 };
 
+mod padding;
+pub use padding::{VariableLengthPaddingChip, VariableLengthPaddingConfig};
+
 mod pow5;
 pub use pow5::{Pow5Chip, Pow5Config, StateWord};
 
+mod poseidon2;
+pub use poseidon2::{Poseidon2Chip, Poseidon2Config};
+
 pub mod primitives;
-use primitives::{Absorbing, ConstantLength, Domain, Spec, SpongeMode, Squeezing, State};
+use primitives::{
+    Absorbing, ConstantLength, Domain, Spec, SpongeMode, Squeezing, State, TranscriptDomain,
+    VariableLength,
+};
 
 /// A word from the padded input to a Poseidon sponge.
 #[derive(Clone, Debug)]
@@ -209,6 +218,54 @@ impl<
     }
 }
 
+impl<
+        F: FieldExt,
+        PoseidonChip: PoseidonSpongeInstructions<F, S, TranscriptDomain, T, RATE>,
+        S: Spec<F, T, RATE>,
+        const T: usize,
+        const RATE: usize,
+    > Sponge<F, PoseidonChip, S, Absorbing<PaddedWord<F>, RATE>, TranscriptDomain, T, RATE>
+{
+    /// Squeezes a challenge as the [`PoseidonRead`] and [`PoseidonWrite`] transcripts
+    /// do, and resumes absorbing.
+    ///
+    /// The elements absorbed since the last challenge are padded with zeros to a
+    /// full block before the permutation, and the challenge is the first word of the
+    /// resulting state.
+    ///
+    /// [`PoseidonRead`]: halo2_proofs::transcript::PoseidonRead
+    /// [`PoseidonWrite`]: halo2_proofs::transcript::PoseidonWrite
+    pub fn squeeze_challenge(
+        &mut self,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        for entry in self.mode.0.iter_mut() {
+            if entry.is_none() {
+                *entry = Some(PaddedWord::Padding(F::zero()));
+            }
+        }
+
+        let output = poseidon_sponge(
+            &self.chip,
+            layouter.namespace(|| "PoseidonSponge"),
+            &mut self.state,
+            Some(&self.mode),
+        )?;
+        self.mode = Absorbing(
+            (0..RATE)
+                .map(|_| None)
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+        );
+
+        Ok(output.0[0]
+            .clone()
+            .expect("get_output returns a full block")
+            .into())
+    }
+}
+
 impl<
         F: FieldExt,
         PoseidonChip: PoseidonSpongeInstructions<F, S, D, T, RATE>,
@@ -295,3 +352,46 @@ impl<
             .squeeze(layouter.namespace(|| "squeeze"))
     }
 }
+
+impl<
+        F: FieldExt,
+        PoseidonChip: PoseidonSpongeInstructions<F, S, VariableLength<MAX>, T, RATE>,
+        S: Spec<F, T, RATE>,
+        const T: usize,
+        const RATE: usize,
+        const MAX: usize,
+    > Hash<F, PoseidonChip, S, VariableLength<MAX>, T, RATE>
+{
+    /// Hashes the first `len` words of the given input.
+    ///
+    /// The padding is computed by `padding_chip`, so that the circuit is the same for
+    /// every `len` up to `MAX`. The remaining words of `message` are ignored.
+    pub fn hash(
+        mut self,
+        mut layouter: impl Layouter<F>,
+        padding_chip: &VariableLengthPaddingChip<F>,
+        message: [AssignedCell<F, F>; MAX],
+        len: AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let padded = padding_chip.pad(layouter.namespace(|| "pad"), &message, &len)?;
+
+        // The padding chip returns the words up to and including the one after the
+        // longest message; the rest of the padding is always zero.
+        for (i, value) in padded
+            .into_iter()
+            .map(PaddedWord::Message)
+            .chain(
+                <VariableLength<MAX> as Domain<F, RATE>>::padding(MAX)
+                    .skip(1)
+                    .map(PaddedWord::Padding),
+            )
+            .enumerate()
+        {
+            self.sponge
+                .absorb(layouter.namespace(|| format!("absorb_{}", i)), value)?;
+        }
+        self.sponge
+            .finish_absorbing(layouter.namespace(|| "finish absorbing"))?
+            .squeeze(layouter.namespace(|| "squeeze"))
+    }
+}
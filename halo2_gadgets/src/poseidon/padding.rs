@@ -0,0 +1,219 @@
+//! A chip that pads messages of witnessed length for [`VariableLength`] hashing.
+//!
+//! [`VariableLength`]: super::primitives::VariableLength
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::utilities::bool_check;
+
+/// Configuration for a [`VariableLengthPaddingChip`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariableLengthPaddingConfig {
+    q_pad: Selector,
+    message: Column<Advice>,
+    flag: Column<Advice>,
+    word: Column<Advice>,
+    count: Column<Advice>,
+}
+
+/// A chip that pads a message of witnessed length `len`, out of a maximum of `MAX`
+/// words, with a one followed by zeros.
+///
+/// The chip uses one row per message word, plus two. Row `i + 1` holds the message word
+/// $m_i$, a flag $b_i = [i < len]$, the padded word $w_i$ and the running count
+/// $c_i = \sum_{j \leq i} b_j$. The first row fixes $b_{-1} = 1$ and $c_{-1} = 0$, and
+/// the last row fixes $b_{MAX} = 0$ and constrains $c_{MAX}$ to equal `len`. In between,
+/// the flags are boolean and can only go from one to zero, so exactly the first `len` of
+/// them are set, and
+///
+/// $$w_i = b_i \cdot m_i + b_{i-1} - b_i.$$
+#[derive(Clone, Debug)]
+pub struct VariableLengthPaddingChip<F: FieldExt> {
+    config: VariableLengthPaddingConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for VariableLengthPaddingChip<F> {
+    type Config = VariableLengthPaddingConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> VariableLengthPaddingChip<F> {
+    /// Configures this chip for use in a circuit.
+    ///
+    /// The circuit must have a fixed column enabled for constants.
+    ///
+    /// # Side-effects
+    ///
+    /// All columns in `advices` will be equality-enabled.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 4],
+    ) -> VariableLengthPaddingConfig {
+        for advice in advices {
+            meta.enable_equality(advice);
+        }
+
+        let config = VariableLengthPaddingConfig {
+            q_pad: meta.selector(),
+            message: advices[0],
+            flag: advices[1],
+            word: advices[2],
+            count: advices[3],
+        };
+
+        meta.create_gate("variable-length padding", |meta| {
+            let q_pad = meta.query_selector(config.q_pad);
+
+            let message = meta.query_advice(config.message, Rotation::cur());
+            let flag_prev = meta.query_advice(config.flag, Rotation::prev());
+            let flag = meta.query_advice(config.flag, Rotation::cur());
+            let word = meta.query_advice(config.word, Rotation::cur());
+            let count_prev = meta.query_advice(config.count, Rotation::prev());
+            let count = meta.query_advice(config.count, Rotation::cur());
+
+            let one = Expression::Constant(F::one());
+
+            Constraints::with_selector(
+                q_pad,
+                [
+                    ("flag is bool", bool_check(flag.clone())),
+                    (
+                        "flag is not set after being unset",
+                        flag.clone() * (one - flag_prev.clone()),
+                    ),
+                    (
+                        "padded word",
+                        flag.clone() * message + flag_prev - flag.clone() - word,
+                    ),
+                    ("running count", count_prev + flag - count),
+                ],
+            )
+        });
+
+        config
+    }
+
+    /// Constructs a [`VariableLengthPaddingChip`] given a [`VariableLengthPaddingConfig`].
+    pub fn construct(config: VariableLengthPaddingConfig) -> Self {
+        VariableLengthPaddingChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Pads the first `len` words of `message`, returning the `MAX + 1` padded words.
+    ///
+    /// The remaining words of `message` are ignored. The circuit is not satisfied if
+    /// `len` is greater than `MAX`.
+    pub fn pad<const MAX: usize>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        message: &[AssignedCell<F, F>; MAX],
+        len: &AssignedCell<F, F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config();
+
+        // The length as an integer, if it is at most MAX.
+        let len_value = len.value().map(|len| {
+            (0..=MAX)
+                .find(|i| F::from(*i as u64) == *len)
+                .unwrap_or(usize::MAX)
+        });
+
+        layouter.assign_region(
+            || "variable-length padding",
+            |mut region| {
+                region.assign_advice_from_constant(|| "initial flag", config.flag, 0, F::one())?;
+                region.assign_advice_from_constant(
+                    || "initial count",
+                    config.count,
+                    0,
+                    F::zero(),
+                )?;
+
+                let mut words = Vec::with_capacity(MAX + 1);
+                for i in 0..=MAX {
+                    let offset = i + 1;
+                    config.q_pad.enable(&mut region, offset)?;
+
+                    let message = match message.get(i) {
+                        Some(word) => word.copy_advice(
+                            || format!("message_{}", i),
+                            &mut region,
+                            config.message,
+                            offset,
+                        )?,
+                        // The last row has no message word; its flag is unset.
+                        None => region.assign_advice(
+                            || "no message",
+                            config.message,
+                            offset,
+                            || Value::known(F::zero()),
+                        )?,
+                    };
+
+                    if i == MAX {
+                        region.assign_advice_from_constant(
+                            || "final flag",
+                            config.flag,
+                            offset,
+                            F::zero(),
+                        )?;
+                    } else {
+                        region.assign_advice(
+                            || format!("flag_{}", i),
+                            config.flag,
+                            offset,
+                            || len_value.map(|len| F::from((i < len) as u64)),
+                        )?;
+                    }
+
+                    words.push(region.assign_advice(
+                        || format!("word_{}", i),
+                        config.word,
+                        offset,
+                        || {
+                            message.value().zip(len_value).map(|(message, len)| {
+                                if i < len {
+                                    *message
+                                } else if i == len {
+                                    F::one()
+                                } else {
+                                    F::zero()
+                                }
+                            })
+                        },
+                    )?);
+
+                    let count = region.assign_advice(
+                        || format!("count_{}", i),
+                        config.count,
+                        offset,
+                        || len_value.map(|len| F::from(std::cmp::min(i + 1, len) as u64)),
+                    )?;
+                    if i == MAX {
+                        region.constrain_equal(count.cell(), len.cell())?;
+                    }
+                }
+
+                Ok(words)
+            },
+        )
+    }
+}
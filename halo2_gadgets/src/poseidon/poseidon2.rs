@@ -0,0 +1,744 @@
+use std::convert::TryInto;
+use std::iter;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region, Value},
+    plonk::{
+        Advice, Any, Column, ConstraintSystem, Constraints, Error, Expression, Fixed, Selector,
+    },
+    poly::Rotation,
+};
+
+use super::{
+    primitives::{Absorbing, Domain, Mds, Poseidon2Spec, Squeezing, State},
+    PaddedWord, PoseidonInstructions, PoseidonSpongeInstructions, StateWord,
+};
+
+/// Configuration for a [`Poseidon2Chip`].
+#[derive(Clone, Debug)]
+pub struct Poseidon2Config<F: FieldExt, const WIDTH: usize, const RATE: usize> {
+    pub(crate) state: [Column<Advice>; WIDTH],
+    internal_sbox: [Column<Advice>; WIDTH],
+    rc: [Column<Fixed>; WIDTH],
+    s_external_linear: Selector,
+    s_external: Selector,
+    s_internal: Selector,
+    s_internal_rem: Option<Selector>,
+    s_pad_and_add: Selector,
+
+    half_full_rounds: usize,
+    partial_rounds: usize,
+    alpha: [u64; 4],
+    round_constants: Vec<[F; WIDTH]>,
+    m_e: Mds<F, WIDTH>,
+    internal_diagonal: [F; WIDTH],
+}
+
+/// A Poseidon2 chip using an $x^5$ S-Box.
+///
+/// The chip is implemented using a single round per row for external (full) rounds, and
+/// `WIDTH` rounds per row for internal (partial) rounds. As an internal round only
+/// applies the S-box to the first word, and its linear layer is $J + \mathrm{diag}(d)$,
+/// the state after each internal round in a row is a linear combination of the row's
+/// state, round constants and S-box outputs, which are witnessed in `internal_sbox`.
+///
+/// A permutation of width 3 with $R_F = 8, R_P = 56$ takes 29 rows, compared to 37 for
+/// the equivalent [`Pow5Chip`](super::Pow5Chip).
+#[derive(Debug)]
+pub struct Poseidon2Chip<F: FieldExt, const WIDTH: usize, const RATE: usize> {
+    config: Poseidon2Config<F, WIDTH, RATE>,
+}
+
+impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Poseidon2Chip<F, WIDTH, RATE> {
+    /// Configures this chip for use in a circuit.
+    ///
+    /// # Side-effects
+    ///
+    /// All columns in `state` and `rc` will be equality-enabled.
+    pub fn configure<S: Poseidon2Spec<F, WIDTH, RATE>>(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+        internal_sbox: [Column<Advice>; WIDTH],
+        rc: [Column<Fixed>; WIDTH],
+    ) -> Poseidon2Config<F, WIDTH, RATE> {
+        assert_eq!(RATE, WIDTH - 1);
+        // This gadget requires R_F to be even.
+        assert!(S::full_rounds() & 1 == 0);
+        let half_full_rounds = S::full_rounds() / 2;
+        let partial_rounds = S::partial_rounds();
+        let (round_constants, m_e, _) = S::constants();
+        let internal_diagonal = S::internal_diagonal();
+
+        // This allows state words to be initialized (by constraining them equal to fixed
+        // values), and used in a permutation from an arbitrary region. The rc columns
+        // are used as "scratch space" for padding outside of the permutation regions.
+        for column in iter::empty()
+            .chain(state.iter().cloned().map(Column::<Any>::from))
+            .chain(rc.iter().cloned().map(Column::<Any>::from))
+        {
+            meta.enable_equality(column);
+        }
+
+        let s_external_linear = meta.selector();
+        let s_external = meta.selector();
+        let s_pad_and_add = meta.selector();
+
+        let alpha = [5, 0, 0, 0];
+        let pow_5 = |v: Expression<F>| {
+            let v2 = v.clone() * v.clone();
+            v2.clone() * v2 * v
+        };
+
+        meta.create_gate("external linear layer", |meta| {
+            let s_external_linear = meta.query_selector(s_external_linear);
+
+            Constraints::with_selector(
+                s_external_linear,
+                (0..WIDTH)
+                    .map(|next_idx| {
+                        let state_next = meta.query_advice(state[next_idx], Rotation::next());
+                        let expr = (0..WIDTH)
+                            .map(|idx| {
+                                let state_cur = meta.query_advice(state[idx], Rotation::cur());
+                                state_cur * m_e[next_idx][idx]
+                            })
+                            .reduce(|acc, term| acc + term)
+                            .expect("WIDTH > 0");
+                        expr - state_next
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        });
+
+        meta.create_gate("external round", |meta| {
+            let s_external = meta.query_selector(s_external);
+
+            Constraints::with_selector(
+                s_external,
+                (0..WIDTH)
+                    .map(|next_idx| {
+                        let state_next = meta.query_advice(state[next_idx], Rotation::next());
+                        let expr = (0..WIDTH)
+                            .map(|idx| {
+                                let state_cur = meta.query_advice(state[idx], Rotation::cur());
+                                let rc = meta.query_fixed(rc[idx], Rotation::cur());
+                                pow_5(state_cur + rc) * m_e[next_idx][idx]
+                            })
+                            .reduce(|acc, term| acc + term)
+                            .expect("WIDTH > 0");
+                        expr - state_next
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        });
+
+        // Creates a gate applying `rounds` internal rounds in a row. We track each word
+        // of the state as a vector of coefficients over the row's cells: the state words,
+        // then the round constants, then the S-box outputs.
+        let internal_gate = |meta: &mut ConstraintSystem<F>, rounds: usize| {
+            let selector = meta.selector();
+
+            meta.create_gate("internal rounds", |meta| {
+                let s_internal = meta.query_selector(selector);
+
+                let mut cells = Vec::with_capacity(WIDTH + 2 * rounds);
+                for column in state.iter() {
+                    cells.push(meta.query_advice(*column, Rotation::cur()));
+                }
+                for column in rc[..rounds].iter() {
+                    cells.push(meta.query_fixed(*column, Rotation::cur()));
+                }
+                for column in internal_sbox[..rounds].iter() {
+                    cells.push(meta.query_advice(*column, Rotation::cur()));
+                }
+                let unit = |idx: usize| {
+                    let mut coeffs = vec![F::zero(); cells.len()];
+                    coeffs[idx] = F::one();
+                    coeffs
+                };
+                let to_expression = |coeffs: &[F]| {
+                    coeffs
+                        .iter()
+                        .zip(cells.iter())
+                        .filter(|(coeff, _)| !coeff.is_zero_vartime())
+                        .map(|(coeff, cell)| cell.clone() * *coeff)
+                        .reduce(|acc, term| acc + term)
+                        .unwrap_or_else(|| Expression::Constant(F::zero()))
+                };
+
+                let mut words = (0..WIDTH).map(&unit).collect::<Vec<_>>();
+                let mut constraints = Vec::with_capacity(rounds + WIDTH);
+                for round in 0..rounds {
+                    let sbox_in = words[0]
+                        .iter()
+                        .zip(unit(WIDTH + round))
+                        .map(|(word, rc)| *word + rc)
+                        .collect::<Vec<_>>();
+                    let sbox_out = WIDTH + rounds + round;
+                    constraints.push(pow_5(to_expression(&sbox_in)) - cells[sbox_out].clone());
+                    words[0] = unit(sbox_out);
+
+                    let sum = words
+                        .iter()
+                        .fold(vec![F::zero(); cells.len()], |acc, word| {
+                            acc.iter().zip(word.iter()).map(|(a, b)| *a + b).collect()
+                        });
+                    for (word, d) in words.iter_mut().zip(internal_diagonal.iter()) {
+                        for (coeff, sum) in word.iter_mut().zip(sum.iter()) {
+                            *coeff = *coeff * d + sum;
+                        }
+                    }
+                }
+
+                for (idx, word) in words.iter().enumerate() {
+                    let state_next = meta.query_advice(state[idx], Rotation::next());
+                    constraints.push(to_expression(word) - state_next);
+                }
+
+                Constraints::with_selector(s_internal, constraints)
+            });
+
+            selector
+        };
+        let s_internal = internal_gate(meta, WIDTH);
+        let s_internal_rem =
+            (partial_rounds % WIDTH != 0).then(|| internal_gate(meta, partial_rounds % WIDTH));
+
+        meta.create_gate("pad-and-add", |meta| {
+            let initial_state_rate = meta.query_advice(state[RATE], Rotation::prev());
+            let output_state_rate = meta.query_advice(state[RATE], Rotation::next());
+
+            let s_pad_and_add = meta.query_selector(s_pad_and_add);
+
+            let pad_and_add = |idx: usize| {
+                let initial_state = meta.query_advice(state[idx], Rotation::prev());
+                let input = meta.query_advice(state[idx], Rotation::cur());
+                let output_state = meta.query_advice(state[idx], Rotation::next());
+
+                // We pad the input by storing the required padding in fixed columns and
+                // then constraining the corresponding input columns to be equal to it.
+                initial_state + input - output_state
+            };
+
+            Constraints::with_selector(
+                s_pad_and_add,
+                (0..RATE)
+                    .map(pad_and_add)
+                    // The capacity element is never altered by the input.
+                    .chain(Some(initial_state_rate - output_state_rate))
+                    .collect::<Vec<_>>(),
+            )
+        });
+
+        Poseidon2Config {
+            state,
+            internal_sbox,
+            rc,
+            s_external_linear,
+            s_external,
+            s_internal,
+            s_internal_rem,
+            s_pad_and_add,
+            half_full_rounds,
+            partial_rounds,
+            alpha,
+            round_constants,
+            m_e,
+            internal_diagonal,
+        }
+    }
+
+    /// Construct a [`Poseidon2Chip`].
+    pub fn construct(config: Poseidon2Config<F, WIDTH, RATE>) -> Self {
+        Poseidon2Chip { config }
+    }
+}
+
+impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Chip<F> for Poseidon2Chip<F, WIDTH, RATE> {
+    type Config = Poseidon2Config<F, WIDTH, RATE>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, S: Poseidon2Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize>
+    PoseidonInstructions<F, S, WIDTH, RATE> for Poseidon2Chip<F, WIDTH, RATE>
+{
+    type Word = StateWord<F>;
+
+    fn permute(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        initial_state: &State<Self::Word, WIDTH>,
+    ) -> Result<State<Self::Word, WIDTH>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "permute state",
+            |mut region| {
+                // Load the initial state into this region.
+                let state = Poseidon2State::load(&mut region, config, initial_state)?;
+
+                let state = state.external_linear_layer(&mut region, config, 0)?;
+                let mut offset = 1;
+
+                let state = (0..config.half_full_rounds).fold(Ok(state), |res, r| {
+                    res.and_then(|state| state.external_round(&mut region, config, r, offset + r))
+                })?;
+                offset += config.half_full_rounds;
+
+                let mut state = state;
+                let mut round = config.half_full_rounds;
+                for rounds in (0..config.partial_rounds)
+                    .step_by(WIDTH)
+                    .map(|r| std::cmp::min(WIDTH, config.partial_rounds - r))
+                {
+                    state = state.internal_rounds(&mut region, config, round, rounds, offset)?;
+                    round += rounds;
+                    offset += 1;
+                }
+
+                let state = (0..config.half_full_rounds).fold(Ok(state), |res, r| {
+                    res.and_then(|state| {
+                        state.external_round(&mut region, config, round + r, offset + r)
+                    })
+                })?;
+
+                Ok(state.0)
+            },
+        )
+    }
+}
+
+impl<
+        F: FieldExt,
+        S: Poseidon2Spec<F, WIDTH, RATE>,
+        D: Domain<F, RATE>,
+        const WIDTH: usize,
+        const RATE: usize,
+    > PoseidonSpongeInstructions<F, S, D, WIDTH, RATE> for Poseidon2Chip<F, WIDTH, RATE>
+{
+    fn initial_state(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<State<Self::Word, WIDTH>, Error> {
+        let config = self.config();
+        let state = layouter.assign_region(
+            || format!("initial state for domain {}", D::name()),
+            |mut region| {
+                let mut state = Vec::with_capacity(WIDTH);
+                let mut load_state_word = |i: usize, value: F| -> Result<_, Error> {
+                    let var = region.assign_advice_from_constant(
+                        || format!("state_{}", i),
+                        config.state[i],
+                        0,
+                        value,
+                    )?;
+                    state.push(StateWord(var));
+
+                    Ok(())
+                };
+
+                for i in 0..RATE {
+                    load_state_word(i, F::zero())?;
+                }
+                load_state_word(RATE, D::initial_capacity_element())?;
+
+                Ok(state)
+            },
+        )?;
+
+        Ok(state.try_into().unwrap())
+    }
+
+    fn add_input(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        initial_state: &State<Self::Word, WIDTH>,
+        input: &Absorbing<PaddedWord<F>, RATE>,
+    ) -> Result<State<Self::Word, WIDTH>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || format!("add input for domain {}", D::name()),
+            |mut region| {
+                config.s_pad_and_add.enable(&mut region, 1)?;
+
+                // Load the initial state into this region.
+                let load_state_word = |i: usize| {
+                    initial_state[i]
+                        .0
+                        .copy_advice(
+                            || format!("load state_{}", i),
+                            &mut region,
+                            config.state[i],
+                            0,
+                        )
+                        .map(StateWord)
+                };
+                let initial_state: Result<Vec<_>, Error> =
+                    (0..WIDTH).map(load_state_word).collect();
+                let initial_state = initial_state?;
+
+                // Load the input into this region.
+                let load_input_word = |i: usize| {
+                    let constraint_var = match input.0[i].clone() {
+                        Some(PaddedWord::Message(word)) => word,
+                        Some(PaddedWord::Padding(padding_value)) => region.assign_fixed(
+                            || format!("load pad_{}", i),
+                            config.rc[i],
+                            1,
+                            || Value::known(padding_value),
+                        )?,
+                        _ => panic!("Input is not padded"),
+                    };
+                    constraint_var
+                        .copy_advice(
+                            || format!("load input_{}", i),
+                            &mut region,
+                            config.state[i],
+                            1,
+                        )
+                        .map(StateWord)
+                };
+                let input: Result<Vec<_>, Error> = (0..RATE).map(load_input_word).collect();
+                let input = input?;
+
+                // Constrain the output.
+                let constrain_output_word = |i: usize| {
+                    let value = initial_state[i].0.value().copied()
+                        + input
+                            .get(i)
+                            .map(|word| word.0.value().cloned())
+                            // The capacity element is never altered by the input.
+                            .unwrap_or_else(|| Value::known(F::zero()));
+                    region
+                        .assign_advice(
+                            || format!("load output_{}", i),
+                            config.state[i],
+                            2,
+                            || value,
+                        )
+                        .map(StateWord)
+                };
+
+                let output: Result<Vec<_>, Error> = (0..WIDTH).map(constrain_output_word).collect();
+                output.map(|output| output.try_into().unwrap())
+            },
+        )
+    }
+
+    fn get_output(state: &State<Self::Word, WIDTH>) -> Squeezing<Self::Word, RATE> {
+        Squeezing(
+            state[..RATE]
+                .iter()
+                .map(|word| Some(word.clone()))
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Poseidon2State<F: FieldExt, const WIDTH: usize>([StateWord<F>; WIDTH]);
+
+impl<F: FieldExt, const WIDTH: usize> Poseidon2State<F, WIDTH> {
+    fn load<const RATE: usize>(
+        region: &mut Region<F>,
+        config: &Poseidon2Config<F, WIDTH, RATE>,
+        initial_state: &State<StateWord<F>, WIDTH>,
+    ) -> Result<Self, Error> {
+        let load_state_word = |i: usize| {
+            initial_state[i]
+                .0
+                .copy_advice(|| format!("load state_{}", i), region, config.state[i], 0)
+                .map(StateWord)
+        };
+
+        let state: Result<Vec<_>, _> = (0..WIDTH).map(load_state_word).collect();
+        state.map(|state| Poseidon2State(state.try_into().unwrap()))
+    }
+
+    fn values(&self) -> Value<Vec<F>> {
+        self.0.iter().map(|word| word.0.value().cloned()).collect()
+    }
+
+    fn apply_m_e<const RATE: usize>(
+        config: &Poseidon2Config<F, WIDTH, RATE>,
+        r: Value<Vec<F>>,
+    ) -> [Value<F>; WIDTH] {
+        config
+            .m_e
+            .iter()
+            .map(|m_i| {
+                r.as_ref().map(|r| {
+                    m_i.iter()
+                        .zip(r.iter())
+                        .fold(F::zero(), |acc, (m_ij, r_j)| acc + *m_ij * r_j)
+                })
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+
+    fn external_linear_layer<const RATE: usize>(
+        self,
+        region: &mut Region<F>,
+        config: &Poseidon2Config<F, WIDTH, RATE>,
+        offset: usize,
+    ) -> Result<Self, Error> {
+        config.s_external_linear.enable(region, offset)?;
+
+        let next_state = Self::apply_m_e(config, self.values());
+        Self::assign_next_state(region, config, "initial", offset, next_state)
+    }
+
+    fn external_round<const RATE: usize>(
+        self,
+        region: &mut Region<F>,
+        config: &Poseidon2Config<F, WIDTH, RATE>,
+        round: usize,
+        offset: usize,
+    ) -> Result<Self, Error> {
+        config.s_external.enable(region, offset)?;
+
+        // Load the round constants.
+        for i in 0..WIDTH {
+            region.assign_fixed(
+                || format!("round_{} rc_{}", round, i),
+                config.rc[i],
+                offset,
+                || Value::known(config.round_constants[round][i]),
+            )?;
+        }
+
+        let r = self.values().map(|p| {
+            p.iter()
+                .zip(config.round_constants[round].iter())
+                .map(|(p_i, rc_i)| (*p_i + rc_i).pow(&config.alpha))
+                .collect()
+        });
+        let next_state = Self::apply_m_e(config, r);
+
+        Self::assign_next_state(
+            region,
+            config,
+            &format!("round_{}", round + 1),
+            offset,
+            next_state,
+        )
+    }
+
+    fn internal_rounds<const RATE: usize>(
+        self,
+        region: &mut Region<F>,
+        config: &Poseidon2Config<F, WIDTH, RATE>,
+        round: usize,
+        rounds: usize,
+        offset: usize,
+    ) -> Result<Self, Error> {
+        if rounds == WIDTH {
+            config.s_internal.enable(region, offset)?;
+        } else {
+            config
+                .s_internal_rem
+                .expect("configured when R_P is not a multiple of WIDTH")
+                .enable(region, offset)?;
+        }
+
+        let mut state = self.values();
+        for j in 0..rounds {
+            let rc = config.round_constants[round + j][0];
+            region.assign_fixed(
+                || format!("round_{} rc_0", round + j),
+                config.rc[j],
+                offset,
+                || Value::known(rc),
+            )?;
+
+            let sbox_out = state
+                .as_ref()
+                .map(|state| (state[0] + rc).pow(&config.alpha));
+            region.assign_advice(
+                || format!("round_{} sbox", round + j),
+                config.internal_sbox[j],
+                offset,
+                || sbox_out,
+            )?;
+
+            state = state.zip(sbox_out).map(|(mut state, sbox_out)| {
+                state[0] = sbox_out;
+                let sum = state.iter().fold(F::zero(), |acc, word| acc + word);
+                state
+                    .iter()
+                    .zip(config.internal_diagonal.iter())
+                    .map(|(word, d)| *word * d + sum)
+                    .collect()
+            });
+        }
+
+        let next_state = (0..WIDTH)
+            .map(|i| state.as_ref().map(|state| state[i]))
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+        Self::assign_next_state(
+            region,
+            config,
+            &format!("round_{}", round + rounds),
+            offset,
+            next_state,
+        )
+    }
+
+    fn assign_next_state<const RATE: usize>(
+        region: &mut Region<F>,
+        config: &Poseidon2Config<F, WIDTH, RATE>,
+        name: &str,
+        offset: usize,
+        next_state: [Value<F>; WIDTH],
+    ) -> Result<Self, Error> {
+        let next_state_word = |i: usize| {
+            let value = next_state[i];
+            let var = region.assign_advice(
+                || format!("{} state_{}", name, i),
+                config.state[i],
+                offset + 1,
+                || value,
+            )?;
+            Ok(StateWord(var))
+        };
+
+        let next_state: Result<Vec<_>, _> = (0..WIDTH).map(next_state_word).collect();
+        next_state.map(|next_state| Poseidon2State(next_state.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use group::ff::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use halo2curves::bn256::Fr;
+    use rand::rngs::OsRng;
+    use std::convert::TryInto;
+
+    use super::{Poseidon2Chip, Poseidon2Config};
+    use crate::poseidon::{
+        primitives::{self as poseidon, ConstantLength, Poseidon2Pow5T3Bn256},
+        Hash,
+    };
+
+    struct HashCircuit<const L: usize> {
+        message: Value<[Fr; L]>,
+        output: Value<Fr>,
+    }
+
+    impl<const L: usize> Circuit<Fr> for HashCircuit<L> {
+        type Config = Poseidon2Config<Fr, 3, 2>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                message: Value::unknown(),
+                output: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Poseidon2Config<Fr, 3, 2> {
+            let state = (0..3).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let internal_sbox = (0..3).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let rc = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            Poseidon2Chip::configure::<Poseidon2Pow5T3Bn256>(
+                meta,
+                state.try_into().unwrap(),
+                internal_sbox.try_into().unwrap(),
+                rc.try_into().unwrap(),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Poseidon2Config<Fr, 3, 2>,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let chip = Poseidon2Chip::construct(config.clone());
+
+            let message = layouter.assign_region(
+                || "load message",
+                |mut region| {
+                    let message_word = |i: usize| {
+                        let value = self.message.map(|message_vals| message_vals[i]);
+                        region.assign_advice(
+                            || format!("load message_{}", i),
+                            config.state[i % 3],
+                            i / 3,
+                            || value,
+                        )
+                    };
+
+                    let message: Result<Vec<_>, Error> = (0..L).map(message_word).collect();
+                    Ok(message?.try_into().unwrap())
+                },
+            )?;
+
+            let hasher = Hash::<_, _, Poseidon2Pow5T3Bn256, ConstantLength<L>, 3, 2>::init(
+                chip,
+                layouter.namespace(|| "init"),
+            )?;
+            let output = hasher.hash(layouter.namespace(|| "hash"), message)?;
+
+            layouter.assign_region(
+                || "constrain output",
+                |mut region| {
+                    let expected_var = region.assign_advice(
+                        || "load output",
+                        config.state[0],
+                        0,
+                        || self.output,
+                    )?;
+                    region.constrain_equal(output.cell(), expected_var.cell())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn poseidon2_hash() {
+        let rng = OsRng;
+
+        let message = [Fr::random(rng), Fr::random(rng), Fr::random(rng)];
+        let output = poseidon::Hash::<_, Poseidon2Pow5T3Bn256, ConstantLength<3>, 3, 2>::init()
+            .hash(message);
+
+        let k = 7;
+        let circuit = HashCircuit::<3> {
+            message: Value::known(message),
+            output: Value::known(output),
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let circuit = HashCircuit::<3> {
+            message: Value::known(message),
+            output: Value::known(output + Fr::one()),
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
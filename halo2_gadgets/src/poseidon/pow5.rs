@@ -25,6 +25,7 @@ pub struct Pow5Config<F: FieldExt, const WIDTH: usize, const RATE: usize> {
     rc_b: [Column<Fixed>; WIDTH],
     s_full: Selector,
     s_partial: Selector,
+    s_partial_single: Option<Selector>,
     s_pad_and_add: Selector,
 
     half_full_rounds: usize,
@@ -38,7 +39,8 @@ pub struct Pow5Config<F: FieldExt, const WIDTH: usize, const RATE: usize> {
 /// A Poseidon chip using an $x^5$ S-Box.
 ///
 /// The chip is implemented using a single round per row for full rounds, and two rounds
-/// per row for partial rounds.
+/// per row for partial rounds. If the number of partial rounds is odd, the last partial
+/// round occupies a row of its own.
 #[derive(Debug)]
 pub struct Pow5Chip<F: FieldExt, const WIDTH: usize, const RATE: usize> {
     config: Pow5Config<F, WIDTH, RATE>,
@@ -63,9 +65,8 @@ impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Pow5Chip<F, WIDTH, RATE
     ) -> Pow5Config<F, WIDTH, RATE> {
         assert_eq!(RATE, WIDTH - 1);
         // Generate constants for the Poseidon permutation.
-        // This gadget requires R_F and R_P to be even.
+        // This gadget requires R_F to be even.
         assert!(S::full_rounds() & 1 == 0);
-        assert!(S::partial_rounds() & 1 == 0);
         let half_full_rounds = S::full_rounds() / 2;
         let half_partial_rounds = S::partial_rounds() / 2;
         let (round_constants, m_reg, m_inv) = S::constants();
@@ -160,6 +161,44 @@ impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Pow5Chip<F, WIDTH, RATE
             )
         });
 
+        let s_partial_single = (S::partial_rounds() & 1 == 1).then(|| {
+            let s_partial_single = meta.selector();
+
+            meta.create_gate("single partial round", |meta| {
+                let s_partial_single = meta.query_selector(s_partial_single);
+
+                let r = (0..WIDTH)
+                    .map(|idx| {
+                        let state_cur = meta.query_advice(state[idx], Rotation::cur());
+                        let rc_a = meta.query_fixed(rc_a[idx], Rotation::cur());
+                        if idx == 0 {
+                            pow_5(state_cur + rc_a)
+                        } else {
+                            state_cur + rc_a
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                Constraints::with_selector(
+                    s_partial_single,
+                    (0..WIDTH)
+                        .map(|next_idx| {
+                            let state_next = meta.query_advice(state[next_idx], Rotation::next());
+                            let expr = r
+                                .iter()
+                                .enumerate()
+                                .map(|(idx, r_idx)| r_idx.clone() * m_reg[next_idx][idx])
+                                .reduce(|acc, term| acc + term)
+                                .expect("WIDTH > 0");
+                            expr - state_next
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            });
+
+            s_partial_single
+        });
+
         meta.create_gate("pad-and-add", |meta| {
             let initial_state_rate = meta.query_advice(state[RATE], Rotation::prev());
             let output_state_rate = meta.query_advice(state[RATE], Rotation::next());
@@ -193,6 +232,7 @@ impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Pow5Chip<F, WIDTH, RATE
             rc_b,
             s_full,
             s_partial,
+            s_partial_single,
             s_pad_and_add,
             half_full_rounds,
             half_partial_rounds,
@@ -255,13 +295,32 @@ impl<F: FieldExt, S: Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize
                     })
                 })?;
 
+                // An odd number of partial rounds leaves one to be done on its own.
+                let (state, partial_rounds, partial_rows) = match config.s_partial_single {
+                    Some(_) => (
+                        state.single_partial_round(
+                            &mut region,
+                            config,
+                            config.half_full_rounds + 2 * config.half_partial_rounds,
+                            config.half_full_rounds + config.half_partial_rounds,
+                        )?,
+                        2 * config.half_partial_rounds + 1,
+                        config.half_partial_rounds + 1,
+                    ),
+                    None => (
+                        state,
+                        2 * config.half_partial_rounds,
+                        config.half_partial_rounds,
+                    ),
+                };
+
                 let state = (0..config.half_full_rounds).fold(Ok(state), |res, r| {
                     res.and_then(|state| {
                         state.full_round(
                             &mut region,
                             config,
-                            config.half_full_rounds + 2 * config.half_partial_rounds + r,
-                            config.half_full_rounds + config.half_partial_rounds + r,
+                            config.half_full_rounds + partial_rounds + r,
+                            config.half_full_rounds + partial_rows + r,
                         )
                     })
                 })?;
@@ -403,7 +462,7 @@ impl<
 
 /// A word in the Poseidon state.
 #[derive(Clone, Debug)]
-pub struct StateWord<F: FieldExt>(AssignedCell<F, F>);
+pub struct StateWord<F: FieldExt>(pub(crate) AssignedCell<F, F>);
 
 impl<F: FieldExt> From<StateWord<F>> for AssignedCell<F, F> {
     fn from(state_word: StateWord<F>) -> AssignedCell<F, F> {
@@ -533,6 +592,44 @@ impl<F: FieldExt, const WIDTH: usize> Pow5State<F, WIDTH> {
         })
     }
 
+    fn single_partial_round<const RATE: usize>(
+        self,
+        region: &mut Region<F>,
+        config: &Pow5Config<F, WIDTH, RATE>,
+        round: usize,
+        offset: usize,
+    ) -> Result<Self, Error> {
+        let s_partial_single = config
+            .s_partial_single
+            .expect("only used with an odd number of partial rounds");
+        Self::round(region, config, round, offset, s_partial_single, |_| {
+            let m = &config.m_reg;
+            let p: Value<Vec<_>> = self.0.iter().map(|word| word.0.value().cloned()).collect();
+
+            let r: Value<Vec<_>> = p.map(|p| {
+                let r_0 = (p[0] + config.round_constants[round][0]).pow(&config.alpha);
+                let r_i = p[1..]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p_i)| *p_i + config.round_constants[round][i + 1]);
+                std::iter::empty().chain(Some(r_0)).chain(r_i).collect()
+            });
+
+            let state: Vec<Value<_>> = m
+                .iter()
+                .map(|m_i| {
+                    r.as_ref().map(|r| {
+                        m_i.iter()
+                            .zip(r.iter())
+                            .fold(F::zero(), |acc, (m_ij, r_j)| acc + *m_ij * r_j)
+                    })
+                })
+                .collect();
+
+            Ok((round + 1, state.try_into().unwrap()))
+        })
+    }
+
     fn load<const RATE: usize>(
         region: &mut Region<F>,
         config: &Pow5Config<F, WIDTH, RATE>,
@@ -595,37 +692,50 @@ impl<F: FieldExt, const WIDTH: usize> Pow5State<F, WIDTH> {
 #[cfg(test)]
 mod tests {
     use group::ff::{Field, PrimeField};
+    use group::{Curve, Group};
     use halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner, Value},
         dev::MockProver,
-        plonk::{Circuit, ConstraintSystem, Error},
+        plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+        transcript::{
+            encode_point_coordinates, EncodedChallenge, PoseidonRead, PoseidonWrite, Transcript,
+            TranscriptRead, TranscriptReadBuffer, TranscriptWrite, TranscriptWriterBuffer,
+        },
+    };
+    use halo2curves::{
+        bn256::Fr,
+        pasta::{pallas, vesta, Fp},
+        FieldExt,
     };
-    use halo2curves::pasta::{pallas, Fp};
     use rand::rngs::OsRng;
 
     use super::{PoseidonInstructions, Pow5Chip, Pow5Config, StateWord};
     use crate::poseidon::{
-        primitives::{self as poseidon, ConstantLength, P128Pow5T3 as OrchardNullifier, Spec},
-        Hash,
+        primitives::{
+            self as poseidon, ConstantLength, P128Pow5T13Bn256, P128Pow5T3 as OrchardNullifier,
+            P128Pow5T3Bn256, P128Pow5T5Bn256, P128Pow5T9Bn256, Permutation, Spec, TranscriptDomain,
+            VariableLength,
+        },
+        Hash, PaddedWord, Sponge, VariableLengthPaddingChip, VariableLengthPaddingConfig,
     };
     use std::convert::TryInto;
     use std::marker::PhantomData;
 
-    struct PermuteCircuit<S: Spec<Fp, WIDTH, RATE>, const WIDTH: usize, const RATE: usize>(
-        PhantomData<S>,
+    struct PermuteCircuit<F: FieldExt, S: Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize>(
+        PhantomData<(F, S)>,
     );
 
-    impl<S: Spec<Fp, WIDTH, RATE>, const WIDTH: usize, const RATE: usize> Circuit<Fp>
-        for PermuteCircuit<S, WIDTH, RATE>
+    impl<F: FieldExt, S: Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize> Circuit<F>
+        for PermuteCircuit<F, S, WIDTH, RATE>
     {
-        type Config = Pow5Config<Fp, WIDTH, RATE>;
+        type Config = Pow5Config<F, WIDTH, RATE>;
         type FloorPlanner = SimpleFloorPlanner;
 
         fn without_witnesses(&self) -> Self {
-            PermuteCircuit::<S, WIDTH, RATE>(PhantomData)
+            PermuteCircuit::<F, S, WIDTH, RATE>(PhantomData)
         }
 
-        fn configure(meta: &mut ConstraintSystem<Fp>) -> Pow5Config<Fp, WIDTH, RATE> {
+        fn configure(meta: &mut ConstraintSystem<F>) -> Pow5Config<F, WIDTH, RATE> {
             let state = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
             let partial_sbox = meta.advice_column();
 
@@ -643,14 +753,14 @@ mod tests {
 
         fn synthesize(
             &self,
-            config: Pow5Config<Fp, WIDTH, RATE>,
-            mut layouter: impl Layouter<Fp>,
+            config: Pow5Config<F, WIDTH, RATE>,
+            mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
             let initial_state = layouter.assign_region(
                 || "prepare initial state",
                 |mut region| {
                     let state_word = |i: usize| {
-                        let value = Value::known(Fp::from(i as u64));
+                        let value = Value::known(F::from(i as u64));
                         let var = region.assign_advice(
                             || format!("load state_{}", i),
                             config.state[i],
@@ -667,7 +777,7 @@ mod tests {
 
             let chip = Pow5Chip::construct(config.clone());
             let final_state = <Pow5Chip<_, WIDTH, RATE> as PoseidonInstructions<
-                Fp,
+                F,
                 S,
                 WIDTH,
                 RATE,
@@ -675,7 +785,7 @@ mod tests {
 
             // For the purpose of this test, compute the real final state inline.
             let mut expected_final_state = (0..WIDTH)
-                .map(|idx| Fp::from(idx as u64))
+                .map(|idx| F::from(idx as u64))
                 .collect::<Vec<_>>()
                 .try_into()
                 .unwrap();
@@ -712,11 +822,27 @@ mod tests {
     #[test]
     fn poseidon_permute() {
         let k = 6;
-        let circuit = PermuteCircuit::<OrchardNullifier, 3, 2>(PhantomData);
+        let circuit = PermuteCircuit::<Fp, OrchardNullifier, 3, 2>(PhantomData);
         let prover = MockProver::run(k, &circuit, vec![]).unwrap();
         assert_eq!(prover.verify(), Ok(()))
     }
 
+    #[test]
+    fn poseidon_permute_bn256() {
+        fn check<S: Spec<Fr, WIDTH, RATE>, const WIDTH: usize, const RATE: usize>() {
+            let k = 7;
+            let circuit = PermuteCircuit::<Fr, S, WIDTH, RATE>(PhantomData);
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()))
+        }
+
+        // The specs for widths 3, 9 and 13 have an odd number of partial rounds.
+        check::<P128Pow5T3Bn256, 3, 2>();
+        check::<P128Pow5T5Bn256, 5, 4>();
+        check::<P128Pow5T9Bn256, 9, 8>();
+        check::<P128Pow5T13Bn256, 13, 12>();
+    }
+
     struct HashCircuit<
         S: Spec<Fp, WIDTH, RATE>,
         const WIDTH: usize,
@@ -865,6 +991,244 @@ mod tests {
         }
     }
 
+    struct VariableLengthHashCircuit<const MAX: usize> {
+        message: Value<[Fp; MAX]>,
+        len: Value<Fp>,
+        output: Value<Fp>,
+    }
+
+    impl<const MAX: usize> Circuit<Fp> for VariableLengthHashCircuit<MAX> {
+        type Config = (Pow5Config<Fp, 3, 2>, VariableLengthPaddingConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                message: Value::unknown(),
+                len: Value::unknown(),
+                output: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let state = (0..3).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let partial_sbox = meta.advice_column();
+
+            let rc_a = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            let rc_b = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+
+            meta.enable_constant(rc_b[0]);
+
+            let padding = VariableLengthPaddingChip::configure(
+                meta,
+                [state[0], state[1], state[2], partial_sbox],
+            );
+            let poseidon = Pow5Chip::configure::<OrchardNullifier>(
+                meta,
+                state.try_into().unwrap(),
+                partial_sbox,
+                rc_a.try_into().unwrap(),
+                rc_b.try_into().unwrap(),
+            );
+            (poseidon, padding)
+        }
+
+        fn synthesize(
+            &self,
+            (config, padding_config): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = Pow5Chip::construct(config.clone());
+            let padding_chip = VariableLengthPaddingChip::construct(padding_config);
+
+            let (message, len) = layouter.assign_region(
+                || "load message",
+                |mut region| {
+                    let mut message_word = |i: usize| {
+                        region.assign_advice(
+                            || format!("load message_{}", i),
+                            config.state[0],
+                            i,
+                            || self.message.map(|message| message[i]),
+                        )
+                    };
+
+                    let message: Result<Vec<_>, Error> = (0..MAX).map(&mut message_word).collect();
+                    let len =
+                        region.assign_advice(|| "load len", config.state[1], 0, || self.len)?;
+                    Ok((message?.try_into().unwrap(), len))
+                },
+            )?;
+
+            let hasher = Hash::<_, _, OrchardNullifier, VariableLength<MAX>, 3, 2>::init(
+                chip,
+                layouter.namespace(|| "init"),
+            )?;
+            let output = hasher.hash(layouter.namespace(|| "hash"), &padding_chip, message, len)?;
+
+            layouter.assign_region(
+                || "constrain output",
+                |mut region| {
+                    let expected_var = region.assign_advice(
+                        || "load output",
+                        config.state[0],
+                        0,
+                        || self.output,
+                    )?;
+                    region.constrain_equal(output.cell(), expected_var.cell())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn poseidon_hash_variable_length() {
+        let rng = OsRng;
+
+        let message = [(); 5].map(|_| Fp::random(rng));
+        let circuit = |len: usize, output: Fp| VariableLengthHashCircuit::<5> {
+            message: Value::known(message),
+            len: Value::known(Fp::from(len as u64)),
+            output: Value::known(output),
+        };
+
+        for len in 0..=5 {
+            let output = poseidon::Hash::<_, OrchardNullifier, VariableLength<5>, 3, 2>::init()
+                .hash(&message[..len]);
+            let prover = MockProver::run(8, &circuit(len, output), vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            let prover = MockProver::run(8, &circuit(len, output + Fp::one()), vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+
+        // A length greater than the maximum cannot be witnessed.
+        let output =
+            poseidon::Hash::<_, OrchardNullifier, VariableLength<5>, 3, 2>::init().hash(&message);
+        let prover = MockProver::run(8, &circuit(6, output), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// Absorbs each segment of `absorbed` in turn, squeezing a challenge after each
+    /// one, and constrains the challenges to equal the instance column.
+    struct TranscriptCircuit {
+        absorbed: Vec<Vec<Value<Fp>>>,
+    }
+
+    impl Circuit<Fp> for TranscriptCircuit {
+        type Config = (Pow5Config<Fp, 3, 2>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                absorbed: self
+                    .absorbed
+                    .iter()
+                    .map(|segment| vec![Value::unknown(); segment.len()])
+                    .collect(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let state = (0..3).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let partial_sbox = meta.advice_column();
+
+            let rc_a = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            let rc_b = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+
+            meta.enable_constant(rc_b[0]);
+
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let config = Pow5Chip::configure::<OrchardNullifier>(
+                meta,
+                state.try_into().unwrap(),
+                partial_sbox,
+                rc_a.try_into().unwrap(),
+                rc_b.try_into().unwrap(),
+            );
+            (config, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = Pow5Chip::construct(config.clone());
+            let mut sponge = Sponge::<_, _, OrchardNullifier, _, TranscriptDomain, 3, 2>::new(
+                chip,
+                layouter.namespace(|| "init"),
+            )?;
+
+            for (i, segment) in self.absorbed.iter().enumerate() {
+                for value in segment {
+                    let value = layouter.assign_region(
+                        || "load value",
+                        |mut region| {
+                            region.assign_advice(|| "value", config.state[0], 0, || *value)
+                        },
+                    )?;
+                    sponge.absorb(layouter.namespace(|| "absorb"), PaddedWord::Message(value))?;
+                }
+                let challenge = sponge.squeeze_challenge(layouter.namespace(|| "squeeze"))?;
+                layouter.constrain_instance(challenge.cell(), instance, i)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn transcript_equivalence() {
+        type P = Permutation<Fp, OrchardNullifier, 3, 2>;
+
+        let rng = OsRng;
+        let point = vesta::Point::random(rng).to_affine();
+        let scalars: Vec<Fp> = (0..4).map(|_| Fp::random(rng)).collect();
+
+        let mut transcript = PoseidonWrite::<Vec<u8>, vesta::Affine, P, 3, 2>::init(Vec::new());
+        transcript.write_scalar(scalars[0]).unwrap();
+        transcript.write_point(point).unwrap();
+        let mut challenges = vec![transcript.squeeze_challenge().get_scalar()];
+        // Squeezing without absorbing anything pads a whole block.
+        challenges.push(transcript.squeeze_challenge().get_scalar());
+        for scalar in &scalars[1..] {
+            transcript.write_scalar(*scalar).unwrap();
+        }
+        challenges.push(transcript.squeeze_challenge().get_scalar());
+        let proof = transcript.finalize();
+
+        // The verifier reproduces the challenges.
+        let mut transcript = PoseidonRead::<_, vesta::Affine, P, 3, 2>::init(&proof[..]);
+        assert_eq!(transcript.read_scalar().unwrap(), scalars[0]);
+        assert_eq!(transcript.read_point().unwrap(), point);
+        assert_eq!(transcript.squeeze_challenge().get_scalar(), challenges[0]);
+        assert_eq!(transcript.squeeze_challenge().get_scalar(), challenges[1]);
+        for scalar in &scalars[1..] {
+            assert_eq!(transcript.read_scalar().unwrap(), *scalar);
+        }
+        assert_eq!(transcript.squeeze_challenge().get_scalar(), challenges[2]);
+
+        // So does the in-circuit sponge.
+        let first_segment = std::iter::once(scalars[0])
+            .chain(encode_point_coordinates(&point))
+            .collect::<Vec<_>>();
+        let circuit = TranscriptCircuit {
+            absorbed: [first_segment, vec![], scalars[1..].to_vec()]
+                .iter()
+                .map(|segment| segment.iter().copied().map(Value::known).collect())
+                .collect(),
+        };
+        let prover = MockProver::run(10, &circuit, vec![challenges.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let mut wrong = challenges;
+        wrong[1] += Fp::one();
+        let prover = MockProver::run(10, &circuit, vec![wrong]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
     #[cfg(feature = "dev-graph")]
     #[test]
     fn print_poseidon_chip() {
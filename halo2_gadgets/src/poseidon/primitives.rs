@@ -5,7 +5,10 @@ use std::fmt;
 use std::iter;
 use std::marker::PhantomData;
 
-use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    transcript::{PoseidonPermutation, POSEIDON_TRANSCRIPT_DOMAIN},
+};
 
 pub(crate) mod fp;
 pub(crate) mod fq;
@@ -18,6 +21,12 @@ pub(crate) mod test_vectors;
 mod p128pow5t3;
 pub use p128pow5t3::P128Pow5T3;
 
+mod bn256;
+pub use bn256::{P128Pow5T13Bn256, P128Pow5T3Bn256, P128Pow5T5Bn256, P128Pow5T9Bn256};
+
+mod poseidon2;
+pub use poseidon2::{Poseidon2Pow5T3Bn256, Poseidon2Spec};
+
 use grain::SboxType;
 
 /// The type used to hold permutation state.
@@ -51,32 +60,60 @@ pub trait Spec<F: FieldExt, const T: usize, const RATE: usize>: fmt::Debug {
 
     /// Generates `(round_constants, mds, mds^-1)` corresponding to this specification.
     fn constants() -> (Vec<[F; T]>, Mds<F, T>, Mds<F, T>) {
-        let r_f = Self::full_rounds();
-        let r_p = Self::partial_rounds();
-
-        let mut grain = grain::Grain::new(SboxType::Pow, T as u16, r_f as u16, r_p as u16);
-
-        let round_constants = (0..(r_f + r_p))
-            .map(|_| {
-                let mut rc_row = [F::zero(); T];
-                for (rc, value) in rc_row
-                    .iter_mut()
-                    .zip((0..T).map(|_| grain.next_field_element()))
-                {
-                    *rc = value;
-                }
-                rc_row
-            })
-            .collect();
-
-        let (mds, mds_inv) = mds::generate_mds::<F, T>(&mut grain, Self::secure_mds());
+        generate_constants::<F, Self, T, RATE>()
+    }
 
-        (round_constants, mds, mds_inv)
+    /// Applies the permutation for this specification to the given state, using the
+    /// matrix and round constants from [`Spec::constants`].
+    ///
+    /// This is the Poseidon permutation by default. Specifications of another
+    /// permutation over the same sponge, such as [`Poseidon2Spec`], override it.
+    fn permute(state: &mut State<F, T>, mds: &Mds<F, T>, round_constants: &[[F; T]]) {
+        permute::<F, Self, T, RATE>(state, mds, round_constants)
     }
 }
 
+/// Generates `(round_constants, mds, mds^-1)` for the specification `S` with the Grain
+/// LFSR, as in the reference implementation.
+///
+/// This is the default implementation of [`Spec::constants`], exposed so that specs
+/// overriding it (for example to cache the constants) can still derive them.
+pub(crate) fn generate_constants<
+    F: FieldExt,
+    S: Spec<F, T, RATE> + ?Sized,
+    const T: usize,
+    const RATE: usize,
+>() -> (Vec<[F; T]>, Mds<F, T>, Mds<F, T>) {
+    let r_f = S::full_rounds();
+    let r_p = S::partial_rounds();
+
+    let mut grain = grain::Grain::new(SboxType::Pow, T as u16, r_f as u16, r_p as u16);
+
+    let round_constants = (0..(r_f + r_p))
+        .map(|_| {
+            let mut rc_row = [F::zero(); T];
+            for (rc, value) in rc_row
+                .iter_mut()
+                .zip((0..T).map(|_| grain.next_field_element()))
+            {
+                *rc = value;
+            }
+            rc_row
+        })
+        .collect();
+
+    let (mds, mds_inv) = mds::generate_mds::<F, T>(&mut grain, S::secure_mds());
+
+    (round_constants, mds, mds_inv)
+}
+
 /// Runs the Poseidon permutation on the given state.
-pub(crate) fn permute<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize>(
+pub(crate) fn permute<
+    F: FieldExt,
+    S: Spec<F, T, RATE> + ?Sized,
+    const T: usize,
+    const RATE: usize,
+>(
     state: &mut State<F, T>,
     mds: &Mds<F, T>,
     round_constants: &[[F; T]],
@@ -123,6 +160,43 @@ pub(crate) fn permute<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RA
         });
 }
 
+/// The Poseidon permutation specified by `S`, with its constants precomputed.
+///
+/// This is the permutation to use with the [`PoseidonRead`] and [`PoseidonWrite`]
+/// transcripts of `halo2_proofs`, so that their challenges can be reproduced in a
+/// circuit by a [`Sponge`] over the [`TranscriptDomain`].
+///
+/// [`PoseidonRead`]: halo2_proofs::transcript::PoseidonRead
+/// [`PoseidonWrite`]: halo2_proofs::transcript::PoseidonWrite
+/// [`Sponge`]: crate::poseidon::Sponge
+#[derive(Clone, Debug)]
+pub struct Permutation<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize> {
+    mds_matrix: Mds<F, T>,
+    round_constants: Vec<[F; T]>,
+    _marker: PhantomData<S>,
+}
+
+impl<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize> Default
+    for Permutation<F, S, T, RATE>
+{
+    fn default() -> Self {
+        let (round_constants, mds_matrix, _) = S::constants();
+        Permutation {
+            mds_matrix,
+            round_constants,
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize>
+    PoseidonPermutation<F, T, RATE> for Permutation<F, S, T, RATE>
+{
+    fn permute(&self, state: &mut [F; T]) {
+        S::permute(state, &self.mds_matrix, &self.round_constants);
+    }
+}
+
 fn poseidon_sponge<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize>(
     state: &mut State<F, T>,
     input: Option<&Absorbing<F, RATE>>,
@@ -137,7 +211,7 @@ fn poseidon_sponge<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE:
         }
     }
 
-    permute::<F, S, T, RATE>(state, mds_matrix, round_constants);
+    S::permute(state, mds_matrix, round_constants);
 
     let mut output = [None; RATE];
     for (word, value) in output.iter_mut().zip(state.iter()) {
@@ -319,6 +393,75 @@ impl<F: FieldExt, const RATE: usize, const L: usize> Domain<F, RATE> for Constan
     }
 }
 
+/// A Poseidon hash function used with messages of any length up to `MAX`.
+///
+/// The message is padded with a single one followed by zeros, as in
+/// [ePrint 2019/458 section 4.2](https://eprint.iacr.org/2019/458.pdf), but always up to
+/// the number of blocks needed for a message of length `MAX`. The number of permutations
+/// is then independent of the message length, so that a circuit can hash a message whose
+/// length is witnessed.
+///
+/// The capacity element is $MAX \cdot 2^{64} + 1$, which separates this domain from
+/// [`ConstantLength`] (and from `VariableLength` with another `MAX`).
+#[derive(Clone, Copy, Debug)]
+pub struct VariableLength<const MAX: usize>;
+
+impl<const MAX: usize> VariableLength<MAX> {
+    /// The total length of a padded message, a multiple of `RATE`.
+    pub(crate) fn padded_len<const RATE: usize>() -> usize {
+        // There is always room for at least the one padding element.
+        let k = (MAX + RATE) / RATE;
+        k * RATE
+    }
+}
+
+impl<F: FieldExt, const RATE: usize, const MAX: usize> Domain<F, RATE> for VariableLength<MAX> {
+    type Padding = iter::Chain<iter::Once<F>, iter::Take<iter::Repeat<F>>>;
+
+    fn name() -> String {
+        format!("VariableLength<{}>", MAX)
+    }
+
+    fn initial_capacity_element() -> F {
+        F::from_u128(((MAX as u128) << 64) + 1)
+    }
+
+    fn padding(input_len: usize) -> Self::Padding {
+        assert!(input_len <= MAX);
+        iter::once(F::one())
+            .chain(iter::repeat(F::zero()).take(Self::padded_len::<RATE>() - input_len - 1))
+    }
+}
+
+/// The domain of the Poseidon transcripts of `halo2_proofs`.
+///
+/// A transcript pads the elements absorbed since its last squeeze with zeros to a
+/// multiple of `RATE`, using `RATE` zeros if nothing was absorbed. See
+/// [`halo2_proofs::transcript::PoseidonRead`] for the full schedule.
+#[derive(Clone, Copy, Debug)]
+pub struct TranscriptDomain;
+
+impl<F: FieldExt, const RATE: usize> Domain<F, RATE> for TranscriptDomain {
+    type Padding = iter::Take<iter::Repeat<F>>;
+
+    fn name() -> String {
+        "Transcript".to_string()
+    }
+
+    fn initial_capacity_element() -> F {
+        F::from_u128(POSEIDON_TRANSCRIPT_DOMAIN)
+    }
+
+    fn padding(input_len: usize) -> Self::Padding {
+        let padding_len = if input_len == 0 {
+            RATE
+        } else {
+            (RATE - input_len % RATE) % RATE
+        };
+        iter::repeat(F::zero()).take(padding_len)
+    }
+}
+
 /// A Poseidon hash function, built around a sponge.
 pub struct Hash<
     F: FieldExt,
@@ -372,11 +515,36 @@ impl<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize, const
     }
 }
 
+impl<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize, const MAX: usize>
+    Hash<F, S, VariableLength<MAX>, T, RATE>
+{
+    /// Hashes the given input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the message is longer than `MAX`.
+    pub fn hash(mut self, message: &[F]) -> F {
+        for value in
+            message
+                .iter()
+                .copied()
+                .chain(<VariableLength<MAX> as Domain<F, RATE>>::padding(
+                    message.len(),
+                ))
+        {
+            self.sponge.absorb(value);
+        }
+        self.sponge.finish_absorbing().squeeze()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use halo2curves::{pasta::pallas, FieldExt};
 
-    use super::{permute, ConstantLength, Hash, P128Pow5T3 as OrchardNullifier, Spec};
+    use super::{
+        permute, ConstantLength, Hash, P128Pow5T3 as OrchardNullifier, Spec, VariableLength,
+    };
 
     #[test]
     fn orchard_spec_equivalence() {
@@ -393,4 +561,40 @@ mod tests {
         permute::<_, OrchardNullifier, 3, 2>(&mut state, &mds, &round_constants);
         assert_eq!(state[0], result);
     }
+
+    #[test]
+    fn variable_length() {
+        let message = [pallas::Base::from(6), pallas::Base::from(42)];
+
+        let (round_constants, mds, _) = OrchardNullifier::constants();
+
+        let hash = |message: &[pallas::Base]| {
+            Hash::<_, OrchardNullifier, VariableLength<3>, 3, 2>::init().hash(message)
+        };
+
+        // A message of up to three elements is padded to two blocks.
+        let mut state = [
+            message[0],
+            message[1],
+            pallas::Base::from_u128((3 << 64) + 1),
+        ];
+        permute::<_, OrchardNullifier, 3, 2>(&mut state, &mds, &round_constants);
+        state[0] += pallas::Base::one();
+        permute::<_, OrchardNullifier, 3, 2>(&mut state, &mds, &round_constants);
+        assert_eq!(state[0], hash(&message));
+
+        // The padding distinguishes messages with trailing zeros.
+        assert_ne!(
+            hash(&message),
+            hash(&[message[0], message[1], pallas::Base::zero()])
+        );
+        assert_ne!(
+            hash(&message[..1]),
+            hash(&[message[0], pallas::Base::zero()])
+        );
+        assert_ne!(
+            hash(&message),
+            Hash::<_, OrchardNullifier, ConstantLength<2>, 3, 2>::init().hash(message)
+        );
+    }
 }
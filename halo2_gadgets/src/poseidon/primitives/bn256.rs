@@ -0,0 +1,233 @@
+//! Poseidon specifications for the BN254 scalar field.
+//!
+//! These use the parameters of [circomlib] (and the `poseidon-rs` port by iden3): an
+//! $x^5$ S-box, $R_F = 8$, and the number of partial rounds that the reference
+//! `calc_round_numbers.py` script gives for 128-bit security at each width. The round
+//! constants and MDS matrices are derived with the Grain LFSR as in the reference
+//! implementation, and cached on first use:
+//!
+//! ```text
+//! $ sage generate_parameters_grain.sage 1 0 254 <t> 8 <R_P> 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001
+//! ```
+//!
+//! circomlib hashes a message `m` by permuting `[0, m_0, .., m_{t-2}]` and taking the
+//! first word of the result. This differs from the sponge construction in
+//! [`Hash`](super::Hash), which places the domain in the last (capacity) word, so the
+//! two only agree on the permutation itself.
+//!
+//! [circomlib]: https://github.com/iden3/circomlib/blob/master/circuits/poseidon.circom
+
+use halo2_proofs::arithmetic::Field;
+use halo2curves::bn256::Fr;
+use lazy_static::lazy_static;
+
+use super::{generate_constants, Mds, Spec};
+
+lazy_static! {
+    static ref T3_CONSTANTS: (Vec<[Fr; 3]>, Mds<Fr, 3>, Mds<Fr, 3>) =
+        generate_constants::<_, P128Pow5T3Bn256, 3, 2>();
+    static ref T5_CONSTANTS: (Vec<[Fr; 5]>, Mds<Fr, 5>, Mds<Fr, 5>) =
+        generate_constants::<_, P128Pow5T5Bn256, 5, 4>();
+    static ref T9_CONSTANTS: (Vec<[Fr; 9]>, Mds<Fr, 9>, Mds<Fr, 9>) =
+        generate_constants::<_, P128Pow5T9Bn256, 9, 8>();
+    static ref T13_CONSTANTS: (Vec<[Fr; 13]>, Mds<Fr, 13>, Mds<Fr, 13>) =
+        generate_constants::<_, P128Pow5T13Bn256, 13, 12>();
+}
+
+/// Poseidon-128 over the BN254 scalar field using the $x^5$ S-box, with a width of 3
+/// field elements ($R_F = 8, R_P = 57$).
+#[derive(Debug)]
+pub struct P128Pow5T3Bn256;
+
+impl Spec<Fr, 3, 2> for P128Pow5T3Bn256 {
+    fn full_rounds() -> usize {
+        8
+    }
+
+    fn partial_rounds() -> usize {
+        57
+    }
+
+    fn sbox(val: Fr) -> Fr {
+        val.pow_vartime(&[5])
+    }
+
+    fn secure_mds() -> usize {
+        0
+    }
+
+    fn constants() -> (Vec<[Fr; 3]>, Mds<Fr, 3>, Mds<Fr, 3>) {
+        T3_CONSTANTS.clone()
+    }
+}
+
+/// Poseidon-128 over the BN254 scalar field using the $x^5$ S-box, with a width of 5
+/// field elements ($R_F = 8, R_P = 60$).
+#[derive(Debug)]
+pub struct P128Pow5T5Bn256;
+
+impl Spec<Fr, 5, 4> for P128Pow5T5Bn256 {
+    fn full_rounds() -> usize {
+        8
+    }
+
+    fn partial_rounds() -> usize {
+        60
+    }
+
+    fn sbox(val: Fr) -> Fr {
+        val.pow_vartime(&[5])
+    }
+
+    fn secure_mds() -> usize {
+        0
+    }
+
+    fn constants() -> (Vec<[Fr; 5]>, Mds<Fr, 5>, Mds<Fr, 5>) {
+        T5_CONSTANTS.clone()
+    }
+}
+
+/// Poseidon-128 over the BN254 scalar field using the $x^5$ S-box, with a width of 9
+/// field elements ($R_F = 8, R_P = 63$).
+#[derive(Debug)]
+pub struct P128Pow5T9Bn256;
+
+impl Spec<Fr, 9, 8> for P128Pow5T9Bn256 {
+    fn full_rounds() -> usize {
+        8
+    }
+
+    fn partial_rounds() -> usize {
+        63
+    }
+
+    fn sbox(val: Fr) -> Fr {
+        val.pow_vartime(&[5])
+    }
+
+    fn secure_mds() -> usize {
+        0
+    }
+
+    fn constants() -> (Vec<[Fr; 9]>, Mds<Fr, 9>, Mds<Fr, 9>) {
+        T9_CONSTANTS.clone()
+    }
+}
+
+/// Poseidon-128 over the BN254 scalar field using the $x^5$ S-box, with a width of 13
+/// field elements ($R_F = 8, R_P = 65$).
+#[derive(Debug)]
+pub struct P128Pow5T13Bn256;
+
+impl Spec<Fr, 13, 12> for P128Pow5T13Bn256 {
+    fn full_rounds() -> usize {
+        8
+    }
+
+    fn partial_rounds() -> usize {
+        65
+    }
+
+    fn sbox(val: Fr) -> Fr {
+        val.pow_vartime(&[5])
+    }
+
+    fn secure_mds() -> usize {
+        0
+    }
+
+    fn constants() -> (Vec<[Fr; 13]>, Mds<Fr, 13>, Mds<Fr, 13>) {
+        T13_CONSTANTS.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::PrimeField;
+
+    use super::{Fr, P128Pow5T13Bn256, P128Pow5T3Bn256, P128Pow5T5Bn256, P128Pow5T9Bn256};
+    use crate::poseidon::primitives::{permute, Spec};
+
+    /// Permutes `[0, 1, .., T - 1]`, which is the state circomlib permutes to hash
+    /// `[1, .., T - 1]`.
+    fn permute_indices<S: Spec<Fr, T, RATE>, const T: usize, const RATE: usize>() -> [Fr; T] {
+        let (round_constants, mds, _) = S::constants();
+        assert_eq!(
+            round_constants.len(),
+            S::full_rounds() + S::partial_rounds()
+        );
+
+        let mut state = [Fr::zero(); T];
+        for (i, word) in state.iter_mut().enumerate() {
+            *word = Fr::from(i as u64);
+        }
+        permute::<Fr, S, T, RATE>(&mut state, &mds, &round_constants);
+        state
+    }
+
+    fn check_permutation<S: Spec<Fr, T, RATE>, const T: usize, const RATE: usize>(
+        expected: [&str; T],
+    ) {
+        let state = permute_indices::<S, T, RATE>();
+        for (actual, expected) in state.iter().zip(expected.iter()) {
+            assert_eq!(*actual, Fr::from_str_vartime(expected).unwrap());
+        }
+    }
+
+    fn check_hash<S: Spec<Fr, T, RATE>, const T: usize, const RATE: usize>(expected: &str) {
+        let state = permute_indices::<S, T, RATE>();
+        assert_eq!(state[0], Fr::from_str_vartime(expected).unwrap());
+    }
+
+    #[test]
+    fn round_constants() {
+        // The first round constant of each width, from circomlib's `poseidon_constants`.
+        let (rc, _, _) = P128Pow5T3Bn256::constants();
+        assert_eq!(
+            rc[0][0],
+            Fr::from_raw([
+                0x8d21_d473_04cd_8e6e,
+                0x14c4_993c_11bb_2993,
+                0xd059_86d6_56f4_0c21,
+                0x0ee9_a592_ba9a_9518,
+            ])
+        );
+        let (rc, _, _) = P128Pow5T5Bn256::constants();
+        assert_eq!(
+            rc[0][0],
+            Fr::from_raw([
+                0x4f3c_2bd8_1a6d_a891,
+                0xd889_bb4e_bd47_c386,
+                0x7f53_e29c_cac9_8ed7,
+                0x0eb5_44fe_e281_5dda,
+            ])
+        );
+    }
+
+    #[test]
+    fn circomlib_test_vectors() {
+        // circomlib's Poseidon([1, 2]) and Poseidon([1, 2, 3, 4]) are the first words
+        // of these permutations.
+        check_permutation::<P128Pow5T3Bn256, 3, 2>([
+            "7853200120776062878684798364095072458815029376092732009249414926327459813530",
+            "7142104613055408817911962100316808866448378443474503659992478482890339429929",
+            "6549537674122432311777789598043107870002137484850126429160507761192163713804",
+        ]);
+        check_permutation::<P128Pow5T5Bn256, 5, 4>([
+            "18821383157269793795438455681495246036402687001665670618754263018637548127333",
+            "7817711165059374331357136443537800893307845083525445872661165200086166013245",
+            "16733335996448830230979566039396561240864200624113062088822991822580465420551",
+            "6644334865470350789317807668685953492649391266180911382577082600917830417726",
+            "3372108894677221197912083238087960099443657816445944159266857514496320565191",
+        ]);
+
+        // circomlib's Poseidon([1, .., 8]) and Poseidon([1, .., 12]).
+        check_hash::<P128Pow5T9Bn256, 9, 8>(
+            "18604317144381847857886385684060986177838410221561136253933256952257712543953",
+        );
+        check_hash::<P128Pow5T13Bn256, 13, 12>(
+            "2501997477381648492950318384533644783248002172679259592360114615426357826485",
+        );
+    }
+}
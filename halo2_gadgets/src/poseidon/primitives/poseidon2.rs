@@ -0,0 +1,296 @@
+//! The Poseidon2 permutation.
+//!
+//! Poseidon2 ([ePrint 2023/323](https://eprint.iacr.org/2023/323)) keeps the rounds and
+//! S-box of Poseidon, but replaces its MDS matrix with two cheaper linear layers:
+//!
+//! - the external matrix $M_E$, applied to the input and after every full round;
+//! - the internal matrix $M_I = J + \mathrm{diag}(d)$, where $J$ is the all-ones matrix,
+//!   applied after every partial round. Computing $M_I \cdot x$ only needs the sum of
+//!   $x$ and one multiplication per word.
+//!
+//! Partial rounds also only add a round constant to the first word.
+
+use halo2_proofs::arithmetic::{Field, FieldExt};
+use halo2curves::bn256::Fr;
+use lazy_static::lazy_static;
+
+use super::{
+    grain::{Grain, SboxType},
+    Mds, Spec, State,
+};
+
+/// A specification for a Poseidon2 permutation.
+///
+/// [`Spec::constants`] returns the round constants (which are zero in all but the first
+/// word for partial rounds), the external matrix $M_E$ and its inverse, and
+/// [`Spec::permute`] must be overridden to apply the Poseidon2 permutation.
+pub trait Poseidon2Spec<F: FieldExt, const T: usize, const RATE: usize>: Spec<F, T, RATE> {
+    /// The diagonal $d$ of the internal matrix $M_I = J + \mathrm{diag}(d)$.
+    fn internal_diagonal() -> [F; T];
+}
+
+/// Returns the external matrix $M_E$ of Poseidon2 for a width of `T`.
+///
+/// # Panics
+///
+/// Panics if `T` is not 2, 3, or a multiple of 4.
+#[allow(clippy::needless_range_loop)]
+pub(crate) fn external_matrix<F: FieldExt, const T: usize>() -> Mds<F, T> {
+    let mut m_e = [[F::zero(); T]; T];
+    match T {
+        // circ(2, 1) and circ(2, 1, 1).
+        2 | 3 => {
+            for i in 0..T {
+                for j in 0..T {
+                    m_e[i][j] = F::from(if i == j { 2u64 } else { 1 });
+                }
+            }
+        }
+        // circ(2 M_4, M_4, .., M_4) for the 4x4 MDS matrix M_4.
+        _ if T % 4 == 0 => {
+            const M_4: [[u64; 4]; 4] = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]];
+            for i in 0..T {
+                for j in 0..T {
+                    let scale = if i / 4 == j / 4 { 2 } else { 1 };
+                    m_e[i][j] = F::from(scale * M_4[i % 4][j % 4]);
+                }
+            }
+        }
+        _ => panic!("Poseidon2 is not defined for a width of {}", T),
+    }
+    m_e
+}
+
+/// Inverts a matrix with Gauss-Jordan elimination.
+fn invert<F: FieldExt, const T: usize>(m: &Mds<F, T>) -> Mds<F, T> {
+    let mut m = *m;
+    let mut inv = [[F::zero(); T]; T];
+    for (i, row) in inv.iter_mut().enumerate() {
+        row[i] = F::one();
+    }
+
+    for col in 0..T {
+        let pivot = (col..T)
+            .find(|row| !m[*row][col].is_zero_vartime())
+            .expect("matrix is invertible");
+        m.swap(col, pivot);
+        inv.swap(col, pivot);
+
+        let scale = m[col][col].invert().unwrap();
+        for j in 0..T {
+            m[col][j] *= scale;
+            inv[col][j] *= scale;
+        }
+
+        for row in 0..T {
+            if row != col {
+                let factor = m[row][col];
+                for j in 0..T {
+                    let (m_sub, inv_sub) = (factor * m[col][j], factor * inv[col][j]);
+                    m[row][j] -= m_sub;
+                    inv[row][j] -= inv_sub;
+                }
+            }
+        }
+    }
+
+    inv
+}
+
+/// Generates `(round_constants, m_e, m_e^-1)` for the Poseidon2 specification `S` with the
+/// Grain LFSR, as in the reference implementation.
+///
+/// The round constants of a partial round are drawn for its first word only.
+pub(crate) fn generate_constants<
+    F: FieldExt,
+    S: Spec<F, T, RATE> + ?Sized,
+    const T: usize,
+    const RATE: usize,
+>() -> (Vec<[F; T]>, Mds<F, T>, Mds<F, T>) {
+    let r_f = S::full_rounds();
+    let r_p = S::partial_rounds();
+
+    let mut grain = Grain::new(SboxType::Pow, T as u16, r_f as u16, r_p as u16);
+
+    let round_constants = (0..(r_f + r_p))
+        .map(|round| {
+            let mut rc_row = [F::zero(); T];
+            let partial = round >= r_f / 2 && round < r_f / 2 + r_p;
+            let words = if partial { 1 } else { T };
+            for rc in rc_row.iter_mut().take(words) {
+                *rc = grain.next_field_element();
+            }
+            rc_row
+        })
+        .collect();
+
+    let m_e = external_matrix::<F, T>();
+    let m_e_inv = invert(&m_e);
+
+    (round_constants, m_e, m_e_inv)
+}
+
+/// Runs the Poseidon2 permutation on the given state.
+pub(crate) fn permute<
+    F: FieldExt,
+    S: Poseidon2Spec<F, T, RATE> + ?Sized,
+    const T: usize,
+    const RATE: usize,
+>(
+    state: &mut State<F, T>,
+    m_e: &Mds<F, T>,
+    round_constants: &[[F; T]],
+) {
+    let r_f = S::full_rounds() / 2;
+    let r_p = S::partial_rounds();
+    let diagonal = S::internal_diagonal();
+
+    let apply_m_e = |state: &mut State<F, T>| {
+        let mut new_state = [F::zero(); T];
+        // Matrix multiplication
+        #[allow(clippy::needless_range_loop)]
+        for i in 0..T {
+            for j in 0..T {
+                new_state[i] += m_e[i][j] * state[j];
+            }
+        }
+        *state = new_state;
+    };
+
+    let full_round = |state: &mut State<F, T>, rcs: &[F; T]| {
+        for (word, rc) in state.iter_mut().zip(rcs.iter()) {
+            *word = S::sbox(*word + rc);
+        }
+        apply_m_e(state);
+    };
+
+    let part_round = |state: &mut State<F, T>, rcs: &[F; T]| {
+        state[0] = S::sbox(state[0] + rcs[0]);
+        let sum = state.iter().fold(F::zero(), |acc, word| acc + word);
+        for (word, d) in state.iter_mut().zip(diagonal.iter()) {
+            *word = *word * d + sum;
+        }
+    };
+
+    apply_m_e(state);
+
+    let (first, rest) = round_constants.split_at(r_f);
+    let (partial, last) = rest.split_at(r_p);
+    for rcs in first {
+        full_round(state, rcs);
+    }
+    for rcs in partial {
+        part_round(state, rcs);
+    }
+    for rcs in last {
+        full_round(state, rcs);
+    }
+}
+
+lazy_static! {
+    static ref T3_BN256_CONSTANTS: (Vec<[Fr; 3]>, Mds<Fr, 3>, Mds<Fr, 3>) =
+        generate_constants::<_, Poseidon2Pow5T3Bn256, 3, 2>();
+}
+
+/// Poseidon2 over the BN254 scalar field using the $x^5$ S-box, with a width of 3 field
+/// elements ($R_F = 8, R_P = 56$).
+///
+/// This matches the `poseidon2_instance_bn256` parameters of the
+/// [reference implementation](https://github.com/HorizenLabs/poseidon2).
+#[derive(Debug)]
+pub struct Poseidon2Pow5T3Bn256;
+
+impl Spec<Fr, 3, 2> for Poseidon2Pow5T3Bn256 {
+    fn full_rounds() -> usize {
+        8
+    }
+
+    fn partial_rounds() -> usize {
+        56
+    }
+
+    fn sbox(val: Fr) -> Fr {
+        val.pow_vartime(&[5])
+    }
+
+    fn secure_mds() -> usize {
+        unimplemented!()
+    }
+
+    fn constants() -> (Vec<[Fr; 3]>, Mds<Fr, 3>, Mds<Fr, 3>) {
+        T3_BN256_CONSTANTS.clone()
+    }
+
+    fn permute(state: &mut State<Fr, 3>, m_e: &Mds<Fr, 3>, round_constants: &[[Fr; 3]]) {
+        permute::<Fr, Self, 3, 2>(state, m_e, round_constants)
+    }
+}
+
+impl Poseidon2Spec<Fr, 3, 2> for Poseidon2Pow5T3Bn256 {
+    fn internal_diagonal() -> [Fr; 3] {
+        [Fr::one(), Fr::one(), Fr::from(2)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::PrimeField;
+    use halo2curves::FieldExt;
+
+    use super::{external_matrix, invert, Fr, Poseidon2Pow5T3Bn256};
+    use crate::poseidon::primitives::{ConstantLength, Hash, Spec};
+
+    #[test]
+    fn external_matrix_inverse() {
+        fn check<const T: usize>() {
+            let m_e = external_matrix::<Fr, T>();
+            let m_e_inv = invert(&m_e);
+            #[allow(clippy::needless_range_loop)]
+            for i in 0..T {
+                for j in 0..T {
+                    let expected = if i == j { Fr::one() } else { Fr::zero() };
+                    assert_eq!(
+                        (0..T).fold(Fr::zero(), |acc, k| acc + m_e[i][k] * m_e_inv[k][j]),
+                        expected
+                    );
+                }
+            }
+        }
+
+        check::<2>();
+        check::<3>();
+        check::<4>();
+        check::<8>();
+    }
+
+    #[test]
+    fn test_against_reference() {
+        // The test vector of `poseidon2_instance_bn256` in
+        // <https://github.com/HorizenLabs/poseidon2>.
+        let mut state = [Fr::zero(), Fr::one(), Fr::from(2)];
+        let (round_constants, m_e, _) = Poseidon2Pow5T3Bn256::constants();
+        Poseidon2Pow5T3Bn256::permute(&mut state, &m_e, &round_constants);
+
+        let expected = [
+            "5297208644449048816064511434384511824916970985131888684874823260532015509555",
+            "21816030159894113985964609355246484851575571273661473159848781012394295965040",
+            "13940986381491601233448981668101586453321811870310341844570924906201623195336",
+        ];
+        for (actual, expected) in state.iter().zip(expected.iter()) {
+            assert_eq!(*actual, Fr::from_str_vartime(expected).unwrap());
+        }
+    }
+
+    #[test]
+    fn sponge() {
+        let message = [Fr::from(6), Fr::from(42)];
+
+        let result = Hash::<_, Poseidon2Pow5T3Bn256, ConstantLength<2>, 3, 2>::init().hash(message);
+
+        // The sponge uses the Poseidon2 permutation.
+        let (round_constants, m_e, _) = Poseidon2Pow5T3Bn256::constants();
+        let mut state = [message[0], message[1], Fr::from_u128(2 << 64)];
+        Poseidon2Pow5T3Bn256::permute(&mut state, &m_e, &round_constants);
+        assert_eq!(state[0], result);
+    }
+}
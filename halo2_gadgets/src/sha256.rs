@@ -20,6 +20,10 @@ pub use table16::{BlockWord, Table16Chip, Table16Config};
 pub const BLOCK_SIZE: usize = 16;
 /// The size of a SHA-256 digest, in 32-bit words.
 const DIGEST_SIZE: usize = 8;
+/// The size of a SHA-256 block, in bytes.
+pub const BLOCK_SIZE_BYTES: usize = 4 * BLOCK_SIZE;
+/// The size of a SHA-256 digest, in bytes.
+pub const DIGEST_SIZE_BYTES: usize = 4 * DIGEST_SIZE;
 
 /// The set of circuit instructions required to use the [`Sha256`] gadget.
 pub trait Sha256Instructions<F: FieldExt>: Chip<F> {
@@ -28,6 +32,8 @@ pub trait Sha256Instructions<F: FieldExt>: Chip<F> {
     /// Variable representing a 32-bit word of the input block to the SHA-256 compression
     /// function.
     type BlockWord: Copy + fmt::Debug + Default;
+    /// Variable representing a byte, a message length or a flag.
+    type Var: Clone + fmt::Debug;
 
     /// Places the SHA-256 IV in the circuit, returning the initial state variable.
     fn initialization_vector(&self, layouter: &mut impl Layouter<F>) -> Result<Self::State, Error>;
@@ -54,12 +60,54 @@ pub trait Sha256Instructions<F: FieldExt>: Chip<F> {
         layouter: &mut impl Layouter<F>,
         state: &Self::State,
     ) -> Result<[Self::BlockWord; DIGEST_SIZE], Error>;
+
+    /// Pads the first `len` bytes of `message`, returning as many blocks as a message of
+    /// `message.len()` bytes needs. Each block comes with a flag that is set only on the
+    /// final block of the first `len` bytes.
+    ///
+    /// The remaining bytes of `message` are ignored. The circuit is not satisfied if `len`
+    /// is greater than `message.len()`.
+    #[allow(clippy::type_complexity)]
+    fn pad(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        message: &[Self::Var],
+        len: &Self::Var,
+    ) -> Result<Vec<([Self::Var; BLOCK_SIZE_BYTES], Self::Var)>, Error>;
+
+    /// Starting from the given initialized state, processes a padded block of bytes and
+    /// returns the final state.
+    fn compress_bytes(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        initialized_state: &Self::State,
+        block: &[Self::Var; BLOCK_SIZE_BYTES],
+    ) -> Result<Self::State, Error>;
+
+    /// Returns the digest, as bytes, of the one state out of the given states whose flag
+    /// is set.
+    fn digest_bytes(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        states: &[(Self::Var, Self::State)],
+    ) -> Result<[Self::Var; DIGEST_SIZE_BYTES], Error>;
 }
 
 /// The output of a SHA-256 circuit invocation.
 #[derive(Debug)]
 pub struct Sha256Digest<BlockWord>([BlockWord; DIGEST_SIZE]);
 
+/// The output of a SHA-256 circuit invocation on a message of bytes.
+#[derive(Debug)]
+pub struct Sha256DigestBytes<Var>([Var; DIGEST_SIZE_BYTES]);
+
+impl<Var> Sha256DigestBytes<Var> {
+    /// Returns the bytes of the digest.
+    pub fn bytes(&self) -> &[Var; DIGEST_SIZE_BYTES] {
+        &self.0
+    }
+}
+
 /// A gadget that constrains a SHA-256 invocation. It supports input at a granularity of
 /// 32 bits.
 #[derive(Debug)]
@@ -163,4 +211,201 @@ impl<F: FieldExt, Sha256Chip: Sha256Instructions<F>> Sha256<F, Sha256Chip> {
         hasher.update(layouter.namespace(|| "update"), data)?;
         hasher.finalize(layouter.namespace(|| "finalize"))
     }
+
+    /// Computes the digest of the first `len` bytes of `message`.
+    ///
+    /// The circuit has the same shape for every `len` up to `message.len()`: all of the
+    /// blocks that a message of `message.len()` bytes needs are compressed, and the state
+    /// after the final block of the first `len` bytes is selected in-circuit.
+    pub fn digest_bytes(
+        chip: Sha256Chip,
+        mut layouter: impl Layouter<F>,
+        message: &[Sha256Chip::Var],
+        len: &Sha256Chip::Var,
+    ) -> Result<Sha256DigestBytes<Sha256Chip::Var>, Error> {
+        let blocks = chip.pad(&mut layouter.namespace(|| "pad"), message, len)?;
+
+        let mut state = chip.initialization_vector(&mut layouter.namespace(|| "init"))?;
+        let mut states = Vec::with_capacity(blocks.len());
+        for (i, (block, is_final)) in blocks.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("block {}", i));
+            if i > 0 {
+                state = chip.initialization(&mut layouter, &state)?;
+            }
+            state = chip.compress_bytes(&mut layouter, &state, block)?;
+            states.push((is_final.clone(), state.clone()));
+        }
+
+        chip.digest_bytes(&mut layouter.namespace(|| "digest"), &states)
+            .map(Sha256DigestBytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use halo2curves::{bn256, pasta::pallas};
+    use sha2::{Digest, Sha256 as Sha256Reference};
+
+    use super::{BlockWord, Sha256, Table16Chip, Table16Config, BLOCK_SIZE};
+
+    const K: u32 = 17;
+    const MAX: usize = 120;
+
+    struct HashCircuit<F: FieldExt> {
+        message: Value<Vec<u8>>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for HashCircuit<F> {
+        type Config = (Table16Config, Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            HashCircuit {
+                message: Value::unknown(),
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let input = meta.advice_column();
+            meta.enable_equality(input);
+            let digest = meta.instance_column();
+            meta.enable_equality(digest);
+
+            (Table16Chip::configure(meta), input, digest)
+        }
+
+        fn synthesize(
+            &self,
+            (config, input, digest_column): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            Table16Chip::load(config.clone(), &mut layouter)?;
+            let chip = Table16Chip::construct(config);
+
+            let (message, len) = layouter.assign_region(
+                || "message",
+                |mut region| {
+                    let message = (0..MAX)
+                        .map(|i| {
+                            region.assign_advice(
+                                || format!("byte_{}", i),
+                                input,
+                                i,
+                                || {
+                                    self.message
+                                        .as_ref()
+                                        .map(|m| F::from(m.get(i).copied().unwrap_or(0) as u64))
+                                },
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let len = region.assign_advice(
+                        || "len",
+                        input,
+                        MAX,
+                        || self.message.as_ref().map(|m| F::from(m.len() as u64)),
+                    )?;
+                    Ok((message, len))
+                },
+            )?;
+
+            let digest =
+                Sha256::digest_bytes(chip, layouter.namespace(|| "sha256"), &message, &len)?;
+            for (i, byte) in digest.bytes().iter().enumerate() {
+                layouter.constrain_instance(byte.cell(), digest_column, i)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    fn prover<F: FieldExt>(message: &[u8]) -> MockProver<F> {
+        let digest = Sha256Reference::digest(message)
+            .iter()
+            .map(|byte| F::from(*byte as u64))
+            .collect();
+
+        let circuit = HashCircuit::<F> {
+            message: Value::known(message.to_vec()),
+            _marker: PhantomData,
+        };
+        MockProver::run(K, &circuit, vec![digest]).unwrap()
+    }
+
+    fn message(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 + 7) as u8).collect()
+    }
+
+    #[test]
+    fn sha256_bn256() {
+        // Lengths on either side of the last length that fits in one block, and up to
+        // a final block that holds only the length.
+        for len in [0, 55, 56, 119, MAX] {
+            assert_eq!(prover::<bn256::Fr>(&message(len)).verify(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn sha256_pallas() {
+        assert_eq!(prover::<pallas::Base>(b"abc").verify(), Ok(()));
+    }
+
+    #[test]
+    fn sha256_length_exceeds_max() {
+        assert!(prover::<bn256::Fr>(&message(MAX + 1)).verify().is_err());
+    }
+
+    #[test]
+    fn sha256_block_words() {
+        struct MyCircuit {}
+
+        impl Circuit<bn256::Fr> for MyCircuit {
+            type Config = Table16Config;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {}
+            }
+
+            fn configure(meta: &mut ConstraintSystem<bn256::Fr>) -> Self::Config {
+                Table16Chip::configure(meta)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<bn256::Fr>,
+            ) -> Result<(), Error> {
+                Table16Chip::load(config.clone(), &mut layouter)?;
+                let chip = Table16Chip::construct(config);
+
+                // "abc", padded by hand.
+                let mut input = [BlockWord(Value::known(0)); BLOCK_SIZE];
+                input[0] = BlockWord(Value::known(0x6162_6380));
+                input[BLOCK_SIZE - 1] = BlockWord(Value::known(24));
+
+                let digest = Sha256::digest(chip, layouter.namespace(|| "'abc'"), &input)?;
+                let expected = Sha256Reference::digest(b"abc");
+                for (word, expected) in digest.0.iter().zip(expected.chunks(4)) {
+                    word.0
+                        .assert_if_known(|word| word.to_be_bytes()[..] == *expected);
+                }
+
+                Ok(())
+            }
+        }
+
+        let prover = MockProver::<bn256::Fr>::run(K, &MyCircuit {}, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
 }
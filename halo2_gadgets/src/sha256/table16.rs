@@ -1,19 +1,21 @@
 use std::convert::TryInto;
 use std::marker::PhantomData;
 
-use super::Sha256Instructions;
+use super::{Sha256Instructions, BLOCK_SIZE, BLOCK_SIZE_BYTES, DIGEST_SIZE, DIGEST_SIZE_BYTES};
 use halo2_proofs::{
+    arithmetic::FieldExt,
     circuit::{AssignedCell, Chip, Layouter, Region, Value},
     plonk::{Advice, Any, Assigned, Column, ConstraintSystem, Error},
 };
-use halo2curves::pasta::pallas;
 
+mod bytes;
 mod compression;
 mod gates;
 mod message_schedule;
 mod spread_table;
 mod util;
 
+use bytes::*;
 use compression::*;
 use gates::*;
 use message_schedule::*;
@@ -81,10 +83,10 @@ impl<const LEN: usize> From<&Bits<LEN>> for [bool; LEN] {
     }
 }
 
-impl<const LEN: usize> From<&Bits<LEN>> for Assigned<pallas::Base> {
-    fn from(bits: &Bits<LEN>) -> Assigned<pallas::Base> {
+impl<F: FieldExt, const LEN: usize> From<&Bits<LEN>> for Assigned<F> {
+    fn from(bits: &Bits<LEN>) -> Assigned<F> {
         assert!(LEN <= 64);
-        pallas::Base::from(lebs2ip(&bits.0)).into()
+        F::from(lebs2ip(&bits.0)).into()
     }
 }
 
@@ -113,19 +115,19 @@ impl From<u32> for Bits<32> {
 }
 
 #[derive(Clone, Debug)]
-pub struct AssignedBits<const LEN: usize>(AssignedCell<Bits<LEN>, pallas::Base>);
+pub struct AssignedBits<F: FieldExt, const LEN: usize>(AssignedCell<Bits<LEN>, F>);
 
-impl<const LEN: usize> std::ops::Deref for AssignedBits<LEN> {
-    type Target = AssignedCell<Bits<LEN>, pallas::Base>;
+impl<F: FieldExt, const LEN: usize> std::ops::Deref for AssignedBits<F, LEN> {
+    type Target = AssignedCell<Bits<LEN>, F>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-impl<const LEN: usize> AssignedBits<LEN> {
+impl<F: FieldExt, const LEN: usize> AssignedBits<F, LEN> {
     fn assign_bits<A, AR, T: TryInto<[bool; LEN]> + std::fmt::Debug + Clone>(
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, F>,
         annotation: A,
         column: impl Into<Column<Any>>,
         offset: usize,
@@ -157,13 +159,13 @@ impl<const LEN: usize> AssignedBits<LEN> {
     }
 }
 
-impl AssignedBits<16> {
+impl<F: FieldExt> AssignedBits<F, 16> {
     fn value_u16(&self) -> Value<u16> {
         self.value().map(|v| v.into())
     }
 
     fn assign<A, AR>(
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, F>,
         annotation: A,
         column: impl Into<Column<Any>>,
         offset: usize,
@@ -192,13 +194,13 @@ impl AssignedBits<16> {
     }
 }
 
-impl AssignedBits<32> {
+impl<F: FieldExt> AssignedBits<F, 32> {
     fn value_u32(&self) -> Value<u32> {
         self.value().map(|v| v.into())
     }
 
     fn assign<A, AR>(
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, F>,
         annotation: A,
         column: impl Into<Column<Any>>,
         offset: usize,
@@ -233,16 +235,17 @@ pub struct Table16Config {
     lookup: SpreadTableConfig,
     message_schedule: MessageScheduleConfig,
     compression: CompressionConfig,
+    bytes: BytesConfig,
 }
 
 /// A chip that implements SHA-256 with a maximum lookup table size of $2^16$.
 #[derive(Clone, Debug)]
-pub struct Table16Chip {
+pub struct Table16Chip<F: FieldExt> {
     config: Table16Config,
-    _marker: PhantomData<pallas::Base>,
+    _marker: PhantomData<F>,
 }
 
-impl Chip<pallas::Base> for Table16Chip {
+impl<F: FieldExt> Chip<F> for Table16Chip<F> {
     type Config = Table16Config;
     type Loaded = ();
 
@@ -255,9 +258,9 @@ impl Chip<pallas::Base> for Table16Chip {
     }
 }
 
-impl Table16Chip {
+impl<F: FieldExt> Table16Chip<F> {
     /// Reconstructs this chip from the given config.
-    pub fn construct(config: <Self as Chip<pallas::Base>>::Config) -> Self {
+    pub fn construct(config: <Self as Chip<F>>::Config) -> Self {
         Self {
             config,
             _marker: PhantomData,
@@ -265,9 +268,7 @@ impl Table16Chip {
     }
 
     /// Configures a circuit to include this chip.
-    pub fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
-    ) -> <Self as Chip<pallas::Base>>::Config {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> <Self as Chip<F>>::Config {
         // Columns required by this chip:
         let message_schedule = meta.advice_column();
         let extras = [
@@ -304,9 +305,15 @@ impl Table16Chip {
             meta.enable_equality(*column);
         }
 
+        // - A fixed column for the initialization vector and other constants.
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+
         let compression =
             CompressionConfig::configure(meta, lookup_inputs.clone(), message_schedule, extras);
 
+        let bytes = BytesConfig::configure(meta, message_schedule, extras);
+
         let message_schedule =
             MessageScheduleConfig::configure(meta, lookup_inputs, message_schedule, extras);
 
@@ -314,32 +321,29 @@ impl Table16Chip {
             lookup,
             message_schedule,
             compression,
+            bytes,
         }
     }
 
-    /// Loads the lookup table required by this chip into the circuit.
-    pub fn load(
-        config: Table16Config,
-        layouter: &mut impl Layouter<pallas::Base>,
-    ) -> Result<(), Error> {
+    /// Loads the lookup tables required by this chip into the circuit.
+    pub fn load(config: Table16Config, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        config.bytes.load(layouter)?;
         SpreadTableChip::load(config.lookup, layouter)
     }
 }
 
-impl Sha256Instructions<pallas::Base> for Table16Chip {
-    type State = State;
+impl<F: FieldExt> Sha256Instructions<F> for Table16Chip<F> {
+    type State = State<F>;
     type BlockWord = BlockWord;
+    type Var = AssignedCell<F, F>;
 
-    fn initialization_vector(
-        &self,
-        layouter: &mut impl Layouter<pallas::Base>,
-    ) -> Result<State, Error> {
+    fn initialization_vector(&self, layouter: &mut impl Layouter<F>) -> Result<State<F>, Error> {
         self.config().compression.initialize_with_iv(layouter, IV)
     }
 
     fn initialization(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
+        layouter: &mut impl Layouter<F>,
         init_state: &Self::State,
     ) -> Result<Self::State, Error> {
         self.config()
@@ -351,26 +355,76 @@ impl Sha256Instructions<pallas::Base> for Table16Chip {
     // message block and return the final state.
     fn compress(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
+        layouter: &mut impl Layouter<F>,
         initialized_state: &Self::State,
-        input: [Self::BlockWord; super::BLOCK_SIZE],
+        input: [Self::BlockWord; BLOCK_SIZE],
     ) -> Result<Self::State, Error> {
         let config = self.config();
         let (_, w_halves) = config.message_schedule.process(layouter, input)?;
+        let state = config
+            .compression
+            .compress(layouter, initialized_state.clone(), w_halves)?;
         config
             .compression
-            .compress(layouter, initialized_state.clone(), w_halves)
+            .feed_forward(layouter, initialized_state.clone(), state)
     }
 
     fn digest(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
+        layouter: &mut impl Layouter<F>,
         state: &Self::State,
-    ) -> Result<[Self::BlockWord; super::DIGEST_SIZE], Error> {
+    ) -> Result<[Self::BlockWord; DIGEST_SIZE], Error> {
         // Copy the dense forms of the state variable chunks down to this gate.
         // Reconstruct the 32-bit dense words.
         self.config().compression.digest(layouter, state.clone())
     }
+
+    fn pad(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        message: &[Self::Var],
+        len: &Self::Var,
+    ) -> Result<Vec<([Self::Var; BLOCK_SIZE_BYTES], Self::Var)>, Error> {
+        self.config().bytes.pad(layouter, message, len)
+    }
+
+    fn compress_bytes(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        initialized_state: &Self::State,
+        block: &[Self::Var; BLOCK_SIZE_BYTES],
+    ) -> Result<Self::State, Error> {
+        let config = self.config();
+
+        let input: [BlockWord; BLOCK_SIZE] = block
+            .chunks(4)
+            .map(|bytes| {
+                BlockWord(bytes.iter().fold(Value::known(0), |word, byte| {
+                    word.zip(byte.value())
+                        .map(|(word, byte)| (word << 8) | byte.get_lower_128() as u32)
+                }))
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+
+        let (w, w_halves) = config.message_schedule.process(layouter, input)?;
+        config.bytes.pack(layouter, block, &w)?;
+        let state = config
+            .compression
+            .compress(layouter, initialized_state.clone(), w_halves)?;
+        config
+            .compression
+            .feed_forward(layouter, initialized_state.clone(), state)
+    }
+
+    fn digest_bytes(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        states: &[(Self::Var, Self::State)],
+    ) -> Result<[Self::Var; DIGEST_SIZE_BYTES], Error> {
+        self.config().bytes.digest(layouter, states)
+    }
 }
 
 /// Common assignment patterns used by Table16 regions.
@@ -378,9 +432,9 @@ trait Table16Assignment {
     /// Assign cells for general spread computation used in sigma, ch, ch_neg, maj gates
     #[allow(clippy::too_many_arguments)]
     #[allow(clippy::type_complexity)]
-    fn assign_spread_outputs(
+    fn assign_spread_outputs<F: FieldExt>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, F>,
         lookup: &SpreadInputs,
         a_3: Column<Advice>,
         row: usize,
@@ -390,8 +444,8 @@ trait Table16Assignment {
         r_1_odd: Value<[bool; 16]>,
     ) -> Result<
         (
-            (AssignedBits<16>, AssignedBits<16>),
-            (AssignedBits<16>, AssignedBits<16>),
+            (AssignedBits<F, 16>, AssignedBits<F, 16>),
+            (AssignedBits<F, 16>, AssignedBits<F, 16>),
         ),
         Error,
     > {
@@ -430,9 +484,9 @@ trait Table16Assignment {
 
     /// Assign outputs of sigma gates
     #[allow(clippy::too_many_arguments)]
-    fn assign_sigma_outputs(
+    fn assign_sigma_outputs<F: FieldExt>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, F>,
         lookup: &SpreadInputs,
         a_3: Column<Advice>,
         row: usize,
@@ -440,7 +494,7 @@ trait Table16Assignment {
         r_0_odd: Value<[bool; 16]>,
         r_1_even: Value<[bool; 16]>,
         r_1_odd: Value<[bool; 16]>,
-    ) -> Result<(AssignedBits<16>, AssignedBits<16>), Error> {
+    ) -> Result<(AssignedBits<F, 16>, AssignedBits<F, 16>), Error> {
         let (even, _odd) = self.assign_spread_outputs(
             region, lookup, a_3, row, r_0_even, r_0_odd, r_1_even, r_1_odd,
         )?;
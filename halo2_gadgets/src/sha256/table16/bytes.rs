@@ -0,0 +1,559 @@
+use super::{
+    super::{BLOCK_SIZE, BLOCK_SIZE_BYTES, DIGEST_SIZE_BYTES},
+    MessageWord, State, STATE,
+};
+use crate::utilities::bool_check;
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector, TableColumn,
+    },
+    poly::Rotation,
+};
+use std::convert::TryInto;
+
+/// The position in a padded block of the first byte of the message length.
+const LENGTH_OFFSET: usize = BLOCK_SIZE_BYTES - 8;
+
+/// Configuration for the regions that handle a message of bytes: the padding of the
+/// message, the packing of its blocks into words, and the unpacking of the digest.
+///
+/// Every byte that these regions output is range-checked with a lookup in a table of
+/// the 256 bytes.
+#[derive(Clone, Debug)]
+pub(super) struct BytesConfig {
+    message_schedule: Column<Advice>,
+    extras: [Column<Advice>; 6],
+    byte_table: TableColumn,
+
+    q_byte: Selector,
+    q_pad: Selector,
+    q_pad_byte: Selector,
+    q_pad_boundary: Selector,
+    q_pad_length: Selector,
+    q_length: Selector,
+    q_length_end: Selector,
+    q_pack: Selector,
+    q_select: Selector,
+    q_unpack: Selector,
+}
+
+impl BytesConfig {
+    pub(super) fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        message_schedule: Column<Advice>,
+        extras: [Column<Advice>; 6],
+    ) -> Self {
+        let byte_table = meta.lookup_table_column();
+
+        let q_byte = meta.complex_selector();
+        let q_pad = meta.selector();
+        let q_pad_byte = meta.selector();
+        let q_pad_boundary = meta.selector();
+        let q_pad_length = meta.selector();
+        let q_length = meta.selector();
+        let q_length_end = meta.selector();
+        let q_pack = meta.selector();
+        let q_select = meta.selector();
+        let q_unpack = meta.selector();
+
+        // Rename these here for ease of matching the gates to the specification.
+        let a_3 = extras[0];
+        let a_4 = extras[1];
+        let a_5 = message_schedule;
+        let a_6 = extras[2];
+        let a_7 = extras[3];
+        let a_8 = extras[4];
+
+        meta.lookup("byte", |meta| {
+            let q_byte = meta.query_selector(q_byte);
+            let byte = meta.query_advice(a_5, Rotation::cur());
+
+            vec![(q_byte * byte, byte_table)]
+        });
+
+        // The padding has a row for each byte, with the message byte in a_3, a flag that
+        // is set on the first `len` bytes in a_4, the padded byte in a_5, and the running
+        // count of set flags in a_6.
+        meta.create_gate("padding", |meta| {
+            let q_pad = meta.query_selector(q_pad);
+            let flag_prev = meta.query_advice(a_4, Rotation::prev());
+            let flag = meta.query_advice(a_4, Rotation::cur());
+            let count_prev = meta.query_advice(a_6, Rotation::prev());
+            let count = meta.query_advice(a_6, Rotation::cur());
+
+            let one = Expression::Constant(F::one());
+
+            Constraints::with_selector(
+                q_pad,
+                [
+                    ("flag is bool", bool_check(flag.clone())),
+                    (
+                        "flag is not set after being unset",
+                        flag.clone() * (one - flag_prev),
+                    ),
+                    ("running count", count_prev + flag - count),
+                ],
+            )
+        });
+
+        meta.create_gate("padded byte", |meta| {
+            let q_pad_byte = meta.query_selector(q_pad_byte);
+            let message = meta.query_advice(a_3, Rotation::cur());
+            let flag_prev = meta.query_advice(a_4, Rotation::prev());
+            let flag = meta.query_advice(a_4, Rotation::cur());
+            let padded = meta.query_advice(a_5, Rotation::cur());
+
+            Constraints::with_selector(
+                q_pad_byte,
+                Some((
+                    "padded byte",
+                    padded_byte(message, flag_prev, flag) - padded,
+                )),
+            )
+        });
+
+        // The final block is the one that the flags become unset in before the length.
+        // a_7 holds the flag at the same position of the previous block (or 1 in the
+        // first block), and a_8 the final-block flag.
+        meta.create_gate("padded block boundary", |meta| {
+            let q_pad_boundary = meta.query_selector(q_pad_boundary);
+            let flag_block_prev = meta.query_advice(a_7, Rotation::cur());
+            let flag = meta.query_advice(a_4, Rotation::cur());
+            let last = meta.query_advice(a_8, Rotation::cur());
+
+            Constraints::with_selector(
+                q_pad_boundary,
+                Some(("final block", flag_block_prev - flag - last)),
+            )
+        });
+
+        // The last eight bytes of the final block hold the length in bits. a_7 holds a
+        // byte of the length, and a_8 the final-block flag.
+        meta.create_gate("padded length byte", |meta| {
+            let q_pad_length = meta.query_selector(q_pad_length);
+            let message = meta.query_advice(a_3, Rotation::cur());
+            let flag_prev = meta.query_advice(a_4, Rotation::prev());
+            let flag = meta.query_advice(a_4, Rotation::cur());
+            let length_byte = meta.query_advice(a_7, Rotation::cur());
+            let last = meta.query_advice(a_8, Rotation::cur());
+            let padded = meta.query_advice(a_5, Rotation::cur());
+
+            Constraints::with_selector(
+                q_pad_length,
+                Some((
+                    "padded byte",
+                    padded_byte(message, flag_prev, flag) + last * length_byte - padded,
+                )),
+            )
+        });
+
+        // The bytes of the length in bits, in a_5, are accumulated big-endian in a_8.
+        meta.create_gate("length", |meta| {
+            let q_length = meta.query_selector(q_length);
+            let length_byte = meta.query_advice(a_5, Rotation::cur());
+            let acc_prev = meta.query_advice(a_8, Rotation::prev());
+            let acc = meta.query_advice(a_8, Rotation::cur());
+
+            Constraints::with_selector(
+                q_length,
+                Some(("length", acc_prev * F::from(1 << 8) + length_byte - acc)),
+            )
+        });
+
+        meta.create_gate("length end", |meta| {
+            let q_length_end = meta.query_selector(q_length_end);
+            let acc = meta.query_advice(a_8, Rotation::cur());
+            let len = meta.query_advice(a_6, Rotation::cur());
+
+            Constraints::with_selector(
+                q_length_end,
+                Some(("length in bits", len * F::from(8) - acc)),
+            )
+        });
+
+        // A message word in a_5 is made of the big-endian bytes in a_3, a_4, a_6, a_7.
+        meta.create_gate("pack", |meta| {
+            let q_pack = meta.query_selector(q_pack);
+            let word = meta.query_advice(a_5, Rotation::cur());
+            let bytes =
+                [a_3, a_4, a_6, a_7].map(|column| meta.query_advice(column, Rotation::cur()));
+
+            Constraints::with_selector(q_pack, Some(("pack", compose(bytes) - word)))
+        });
+
+        // Accumulates flag ⋅ word over the rows of a selection, with the flag in a_3, the
+        // halves of the word in a_4 and a_6, and the accumulator in a_5.
+        meta.create_gate("select", |meta| {
+            let q_select = meta.query_selector(q_select);
+            let flag = meta.query_advice(a_3, Rotation::cur());
+            let lo = meta.query_advice(a_4, Rotation::cur());
+            let hi = meta.query_advice(a_6, Rotation::cur());
+            let acc_prev = meta.query_advice(a_5, Rotation::prev());
+            let acc = meta.query_advice(a_5, Rotation::cur());
+
+            Constraints::with_selector(
+                q_select,
+                Some((
+                    "select",
+                    acc_prev + flag * (lo + hi * F::from(1 << 16)) - acc,
+                )),
+            )
+        });
+
+        // A selected word is made of the big-endian bytes in the next four rows of a_5.
+        meta.create_gate("unpack", |meta| {
+            let q_unpack = meta.query_selector(q_unpack);
+            let word = meta.query_advice(a_5, Rotation(-4));
+            let bytes = [-3, -2, -1, 0].map(|rotation| meta.query_advice(a_5, Rotation(rotation)));
+
+            Constraints::with_selector(q_unpack, Some(("unpack", compose(bytes) - word)))
+        });
+
+        BytesConfig {
+            message_schedule,
+            extras,
+            byte_table,
+            q_byte,
+            q_pad,
+            q_pad_byte,
+            q_pad_boundary,
+            q_pad_length,
+            q_length,
+            q_length_end,
+            q_pack,
+            q_select,
+            q_unpack,
+        }
+    }
+
+    pub(super) fn load<F: FieldExt>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "byte table",
+            |mut table| {
+                for byte in 0..(1 << 8) {
+                    table.assign_cell(
+                        || "byte",
+                        self.byte_table,
+                        byte,
+                        || Value::known(F::from(byte as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Pads the first `len` bytes of `message` into as many blocks as a message of
+    /// `message.len()` bytes needs. Returns each block with a flag that is set only on
+    /// the final block of the first `len` bytes.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn pad<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        message: &[AssignedCell<F, F>],
+        len: &AssignedCell<F, F>,
+    ) -> Result<Vec<([AssignedCell<F, F>; BLOCK_SIZE_BYTES], AssignedCell<F, F>)>, Error> {
+        // Rename these here for ease of matching the gates to the specification.
+        let a_3 = self.extras[0];
+        let a_4 = self.extras[1];
+        let a_5 = self.message_schedule;
+        let a_6 = self.extras[2];
+        let a_7 = self.extras[3];
+        let a_8 = self.extras[4];
+
+        let num_blocks = (message.len() + 8) / BLOCK_SIZE_BYTES + 1;
+        let num_bytes = num_blocks * BLOCK_SIZE_BYTES;
+
+        // The length as an integer, if it is at most message.len().
+        let len_value = len.value().map(|len| {
+            (0..=message.len())
+                .find(|i| F::from(*i as u64) == *len)
+                .unwrap_or(usize::MAX)
+        });
+        let final_block = len_value.map(|len| len.saturating_add(8) / BLOCK_SIZE_BYTES);
+        let length_bytes: Value<[u8; 8]> = len_value.map(|len| ((len as u64) << 3).to_be_bytes());
+
+        layouter.assign_region(
+            || "pad",
+            |mut region| {
+                // The length in bits, after the padded bytes.
+                let offset = num_bytes + 1;
+                region.assign_advice_from_constant(|| "initial length", a_8, offset, F::zero())?;
+                let mut acc = Value::known(F::zero());
+                let mut length_cells = Vec::with_capacity(8);
+                for k in 0..8 {
+                    let offset = offset + k + 1;
+                    self.q_byte.enable(&mut region, offset)?;
+                    self.q_length.enable(&mut region, offset)?;
+
+                    let length_byte = length_bytes.map(|bytes| F::from(bytes[k] as u64));
+                    length_cells.push(region.assign_advice(
+                        || format!("length_byte_{}", k),
+                        a_5,
+                        offset,
+                        || length_byte,
+                    )?);
+
+                    acc = acc
+                        .zip(length_byte)
+                        .map(|(acc, byte)| acc * F::from(1 << 8) + byte);
+                    region.assign_advice(|| "length", a_8, offset, || acc)?;
+                }
+                self.q_length_end.enable(&mut region, offset + 8)?;
+                len.copy_advice(|| "len", &mut region, a_6, offset + 8)?;
+
+                // The padded bytes.
+                region.assign_advice_from_constant(|| "initial flag", a_4, 0, F::one())?;
+                region.assign_advice_from_constant(|| "initial count", a_6, 0, F::zero())?;
+
+                let mut words = Vec::with_capacity(num_bytes);
+                let mut flags = Vec::with_capacity(num_blocks);
+                let mut flag_block_prev: Option<AssignedCell<F, F>> = None;
+                for i in 0..num_bytes {
+                    let offset = i + 1;
+                    let block = i / BLOCK_SIZE_BYTES;
+                    let position = i % BLOCK_SIZE_BYTES;
+
+                    self.q_byte.enable(&mut region, offset)?;
+                    self.q_pad.enable(&mut region, offset)?;
+                    if position < LENGTH_OFFSET {
+                        self.q_pad_byte.enable(&mut region, offset)?;
+                    } else {
+                        self.q_pad_length.enable(&mut region, offset)?;
+                    }
+
+                    let message_byte = match message.get(i) {
+                        Some(byte) => {
+                            byte.copy_advice(|| format!("message_{}", i), &mut region, a_3, offset)?
+                        }
+                        // Past the end of the message, the flags are unset.
+                        None => region.assign_advice(
+                            || "no message",
+                            a_3,
+                            offset,
+                            || Value::known(F::zero()),
+                        )?,
+                    };
+
+                    let flag = if i == message.len() {
+                        region.assign_advice_from_constant(
+                            || "final flag",
+                            a_4,
+                            offset,
+                            F::zero(),
+                        )?
+                    } else {
+                        region.assign_advice(
+                            || format!("flag_{}", i),
+                            a_4,
+                            offset,
+                            || len_value.map(|len| F::from((i < len) as u64)),
+                        )?
+                    };
+
+                    let count = region.assign_advice(
+                        || format!("count_{}", i),
+                        a_6,
+                        offset,
+                        || len_value.map(|len| F::from(std::cmp::min(i + 1, len) as u64)),
+                    )?;
+                    if i == num_bytes - 1 {
+                        region.constrain_equal(count.cell(), len.cell())?;
+                    }
+
+                    // The flag at the last position before the length decides which
+                    // block is the final one.
+                    if position == LENGTH_OFFSET - 1 {
+                        self.q_pad_boundary.enable(&mut region, offset)?;
+                        match &flag_block_prev {
+                            Some(flag_block_prev) => {
+                                flag_block_prev.copy_advice(
+                                    || "previous block flag",
+                                    &mut region,
+                                    a_7,
+                                    offset,
+                                )?;
+                            }
+                            None => {
+                                region.assign_advice_from_constant(
+                                    || "previous block flag",
+                                    a_7,
+                                    offset,
+                                    F::one(),
+                                )?;
+                            }
+                        }
+                        flags.push(region.assign_advice(
+                            || format!("final_{}", block),
+                            a_8,
+                            offset,
+                            || {
+                                final_block
+                                    .map(|final_block| F::from((block == final_block) as u64))
+                            },
+                        )?);
+                        flag_block_prev = Some(flag);
+                    } else if position >= LENGTH_OFFSET {
+                        length_cells[position - LENGTH_OFFSET].copy_advice(
+                            || "length byte",
+                            &mut region,
+                            a_7,
+                            offset,
+                        )?;
+                        flags[block].copy_advice(|| "final block", &mut region, a_8, offset)?;
+                    }
+
+                    let is_final = final_block.map(|final_block| block == final_block);
+                    words.push(region.assign_advice(
+                        || format!("word_{}", i),
+                        a_5,
+                        offset,
+                        || {
+                            message_byte
+                                .value()
+                                .zip(len_value)
+                                .zip(is_final)
+                                .zip(length_bytes)
+                                .map(|(((message, len), is_final), length_bytes)| {
+                                    if i < len {
+                                        *message
+                                    } else if i == len {
+                                        F::from(0x80)
+                                    } else if is_final && position >= LENGTH_OFFSET {
+                                        F::from(length_bytes[position - LENGTH_OFFSET] as u64)
+                                    } else {
+                                        F::zero()
+                                    }
+                                })
+                        },
+                    )?);
+                }
+
+                Ok(words
+                    .chunks(BLOCK_SIZE_BYTES)
+                    .map(|block| block.to_vec().try_into().unwrap())
+                    .zip(flags)
+                    .collect())
+            },
+        )
+    }
+
+    /// Constrains the first [`BLOCK_SIZE`] words of a message schedule to be made of
+    /// the big-endian bytes of `block`.
+    pub(super) fn pack<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        block: &[AssignedCell<F, F>; BLOCK_SIZE_BYTES],
+        words: &[MessageWord<F>],
+    ) -> Result<(), Error> {
+        // Rename these here for ease of matching the gates to the specification.
+        let a_3 = self.extras[0];
+        let a_4 = self.extras[1];
+        let a_5 = self.message_schedule;
+        let a_6 = self.extras[2];
+        let a_7 = self.extras[3];
+
+        layouter.assign_region(
+            || "pack",
+            |mut region| {
+                for (row, (bytes, word)) in
+                    block.chunks(4).zip(words[..BLOCK_SIZE].iter()).enumerate()
+                {
+                    self.q_pack.enable(&mut region, row)?;
+                    for (byte, column) in bytes.iter().zip([a_3, a_4, a_6, a_7]) {
+                        byte.copy_advice(|| "byte", &mut region, column, row)?;
+                    }
+                    word.copy_advice(|| "word", &mut region, a_5, row)?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Returns the big-endian bytes of the digest of the one state, out of the given
+    /// states, whose flag is set.
+    pub(super) fn digest<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        states: &[(AssignedCell<F, F>, State<F>)],
+    ) -> Result<[AssignedCell<F, F>; DIGEST_SIZE_BYTES], Error> {
+        let mut digest = Vec::with_capacity(DIGEST_SIZE_BYTES);
+        for i in 0..STATE {
+            digest.extend(layouter.assign_region(
+                || format!("digest word {}", i),
+                |mut region| self.select_word(&mut region, i, states),
+            )?);
+        }
+        Ok(digest.try_into().unwrap())
+    }
+
+    fn select_word<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        idx: usize,
+        states: &[(AssignedCell<F, F>, State<F>)],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        // Rename these here for ease of matching the gates to the specification.
+        let a_3 = self.extras[0];
+        let a_4 = self.extras[1];
+        let a_5 = self.message_schedule;
+        let a_6 = self.extras[2];
+
+        let mut acc = region.assign_advice_from_constant(|| "initial", a_5, 0, F::zero())?;
+        for (k, (flag, state)) in states.iter().enumerate() {
+            let row = k + 1;
+            self.q_select.enable(region, row)?;
+
+            let dense_halves = state.dense_halves();
+            let (lo, hi) = &dense_halves[idx];
+            flag.copy_advice(|| "flag", region, a_3, row)?;
+            lo.copy_advice(|| "lo", region, a_4, row)?;
+            hi.copy_advice(|| "hi", region, a_6, row)?;
+
+            let value = acc
+                .value()
+                .zip(flag.value())
+                .zip(lo.value_u16().zip(hi.value_u16()))
+                .map(|((acc, flag), (lo, hi))| {
+                    *acc + *flag * F::from(lo as u64 + (1 << 16) * hi as u64)
+                });
+            acc = region.assign_advice(|| "selected", a_5, row, || value)?;
+        }
+
+        let word = acc.value().map(|word| word.get_lower_128() as u32);
+        let row = states.len();
+        self.q_unpack.enable(region, row + 4)?;
+        (0..4)
+            .map(|k| {
+                self.q_byte.enable(region, row + k + 1)?;
+                region.assign_advice(
+                    || format!("byte_{}", k),
+                    a_5,
+                    row + k + 1,
+                    || word.map(|word| F::from(word.to_be_bytes()[k] as u64)),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Returns the padded byte at a position before the length: the message byte while the
+/// flag is set, 0x80 right after the message, and 0 after that.
+fn padded_byte<F: FieldExt>(
+    message: Expression<F>,
+    flag_prev: Expression<F>,
+    flag: Expression<F>,
+) -> Expression<F> {
+    flag.clone() * message + (flag_prev - flag) * F::from(0x80)
+}
+
+/// Returns the word made of the given big-endian bytes.
+fn compose<F: FieldExt>(bytes: [Expression<F>; 4]) -> Expression<F> {
+    bytes
+        .into_iter()
+        .fold(Expression::Constant(F::zero()), |acc, byte| {
+            acc * F::from(1 << 8) + byte
+        })
+}
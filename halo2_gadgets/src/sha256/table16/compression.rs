@@ -4,17 +4,18 @@ use super::{
     AssignedBits, BlockWord, SpreadInputs, SpreadVar, Table16Assignment, ROUNDS, STATE,
 };
 use halo2_proofs::{
+    arithmetic::FieldExt,
     circuit::{Layouter, Value},
     plonk::{Advice, Column, ConstraintSystem, Error, Selector},
     poly::Rotation,
 };
-use halo2curves::pasta::pallas;
 use std::convert::TryInto;
 use std::ops::Range;
 
 mod compression_gates;
 mod compression_util;
 mod subregion_digest;
+mod subregion_feed_forward;
 mod subregion_initial;
 mod subregion_main;
 
@@ -80,16 +81,16 @@ pub trait UpperSigmaVar<
 ///   We align the columns to make it efficient to copy-constrain these forms where they
 ///   are needed.
 #[derive(Clone, Debug)]
-pub struct AbcdVar {
-    a: SpreadVar<2, 4>,
-    b: SpreadVar<11, 22>,
-    c_lo: SpreadVar<3, 6>,
-    c_mid: SpreadVar<3, 6>,
-    c_hi: SpreadVar<3, 6>,
-    d: SpreadVar<10, 20>,
+pub struct AbcdVar<F: FieldExt> {
+    a: SpreadVar<F, 2, 4>,
+    b: SpreadVar<F, 11, 22>,
+    c_lo: SpreadVar<F, 3, 6>,
+    c_mid: SpreadVar<F, 3, 6>,
+    c_hi: SpreadVar<F, 3, 6>,
+    d: SpreadVar<F, 10, 20>,
 }
 
-impl AbcdVar {
+impl<F: FieldExt> AbcdVar<F> {
     fn a_range() -> Range<usize> {
         0..2
     }
@@ -127,7 +128,7 @@ impl AbcdVar {
     }
 }
 
-impl UpperSigmaVar<4, 22, 18, 20> for AbcdVar {
+impl<F: FieldExt> UpperSigmaVar<4, 22, 18, 20> for AbcdVar<F> {
     fn spread_a(&self) -> Value<[bool; 4]> {
         self.a.spread.value().map(|v| v.0)
     }
@@ -169,16 +170,16 @@ impl UpperSigmaVar<4, 22, 18, 20> for AbcdVar {
 ///   We align the columns to make it efficient to copy-constrain these forms where they
 ///   are needed.
 #[derive(Clone, Debug)]
-pub struct EfghVar {
-    a_lo: SpreadVar<3, 6>,
-    a_hi: SpreadVar<3, 6>,
-    b_lo: SpreadVar<2, 4>,
-    b_hi: SpreadVar<3, 6>,
-    c: SpreadVar<14, 28>,
-    d: SpreadVar<7, 14>,
+pub struct EfghVar<F: FieldExt> {
+    a_lo: SpreadVar<F, 3, 6>,
+    a_hi: SpreadVar<F, 3, 6>,
+    b_lo: SpreadVar<F, 2, 4>,
+    b_hi: SpreadVar<F, 3, 6>,
+    c: SpreadVar<F, 14, 28>,
+    d: SpreadVar<F, 7, 14>,
 }
 
-impl EfghVar {
+impl<F: FieldExt> EfghVar<F> {
     fn a_lo_range() -> Range<usize> {
         0..3
     }
@@ -216,7 +217,7 @@ impl EfghVar {
     }
 }
 
-impl UpperSigmaVar<12, 10, 28, 14> for EfghVar {
+impl<F: FieldExt> UpperSigmaVar<12, 10, 28, 14> for EfghVar<F> {
     fn spread_a(&self) -> Value<[bool; 12]> {
         self.a_lo
             .spread
@@ -257,15 +258,15 @@ impl UpperSigmaVar<12, 10, 28, 14> for EfghVar {
 }
 
 #[derive(Clone, Debug)]
-pub struct RoundWordDense(AssignedBits<16>, AssignedBits<16>);
+pub struct RoundWordDense<F: FieldExt>(AssignedBits<F, 16>, AssignedBits<F, 16>);
 
-impl From<(AssignedBits<16>, AssignedBits<16>)> for RoundWordDense {
-    fn from(halves: (AssignedBits<16>, AssignedBits<16>)) -> Self {
+impl<F: FieldExt> From<(AssignedBits<F, 16>, AssignedBits<F, 16>)> for RoundWordDense<F> {
+    fn from(halves: (AssignedBits<F, 16>, AssignedBits<F, 16>)) -> Self {
         Self(halves.0, halves.1)
     }
 }
 
-impl RoundWordDense {
+impl<F: FieldExt> RoundWordDense<F> {
     pub fn value(&self) -> Value<u32> {
         self.0
             .value_u16()
@@ -275,15 +276,15 @@ impl RoundWordDense {
 }
 
 #[derive(Clone, Debug)]
-pub struct RoundWordSpread(AssignedBits<32>, AssignedBits<32>);
+pub struct RoundWordSpread<F: FieldExt>(AssignedBits<F, 32>, AssignedBits<F, 32>);
 
-impl From<(AssignedBits<32>, AssignedBits<32>)> for RoundWordSpread {
-    fn from(halves: (AssignedBits<32>, AssignedBits<32>)) -> Self {
+impl<F: FieldExt> From<(AssignedBits<F, 32>, AssignedBits<F, 32>)> for RoundWordSpread<F> {
+    fn from(halves: (AssignedBits<F, 32>, AssignedBits<F, 32>)) -> Self {
         Self(halves.0, halves.1)
     }
 }
 
-impl RoundWordSpread {
+impl<F: FieldExt> RoundWordSpread<F> {
     pub fn value(&self) -> Value<u64> {
         self.0
             .value_u32()
@@ -293,17 +294,17 @@ impl RoundWordSpread {
 }
 
 #[derive(Clone, Debug)]
-pub struct RoundWordA {
-    pieces: Option<AbcdVar>,
-    dense_halves: RoundWordDense,
-    spread_halves: Option<RoundWordSpread>,
+pub struct RoundWordA<F: FieldExt> {
+    pieces: Option<AbcdVar<F>>,
+    dense_halves: RoundWordDense<F>,
+    spread_halves: Option<RoundWordSpread<F>>,
 }
 
-impl RoundWordA {
+impl<F: FieldExt> RoundWordA<F> {
     pub fn new(
-        pieces: AbcdVar,
-        dense_halves: RoundWordDense,
-        spread_halves: RoundWordSpread,
+        pieces: AbcdVar<F>,
+        dense_halves: RoundWordDense<F>,
+        spread_halves: RoundWordSpread<F>,
     ) -> Self {
         RoundWordA {
             pieces: Some(pieces),
@@ -312,7 +313,7 @@ impl RoundWordA {
         }
     }
 
-    pub fn new_dense(dense_halves: RoundWordDense) -> Self {
+    pub fn new_dense(dense_halves: RoundWordDense<F>) -> Self {
         RoundWordA {
             pieces: None,
             dense_halves,
@@ -322,17 +323,17 @@ impl RoundWordA {
 }
 
 #[derive(Clone, Debug)]
-pub struct RoundWordE {
-    pieces: Option<EfghVar>,
-    dense_halves: RoundWordDense,
-    spread_halves: Option<RoundWordSpread>,
+pub struct RoundWordE<F: FieldExt> {
+    pieces: Option<EfghVar<F>>,
+    dense_halves: RoundWordDense<F>,
+    spread_halves: Option<RoundWordSpread<F>>,
 }
 
-impl RoundWordE {
+impl<F: FieldExt> RoundWordE<F> {
     pub fn new(
-        pieces: EfghVar,
-        dense_halves: RoundWordDense,
-        spread_halves: RoundWordSpread,
+        pieces: EfghVar<F>,
+        dense_halves: RoundWordDense<F>,
+        spread_halves: RoundWordSpread<F>,
     ) -> Self {
         RoundWordE {
             pieces: Some(pieces),
@@ -341,7 +342,7 @@ impl RoundWordE {
         }
     }
 
-    pub fn new_dense(dense_halves: RoundWordDense) -> Self {
+    pub fn new_dense(dense_halves: RoundWordDense<F>) -> Self {
         RoundWordE {
             pieces: None,
             dense_halves,
@@ -351,13 +352,13 @@ impl RoundWordE {
 }
 
 #[derive(Clone, Debug)]
-pub struct RoundWord {
-    dense_halves: RoundWordDense,
-    spread_halves: RoundWordSpread,
+pub struct RoundWord<F: FieldExt> {
+    dense_halves: RoundWordDense<F>,
+    spread_halves: RoundWordSpread<F>,
 }
 
-impl RoundWord {
-    pub fn new(dense_halves: RoundWordDense, spread_halves: RoundWordSpread) -> Self {
+impl<F: FieldExt> RoundWord<F> {
+    pub fn new(dense_halves: RoundWordDense<F>, spread_halves: RoundWordSpread<F>) -> Self {
         RoundWord {
             dense_halves,
             spread_halves,
@@ -367,29 +368,29 @@ impl RoundWord {
 
 /// The internal state for SHA-256.
 #[derive(Clone, Debug)]
-pub struct State {
-    a: Option<StateWord>,
-    b: Option<StateWord>,
-    c: Option<StateWord>,
-    d: Option<StateWord>,
-    e: Option<StateWord>,
-    f: Option<StateWord>,
-    g: Option<StateWord>,
-    h: Option<StateWord>,
+pub struct State<F: FieldExt> {
+    a: Option<StateWord<F>>,
+    b: Option<StateWord<F>>,
+    c: Option<StateWord<F>>,
+    d: Option<StateWord<F>>,
+    e: Option<StateWord<F>>,
+    f: Option<StateWord<F>>,
+    g: Option<StateWord<F>>,
+    h: Option<StateWord<F>>,
 }
 
-impl State {
+impl<F: FieldExt> State<F> {
     #[allow(clippy::many_single_char_names)]
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        a: StateWord,
-        b: StateWord,
-        c: StateWord,
-        d: StateWord,
-        e: StateWord,
-        f: StateWord,
-        g: StateWord,
-        h: StateWord,
+        a: StateWord<F>,
+        b: StateWord<F>,
+        c: StateWord<F>,
+        d: StateWord<F>,
+        e: StateWord<F>,
+        f: StateWord<F>,
+        g: StateWord<F>,
+        h: StateWord<F>,
     ) -> Self {
         State {
             a: Some(a),
@@ -415,18 +416,36 @@ impl State {
             h: None,
         }
     }
+
+    /// Returns the dense halves `(lo, hi)` of the words of this state.
+    #[allow(clippy::many_single_char_names)]
+    #[allow(clippy::type_complexity)]
+    pub(super) fn dense_halves(&self) -> [(AssignedBits<F, 16>, AssignedBits<F, 16>); STATE] {
+        let (a, b, c, d, e, f, g, h) = compression_util::match_state(self.clone());
+        [
+            a.dense_halves,
+            b.dense_halves,
+            c.dense_halves,
+            d,
+            e.dense_halves,
+            f.dense_halves,
+            g.dense_halves,
+            h,
+        ]
+        .map(|dense_halves| (dense_halves.0, dense_halves.1))
+    }
 }
 
 #[derive(Clone, Debug)]
-pub enum StateWord {
-    A(RoundWordA),
-    B(RoundWord),
-    C(RoundWord),
-    D(RoundWordDense),
-    E(RoundWordE),
-    F(RoundWord),
-    G(RoundWord),
-    H(RoundWordDense),
+pub enum StateWord<F: FieldExt> {
+    A(RoundWordA<F>),
+    B(RoundWord<F>),
+    C(RoundWord<F>),
+    D(RoundWordDense<F>),
+    E(RoundWordE<F>),
+    F(RoundWord<F>),
+    G(RoundWord<F>),
+    H(RoundWordDense<F>),
 }
 
 #[derive(Clone, Debug)]
@@ -451,13 +470,14 @@ pub(super) struct CompressionConfig {
     s_decompose_efgh: Selector,
 
     s_digest: Selector,
+    s_feed_forward: Selector,
 }
 
 impl Table16Assignment for CompressionConfig {}
 
 impl CompressionConfig {
-    pub(super) fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
+    pub(super) fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
         lookup: SpreadInputs,
         message_schedule: Column<Advice>,
         extras: [Column<Advice>; 6],
@@ -478,6 +498,7 @@ impl CompressionConfig {
         let s_decompose_efgh = meta.selector();
 
         let s_digest = meta.selector();
+        let s_feed_forward = meta.selector();
 
         // Rename these here for ease of matching the gates to the specification.
         let a_0 = lookup.tag;
@@ -841,6 +862,29 @@ impl CompressionConfig {
             )
         });
 
+        // s_feed_forward to add the compressed state to the initial state
+        meta.create_gate("s_feed_forward", |meta| {
+            let s_feed_forward = meta.query_selector(s_feed_forward);
+            let prev_lo = meta.query_advice(a_3, Rotation::cur());
+            let prev_hi = meta.query_advice(a_4, Rotation::cur());
+            let cur_lo = meta.query_advice(a_5, Rotation::cur());
+            let cur_hi = meta.query_advice(a_6, Rotation::cur());
+            let new_lo = meta.query_advice(a_1, Rotation::cur());
+            let new_hi = meta.query_advice(a_1, Rotation::next());
+            let carry = meta.query_advice(a_9, Rotation::cur());
+
+            CompressionGate::s_feed_forward(
+                s_feed_forward,
+                prev_lo,
+                prev_hi,
+                cur_lo,
+                cur_hi,
+                new_lo,
+                new_hi,
+                carry,
+            )
+        });
+
         CompressionConfig {
             lookup,
             message_schedule,
@@ -856,16 +900,17 @@ impl CompressionConfig {
             s_decompose_abcd,
             s_decompose_efgh,
             s_digest,
+            s_feed_forward,
         }
     }
 
     /// Initialize compression with a constant Initialization Vector of 32-byte words.
     /// Returns an initialized state.
-    pub(super) fn initialize_with_iv(
+    pub(super) fn initialize_with_iv<F: FieldExt>(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
+        layouter: &mut impl Layouter<F>,
         init_state: [u32; STATE],
-    ) -> Result<State, Error> {
+    ) -> Result<State<F>, Error> {
         let mut new_state = State::empty_state();
         layouter.assign_region(
             || "initialize_with_iv",
@@ -879,11 +924,11 @@ impl CompressionConfig {
 
     /// Initialize compression with some initialized state. This could be a state
     /// output from a previous compression round.
-    pub(super) fn initialize_with_state(
+    pub(super) fn initialize_with_state<F: FieldExt>(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
-        init_state: State,
-    ) -> Result<State, Error> {
+        layouter: &mut impl Layouter<F>,
+        init_state: State<F>,
+    ) -> Result<State<F>, Error> {
         let mut new_state = State::empty_state();
         layouter.assign_region(
             || "initialize_with_state",
@@ -896,12 +941,12 @@ impl CompressionConfig {
     }
 
     /// Given an initialized state and a message schedule, perform 64 compression rounds.
-    pub(super) fn compress(
+    pub(super) fn compress<F: FieldExt>(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
-        initialized_state: State,
-        w_halves: [(AssignedBits<16>, AssignedBits<16>); ROUNDS],
-    ) -> Result<State, Error> {
+        layouter: &mut impl Layouter<F>,
+        initialized_state: State<F>,
+        w_halves: [(AssignedBits<F, 16>, AssignedBits<F, 16>); ROUNDS],
+    ) -> Result<State<F>, Error> {
         let mut state = State::empty_state();
         layouter.assign_region(
             || "compress",
@@ -916,11 +961,34 @@ impl CompressionConfig {
         Ok(state)
     }
 
+    /// After the final round, add the compressed state to the state that the compression
+    /// started from. Returns the intermediate hash value.
+    pub(super) fn feed_forward<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        initialized_state: State<F>,
+        state: State<F>,
+    ) -> Result<State<F>, Error> {
+        let mut new_state = State::empty_state();
+        layouter.assign_region(
+            || "feed_forward",
+            |mut region| {
+                new_state = self.assign_feed_forward(
+                    &mut region,
+                    initialized_state.clone(),
+                    state.clone(),
+                )?;
+                Ok(())
+            },
+        )?;
+        Ok(new_state)
+    }
+
     /// After the final round, convert the state into the final digest.
-    pub(super) fn digest(
+    pub(super) fn digest<F: FieldExt>(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
-        state: State,
+        layouter: &mut impl Layouter<F>,
+        state: State<F>,
     ) -> Result<[BlockWord; DIGEST_SIZE], Error> {
         let mut digest = [BlockWord(Value::known(0)); DIGEST_SIZE];
         layouter.assign_region(
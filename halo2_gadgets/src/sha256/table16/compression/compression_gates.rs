@@ -352,16 +352,25 @@ impl<F: FieldExt> CompressionGate<F> {
         k_hi: Expression<F>,
         w_lo: Expression<F>,
         w_hi: Expression<F>,
-    ) -> Option<(&'static str, Expression<F>)> {
+    ) -> impl IntoIterator<Item = Constraint<F>> {
         let lo = h_lo + ch_lo + ch_neg_lo + sigma_e_lo + k_lo + w_lo;
         let hi = h_hi + ch_hi + ch_neg_hi + sigma_e_hi + k_hi + w_hi;
 
         let sum = lo + hi * F::from(1 << 16);
         let h_prime = h_prime_lo + h_prime_hi * F::from(1 << 16);
 
-        let check = sum - (h_prime_carry * F::from(1 << 32)) - h_prime;
+        let check = sum - (h_prime_carry.clone() * F::from(1 << 32)) - h_prime;
 
-        Some(("s_h_prime", s_h_prime * check))
+        // The sum of six 32-bit words has a carry of at most 5.
+        let range_check_carry = Gate::range_check(h_prime_carry, 0, 5);
+
+        Constraints::with_selector(
+            s_h_prime,
+            [
+                ("s_h_prime", check),
+                ("range_check_carry", range_check_carry),
+            ],
+        )
     }
 
     // s_a_new to get A_new = H' + Maj(A, B, C) + s_upper_sigma_0(A)
@@ -377,15 +386,21 @@ impl<F: FieldExt> CompressionGate<F> {
         maj_abc_hi: Expression<F>,
         h_prime_lo: Expression<F>,
         h_prime_hi: Expression<F>,
-    ) -> Option<(&'static str, Expression<F>)> {
+    ) -> impl IntoIterator<Item = Constraint<F>> {
         let lo = sigma_a_lo + maj_abc_lo + h_prime_lo;
         let hi = sigma_a_hi + maj_abc_hi + h_prime_hi;
         let sum = lo + hi * F::from(1 << 16);
         let a_new = a_new_lo + a_new_hi * F::from(1 << 16);
 
-        let check = sum - (a_new_carry * F::from(1 << 32)) - a_new;
+        let check = sum - (a_new_carry.clone() * F::from(1 << 32)) - a_new;
 
-        Some(("s_a_new", s_a_new * check))
+        // The sum of three 32-bit words has a carry of at most 2.
+        let range_check_carry = Gate::range_check(a_new_carry, 0, 2);
+
+        Constraints::with_selector(
+            s_a_new,
+            [("s_a_new", check), ("range_check_carry", range_check_carry)],
+        )
     }
 
     // s_e_new to get E_new = H' + D
@@ -399,15 +414,21 @@ impl<F: FieldExt> CompressionGate<F> {
         d_hi: Expression<F>,
         h_prime_lo: Expression<F>,
         h_prime_hi: Expression<F>,
-    ) -> Option<(&'static str, Expression<F>)> {
+    ) -> impl IntoIterator<Item = Constraint<F>> {
         let lo = h_prime_lo + d_lo;
         let hi = h_prime_hi + d_hi;
         let sum = lo + hi * F::from(1 << 16);
         let e_new = e_new_lo + e_new_hi * F::from(1 << 16);
 
-        let check = sum - (e_new_carry * F::from(1 << 32)) - e_new;
+        let check = sum - (e_new_carry.clone() * F::from(1 << 32)) - e_new;
 
-        Some(("s_e_new", s_e_new * check))
+        // The sum of two 32-bit words has a carry of at most 1.
+        let range_check_carry = Gate::range_check(e_new_carry, 0, 1);
+
+        Constraints::with_selector(
+            s_e_new,
+            [("s_e_new", check), ("range_check_carry", range_check_carry)],
+        )
     }
 
     // s_digest on final round
@@ -441,4 +462,33 @@ impl<F: FieldExt> CompressionGate<F> {
             ],
         )
     }
+
+    // s_feed_forward to get the new state word H_i = H_{i-1} + V, where V is the
+    // compressed state word
+    #[allow(clippy::too_many_arguments)]
+    pub fn s_feed_forward(
+        s_feed_forward: Expression<F>,
+        prev_lo: Expression<F>,
+        prev_hi: Expression<F>,
+        cur_lo: Expression<F>,
+        cur_hi: Expression<F>,
+        new_lo: Expression<F>,
+        new_hi: Expression<F>,
+        carry: Expression<F>,
+    ) -> impl IntoIterator<Item = Constraint<F>> {
+        let lo = prev_lo + cur_lo;
+        let hi = prev_hi + cur_hi;
+        let sum = lo + hi * F::from(1 << 16);
+        let new = new_lo + new_hi * F::from(1 << 16);
+
+        let check = sum - (carry.clone() * F::from(1 << 32)) - new;
+
+        Constraints::with_selector(
+            s_feed_forward,
+            [
+                ("s_feed_forward", check),
+                ("range_check_carry", Gate::range_check(carry, 0, 1)),
+            ],
+        )
+    }
 }
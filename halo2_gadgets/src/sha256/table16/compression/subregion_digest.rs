@@ -1,17 +1,17 @@
 use super::super::{super::DIGEST_SIZE, BlockWord, RoundWordDense};
 use super::{compression_util::*, CompressionConfig, State};
 use halo2_proofs::{
+    arithmetic::FieldExt,
     circuit::{Region, Value},
     plonk::{Advice, Column, Error},
 };
-use halo2curves::pasta::pallas;
 
 impl CompressionConfig {
     #[allow(clippy::many_single_char_names)]
-    pub fn assign_digest(
+    pub fn assign_digest<F: FieldExt>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
-        state: State,
+        region: &mut Region<'_, F>,
+        state: State<F>,
     ) -> Result<[BlockWord; DIGEST_SIZE], Error> {
         let a_3 = self.extras[0];
         let a_4 = self.extras[1];
@@ -35,12 +35,7 @@ impl CompressionConfig {
             .1
             .copy_advice(|| "a_hi", region, a_4, abcd_row)?;
         let a = a.dense_halves.value();
-        region.assign_advice(
-            || "a",
-            a_5,
-            abcd_row,
-            || a.map(|a| pallas::Base::from(a as u64)),
-        )?;
+        region.assign_advice(|| "a", a_5, abcd_row, || a.map(|a| F::from(a as u64)))?;
 
         let b = self.assign_digest_word(region, abcd_row, a_6, a_7, a_8, b.dense_halves)?;
         let c = self.assign_digest_word(region, abcd_row + 1, a_3, a_4, a_5, c.dense_halves)?;
@@ -54,12 +49,7 @@ impl CompressionConfig {
             .1
             .copy_advice(|| "e_hi", region, a_4, efgh_row)?;
         let e = e.dense_halves.value();
-        region.assign_advice(
-            || "e",
-            a_5,
-            efgh_row,
-            || e.map(|e| pallas::Base::from(e as u64)),
-        )?;
+        region.assign_advice(|| "e", a_5, efgh_row, || e.map(|e| F::from(e as u64)))?;
 
         let f = self.assign_digest_word(region, efgh_row, a_6, a_7, a_8, f.dense_halves)?;
         let g = self.assign_digest_word(region, efgh_row + 1, a_3, a_4, a_5, g.dense_halves)?;
@@ -77,14 +67,14 @@ impl CompressionConfig {
         ])
     }
 
-    fn assign_digest_word(
+    fn assign_digest_word<F: FieldExt>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, F>,
         row: usize,
         lo_col: Column<Advice>,
         hi_col: Column<Advice>,
         word_col: Column<Advice>,
-        dense_halves: RoundWordDense,
+        dense_halves: RoundWordDense<F>,
     ) -> Result<Value<u32>, Error> {
         dense_halves.0.copy_advice(|| "lo", region, lo_col, row)?;
         dense_halves.1.copy_advice(|| "hi", region, hi_col, row)?;
@@ -94,7 +84,7 @@ impl CompressionConfig {
             || "word",
             word_col,
             row,
-            || val.map(|val| pallas::Base::from(val as u64)),
+            || val.map(|val| F::from(val as u64)),
         )?;
 
         Ok(val)
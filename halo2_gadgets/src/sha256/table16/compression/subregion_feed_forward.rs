@@ -0,0 +1,105 @@
+use super::super::{
+    util::*, RoundWord, RoundWordA, RoundWordDense, RoundWordE, SpreadVar, SpreadWord, StateWord,
+    STATE,
+};
+use super::{compression_util::*, CompressionConfig, State};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::Error,
+};
+use std::convert::TryInto;
+
+// Rows needed for each word of the feed-forward subregion
+pub const FEED_FORWARD_ROWS: usize = 2;
+
+impl CompressionConfig {
+    /// Adds the compressed state `state` to the state `initialized_state` that the
+    /// compression started from, returning the new intermediate hash value.
+    #[allow(clippy::many_single_char_names)]
+    pub fn assign_feed_forward<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        initialized_state: State<F>,
+        state: State<F>,
+    ) -> Result<State<F>, Error> {
+        let (a_prev, b_prev, c_prev, d_prev, e_prev, f_prev, g_prev, h_prev) =
+            match_state(initialized_state);
+        let (a, b, c, d, e, f, g, h) = match_state(state);
+
+        let words: [(RoundWordDense<F>, RoundWordDense<F>); STATE] = [
+            (a_prev.dense_halves, a.dense_halves),
+            (b_prev.dense_halves, b.dense_halves),
+            (c_prev.dense_halves, c.dense_halves),
+            (d_prev, d),
+            (e_prev.dense_halves, e.dense_halves),
+            (f_prev.dense_halves, f.dense_halves),
+            (g_prev.dense_halves, g.dense_halves),
+            (h_prev, h),
+        ];
+
+        let mut new_words = Vec::with_capacity(STATE);
+        for (idx, (prev, cur)) in words.iter().enumerate() {
+            new_words.push(self.assign_feed_forward_word(
+                region,
+                idx * FEED_FORWARD_ROWS,
+                prev,
+                cur,
+            )?);
+        }
+        let [a, b, c, d, e, f, g, h]: [RoundWord<F>; STATE] = new_words.try_into().unwrap();
+
+        Ok(State::new(
+            StateWord::A(RoundWordA::new_dense(a.dense_halves)),
+            StateWord::B(b),
+            StateWord::C(c),
+            StateWord::D(d.dense_halves),
+            StateWord::E(RoundWordE::new_dense(e.dense_halves)),
+            StateWord::F(f),
+            StateWord::G(g),
+            StateWord::H(h.dense_halves),
+        ))
+    }
+
+    fn assign_feed_forward_word<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        row: usize,
+        prev: &RoundWordDense<F>,
+        cur: &RoundWordDense<F>,
+    ) -> Result<RoundWord<F>, Error> {
+        // Rename these here for ease of matching the gates to the specification.
+        let a_3 = self.extras[0];
+        let a_4 = self.extras[1];
+        let a_5 = self.message_schedule;
+        let a_6 = self.extras[2];
+        let a_9 = self.extras[5];
+
+        self.s_feed_forward.enable(region, row)?;
+
+        prev.0.copy_advice(|| "prev_lo", region, a_3, row)?;
+        prev.1.copy_advice(|| "prev_hi", region, a_4, row)?;
+        cur.0.copy_advice(|| "cur_lo", region, a_5, row)?;
+        cur.1.copy_advice(|| "cur_hi", region, a_6, row)?;
+
+        let (new, carry) = sum_with_carry(vec![
+            (prev.0.value_u16(), prev.1.value_u16()),
+            (cur.0.value_u16(), cur.1.value_u16()),
+        ]);
+
+        region.assign_advice(|| "carry", a_9, row, || carry.map(F::from))?;
+
+        // Range-check the halves of the new word with the spread table.
+        let new: Value<[bool; 32]> = new.map(|w| i2lebsp(w.into()));
+        let lo: Value<[bool; 16]> = new.map(|w| w[..16].try_into().unwrap());
+        let hi: Value<[bool; 16]> = new.map(|w| w[16..].try_into().unwrap());
+        let new_lo = SpreadVar::with_lookup(region, &self.lookup, row, lo.map(SpreadWord::new))?;
+        let new_hi =
+            SpreadVar::with_lookup(region, &self.lookup, row + 1, hi.map(SpreadWord::new))?;
+
+        Ok(RoundWord::new(
+            (new_lo.dense, new_hi.dense).into(),
+            (new_lo.spread, new_hi.spread).into(),
+        ))
+    }
+}
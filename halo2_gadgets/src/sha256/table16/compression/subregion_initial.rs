@@ -2,18 +2,18 @@ use super::super::{RoundWord, StateWord, STATE};
 use super::{compression_util::*, CompressionConfig, State};
 
 use halo2_proofs::{
+    arithmetic::FieldExt,
     circuit::{Region, Value},
     plonk::Error,
 };
-use halo2curves::pasta::pallas;
 
 impl CompressionConfig {
     #[allow(clippy::many_single_char_names)]
-    pub fn initialize_iv(
+    pub fn initialize_iv<F: FieldExt>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, F>,
         iv: [u32; STATE],
-    ) -> Result<State, Error> {
+    ) -> Result<State<F>, Error> {
         let a_7 = self.extras[3];
 
         // Decompose E into (6, 5, 14, 7)-bit chunks
@@ -40,6 +40,24 @@ impl CompressionConfig {
         let d =
             self.assign_word_halves_dense(region, d_row, a_7, d_row + 1, a_7, Value::known(iv[3]))?;
 
+        // Fix the initialization vector in the circuit.
+        for (dense_halves, word) in [
+            &a.dense_halves,
+            &b.dense_halves,
+            &c.dense_halves,
+            &d,
+            &e.dense_halves,
+            &f.dense_halves,
+            &g.dense_halves,
+            &h,
+        ]
+        .into_iter()
+        .zip(iv)
+        {
+            region.constrain_constant(dense_halves.0.cell(), F::from(word as u64 & 0xffff))?;
+            region.constrain_constant(dense_halves.1.cell(), F::from(word as u64 >> 16))?;
+        }
+
         Ok(State::new(
             StateWord::A(a),
             StateWord::B(b),
@@ -53,44 +71,59 @@ impl CompressionConfig {
     }
 
     #[allow(clippy::many_single_char_names)]
-    pub fn initialize_state(
+    pub fn initialize_state<F: FieldExt>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
-        state: State,
-    ) -> Result<State, Error> {
+        region: &mut Region<'_, F>,
+        state: State<F>,
+    ) -> Result<State<F>, Error> {
         let a_7 = self.extras[3];
-        let (a, b, c, d, e, f, g, h) = match_state(state);
+        let (a_prev, b_prev, c_prev, d_prev, e_prev, f_prev, g_prev, h_prev) = match_state(state);
 
         // Decompose E into (6, 5, 14, 7)-bit chunks
-        let e = e.dense_halves.value();
+        let e = e_prev.dense_halves.value();
         let e = self.decompose_e(region, RoundIdx::Init, e)?;
 
         // Decompose F, G
-        let f = f.dense_halves.value();
+        let f = f_prev.dense_halves.value();
         let f = self.decompose_f(region, InitialRound, f)?;
-        let g = g.dense_halves.value();
+        let g = g_prev.dense_halves.value();
         let g = self.decompose_g(region, InitialRound, g)?;
 
         // Assign H
-        let h = h.value();
+        let h = h_prev.value();
         let h_row = get_h_row(RoundIdx::Init);
         let h = self.assign_word_halves_dense(region, h_row, a_7, h_row + 1, a_7, h)?;
 
         // Decompose A into (2, 11, 9, 10)-bit chunks
-        let a = a.dense_halves.value();
+        let a = a_prev.dense_halves.value();
         let a = self.decompose_a(region, RoundIdx::Init, a)?;
 
         // Decompose B, C
-        let b = b.dense_halves.value();
+        let b = b_prev.dense_halves.value();
         let b = self.decompose_b(region, InitialRound, b)?;
-        let c = c.dense_halves.value();
+        let c = c_prev.dense_halves.value();
         let c = self.decompose_c(region, InitialRound, c)?;
 
         // Assign D
-        let d = d.value();
+        let d = d_prev.value();
         let d_row = get_d_row(RoundIdx::Init);
         let d = self.assign_word_halves_dense(region, d_row, a_7, d_row + 1, a_7, d)?;
 
+        // Tie the initialized state to the given one.
+        for (dense_halves, prev) in [
+            (&a.dense_halves, &a_prev.dense_halves),
+            (&b.dense_halves, &b_prev.dense_halves),
+            (&c.dense_halves, &c_prev.dense_halves),
+            (&d, &d_prev),
+            (&e.dense_halves, &e_prev.dense_halves),
+            (&f.dense_halves, &f_prev.dense_halves),
+            (&g.dense_halves, &g_prev.dense_halves),
+            (&h, &h_prev),
+        ] {
+            region.constrain_equal(dense_halves.0.cell(), prev.0.cell())?;
+            region.constrain_equal(dense_halves.1.cell(), prev.1.cell())?;
+        }
+
         Ok(State::new(
             StateWord::A(a),
             StateWord::B(b),
@@ -103,12 +136,12 @@ impl CompressionConfig {
         ))
     }
 
-    fn decompose_b(
+    fn decompose_b<F: FieldExt>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, F>,
         round_idx: InitialRound,
         b_val: Value<u32>,
-    ) -> Result<RoundWord, Error> {
+    ) -> Result<RoundWord<F>, Error> {
         let row = get_decompose_b_row(round_idx);
 
         let (dense_halves, spread_halves) = self.assign_word_halves(region, row, b_val)?;
@@ -116,12 +149,12 @@ impl CompressionConfig {
         Ok(RoundWord::new(dense_halves, spread_halves))
     }
 
-    fn decompose_c(
+    fn decompose_c<F: FieldExt>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, F>,
         round_idx: InitialRound,
         c_val: Value<u32>,
-    ) -> Result<RoundWord, Error> {
+    ) -> Result<RoundWord<F>, Error> {
         let row = get_decompose_c_row(round_idx);
 
         let (dense_halves, spread_halves) = self.assign_word_halves(region, row, c_val)?;
@@ -129,12 +162,12 @@ impl CompressionConfig {
         Ok(RoundWord::new(dense_halves, spread_halves))
     }
 
-    fn decompose_f(
+    fn decompose_f<F: FieldExt>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, F>,
         round_idx: InitialRound,
         f_val: Value<u32>,
-    ) -> Result<RoundWord, Error> {
+    ) -> Result<RoundWord<F>, Error> {
         let row = get_decompose_f_row(round_idx);
 
         let (dense_halves, spread_halves) = self.assign_word_halves(region, row, f_val)?;
@@ -142,12 +175,12 @@ impl CompressionConfig {
         Ok(RoundWord::new(dense_halves, spread_halves))
     }
 
-    fn decompose_g(
+    fn decompose_g<F: FieldExt>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, F>,
         round_idx: InitialRound,
         g_val: Value<u32>,
-    ) -> Result<RoundWord, Error> {
+    ) -> Result<RoundWord<F>, Error> {
         let row = get_decompose_g_row(round_idx);
 
         let (dense_halves, spread_halves) = self.assign_word_halves(region, row, g_val)?;
@@ -1,17 +1,18 @@
-use super::super::{AssignedBits, RoundWord, RoundWordA, RoundWordE, StateWord, ROUND_CONSTANTS};
+use super::super::{
+    AssignedBits, RoundWord, RoundWordA, RoundWordDense, RoundWordE, StateWord, ROUND_CONSTANTS,
+};
 use super::{compression_util::*, CompressionConfig, State};
-use halo2_proofs::{circuit::Region, plonk::Error};
-use halo2curves::pasta::pallas;
+use halo2_proofs::{arithmetic::FieldExt, circuit::Region, plonk::Error};
 
 impl CompressionConfig {
     #[allow(clippy::many_single_char_names)]
-    pub fn assign_round(
+    pub fn assign_round<F: FieldExt>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, F>,
         round_idx: MainRoundIdx,
-        state: State,
-        schedule_word: &(AssignedBits<16>, AssignedBits<16>),
-    ) -> Result<State, Error> {
+        state: State<F>,
+        schedule_word: &(AssignedBits<F, 16>, AssignedBits<F, 16>),
+    ) -> Result<State<F>, Error> {
         let a_3 = self.extras[0];
         let a_4 = self.extras[1];
         let a_7 = self.extras[3];
@@ -106,11 +107,32 @@ impl CompressionConfig {
             let abcd_row = get_digest_abcd_row();
             let efgh_row = get_digest_efgh_row();
 
-            let a_final =
-                self.assign_word_halves_dense(region, abcd_row, a_3, abcd_row, a_4, a_new_val)?;
-
-            let e_final =
-                self.assign_word_halves_dense(region, efgh_row, a_3, efgh_row, a_4, e_new_val)?;
+            // Copy A_new and E_new into the final state
+            let a_final = RoundWordDense::from((
+                AssignedBits(
+                    a_new_dense
+                        .0
+                        .copy_advice(|| "a_final_lo", region, a_3, abcd_row)?,
+                ),
+                AssignedBits(
+                    a_new_dense
+                        .1
+                        .copy_advice(|| "a_final_hi", region, a_4, abcd_row)?,
+                ),
+            ));
+
+            let e_final = RoundWordDense::from((
+                AssignedBits(
+                    e_new_dense
+                        .0
+                        .copy_advice(|| "e_final_lo", region, a_3, efgh_row)?,
+                ),
+                AssignedBits(
+                    e_new_dense
+                        .1
+                        .copy_advice(|| "e_final_hi", region, a_4, efgh_row)?,
+                ),
+            ));
 
             Ok(State::new(
                 StateWord::A(RoundWordA::new_dense(a_final)),
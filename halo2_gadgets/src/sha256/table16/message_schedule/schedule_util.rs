@@ -1,10 +1,10 @@
 use super::super::AssignedBits;
 use super::MessageScheduleConfig;
 use halo2_proofs::{
+    arithmetic::FieldExt,
     circuit::{Region, Value},
     plonk::Error,
 };
-use halo2curves::pasta::pallas;
 
 #[cfg(test)]
 use super::super::{super::BLOCK_SIZE, BlockWord, ROUNDS};
@@ -150,12 +150,18 @@ pub const MSG_SCHEDULE_TEST_OUTPUT: [u32; ROUNDS] = [
 
 impl MessageScheduleConfig {
     // Assign a word and its hi and lo halves
-    pub fn assign_word_and_halves(
+    pub fn assign_word_and_halves<F: FieldExt>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, F>,
         word: Value<u32>,
         word_idx: usize,
-    ) -> Result<(AssignedBits<32>, (AssignedBits<16>, AssignedBits<16>)), Error> {
+    ) -> Result<
+        (
+            AssignedBits<F, 32>,
+            (AssignedBits<F, 16>, AssignedBits<F, 16>),
+        ),
+        Error,
+    > {
         // Rename these here for ease of matching the gates to the specification.
         let a_3 = self.extras[0];
         let a_4 = self.extras[1];
@@ -164,14 +170,26 @@ impl MessageScheduleConfig {
 
         let w_lo = {
             let w_lo_val = word.map(|word| word as u16);
-            AssignedBits::<16>::assign(region, || format!("W_{}_lo", word_idx), a_3, row, w_lo_val)?
+            AssignedBits::<F, 16>::assign(
+                region,
+                || format!("W_{}_lo", word_idx),
+                a_3,
+                row,
+                w_lo_val,
+            )?
         };
         let w_hi = {
             let w_hi_val = word.map(|word| (word >> 16) as u16);
-            AssignedBits::<16>::assign(region, || format!("W_{}_hi", word_idx), a_4, row, w_hi_val)?
+            AssignedBits::<F, 16>::assign(
+                region,
+                || format!("W_{}_hi", word_idx),
+                a_4,
+                row,
+                w_hi_val,
+            )?
         };
 
-        let word = AssignedBits::<32>::assign(
+        let word = AssignedBits::<F, 32>::assign(
             region,
             || format!("W_{}", word_idx),
             self.message_schedule,
@@ -0,0 +1,28 @@
+//! Building blocks for verifying halo2 proofs inside a circuit.
+//!
+//! **This module does not verify proofs.** Recursive verification replays the
+//! checks of [`halo2_proofs::plonk::verify_proof`] in a circuit. They split into
+//! work over the scalar field of the proof, which evaluates the constraint system
+//! at the challenge point $x$, and work over its curve, which reduces the
+//! multiopen argument to a deferred pairing check or accumulator. Only part of
+//! the scalar-field work is implemented:
+//!
+//! - [`arithmetic`] provides the native field operations the other gadgets are
+//!   written against;
+//! - [`vanishing`] evaluates the custom gates of a verifying key and the
+//!   expected evaluation of the quotient polynomial $h(x)$.
+//!
+//! The following are not implemented, so these gadgets must be given challenges
+//! and evaluations that the circuit does not check against a proof:
+//!
+//! - an in-circuit transcript that absorbs the proof and squeezes the
+//!   challenges;
+//! - the expressions of the permutation, lookup, LogUp and shuffle arguments;
+//! - the multiopen argument (SHPLONK, GWC or IPA) and its reduction to an
+//!   accumulator, which needs in-circuit arithmetic over the proof's curve.
+//!
+//! A circuit built from these gadgets alone is therefore not a sound recursive
+//! verifier.
+
+pub mod arithmetic;
+pub mod vanishing;
@@ -0,0 +1,490 @@
+//! Gadget and chip for arithmetic in the native field.
+
+use crate::utilities::UtilitiesInstructions;
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed},
+    poly::Rotation,
+};
+use halo2curves::FieldExt;
+use std::marker::PhantomData;
+
+/// Instructions for arithmetic on field elements of the native field.
+pub trait ArithmeticInstructions<F: FieldExt>: UtilitiesInstructions<F> {
+    /// Witnesses the constant `value`, constrained to be equal to it.
+    fn constant(&self, layouter: impl Layouter<F>, value: F) -> Result<Self::Var, Error>;
+
+    /// Returns `a + b`.
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        b: &Self::Var,
+    ) -> Result<Self::Var, Error>;
+
+    /// Returns `a - b`.
+    fn sub(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        b: &Self::Var,
+    ) -> Result<Self::Var, Error>;
+
+    /// Returns `a * b`.
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        b: &Self::Var,
+    ) -> Result<Self::Var, Error>;
+
+    /// Returns `-a`.
+    fn neg(&self, layouter: impl Layouter<F>, a: &Self::Var) -> Result<Self::Var, Error>;
+
+    /// Returns `a * scalar` for a constant `scalar`.
+    fn scale(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        scalar: F,
+    ) -> Result<Self::Var, Error>;
+
+    /// Returns `a + constant` for a constant `constant`.
+    fn add_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        constant: F,
+    ) -> Result<Self::Var, Error>;
+
+    /// Returns `a^{-1}`.
+    ///
+    /// The circuit is unsatisfiable if `a` is zero.
+    fn invert(&self, layouter: impl Layouter<F>, a: &Self::Var) -> Result<Self::Var, Error>;
+
+    /// Constrains `a` and `b` to be equal.
+    fn assert_equal(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        b: &Self::Var,
+    ) -> Result<(), Error>;
+}
+
+/// Configuration for the [`ArithmeticChip`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArithmeticConfig {
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+    q_m: Column<Fixed>,
+    q_l: Column<Fixed>,
+    q_r: Column<Fixed>,
+    q_o: Column<Fixed>,
+    q_c: Column<Fixed>,
+}
+
+/// A chip implementing [`ArithmeticInstructions`] with a single gate
+///
+/// $$q_m \cdot a \cdot b + q_l \cdot a + q_r \cdot b + q_o \cdot c + q_c = 0,$$
+///
+/// where the coefficients are fixed columns. Each instruction uses one row.
+#[derive(Clone, Debug)]
+pub struct ArithmeticChip<F> {
+    config: ArithmeticConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for ArithmeticChip<F> {
+    type Config = ArithmeticConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> UtilitiesInstructions<F> for ArithmeticChip<F> {
+    type Var = AssignedCell<F, F>;
+}
+
+/// The coefficients $(q_m, q_l, q_r, q_o, q_c)$ of a row of the gate.
+struct Coefficients<F> {
+    q_m: F,
+    q_l: F,
+    q_r: F,
+    q_o: F,
+    q_c: F,
+}
+
+impl<F: FieldExt> Default for Coefficients<F> {
+    fn default() -> Self {
+        Coefficients {
+            q_m: F::zero(),
+            q_l: F::zero(),
+            q_r: F::zero(),
+            q_o: F::zero(),
+            q_c: F::zero(),
+        }
+    }
+}
+
+impl<F: FieldExt> ArithmeticChip<F> {
+    /// Configures this chip for use in a circuit.
+    ///
+    /// # Side-effects
+    ///
+    /// All `advices` will be equality-enabled.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 3],
+        fixed: [Column<Fixed>; 5],
+    ) -> ArithmeticConfig {
+        for advice in advices.iter() {
+            meta.enable_equality(*advice);
+        }
+
+        let config = ArithmeticConfig {
+            a: advices[0],
+            b: advices[1],
+            c: advices[2],
+            q_m: fixed[0],
+            q_l: fixed[1],
+            q_r: fixed[2],
+            q_o: fixed[3],
+            q_c: fixed[4],
+        };
+
+        // Rows that are not assigned by this chip have all coefficients set to zero,
+        // so the gate does not need a selector.
+        meta.create_gate("arithmetic", |meta| {
+            let a = meta.query_advice(config.a, Rotation::cur());
+            let b = meta.query_advice(config.b, Rotation::cur());
+            let c = meta.query_advice(config.c, Rotation::cur());
+            let q_m = meta.query_fixed(config.q_m, Rotation::cur());
+            let q_l = meta.query_fixed(config.q_l, Rotation::cur());
+            let q_r = meta.query_fixed(config.q_r, Rotation::cur());
+            let q_o = meta.query_fixed(config.q_o, Rotation::cur());
+            let q_c = meta.query_fixed(config.q_c, Rotation::cur());
+
+            vec![q_m * a.clone() * b.clone() + q_l * a + q_r * b + q_o * c + q_c]
+        });
+
+        config
+    }
+
+    /// Constructs an [`ArithmeticChip`] given an [`ArithmeticConfig`].
+    pub fn construct(config: ArithmeticConfig) -> Self {
+        ArithmeticChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns one row of the gate, copying in `a` and `b` if they are given, and
+    /// witnessing `c`. Returns the cells in columns `b` and `c`.
+    fn assign_row(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Option<&AssignedCell<F, F>>,
+        b: (Option<&AssignedCell<F, F>>, Value<F>),
+        c: Value<F>,
+        coefficients: Coefficients<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "arithmetic",
+            |mut region| {
+                for (name, column, coefficient) in [
+                    ("q_m", config.q_m, coefficients.q_m),
+                    ("q_l", config.q_l, coefficients.q_l),
+                    ("q_r", config.q_r, coefficients.q_r),
+                    ("q_o", config.q_o, coefficients.q_o),
+                    ("q_c", config.q_c, coefficients.q_c),
+                ] {
+                    region.assign_fixed(|| name, column, 0, || Value::known(coefficient))?;
+                }
+
+                match a {
+                    Some(a) => a.copy_advice(|| "a", &mut region, config.a, 0)?,
+                    None => {
+                        region.assign_advice(|| "a", config.a, 0, || Value::known(F::zero()))?
+                    }
+                };
+                let b = match b {
+                    (Some(b), _) => b.copy_advice(|| "b", &mut region, config.b, 0)?,
+                    (None, value) => region.assign_advice(|| "b", config.b, 0, || value)?,
+                };
+                let c = region.assign_advice(|| "c", config.c, 0, || c)?;
+
+                Ok((b, c))
+            },
+        )
+    }
+}
+
+impl<F: FieldExt> ArithmeticInstructions<F> for ArithmeticChip<F> {
+    fn constant(&self, layouter: impl Layouter<F>, value: F) -> Result<Self::Var, Error> {
+        let (_, c) = self.assign_row(
+            layouter,
+            None,
+            (None, Value::known(F::zero())),
+            Value::known(value),
+            Coefficients {
+                q_o: F::one(),
+                q_c: -value,
+                ..Default::default()
+            },
+        )?;
+        Ok(c)
+    }
+
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        b: &Self::Var,
+    ) -> Result<Self::Var, Error> {
+        let (_, c) = self.assign_row(
+            layouter,
+            Some(a),
+            (Some(b), Value::unknown()),
+            a.value().zip(b.value()).map(|(a, b)| *a + b),
+            Coefficients {
+                q_l: F::one(),
+                q_r: F::one(),
+                q_o: -F::one(),
+                ..Default::default()
+            },
+        )?;
+        Ok(c)
+    }
+
+    fn sub(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        b: &Self::Var,
+    ) -> Result<Self::Var, Error> {
+        let (_, c) = self.assign_row(
+            layouter,
+            Some(a),
+            (Some(b), Value::unknown()),
+            a.value().zip(b.value()).map(|(a, b)| *a - b),
+            Coefficients {
+                q_l: F::one(),
+                q_r: -F::one(),
+                q_o: -F::one(),
+                ..Default::default()
+            },
+        )?;
+        Ok(c)
+    }
+
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        b: &Self::Var,
+    ) -> Result<Self::Var, Error> {
+        let (_, c) = self.assign_row(
+            layouter,
+            Some(a),
+            (Some(b), Value::unknown()),
+            a.value().zip(b.value()).map(|(a, b)| *a * b),
+            Coefficients {
+                q_m: F::one(),
+                q_o: -F::one(),
+                ..Default::default()
+            },
+        )?;
+        Ok(c)
+    }
+
+    fn neg(&self, layouter: impl Layouter<F>, a: &Self::Var) -> Result<Self::Var, Error> {
+        self.scale(layouter, a, -F::one())
+    }
+
+    fn scale(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        scalar: F,
+    ) -> Result<Self::Var, Error> {
+        let (_, c) = self.assign_row(
+            layouter,
+            Some(a),
+            (None, Value::known(F::zero())),
+            a.value().map(|a| *a * scalar),
+            Coefficients {
+                q_l: scalar,
+                q_o: -F::one(),
+                ..Default::default()
+            },
+        )?;
+        Ok(c)
+    }
+
+    fn add_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        constant: F,
+    ) -> Result<Self::Var, Error> {
+        let (_, c) = self.assign_row(
+            layouter,
+            Some(a),
+            (None, Value::known(F::zero())),
+            a.value().map(|a| *a + constant),
+            Coefficients {
+                q_l: F::one(),
+                q_o: -F::one(),
+                q_c: constant,
+                ..Default::default()
+            },
+        )?;
+        Ok(c)
+    }
+
+    fn invert(&self, layouter: impl Layouter<F>, a: &Self::Var) -> Result<Self::Var, Error> {
+        // a * a^{-1} - 1 = 0, with a^{-1} witnessed in column b.
+        let (inverse, _) = self.assign_row(
+            layouter,
+            Some(a),
+            (None, a.value().map(|a| a.invert().unwrap_or(F::zero()))),
+            Value::known(F::zero()),
+            Coefficients {
+                q_m: F::one(),
+                q_c: -F::one(),
+                ..Default::default()
+            },
+        )?;
+        Ok(inverse)
+    }
+
+    fn assert_equal(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Self::Var,
+        b: &Self::Var,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "assert equal",
+            |mut region| region.constrain_equal(a.cell(), b.cell()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArithmeticChip, ArithmeticConfig, ArithmeticInstructions};
+    use crate::utilities::UtilitiesInstructions;
+    use group::ff::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+    use halo2curves::pasta::pallas::Base;
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        a: Value<Base>,
+        b: Value<Base>,
+        expected: Value<Base>,
+    }
+
+    impl Circuit<Base> for MyCircuit {
+        type Config = (ArithmeticConfig, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let fixed = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+
+            (ArithmeticChip::configure(meta, advices, fixed), advices[0])
+        }
+
+        fn synthesize(
+            &self,
+            (config, advice): Self::Config,
+            mut layouter: impl Layouter<Base>,
+        ) -> Result<(), Error> {
+            let chip = ArithmeticChip::construct(config);
+
+            let a = chip.load_private(layouter.namespace(|| "a"), advice, self.a)?;
+            let b = chip.load_private(layouter.namespace(|| "b"), advice, self.b)?;
+            let expected =
+                chip.load_private(layouter.namespace(|| "expected"), advice, self.expected)?;
+
+            // (-(a + b) * a - 3a + 5) / b
+            let sum = chip.add(layouter.namespace(|| "a + b"), &a, &b)?;
+            let neg = chip.neg(layouter.namespace(|| "-(a + b)"), &sum)?;
+            let product = chip.mul(layouter.namespace(|| "-(a + b) * a"), &neg, &a)?;
+            let scaled = chip.scale(layouter.namespace(|| "3a"), &a, Base::from(3))?;
+            let difference = chip.sub(layouter.namespace(|| "- 3a"), &product, &scaled)?;
+            let numerator =
+                chip.add_constant(layouter.namespace(|| "+ 5"), &difference, Base::from(5))?;
+            let b_inv = chip.invert(layouter.namespace(|| "1 / b"), &b)?;
+            let result = chip.mul(layouter.namespace(|| "/ b"), &numerator, &b_inv)?;
+            chip.assert_equal(layouter.namespace(|| "result"), &result, &expected)?;
+
+            let one = chip.constant(layouter.namespace(|| "one"), Base::one())?;
+            let check = chip.mul(layouter.namespace(|| "b / b"), &b, &b_inv)?;
+            chip.assert_equal(layouter.namespace(|| "b / b = 1"), &check, &one)
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = Base::random(OsRng);
+        let b = Base::random(OsRng);
+        let expected = (-(a + b) * a - a * Base::from(3) + Base::from(5)) * b.invert().unwrap();
+
+        let circuit = MyCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+            expected: Value::known(expected),
+        };
+        let prover = MockProver::run(5, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // A wrong result is rejected.
+        let circuit = MyCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+            expected: Value::known(expected + Base::one()),
+        };
+        let prover = MockProver::run(5, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Zero cannot be inverted.
+        let circuit = MyCircuit {
+            a: Value::known(a),
+            b: Value::known(Base::zero()),
+            expected: Value::known(Base::zero()),
+        };
+        let prover = MockProver::run(5, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,514 @@
+//! Scalar-field checks of the vanishing argument.
+//!
+//! For a circuit whose native field is the scalar field of the proof being
+//! verified:
+//!
+//! - [`evaluate_gates`] evaluates the custom gates of a verifying key's
+//!   constraint system at the claimed evaluations of its queries;
+//! - [`lagrange_evals`] computes $x^n$, $\ell_\mathsf{last}(x)$,
+//!   $\ell_\mathsf{blind}(x)$ and $\ell_0(x)$;
+//! - [`expected_h_eval`] folds the evaluated constraints with $y$ and divides by
+//!   the vanishing polynomial, giving the expected evaluation of $h(x)$.
+//!
+//! The challenges and evaluations are inputs to these gadgets, and nothing here
+//! checks that they came from a proof.
+
+use std::io;
+
+use halo2_proofs::{
+    circuit::Layouter,
+    plonk::{ArgumentKind, Column, ColumnType, ConstraintSystem, Error, Expression},
+    poly::{EvaluationDomain, Rotation},
+};
+use halo2curves::FieldExt;
+
+use super::arithmetic::ArithmeticInstructions;
+
+/// The claimed evaluations at $x$ of the queries of a constraint system, and the
+/// values of its challenges.
+///
+/// Each vector is indexed like the corresponding query list of the
+/// [`ConstraintSystem`] (for example [`ConstraintSystem::advice_queries`]), in the
+/// order in which the verifier reads the evaluations from the transcript.
+#[derive(Clone, Debug)]
+pub struct QueryEvaluations<V> {
+    /// Evaluations of the advice queries.
+    pub advice: Vec<V>,
+    /// Evaluations of the fixed queries.
+    pub fixed: Vec<V>,
+    /// Evaluations of the instance queries.
+    pub instance: Vec<V>,
+    /// Values of the challenges, indexed by [`Challenge::index`].
+    ///
+    /// [`Challenge::index`]: halo2_proofs::plonk::Challenge::index
+    pub challenges: Vec<V>,
+}
+
+/// Returns the position of the query of `column_index` at `rotation` in `queries`.
+fn query_index<C: ColumnType>(
+    queries: &[(Column<C>, Rotation)],
+    column_index: usize,
+    rotation: Rotation,
+) -> Option<usize> {
+    queries.iter().position(|(column, query_rotation)| {
+        column.index() == column_index && *query_rotation == rotation
+    })
+}
+
+/// Attributes a failure to evaluate the constraint system to the vanishing
+/// argument, whose evaluations the caller read from the proof.
+fn invalid_evaluations(message: String) -> Error {
+    Error::ArgumentTranscript {
+        argument: ArgumentKind::Vanishing,
+        source: io::Error::new(io::ErrorKind::InvalidData, message),
+    }
+}
+
+/// Returns the evaluation at `index`. If the query is missing from the
+/// constraint system or has no evaluation, the error names the query described
+/// by `query`.
+fn lookup_eval<V: Clone>(
+    evals: &[V],
+    index: Option<usize>,
+    query: impl FnOnce() -> String,
+) -> Result<V, Error> {
+    index
+        .and_then(|index| evals.get(index))
+        .cloned()
+        .ok_or_else(|| invalid_evaluations(format!("no evaluation of {}", query())))
+}
+
+fn evaluate<F, A, L>(
+    chip: &A,
+    layouter: &mut L,
+    cs: &ConstraintSystem<F>,
+    expression: &Expression<F>,
+    evals: &QueryEvaluations<A::Var>,
+) -> Result<A::Var, Error>
+where
+    F: FieldExt,
+    A: ArithmeticInstructions<F>,
+    L: Layouter<F>,
+{
+    match expression {
+        Expression::Constant(scalar) => chip.constant(layouter.namespace(|| "constant"), *scalar),
+        // Selectors are compressed into fixed columns when the verifying key is
+        // generated, so they cannot appear in its constraint system.
+        Expression::Selector(_) => Err(invalid_evaluations(
+            "no evaluation of a selector that is not compressed into a fixed column".to_string(),
+        )),
+        Expression::Fixed(query) => lookup_eval(
+            &evals.fixed,
+            query_index(cs.fixed_queries(), query.column_index(), query.rotation()),
+            || {
+                format!(
+                    "fixed column {} at rotation {}",
+                    query.column_index(),
+                    query.rotation().0
+                )
+            },
+        ),
+        Expression::Advice(query) => lookup_eval(
+            &evals.advice,
+            query_index(cs.advice_queries(), query.column_index(), query.rotation()),
+            || {
+                format!(
+                    "advice column {} at rotation {}",
+                    query.column_index(),
+                    query.rotation().0
+                )
+            },
+        ),
+        Expression::Instance(query) => lookup_eval(
+            &evals.instance,
+            query_index(
+                cs.instance_queries(),
+                query.column_index(),
+                query.rotation(),
+            ),
+            || {
+                format!(
+                    "instance column {} at rotation {}",
+                    query.column_index(),
+                    query.rotation().0
+                )
+            },
+        ),
+        Expression::Challenge(challenge) => {
+            lookup_eval(&evals.challenges, Some(challenge.index()), || {
+                format!("challenge {}", challenge.index())
+            })
+        }
+        Expression::Negated(a) => {
+            let a = evaluate(chip, layouter, cs, a, evals)?;
+            chip.neg(layouter.namespace(|| "negated"), &a)
+        }
+        Expression::Sum(a, b) => {
+            let a = evaluate(chip, layouter, cs, a, evals)?;
+            let b = evaluate(chip, layouter, cs, b, evals)?;
+            chip.add(layouter.namespace(|| "sum"), &a, &b)
+        }
+        Expression::Product(a, b) => {
+            let a = evaluate(chip, layouter, cs, a, evals)?;
+            let b = evaluate(chip, layouter, cs, b, evals)?;
+            chip.mul(layouter.namespace(|| "product"), &a, &b)
+        }
+        Expression::Scaled(a, scalar) => {
+            let a = evaluate(chip, layouter, cs, a, evals)?;
+            chip.scale(layouter.namespace(|| "scaled"), &a, *scalar)
+        }
+    }
+}
+
+/// Evaluates `expression` at the query evaluations `evals` of the constraint
+/// system `cs`.
+///
+/// Returns [`Error::ArgumentTranscript`] for the vanishing argument if the
+/// expression queries a selector, or a query that has no evaluation in `evals`.
+pub fn evaluate_expression<F: FieldExt, A: ArithmeticInstructions<F>>(
+    chip: &A,
+    mut layouter: impl Layouter<F>,
+    cs: &ConstraintSystem<F>,
+    expression: &Expression<F>,
+    evals: &QueryEvaluations<A::Var>,
+) -> Result<A::Var, Error> {
+    evaluate(chip, &mut layouter, cs, expression, evals)
+}
+
+/// Evaluates every gate polynomial of `cs`, in the order in which the verifier
+/// folds them into the vanishing argument.
+pub fn evaluate_gates<F: FieldExt, A: ArithmeticInstructions<F>>(
+    chip: &A,
+    mut layouter: impl Layouter<F>,
+    cs: &ConstraintSystem<F>,
+    evals: &QueryEvaluations<A::Var>,
+) -> Result<Vec<A::Var>, Error> {
+    cs.gates()
+        .iter()
+        .flat_map(|gate| gate.polynomials())
+        .map(|poly| evaluate(chip, &mut layouter, cs, poly, evals))
+        .collect()
+}
+
+/// Evaluations at $x$ of the Lagrange polynomials used by the verifier.
+#[derive(Clone, Debug)]
+pub struct LagrangeEvals<V> {
+    /// $x^n$.
+    pub xn: V,
+    /// $\ell_\mathsf{last}(x)$, the Lagrange polynomial of the last usable row.
+    pub l_last: V,
+    /// $\ell_\mathsf{blind}(x)$, the sum of the Lagrange polynomials of the
+    /// blinding rows.
+    pub l_blind: V,
+    /// $\ell_0(x)$, the Lagrange polynomial of the first row.
+    pub l_0: V,
+}
+
+/// Computes $x^n$ and the Lagrange evaluations at `x` for a circuit over `domain`
+/// with `blinding_factors` blinding rows, matching
+/// [`EvaluationDomain::l_i_range`].
+///
+/// Each $\ell_i(x) = \frac{\omega^i (x^n - 1)}{n (x - \omega^i)}$ is computed with
+/// one inversion. The circuit is unsatisfiable if $x$ is one of the
+/// $\omega^i$, which happens with negligible probability for an honest challenge.
+pub fn lagrange_evals<F: FieldExt, A: ArithmeticInstructions<F>>(
+    chip: &A,
+    mut layouter: impl Layouter<F>,
+    domain: &EvaluationDomain<F>,
+    blinding_factors: usize,
+    x: &A::Var,
+) -> Result<LagrangeEvals<A::Var>, Error> {
+    let xn = (0..domain.k()).try_fold(x.clone(), |acc, _| {
+        chip.mul(layouter.namespace(|| "square"), &acc, &acc)
+    })?;
+
+    // (x^n - 1) / n
+    let n_inv = F::from(1 << domain.k()).invert().unwrap();
+    let xn_minus_one = chip.add_constant(layouter.namespace(|| "x^n - 1"), &xn, -F::one())?;
+    let common = chip.scale(layouter.namespace(|| "(x^n - 1) / n"), &xn_minus_one, n_inv)?;
+
+    // l_i(x) for i in -(blinding_factors + 1)..=0, in that order.
+    let omega_inv = domain.get_omega_inv();
+    let l_i = (0..=blinding_factors + 1)
+        .rev()
+        .map(|exponent| {
+            let omega_i = omega_inv.pow_vartime(&[exponent as u64]);
+            let denominator = chip.add_constant(layouter.namespace(|| "x - ω^i"), x, -omega_i)?;
+            let inverse = chip.invert(layouter.namespace(|| "1 / (x - ω^i)"), &denominator)?;
+            let l_i = chip.mul(layouter.namespace(|| "l_i / ω^i"), &common, &inverse)?;
+            chip.scale(layouter.namespace(|| "l_i"), &l_i, omega_i)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let l_last = l_i[0].clone();
+    let l_0 = l_i[blinding_factors + 1].clone();
+    let l_blind = if blinding_factors == 0 {
+        chip.constant(layouter.namespace(|| "l_blind"), F::zero())?
+    } else {
+        l_i[2..=blinding_factors]
+            .iter()
+            .try_fold(l_i[1].clone(), |acc, l_i| {
+                chip.add(layouter.namespace(|| "l_blind"), &acc, l_i)
+            })?
+    };
+
+    Ok(LagrangeEvals {
+        xn,
+        l_last,
+        l_blind,
+        l_0,
+    })
+}
+
+/// Computes the expected evaluation of $h(x)$ from the evaluated constraints
+/// `expressions`, as the verifier of the vanishing argument does:
+/// $h(x) = \frac{\sum_i y^{m - 1 - i} e_i}{x^n - 1}$ for $m$ expressions $e_i$.
+pub fn expected_h_eval<F: FieldExt, A: ArithmeticInstructions<F>>(
+    chip: &A,
+    mut layouter: impl Layouter<F>,
+    expressions: impl IntoIterator<Item = A::Var>,
+    y: &A::Var,
+    xn: &A::Var,
+) -> Result<A::Var, Error> {
+    let mut expressions = expressions.into_iter();
+    let first = match expressions.next() {
+        Some(first) => first,
+        None => chip.constant(layouter.namespace(|| "no constraints"), F::zero())?,
+    };
+    let folded = expressions.try_fold(first, |acc, e| {
+        let acc = chip.mul(layouter.namespace(|| "h * y"), &acc, y)?;
+        chip.add(layouter.namespace(|| "h * y + e"), &acc, &e)
+    })?;
+
+    let xn_minus_one = chip.add_constant(layouter.namespace(|| "x^n - 1"), xn, -F::one())?;
+    let inverse = chip.invert(layouter.namespace(|| "1 / (x^n - 1)"), &xn_minus_one)?;
+    chip.mul(layouter.namespace(|| "h(x)"), &folded, &inverse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate_gates, expected_h_eval, lagrange_evals, query_index, QueryEvaluations};
+    use crate::utilities::UtilitiesInstructions;
+    use crate::verifier::arithmetic::{ArithmeticChip, ArithmeticConfig, ArithmeticInstructions};
+    use group::ff::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{
+            Advice, ArgumentKind, Circuit, Column, ConstraintSystem, Error, Expression, FirstPhase,
+        },
+        poly::{EvaluationDomain, Rotation},
+    };
+    use halo2curves::pasta::pallas::Base;
+    use rand::rngs::OsRng;
+
+    const K: u32 = 4;
+    const BLINDING_FACTORS: usize = 3;
+
+    /// The constraint system of the circuit being verified.
+    fn verified_cs() -> ConstraintSystem<Base> {
+        let mut meta = ConstraintSystem::default();
+        let a = meta.advice_column();
+        let f = meta.fixed_column();
+        let i = meta.instance_column();
+        let theta = meta.challenge_usable_after(FirstPhase);
+        meta.create_gate("toy", |meta| {
+            let a_cur = meta.query_advice(a, Rotation::cur());
+            let a_next = meta.query_advice(a, Rotation::next());
+            let f_cur = meta.query_fixed(f, Rotation::cur());
+            let i_cur = meta.query_instance(i, Rotation::cur());
+            let theta = meta.query_challenge(theta);
+            vec![
+                a_cur.clone() * f_cur - a_next * Base::from(3) + i_cur * theta,
+                -(a_cur.square() + Expression::Constant(Base::from(5))),
+            ]
+        });
+        meta
+    }
+
+    /// Evaluates the gates of `cs` off-circuit.
+    fn evaluate_off_circuit(
+        cs: &ConstraintSystem<Base>,
+        evals: &QueryEvaluations<Base>,
+    ) -> Vec<Base> {
+        cs.gates()
+            .iter()
+            .flat_map(|gate| gate.polynomials())
+            .map(|poly| {
+                poly.evaluate(
+                    &|scalar| scalar,
+                    &|_| panic!("no selectors"),
+                    &|query| {
+                        let index =
+                            query_index(cs.fixed_queries(), query.column_index(), query.rotation());
+                        evals.fixed[index.unwrap()]
+                    },
+                    &|query| {
+                        let index = query_index(
+                            cs.advice_queries(),
+                            query.column_index(),
+                            query.rotation(),
+                        );
+                        evals.advice[index.unwrap()]
+                    },
+                    &|query| {
+                        let index = query_index(
+                            cs.instance_queries(),
+                            query.column_index(),
+                            query.rotation(),
+                        );
+                        evals.instance[index.unwrap()]
+                    },
+                    &|challenge| evals.challenges[challenge.index()],
+                    &|a| -a,
+                    &|a, b| a + b,
+                    &|a, b| a * b,
+                    &|a, scalar| a * scalar,
+                )
+            })
+            .collect()
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit {
+        evals: QueryEvaluations<Base>,
+        x: Base,
+        y: Base,
+        /// The gate evaluations, then l_last, l_blind, l_0 and h(x).
+        expected: Vec<Base>,
+    }
+
+    impl Circuit<Base> for MyCircuit {
+        type Config = (ArithmeticConfig, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let fixed = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+
+            (ArithmeticChip::configure(meta, advices, fixed), advices[0])
+        }
+
+        fn synthesize(
+            &self,
+            (config, advice): Self::Config,
+            mut layouter: impl Layouter<Base>,
+        ) -> Result<(), Error> {
+            let chip = ArithmeticChip::construct(config);
+            let mut load = |values: &[Base]| {
+                values
+                    .iter()
+                    .map(|value| {
+                        chip.load_private(
+                            layouter.namespace(|| "load"),
+                            advice,
+                            Value::known(*value),
+                        )
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            };
+
+            let evals = QueryEvaluations {
+                advice: load(&self.evals.advice)?,
+                fixed: load(&self.evals.fixed)?,
+                instance: load(&self.evals.instance)?,
+                challenges: load(&self.evals.challenges)?,
+            };
+            let x = load(&[self.x])?.remove(0);
+            let y = load(&[self.y])?.remove(0);
+            let expected = load(&self.expected)?;
+
+            let cs = verified_cs();
+            let domain = EvaluationDomain::<Base>::new(1, K);
+            let gates = evaluate_gates(&chip, layouter.namespace(|| "gates"), &cs, &evals)?;
+            let lagrange = lagrange_evals(
+                &chip,
+                layouter.namespace(|| "lagrange"),
+                &domain,
+                BLINDING_FACTORS,
+                &x,
+            )?;
+            let h = expected_h_eval(
+                &chip,
+                layouter.namespace(|| "h(x)"),
+                gates.iter().cloned(),
+                &y,
+                &lagrange.xn,
+            )?;
+
+            let results =
+                gates
+                    .iter()
+                    .chain([&lagrange.l_last, &lagrange.l_blind, &lagrange.l_0, &h]);
+            for (result, expected) in results.zip(expected.iter()) {
+                chip.assert_equal(layouter.namespace(|| "check"), result, expected)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn vanishing_argument() {
+        let cs = verified_cs();
+        let random = |n: usize| (0..n).map(|_| Base::random(OsRng)).collect::<Vec<_>>();
+        let evals = QueryEvaluations {
+            advice: random(cs.advice_queries().len()),
+            fixed: random(cs.fixed_queries().len()),
+            instance: random(cs.instance_queries().len()),
+            challenges: random(1),
+        };
+        let x = Base::random(OsRng);
+        let y = Base::random(OsRng);
+
+        let mut expected = evaluate_off_circuit(&cs, &evals);
+        let domain = EvaluationDomain::<Base>::new(1, K);
+        let xn = x.pow_vartime(&[1 << K]);
+        let l_i = domain.l_i_range(x, xn, -(BLINDING_FACTORS as i32 + 1)..=0);
+        let h = expected.iter().fold(Base::zero(), |h, e| h * y + e)
+            * (xn - Base::one()).invert().unwrap();
+        expected.push(l_i[0]);
+        expected.push(l_i[1..=BLINDING_FACTORS].iter().sum());
+        expected.push(l_i[BLINDING_FACTORS + 1]);
+        expected.push(h);
+
+        let circuit = MyCircuit {
+            evals,
+            x,
+            y,
+            expected: expected.clone(),
+        };
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // A wrong claimed evaluation of h(x) is rejected.
+        let mut bad = circuit.clone();
+        *bad.expected.last_mut().unwrap() += Base::one();
+        let prover = MockProver::run(8, &bad, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // A missing evaluation is reported against the vanishing argument.
+        let mut missing = circuit;
+        missing.evals.challenges.clear();
+        match MockProver::run(8, &missing, vec![]) {
+            Err(Error::ArgumentTranscript {
+                argument: ArgumentKind::Vanishing,
+                source,
+            }) => assert_eq!(source.to_string(), "no evaluation of challenge 0"),
+            _ => panic!("expected a vanishing argument error"),
+        }
+    }
+}
@@ -481,6 +481,56 @@ pub(crate) fn powers<F: FieldExt>(base: F) -> impl Iterator<Item = F> {
     std::iter::successors(Some(F::one()), move |power| Some(base * power))
 }
 
+/// The number of bits in each limb when a base field element is split into limbs
+/// that fit in the scalar field.
+pub const LIMB_BITS: usize = 68;
+
+/// Returns the number of limbs of [`LIMB_BITS`] bits needed for a base field element.
+pub(crate) fn num_limbs<C: CurveAffine>() -> usize {
+    (C::Base::NUM_BITS as usize + LIMB_BITS - 1) / LIMB_BITS
+}
+
+/// Splits a little-endian integer into limbs of [`LIMB_BITS`] bits.
+pub(crate) fn to_limbs<F: Field>(bytes: &[u8], num_limbs: usize) -> impl Iterator<Item = F> + '_ {
+    let bit = move |i: usize| {
+        bytes
+            .get(i / 8)
+            .map_or(false, |byte| (byte >> (i % 8)) & 1 == 1)
+    };
+    (0..num_limbs).map(move |limb| {
+        (0..LIMB_BITS).rev().fold(F::zero(), |acc, i| {
+            let acc = acc.double();
+            if bit(limb * LIMB_BITS + i) {
+                acc + F::one()
+            } else {
+                acc
+            }
+        })
+    })
+}
+
+/// Recombines limbs of [`LIMB_BITS`] bits into a field element, or returns `None`
+/// if a limb is out of range or the result is not canonical.
+pub(crate) fn from_limbs<F: PrimeField, B: PrimeField>(limbs: &[F]) -> Option<B> {
+    let mut repr = B::Repr::default();
+    let bytes = repr.as_mut();
+    for (limb_index, limb) in limbs.iter().enumerate() {
+        let limb = limb.to_repr();
+        let limb = limb.as_ref();
+        for i in 0..limb.len() * 8 {
+            if (limb[i / 8] >> (i % 8)) & 1 == 0 {
+                continue;
+            }
+            let position = limb_index * LIMB_BITS + i;
+            if i >= LIMB_BITS || position / 8 >= bytes.len() {
+                return None;
+            }
+            bytes[position / 8] |= 1 << (position % 8);
+        }
+    }
+    Option::from(B::from_repr(repr))
+}
+
 #[cfg(test)]
 use rand_core::OsRng;
 
@@ -7,7 +7,8 @@ use ff::Field;
 use crate::{
     arithmetic::FieldExt,
     plonk::{
-        Advice, Any, Assigned, Challenge, Column, Error, Fixed, Instance, Selector, TableColumn,
+        Advice, Any, Assigned, Challenge, Column, DynamicTable, Error, Fixed, Instance, Selector,
+        TableColumn,
     },
 };
 
@@ -349,6 +350,25 @@ impl<'r, F: Field> Region<'r, F> {
         })
     }
 
+    /// Adds the row at `offset` within this region to the given dynamic lookup table, by
+    /// setting the table's tag on that row.
+    ///
+    /// The table's advice columns must be assigned at `offset` separately.
+    pub fn add_to_dynamic_table(
+        &mut self,
+        table: &DynamicTable,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.region.assign_fixed(
+            &|| "dynamic table tag".into(),
+            table.tag(),
+            offset,
+            &mut || Value::known(Assigned::from(F::one())),
+        )?;
+
+        Ok(())
+    }
+
     /// Constrains a cell to have a constant value.
     ///
     /// Returns an error if the cell is in a column where equality has not been enabled.
@@ -434,6 +454,39 @@ pub trait Layouter<F: Field> {
         N: Fn() -> NR,
         NR: Into<String>;
 
+    /// Assign several independent regions of gates, returning their results in order.
+    ///
+    /// This behaves like calling [`Layouter::assign_region`] once per closure, but
+    /// allows the layouter to synthesize the regions concurrently: the closures may
+    /// not depend on each other's cells, although they may constrain cells of regions
+    /// that were assigned before this call. Layouters that do not support concurrent
+    /// synthesis assign the regions one after another.
+    ///
+    /// ```ignore
+    /// let cells = layouter.assign_regions(
+    ///     || "range check",
+    ///     values.iter().map(|value| move |mut region: Region<'_, F>| {
+    ///         chip.assign(&mut region, *value)
+    ///     }).collect(),
+    /// )?;
+    /// ```
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        assignments
+            .into_iter()
+            .map(|assignment| self.assign_region(&name, assignment))
+            .collect()
+    }
+
     /// Assign a table region to an absolute row number.
     ///
     /// ```ignore
@@ -457,6 +510,44 @@ pub trait Layouter<F: Field> {
         row: usize,
     ) -> Result<(), Error>;
 
+    /// Assign rows of a dynamic lookup table in a new region.
+    ///
+    /// Each entry of `rows` holds one value per column of `table`, in the order the
+    /// columns were passed to `ConstraintSystem::dynamic_table`. Every row is tagged
+    /// as part of the table. Returns the assigned cells, row by row, so that they can
+    /// be constrained against the rest of the circuit.
+    ///
+    /// ```ignore
+    /// let cells = layouter.assign_dynamic_table(|| "memory", &config.memory, &rows)?;
+    /// ```
+    fn assign_dynamic_table<N, NR>(
+        &mut self,
+        name: N,
+        table: &DynamicTable,
+        rows: &[Vec<Value<F>>],
+    ) -> Result<Vec<Vec<AssignedCell<F, F>>>, Error>
+    where
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.assign_region(name, |mut region| {
+            rows.iter()
+                .enumerate()
+                .map(|(offset, row)| {
+                    assert_eq!(row.len(), table.columns().len());
+
+                    region.add_to_dynamic_table(table, offset)?;
+                    row.iter()
+                        .zip(table.columns().iter())
+                        .map(|(value, column)| {
+                            region.assign_advice(|| "dynamic table", *column, offset, || *value)
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+    }
+
     /// Queries the value of the given challenge.
     ///
     /// Returns `Value::unknown()` if the current synthesis phase is before the challenge can be queried.
@@ -509,6 +600,20 @@ impl<'a, F: Field, L: Layouter<F> + 'a> Layouter<F> for NamespacedLayouter<'a, F
         self.0.assign_region(name, assignment)
     }
 
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.0.assign_regions(name, assignments)
+    }
+
     fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
     where
         A: FnMut(Table<'_, F>) -> Result<(), Error>,
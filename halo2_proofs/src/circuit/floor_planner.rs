@@ -1,5 +1,6 @@
 //! Implementations of common circuit floor planners.
 
+mod parallel;
 pub(super) mod single_pass;
 
 mod v1;
@@ -0,0 +1,303 @@
+//! Helpers for assigning independent regions on worker threads.
+//!
+//! [`Assignment`] takes `&mut self`, so regions cannot write into it concurrently.
+//! Instead, each region is synthesized on its own thread into a [`RegionRecord`],
+//! which holds the evaluated witnesses and the copy constraints of the region. The
+//! records are then replayed into the [`Assignment`] in region order, which is cheap
+//! compared to computing the witnesses.
+//!
+//! Regions are synthesized twice, as with [`Layouter::assign_region`]: once to
+//! measure their shapes (with [`InstanceTrackingShape`]), so that they can be placed
+//! in disjoint rows, and once to record their assignments.
+//!
+//! [`Layouter::assign_region`]: crate::circuit::Layouter::assign_region
+
+use std::collections::HashMap;
+use std::fmt;
+
+use ff::Field;
+
+use crate::{
+    circuit::{
+        layouter::{RegionLayouter, RegionShape},
+        Cell, RegionIndex, RegionStart, Value,
+    },
+    plonk::{Advice, Any, Assigned, Assignment, Column, Error, Fixed, Instance, Selector},
+};
+
+/// The values of the instance cells read by a batch of regions, keyed by column and
+/// absolute row.
+pub(super) type InstanceValues<F> = HashMap<(Column<Instance>, usize), Value<F>>;
+
+/// A [`RegionShape`] that also tracks the instance cells read by the region.
+///
+/// Worker threads do not have access to the [`Assignment`], so the instance cells
+/// are queried between the two passes.
+#[derive(Debug)]
+pub(super) struct InstanceTrackingShape {
+    pub(super) shape: RegionShape,
+    pub(super) instance_queries: Vec<(Column<Instance>, usize)>,
+}
+
+impl InstanceTrackingShape {
+    pub(super) fn new(region_index: RegionIndex) -> Self {
+        InstanceTrackingShape {
+            shape: RegionShape::new(region_index),
+            instance_queries: vec![],
+        }
+    }
+}
+
+impl<F: Field> RegionLayouter<F> for InstanceTrackingShape {
+    fn enable_selector<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        RegionLayouter::<F>::enable_selector(&mut self.shape, annotation, selector, offset)
+    }
+
+    fn name_column<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Any>,
+    ) {
+        RegionLayouter::<F>::name_column(&mut self.shape, annotation, column)
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        self.shape.assign_advice(annotation, column, offset, to)
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        self.shape
+            .assign_advice_from_constant(annotation, column, offset, constant)
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        instance: Column<Instance>,
+        row: usize,
+        advice: Column<Advice>,
+        offset: usize,
+    ) -> Result<(Cell, Value<F>), Error> {
+        self.instance_queries.push((instance, row));
+        self.shape
+            .assign_advice_from_instance(annotation, instance, row, advice, offset)
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        self.shape.assign_fixed(annotation, column, offset, to)
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.shape.constrain_constant(cell, constant)
+    }
+
+    fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error> {
+        RegionLayouter::<F>::constrain_equal(&mut self.shape, left, right)
+    }
+}
+
+/// An assignment made by a region, with offsets relative to the start of the region.
+#[derive(Debug)]
+enum Op<F: Field> {
+    AnnotateColumn(String, Column<Any>),
+    EnableSelector(String, Selector, usize),
+    AssignAdvice(String, Column<Advice>, usize, Value<Assigned<F>>),
+    AssignFixed(String, Column<Fixed>, usize, Value<Assigned<F>>),
+    Copy(Cell, Cell),
+    CopyInstance(Cell, Column<Instance>, usize),
+}
+
+/// A [`RegionLayouter`] that records the assignments of a region instead of making
+/// them, so that the region can be synthesized on a worker thread.
+///
+/// Witnesses and annotations are evaluated eagerly, as the closures that produce them
+/// cannot outlive the synthesis of the region.
+pub(super) struct RegionRecord<'i, F: Field> {
+    region_index: RegionIndex,
+    instances: &'i InstanceValues<F>,
+    ops: Vec<Op<F>>,
+    /// Stores the constants to be assigned, and the cells to which they are copied.
+    pub(super) constants: Vec<(Assigned<F>, Cell)>,
+}
+
+impl<'i, F: Field> fmt::Debug for RegionRecord<'i, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegionRecord")
+            .field("region_index", &self.region_index)
+            .field("ops", &self.ops)
+            .field("constants", &self.constants)
+            .finish()
+    }
+}
+
+impl<'i, F: Field> RegionRecord<'i, F> {
+    pub(super) fn new(region_index: RegionIndex, instances: &'i InstanceValues<F>) -> Self {
+        RegionRecord {
+            region_index,
+            instances,
+            ops: vec![],
+            constants: vec![],
+        }
+    }
+
+    /// Replays the recorded assignments into `cs`, given the starting row of every
+    /// region assigned so far.
+    ///
+    /// The caller is responsible for entering and exiting the region.
+    pub(super) fn replay<CS: Assignment<F>>(
+        &mut self,
+        cs: &mut CS,
+        regions: &[RegionStart],
+    ) -> Result<(), Error> {
+        let start = *regions[*self.region_index];
+        let row = |cell: &Cell| *regions[*cell.region_index] + cell.row_offset;
+
+        for op in self.ops.drain(..) {
+            match op {
+                Op::AnnotateColumn(annotation, column) => {
+                    cs.annotate_column(|| annotation, column);
+                }
+                Op::EnableSelector(annotation, selector, offset) => {
+                    cs.enable_selector(|| annotation, &selector, start + offset)?;
+                }
+                Op::AssignAdvice(annotation, column, offset, value) => {
+                    cs.assign_advice(|| annotation, column, start + offset, || value)?;
+                }
+                Op::AssignFixed(annotation, column, offset, value) => {
+                    cs.assign_fixed(|| annotation, column, start + offset, || value)?;
+                }
+                Op::Copy(left, right) => {
+                    cs.copy(left.column, row(&left), right.column, row(&right))?;
+                }
+                Op::CopyInstance(cell, instance, instance_row) => {
+                    cs.copy(cell.column, row(&cell), instance.into(), instance_row)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'i, F: Field> RegionLayouter<F> for RegionRecord<'i, F> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.ops
+            .push(Op::EnableSelector(annotation(), *selector, offset));
+        Ok(())
+    }
+
+    fn name_column<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Any>,
+    ) {
+        self.ops.push(Op::AnnotateColumn(annotation(), column));
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        self.ops
+            .push(Op::AssignAdvice(annotation(), column, offset, to()));
+
+        Ok(Cell {
+            region_index: self.region_index,
+            row_offset: offset,
+            column: column.into(),
+        })
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        let advice =
+            self.assign_advice(annotation, column, offset, &mut || Value::known(constant))?;
+        self.constrain_constant(advice, constant)?;
+
+        Ok(advice)
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        instance: Column<Instance>,
+        row: usize,
+        advice: Column<Advice>,
+        offset: usize,
+    ) -> Result<(Cell, Value<F>), Error> {
+        // The shape pass recorded every instance cell that this region reads, so a
+        // missing value means that the region did not synthesize deterministically.
+        let value = *self
+            .instances
+            .get(&(instance, row))
+            .ok_or(Error::Synthesis)?;
+
+        let cell = self.assign_advice(annotation, advice, offset, &mut || value.to_field())?;
+        self.ops.push(Op::CopyInstance(cell, instance, row));
+
+        Ok((cell, value))
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        self.ops
+            .push(Op::AssignFixed(annotation(), column, offset, to()));
+
+        Ok(Cell {
+            region_index: self.region_index,
+            row_offset: offset,
+            column: column.into(),
+        })
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.constants.push((constant, cell));
+        Ok(())
+    }
+
+    fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error> {
+        self.ops.push(Op::Copy(left, right));
+        Ok(())
+    }
+}
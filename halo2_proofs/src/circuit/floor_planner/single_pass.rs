@@ -1,10 +1,12 @@
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap};
 use std::fmt;
 use std::marker::PhantomData;
 
 use ff::Field;
+use rayon::prelude::*;
 
+use super::parallel::{InstanceTrackingShape, InstanceValues, RegionRecord};
 use crate::{
     circuit::{
         layouter::{RegionColumn, RegionLayouter, RegionShape, TableLayouter},
@@ -21,6 +23,9 @@ use crate::{
 /// This floor planner is suitable for debugging circuits. It aims to reflect the circuit
 /// "business logic" in the circuit layout as closely as possible. It uses a single-pass
 /// layouter that does not reorder regions for optimal packing.
+///
+/// Regions assigned together with [`Layouter::assign_regions`] are synthesized
+/// concurrently, and placed in the order they were given.
 #[derive(Debug)]
 pub struct SimpleFloorPlanner;
 
@@ -71,28 +76,10 @@ impl<'a, F: Field, CS: Assignment<F>> SingleChipLayouter<'a, F, CS> {
         };
         Ok(ret)
     }
-}
-
-impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a, F, CS> {
-    type Root = Self;
 
-    fn assign_region<A, AR, N, NR>(&mut self, name: N, mut assignment: A) -> Result<AR, Error>
-    where
-        A: FnMut(Region<'_, F>) -> Result<AR, Error>,
-        N: Fn() -> NR,
-        NR: Into<String>,
-    {
-        let region_index = self.regions.len();
-
-        // Get shape of the region.
-        let mut shape = RegionShape::new(region_index.into());
-        {
-            let region: &mut dyn RegionLayouter<F> = &mut shape;
-            assignment(region.into())?;
-        }
-
-        // Lay out this region. We implement the simplest approach here: position the
-        // region starting at the earliest row for which none of the columns are in use.
+    /// Lays out a region. We implement the simplest approach here: position the
+    /// region starting at the earliest row for which none of the columns are in use.
+    fn lay_out_region(&mut self, shape: RegionShape) {
         let mut region_start = 0;
         for column in &shape.columns {
             region_start = cmp::max(region_start, self.columns.get(column).cloned().unwrap_or(0));
@@ -103,19 +90,14 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a
         for column in shape.columns {
             self.columns.insert(column, region_start + shape.row_count);
         }
+    }
 
-        // Assign region cells.
-        self.cs.enter_region(name);
-        let mut region = SingleChipLayouterRegion::new(self, region_index.into());
-        let result = {
-            let region: &mut dyn RegionLayouter<F> = &mut region;
-            assignment(region.into())
-        }?;
-        let constants_to_assign = region.constants;
-        self.cs.exit_region();
-
-        // Assign constants. For the simple floor planner, we assign constants in order in
-        // the first `constants` column.
+    /// Assigns constants. For the simple floor planner, we assign constants in order in
+    /// the first `constants` column.
+    fn assign_constants(
+        &mut self,
+        constants_to_assign: Vec<(Assigned<F>, Cell)>,
+    ) -> Result<(), Error> {
         if self.constants.is_empty() {
             if !constants_to_assign.is_empty() {
                 return Err(Error::NotEnoughColumnsForConstants);
@@ -143,9 +125,113 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a
             }
         }
 
+        Ok(())
+    }
+}
+
+impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a, F, CS> {
+    type Root = Self;
+
+    fn assign_region<A, AR, N, NR>(&mut self, name: N, mut assignment: A) -> Result<AR, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let region_index = self.regions.len();
+
+        // Get shape of the region.
+        let mut shape = RegionShape::new(region_index.into());
+        {
+            let region: &mut dyn RegionLayouter<F> = &mut shape;
+            assignment(region.into())?;
+        }
+
+        self.lay_out_region(shape);
+
+        // Assign region cells.
+        self.cs.enter_region(name);
+        let mut region = SingleChipLayouterRegion::new(self, region_index.into());
+        let result = {
+            let region: &mut dyn RegionLayouter<F> = &mut region;
+            assignment(region.into())
+        }?;
+        let constants_to_assign = region.constants;
+        self.cs.exit_region();
+
+        self.assign_constants(constants_to_assign)?;
+
         Ok(result)
     }
 
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        mut assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let first_region = self.regions.len();
+
+        // Get the shapes of the regions, and the instance cells that they read.
+        let shapes = assignments
+            .par_iter_mut()
+            .enumerate()
+            .map(|(i, assignment)| -> Result<_, Error> {
+                let mut shape = InstanceTrackingShape::new((first_region + i).into());
+                {
+                    let region: &mut dyn RegionLayouter<F> = &mut shape;
+                    assignment(region.into())?;
+                }
+                Ok(shape)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        // Lay out the regions in order, which places regions that share a column in
+        // disjoint rows.
+        let mut instances = InstanceValues::default();
+        for shape in shapes {
+            for (column, row) in shape.instance_queries {
+                if let Entry::Vacant(entry) = instances.entry((column, row)) {
+                    entry.insert(self.cs.query_instance(column, row)?);
+                }
+            }
+            self.lay_out_region(shape.shape);
+        }
+
+        // Record the region cells concurrently.
+        let instances = &instances;
+        let records = assignments
+            .par_iter_mut()
+            .enumerate()
+            .map(|(i, assignment)| -> Result<_, Error> {
+                let mut record = RegionRecord::new((first_region + i).into(), instances);
+                let result = {
+                    let region: &mut dyn RegionLayouter<F> = &mut record;
+                    assignment(region.into())
+                }?;
+                Ok((result, record))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        // Assign region cells, and then constants, in region order.
+        let mut results = Vec::with_capacity(records.len());
+        for (result, mut record) in records {
+            self.cs.enter_region(&name);
+            record.replay(self.cs, &self.regions)?;
+            self.cs.exit_region();
+
+            self.assign_constants(record.constants)?;
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
     fn assign_table<A, N, NR>(&mut self, name: N, mut assignment: A) -> Result<(), Error>
     where
         A: FnMut(Table<'_, F>) -> Result<(), Error>,
@@ -154,8 +240,8 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a
     {
         // Maintenance hazard: there is near-duplicate code in `v1::AssignmentPass::assign_table`.
         // Assign table cells.
-        self.cs.enter_region(name);
-        let mut table = SimpleTableLayouter::new(self.cs, &self.table_columns);
+        self.cs.enter_region(&name);
+        let mut table = SimpleTableLayouter::new(self.cs, name().into(), &self.table_columns);
         {
             let table: &mut dyn TableLayouter<F> = &mut table;
             assignment(table.into())
@@ -180,7 +266,7 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a
                     _ => None,
                 }) {
                 Some(Some(len)) => len,
-                _ => return Err(Error::Synthesis), // TODO better error
+                _ => return Err(incomplete_table_error(name(), &default_and_assigned)),
             }
         };
 
@@ -393,6 +479,7 @@ type DefaultTableValue<F> = Option<Value<Assigned<F>>>;
 
 pub(crate) struct SimpleTableLayouter<'r, 'a, F: Field, CS: Assignment<F> + 'a> {
     cs: &'a mut CS,
+    name: String,
     used_columns: &'r [TableColumn],
     // maps from a fixed column to a pair (default value, vector saying which rows are assigned)
     pub(crate) default_and_assigned: HashMap<TableColumn, (DefaultTableValue<F>, Vec<bool>)>,
@@ -401,6 +488,7 @@ pub(crate) struct SimpleTableLayouter<'r, 'a, F: Field, CS: Assignment<F> + 'a>
 impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> fmt::Debug for SimpleTableLayouter<'r, 'a, F, CS> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SimpleTableLayouter")
+            .field("name", &self.name)
             .field("used_columns", &self.used_columns)
             .field("default_and_assigned", &self.default_and_assigned)
             .finish()
@@ -408,15 +496,45 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> fmt::Debug for SimpleTableLayoute
 }
 
 impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> SimpleTableLayouter<'r, 'a, F, CS> {
-    pub(crate) fn new(cs: &'a mut CS, used_columns: &'r [TableColumn]) -> Self {
+    pub(crate) fn new(cs: &'a mut CS, name: String, used_columns: &'r [TableColumn]) -> Self {
         SimpleTableLayouter {
             cs,
+            name,
             used_columns,
             default_and_assigned: HashMap::default(),
         }
     }
 }
 
+/// Constructs the error for a table whose columns are not all assigned up to the
+/// same length, pointing at the first cell that is missing an assignment.
+pub(crate) fn incomplete_table_error<F: Field, NR: Into<String>>(
+    name: NR,
+    default_and_assigned: &HashMap<TableColumn, (DefaultTableValue<F>, Vec<bool>)>,
+) -> Error {
+    let len = default_and_assigned
+        .values()
+        .map(|(_, assigned)| assigned.len())
+        .max()
+        .unwrap_or(0);
+
+    default_and_assigned
+        .iter()
+        .filter_map(|(column, (_, assigned))| {
+            (0..len)
+                .find(|row| !assigned.get(*row).cloned().unwrap_or(false))
+                .map(|row| (*column, row))
+        })
+        .min_by_key(|(column, row)| (*row, column.inner().index()))
+        .map(|(column, row)| Error::SynthesisAt {
+            region: Some(name.into()),
+            column: column.inner().into(),
+            row,
+            reason: "table column is not assigned up to the length of the table".to_string(),
+        })
+        .unwrap_or(Error::Synthesis)
+}
+
 impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> TableLayouter<F>
     for SimpleTableLayouter<'r, 'a, F, CS>
 {
@@ -428,7 +546,12 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> TableLayouter<F>
         to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
     ) -> Result<(), Error> {
         if self.used_columns.contains(&column) {
-            return Err(Error::Synthesis); // TODO better error
+            return Err(Error::SynthesisAt {
+                region: Some(self.name.clone()),
+                column: column.inner().into(),
+                row: offset,
+                reason: "table column has already been used by another table".to_string(),
+            });
         }
 
         let entry = self.default_and_assigned.entry(column).or_default();
@@ -450,7 +573,14 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> TableLayouter<F>
             (true, 0) => entry.0 = Some(value),
             // Since there is already an existing default value for this table column,
             // the caller should not be attempting to assign another value at offset 0.
-            (false, 0) => return Err(Error::Synthesis), // TODO better error
+            (false, 0) => {
+                return Err(Error::SynthesisAt {
+                    region: Some(self.name.clone()),
+                    column: column.inner().into(),
+                    row: offset,
+                    reason: "table cell at offset 0 has already been assigned".to_string(),
+                })
+            }
             _ => (),
         }
         if entry.1.len() <= offset {
@@ -515,4 +645,213 @@ mod tests {
             Error::NotEnoughColumnsForConstants,
         ));
     }
+
+    #[test]
+    fn table_column_reused() {
+        use crate::{
+            circuit::{Layouter, Value},
+            plonk::{ConstraintSystem, TableColumn},
+        };
+
+        struct MyCircuit {}
+
+        impl Circuit<vesta::Scalar> for MyCircuit {
+            type Config = TableColumn;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {}
+            }
+
+            fn configure(meta: &mut ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                meta.lookup_table_column()
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), Error> {
+                for name in ["first table", "second table"] {
+                    layouter.assign_table(
+                        || name,
+                        |mut table| {
+                            table.assign_cell(
+                                || "value",
+                                config,
+                                0,
+                                || Value::known(vesta::Scalar::zero()),
+                            )
+                        },
+                    )?;
+                }
+
+                Ok(())
+            }
+        }
+
+        match MockProver::run(3, &MyCircuit {}, vec![]) {
+            Err(Error::SynthesisAt {
+                region,
+                column,
+                row,
+                ..
+            }) => {
+                assert_eq!(region.as_deref(), Some("second table"));
+                assert_eq!(column.index(), 0);
+                assert_eq!(row, 0);
+            }
+            _ => panic!("expected a synthesis error in the second table"),
+        }
+    }
+
+    #[test]
+    fn assign_regions() {
+        use crate::{
+            circuit::{Layouter, Region, Value},
+            plonk::{keygen_vk, ConstraintSystem, Expression, Instance, Selector},
+            poly::{commitment::ParamsProver, ipa::commitment::ParamsIPA, Rotation},
+        };
+        use ff::Field;
+
+        const K: u32 = 5;
+
+        #[derive(Clone)]
+        struct Config {
+            a: Column<Advice>,
+            b: Column<Advice>,
+            s: Selector,
+            instance: Column<Instance>,
+        }
+
+        /// Squares each value in its own region. The first value is copied from an
+        /// earlier region, and the others from the instance column.
+        struct MyCircuit {
+            values: Vec<Value<vesta::Scalar>>,
+            parallel: bool,
+        }
+
+        impl Circuit<vesta::Scalar> for MyCircuit {
+            type Config = Config;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {
+                    values: vec![Value::unknown(); self.values.len()],
+                    parallel: self.parallel,
+                }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let s = meta.selector();
+                let instance = meta.instance_column();
+                let constant = meta.fixed_column();
+                meta.enable_equality(a);
+                meta.enable_equality(b);
+                meta.enable_equality(instance);
+                meta.enable_constant(constant);
+
+                meta.create_gate("square", |meta| {
+                    let s = meta.query_selector(s);
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let b = meta.query_advice(b, Rotation::cur());
+                    let b_next = meta.query_advice(b, Rotation::next());
+                    vec![
+                        s.clone() * (a.clone() * a - b),
+                        s * (b_next - Expression::Constant(vesta::Scalar::one())),
+                    ]
+                });
+
+                Config { a, b, s, instance }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), Error> {
+                let first = layouter.assign_region(
+                    || "first",
+                    |mut region| {
+                        region.assign_advice_from_instance(|| "a", config.instance, 0, config.a, 0)
+                    },
+                )?;
+
+                let assignments: Vec<_> = self
+                    .values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        let config = config.clone();
+                        let first = first.cell();
+                        move |mut region: Region<'_, vesta::Scalar>| {
+                            config.s.enable(&mut region, 0)?;
+                            let a = if i == 0 {
+                                let a = region.assign_advice(|| "a", config.a, 0, || *value)?;
+                                region.constrain_equal(first, a.cell())?;
+                                a
+                            } else {
+                                region.assign_advice_from_instance(
+                                    || "a",
+                                    config.instance,
+                                    i,
+                                    config.a,
+                                    0,
+                                )?
+                            };
+                            region.assign_advice(
+                                || "b",
+                                config.b,
+                                0,
+                                || a.value().map(|a| a.square()),
+                            )?;
+                            region.assign_advice_from_constant(
+                                || "one",
+                                config.b,
+                                1,
+                                vesta::Scalar::one(),
+                            )?;
+                            Ok(a)
+                        }
+                    })
+                    .collect();
+
+                if self.parallel {
+                    layouter.assign_regions(|| "square", assignments)?;
+                } else {
+                    assignments
+                        .into_iter()
+                        .map(|assignment| layouter.assign_region(|| "square", assignment))
+                        .collect::<Result<Vec<_>, _>>()?;
+                }
+
+                Ok(())
+            }
+        }
+
+        let instances: Vec<vesta::Scalar> = (1..6).map(vesta::Scalar::from).collect();
+        let circuit = |parallel| MyCircuit {
+            values: instances.iter().cloned().map(Value::known).collect(),
+            parallel,
+        };
+
+        let prover = MockProver::run(K, &circuit(true), vec![instances.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let mut wrong = instances.clone();
+        wrong[0] += vesta::Scalar::one();
+        let prover = MockProver::run(K, &circuit(true), vec![wrong]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Both modes lay out the circuit identically.
+        let params = ParamsIPA::<vesta::Affine>::new(K);
+        let parallel = keygen_vk(&params, &circuit(true)).unwrap();
+        let sequential = keygen_vk(&params, &circuit(false)).unwrap();
+        assert_eq!(
+            format!("{:?}", parallel.pinned()),
+            format!("{:?}", sequential.pinned())
+        );
+    }
 }
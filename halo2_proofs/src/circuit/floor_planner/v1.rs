@@ -4,7 +4,7 @@ use ff::Field;
 
 use crate::{
     circuit::{
-        floor_planner::single_pass::SimpleTableLayouter,
+        floor_planner::single_pass::{incomplete_table_error, SimpleTableLayouter},
         layouter::{RegionColumn, RegionLayouter, RegionShape, TableLayouter},
         Cell, Layouter, Region, RegionIndex, RegionStart, Table, Value,
     },
@@ -282,7 +282,7 @@ impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> AssignmentPass<'p, 'a, F, CS> {
         let region_index = self.region_index;
         self.region_index += 1;
 
-        self.plan.cs.enter_region(name);
+        self.plan.cs.enter_region(&name);
         let mut region = V1Region::new(self.plan, region_index.into());
         let result = {
             let region: &mut dyn RegionLayouter<F> = &mut region;
@@ -302,8 +302,9 @@ impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> AssignmentPass<'p, 'a, F, CS> {
         // Maintenance hazard: there is near-duplicate code in `SingleChipLayouter::assign_table`.
 
         // Assign table cells.
-        self.plan.cs.enter_region(name);
-        let mut table = SimpleTableLayouter::new(self.plan.cs, &self.plan.table_columns);
+        self.plan.cs.enter_region(&name);
+        let mut table =
+            SimpleTableLayouter::new(self.plan.cs, name().into(), &self.plan.table_columns);
         let result = {
             let table: &mut dyn TableLayouter<F> = &mut table;
             assignment(table.into())
@@ -328,7 +329,7 @@ impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> AssignmentPass<'p, 'a, F, CS> {
                     _ => None,
                 }) {
                 Some(Some(len)) => len,
-                _ => return Err(Error::Synthesis), // TODO better error
+                _ => return Err(incomplete_table_error(name(), &default_and_assigned)),
             }
         };
 
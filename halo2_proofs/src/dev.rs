@@ -1,5 +1,6 @@
 //! Tools for developing circuits.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
@@ -43,6 +44,11 @@ pub use cost::CircuitCost;
 mod gates;
 pub use gates::CircuitGates;
 
+mod lint;
+pub use lint::{CircuitLint, LintIssue, LookupArgument};
+
+pub mod witness;
+
 #[cfg(feature = "dev-graph")]
 mod graph;
 
@@ -50,7 +56,7 @@ mod graph;
 #[cfg_attr(docsrs, doc(cfg(feature = "dev-graph")))]
 pub use graph::{circuit_dot_graph, layout::CircuitLayout};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct Region {
     /// The name of the region. Not required to be unique.
     name: String,
@@ -282,7 +288,7 @@ impl<F: Group + Field> Mul<F> for Value<F> {
 ///     } if current_k == 2,
 /// ));
 /// ```
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct MockProver<F: Group + Field> {
     k: u32,
     n: u32,
@@ -410,7 +416,7 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
 
     fn assign_advice<V, VR, A, AR>(
         &mut self,
-        _: A,
+        annotation: A,
         column: Column<Advice>,
         row: usize,
         to: V,
@@ -446,16 +452,29 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
                 if let CellValue::Assigned(value) = value {
                     // Inconsistent assignment between different phases.
                     if value != &to {
-                        return Err(Error::Synthesis);
+                        return Err(Error::SynthesisAt {
+                            region: self.current_region.as_ref().map(|r| r.name.clone()),
+                            column: column.into(),
+                            row,
+                            reason: format!(
+                                "inconsistent assignment of \"{}\" between phases",
+                                annotation().into()
+                            ),
+                        });
                     }
                 } else {
                     *value = CellValue::Assigned(to);
                 }
             }
-            Err(err) => {
+            Err(_) => {
                 // Propagate `assign` error if the column is in current phase.
                 if self.in_phase(column.column_type().phase) {
-                    return Err(err);
+                    return Err(Error::SynthesisAt {
+                        region: self.current_region.as_ref().map(|r| r.name.clone()),
+                        column: column.into(),
+                        row,
+                        reason: format!("missing witness for \"{}\"", annotation().into()),
+                    });
                 }
             }
         }
@@ -571,6 +590,30 @@ impl<F: FieldExt> MockProver<F> {
         k: u32,
         circuit: &ConcreteCircuit,
         instance: Vec<Vec<F>>,
+    ) -> Result<Self, Error> {
+        Self::run_inner(k, circuit, instance, None)
+    }
+
+    /// Runs a synthetic keygen-and-prove operation on the given circuit, using the given
+    /// values for the circuit's challenges instead of deriving them deterministically.
+    ///
+    /// `challenges` is indexed by [`Challenge::index`], and must contain a value for
+    /// every challenge in the circuit. Passing the values squeezed by a real prover lets
+    /// later-phase columns be reproduced (and debugged) exactly.
+    pub fn run_with_challenges<ConcreteCircuit: Circuit<F>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+        instance: Vec<Vec<F>>,
+        challenges: Vec<F>,
+    ) -> Result<Self, Error> {
+        Self::run_inner(k, circuit, instance, Some(challenges))
+    }
+
+    fn run_inner<ConcreteCircuit: Circuit<F>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+        instance: Vec<Vec<F>>,
+        challenges: Option<Vec<F>>,
     ) -> Result<Self, Error> {
         let n = 1 << k;
 
@@ -618,15 +661,24 @@ impl<F: FieldExt> MockProver<F> {
         let permutation = permutation::keygen::Assembly::new(n, &cs.permutation);
         let constants = cs.constants.clone();
 
-        // Use hash chain to derive deterministic challenges for testing
-        let challenges = {
-            let mut hash: [u8; 64] = blake2b(b"Halo2-MockProver").as_bytes().try_into().unwrap();
-            iter::repeat_with(|| {
-                hash = blake2b(&hash).as_bytes().try_into().unwrap();
-                F::from_bytes_wide(&hash)
-            })
-            .take(cs.num_challenges)
-            .collect()
+        let challenges = match challenges {
+            Some(challenges) => {
+                if challenges.len() != cs.num_challenges {
+                    return Err(Error::InvalidChallenges);
+                }
+                challenges
+            }
+            // Use hash chain to derive deterministic challenges for testing
+            None => {
+                let mut hash: [u8; 64] =
+                    blake2b(b"Halo2-MockProver").as_bytes().try_into().unwrap();
+                iter::repeat_with(|| {
+                    hash = blake2b(&hash).as_bytes().try_into().unwrap();
+                    F::from_bytes_wide(&hash)
+                })
+                .take(cs.num_challenges)
+                .collect()
+            }
         };
 
         let mut prover = MockProver {
@@ -674,6 +726,97 @@ impl<F: FieldExt> MockProver<F> {
         self.verify_at_rows(self.usable_rows.clone(), self.usable_rows.clone())
     }
 
+    /// Returns `Ok(())` if this `MockProver` is satisfied, or the failures indicating the
+    /// reasons that the circuit is not satisfied, grouped by phase.
+    ///
+    /// A failure belongs to the latest phase whose advice columns or challenges it
+    /// depends on; a failure that only involves first-phase advice, fixed and instance
+    /// columns belongs to phase 0. Failures in later phases are often a consequence of
+    /// failures in earlier ones, so the earliest phase is usually the one to look at.
+    pub fn verify_per_phase(&self) -> Result<(), BTreeMap<u8, Vec<VerifyFailure>>> {
+        let failures = match self.verify() {
+            Ok(()) => return Ok(()),
+            Err(failures) => failures,
+        };
+
+        let mut phases: BTreeMap<u8, Vec<VerifyFailure>> = BTreeMap::new();
+        for failure in failures {
+            phases
+                .entry(self.failure_phase(&failure))
+                .or_default()
+                .push(failure);
+        }
+        Err(phases)
+    }
+
+    fn failure_phase(&self, failure: &VerifyFailure) -> u8 {
+        let column_phase = |column_type: &Any| match column_type {
+            Any::Advice(advice) => advice.phase(),
+            _ => 0,
+        };
+        fn expressions_phase<'a, F: Field + 'a>(
+            expressions: impl Iterator<Item = &'a Expression<F>>,
+        ) -> u8 {
+            expressions
+                .map(|expression| {
+                    expression.evaluate(
+                        &|_| 0,
+                        &|_| 0,
+                        &|_| 0,
+                        &|query| query.phase(),
+                        &|_| 0,
+                        // A challenge is squeezed after its phase, so anything that
+                        // depends on it belongs to the next one.
+                        &|challenge| challenge.phase() + 1,
+                        &|a| a,
+                        &|a, b| a.max(b),
+                        &|a, b| a.max(b),
+                        &|a, _| a,
+                    )
+                })
+                .max()
+                .unwrap_or(0)
+        }
+
+        match failure {
+            VerifyFailure::CellNotAssigned { column, .. } => column_phase(column.column_type()),
+            VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+            | VerifyFailure::ConstraintPoisoned { constraint } => expressions_phase(iter::once(
+                &self.cs.gates[constraint.gate.index].polynomials()[constraint.index],
+            )),
+            VerifyFailure::Lookup { lookup_index, .. } => {
+                let lookup = &self.cs.lookups[*lookup_index];
+                expressions_phase(
+                    lookup
+                        .input_expressions
+                        .iter()
+                        .chain(lookup.table_expressions.iter()),
+                )
+            }
+            VerifyFailure::LogUpLookup { lookup_index, .. } => {
+                let logup = &self.cs.logups[*lookup_index];
+                expressions_phase(
+                    logup
+                        .input_expressions
+                        .iter()
+                        .flatten()
+                        .chain(logup.table_expressions.iter()),
+                )
+            }
+            VerifyFailure::Shuffle { shuffle_index, .. } => {
+                let shuffle = &self.cs.shuffles[*shuffle_index];
+                expressions_phase(
+                    shuffle
+                        .input_expressions
+                        .iter()
+                        .chain(shuffle.shuffle_expressions.iter()),
+                )
+            }
+            VerifyFailure::Permutation { column, .. }
+            | VerifyFailure::UnderConstrained { column, .. } => column_phase(&column.column_type),
+        }
+    }
+
     /// Returns `Ok(())` if this `MockProver` is satisfied, or a list of errors indicating
     /// the reasons that the circuit is not satisfied.
     /// Constraints are only checked at `gate_row_ids`,
@@ -955,6 +1098,204 @@ impl<F: FieldExt> MockProver<F> {
                         .collect::<Vec<_>>()
                 });
 
+        // Check that all LogUp lookup inputs exist in their respective tables.
+        let logup_errors = self
+            .cs
+            .logups
+            .iter()
+            .enumerate()
+            .flat_map(|(lookup_index, logup)| {
+                let load = |expression: &Expression<F>, row| {
+                    expression.evaluate_lazy(
+                        &|scalar| Value::Real(scalar),
+                        &|_| panic!("virtual selectors are removed during optimization"),
+                        &|query| {
+                            let query = self.cs.fixed_queries[query.index];
+                            let column_index = query.0.index();
+                            let rotation = query.1 .0;
+                            self.fixed[column_index]
+                                [(row as i32 + n + rotation) as usize % n as usize]
+                                .into()
+                        },
+                        &|query| {
+                            let query = self.cs.advice_queries[query.index];
+                            let column_index = query.0.index();
+                            let rotation = query.1 .0;
+                            self.advice[column_index]
+                                [(row as i32 + n + rotation) as usize % n as usize]
+                                .into()
+                        },
+                        &|query| {
+                            let query = self.cs.instance_queries[query.index];
+                            let column_index = query.0.index();
+                            let rotation = query.1 .0;
+                            Value::Real(
+                                self.instance[column_index]
+                                    [(row as i32 + n + rotation) as usize % n as usize],
+                            )
+                        },
+                        &|challenge| Value::Real(self.challenges[challenge.index()]),
+                        &|a| -a,
+                        &|a, b| a + b,
+                        &|a, b| a * b,
+                        &|a, scalar| a * scalar,
+                        &Value::Real(F::zero()),
+                    )
+                };
+
+                assert!(self.usable_rows.end > 0);
+
+                // In the real prover, the LogUp lookup expressions are never enforced on
+                // unusable rows, due to the (1 - (l_last(X) + l_blind(X))) term.
+                let mut table: Vec<Vec<Value<F>>> = self
+                    .usable_rows
+                    .clone()
+                    .into_iter()
+                    .map(|table_row| {
+                        logup
+                            .table_expressions
+                            .iter()
+                            .map(|c| load(c, table_row))
+                            .collect()
+                    })
+                    .collect();
+                table.sort_unstable();
+                table.dedup();
+                let table = &table;
+
+                logup
+                    .input_expressions
+                    .iter()
+                    .flat_map(|input| {
+                        lookup_input_row_ids
+                            .clone()
+                            .into_iter()
+                            .filter_map(|input_row| {
+                                let t: Vec<_> = input.iter().map(|c| load(c, input_row)).collect();
+
+                                if table.binary_search(&t).is_err() {
+                                    Some(VerifyFailure::LogUpLookup {
+                                        name: logup.name,
+                                        lookup_index,
+                                        location: FailureLocation::find_expressions(
+                                            &self.cs,
+                                            &self.regions,
+                                            input_row,
+                                            input.iter(),
+                                        ),
+                                    })
+                                } else {
+                                    None
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+        // Check that each shuffle's input rows are a permutation of its shuffle rows.
+        let shuffle_errors =
+            self.cs
+                .shuffles
+                .iter()
+                .enumerate()
+                .flat_map(|(shuffle_index, shuffle)| {
+                    let load = |expression: &Expression<F>, row| {
+                        expression.evaluate_lazy(
+                            &|scalar| Value::Real(scalar),
+                            &|_| panic!("virtual selectors are removed during optimization"),
+                            &|query| {
+                                let query = self.cs.fixed_queries[query.index];
+                                let column_index = query.0.index();
+                                let rotation = query.1 .0;
+                                self.fixed[column_index]
+                                    [(row as i32 + n + rotation) as usize % n as usize]
+                                    .into()
+                            },
+                            &|query| {
+                                let query = self.cs.advice_queries[query.index];
+                                let column_index = query.0.index();
+                                let rotation = query.1 .0;
+                                self.advice[column_index]
+                                    [(row as i32 + n + rotation) as usize % n as usize]
+                                    .into()
+                            },
+                            &|query| {
+                                let query = self.cs.instance_queries[query.index];
+                                let column_index = query.0.index();
+                                let rotation = query.1 .0;
+                                Value::Real(
+                                    self.instance[column_index]
+                                        [(row as i32 + n + rotation) as usize % n as usize],
+                                )
+                            },
+                            &|challenge| Value::Real(self.challenges[challenge.index()]),
+                            &|a| -a,
+                            &|a, b| a + b,
+                            &|a, b| a * b,
+                            &|a, scalar| a * scalar,
+                            &Value::Real(F::zero()),
+                        )
+                    };
+
+                    assert!(shuffle.shuffle_expressions.len() == shuffle.input_expressions.len());
+                    assert!(self.usable_rows.end > 0);
+
+                    // In the real prover, the shuffle expressions are never enforced on
+                    // unusable rows, due to the (1 - (l_last(X) + l_blind(X))) term.
+                    let mut shuffle_rows: Vec<Vec<Value<F>>> = self
+                        .usable_rows
+                        .clone()
+                        .into_iter()
+                        .map(|row| {
+                            shuffle
+                                .shuffle_expressions
+                                .iter()
+                                .map(|c| load(c, row))
+                                .collect()
+                        })
+                        .collect();
+                    shuffle_rows.sort_unstable();
+
+                    let mut input_rows: Vec<(Vec<Value<F>>, usize)> = self
+                        .usable_rows
+                        .clone()
+                        .into_iter()
+                        .map(|input_row| {
+                            let t = shuffle
+                                .input_expressions
+                                .iter()
+                                .map(|c| load(c, input_row))
+                                .collect();
+
+                            // Also keep track of the original input row, since we're going to sort.
+                            (t, input_row)
+                        })
+                        .collect();
+                    input_rows.sort_unstable();
+
+                    input_rows
+                        .iter()
+                        .zip(shuffle_rows.iter())
+                        .filter_map(|((input_value, row), shuffle_value)| {
+                            if shuffle_value != input_value {
+                                Some(VerifyFailure::Shuffle {
+                                    name: shuffle.name,
+                                    shuffle_index,
+                                    location: FailureLocation::find_expressions(
+                                        &self.cs,
+                                        &self.regions,
+                                        *row,
+                                        shuffle.input_expressions.iter(),
+                                    ),
+                                })
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                });
+
         // Check that permutations preserve the original values of the cells.
         let perm_errors = {
             // Original values of columns involved in the permutation.
@@ -1004,6 +1345,8 @@ impl<F: FieldExt> MockProver<F> {
             .chain(selector_errors)
             .chain(gate_errors)
             .chain(lookup_errors)
+            .chain(logup_errors)
+            .chain(shuffle_errors)
             .chain(perm_errors)
             .collect();
         if errors.is_empty() {
@@ -1316,6 +1659,186 @@ impl<F: FieldExt> MockProver<F> {
                         .collect::<Vec<_>>()
                 });
 
+        // Check that all LogUp lookup inputs exist in their respective tables.
+        let logup_errors = self
+            .cs
+            .logups
+            .iter()
+            .enumerate()
+            .flat_map(|(lookup_index, logup)| {
+                let load = |expression: &Expression<F>, row| {
+                    expression.evaluate_lazy(
+                        &|scalar| Value::Real(scalar),
+                        &|_| panic!("virtual selectors are removed during optimization"),
+                        &|query| {
+                            self.fixed[query.column_index]
+                                [(row as i32 + n + query.rotation.0) as usize % n as usize]
+                                .into()
+                        },
+                        &|query| {
+                            self.advice[query.column_index]
+                                [(row as i32 + n + query.rotation.0) as usize % n as usize]
+                                .into()
+                        },
+                        &|query| {
+                            Value::Real(
+                                self.instance[query.column_index]
+                                    [(row as i32 + n + query.rotation.0) as usize % n as usize],
+                            )
+                        },
+                        &|challenge| Value::Real(self.challenges[challenge.index()]),
+                        &|a| -a,
+                        &|a, b| a + b,
+                        &|a, b| a * b,
+                        &|a, scalar| a * scalar,
+                        &Value::Real(F::zero()),
+                    )
+                };
+
+                assert!(self.usable_rows.end > 0);
+
+                // In the real prover, the LogUp lookup expressions are never enforced on
+                // unusable rows, due to the (1 - (l_last(X) + l_blind(X))) term.
+                let mut table: Vec<Vec<Value<F>>> = self
+                    .usable_rows
+                    .clone()
+                    .into_par_iter()
+                    .map(|table_row| {
+                        logup
+                            .table_expressions
+                            .iter()
+                            .map(|c| load(c, table_row))
+                            .collect()
+                    })
+                    .collect();
+                table.par_sort_unstable();
+                table.dedup();
+                let table = &table;
+
+                logup
+                    .input_expressions
+                    .iter()
+                    .flat_map(|input| {
+                        lookup_input_row_ids
+                            .clone()
+                            .into_par_iter()
+                            .filter_map(|input_row| {
+                                let t: Vec<_> = input.iter().map(|c| load(c, input_row)).collect();
+
+                                if table.binary_search(&t).is_err() {
+                                    Some(VerifyFailure::LogUpLookup {
+                                        name: logup.name,
+                                        lookup_index,
+                                        location: FailureLocation::find_expressions(
+                                            &self.cs,
+                                            &self.regions,
+                                            input_row,
+                                            input.iter(),
+                                        ),
+                                    })
+                                } else {
+                                    None
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+        // Check that each shuffle's input rows are a permutation of its shuffle rows.
+        let shuffle_errors =
+            self.cs
+                .shuffles
+                .iter()
+                .enumerate()
+                .flat_map(|(shuffle_index, shuffle)| {
+                    let load = |expression: &Expression<F>, row| {
+                        expression.evaluate_lazy(
+                            &|scalar| Value::Real(scalar),
+                            &|_| panic!("virtual selectors are removed during optimization"),
+                            &|query| {
+                                self.fixed[query.column_index]
+                                    [(row as i32 + n + query.rotation.0) as usize % n as usize]
+                                    .into()
+                            },
+                            &|query| {
+                                self.advice[query.column_index]
+                                    [(row as i32 + n + query.rotation.0) as usize % n as usize]
+                                    .into()
+                            },
+                            &|query| {
+                                Value::Real(
+                                    self.instance[query.column_index]
+                                        [(row as i32 + n + query.rotation.0) as usize % n as usize],
+                                )
+                            },
+                            &|challenge| Value::Real(self.challenges[challenge.index()]),
+                            &|a| -a,
+                            &|a, b| a + b,
+                            &|a, b| a * b,
+                            &|a, scalar| a * scalar,
+                            &Value::Real(F::zero()),
+                        )
+                    };
+
+                    assert!(shuffle.shuffle_expressions.len() == shuffle.input_expressions.len());
+                    assert!(self.usable_rows.end > 0);
+
+                    // In the real prover, the shuffle expressions are never enforced on
+                    // unusable rows, due to the (1 - (l_last(X) + l_blind(X))) term.
+                    let mut shuffle_rows: Vec<Vec<Value<F>>> = self
+                        .usable_rows
+                        .clone()
+                        .into_par_iter()
+                        .map(|row| {
+                            shuffle
+                                .shuffle_expressions
+                                .iter()
+                                .map(|c| load(c, row))
+                                .collect()
+                        })
+                        .collect();
+                    shuffle_rows.par_sort_unstable();
+
+                    let mut input_rows: Vec<(Vec<Value<F>>, usize)> = self
+                        .usable_rows
+                        .clone()
+                        .into_par_iter()
+                        .map(|input_row| {
+                            let t = shuffle
+                                .input_expressions
+                                .iter()
+                                .map(|c| load(c, input_row))
+                                .collect();
+
+                            // Also keep track of the original input row, since we're going to sort.
+                            (t, input_row)
+                        })
+                        .collect();
+                    input_rows.par_sort_unstable();
+
+                    input_rows
+                        .par_iter()
+                        .zip(shuffle_rows.par_iter())
+                        .filter_map(|((input_value, row), shuffle_value)| {
+                            if shuffle_value != input_value {
+                                Some(VerifyFailure::Shuffle {
+                                    name: shuffle.name,
+                                    shuffle_index,
+                                    location: FailureLocation::find_expressions(
+                                        &self.cs,
+                                        &self.regions,
+                                        *row,
+                                        shuffle.input_expressions.iter(),
+                                    ),
+                                })
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                });
+
         // Check that permutations preserve the original values of the cells.
         let perm_errors = {
             // Original values of columns involved in the permutation.
@@ -1369,6 +1892,8 @@ impl<F: FieldExt> MockProver<F> {
             .chain(selector_errors)
             .chain(gate_errors)
             .chain(lookup_errors)
+            .chain(logup_errors)
+            .chain(shuffle_errors)
             .chain(perm_errors)
             .collect();
         if errors.is_empty() {
@@ -1388,47 +1913,324 @@ impl<F: FieldExt> MockProver<F> {
         }
     }
 
-    /// Panics if the circuit being checked by this `MockProver` is not satisfied.
+    /// Returns `Ok(())` if this `MockProver` is satisfied and no advice cell can be changed
+    /// without violating a constraint, or a list of errors indicating the reasons that
+    /// the circuit is not satisfied or is under-constrained.
     ///
-    /// Any verification failures will be pretty-printed to stderr before the function
-    /// panics.
+    /// Each advice cell assigned within a region is replaced in turn by a pseudorandom
+    /// field element. Cells whose replacement leaves all gates, lookups, shuffles and
+    /// equality constraints satisfied are reported as [`VerifyFailure::UnderConstrained`],
+    /// grouped by region and column.
     ///
-    /// Apart from the stderr output, this method is equivalent to:
-    /// ```ignore
-    /// assert_eq!(prover.verify(), Ok(()));
-    /// ```
-    pub fn assert_satisfied(&self) {
-        if let Err(errs) = self.verify() {
-            for err in errs {
-                err.emit(self);
-                eprintln!();
-            }
-            panic!("circuit was not satisfied");
-        }
-    }
+    /// As the circuit is satisfied before each replacement, only the rows that query the
+    /// replaced cell and the copy cycle the cell belongs to are checked again, so the
+    /// cost of each replacement does not grow with the number of rows.
+    pub fn verify_constrained(&self) -> Result<(), Vec<VerifyFailure>> {
+        self.verify()?;
 
-    /// Panics if the circuit being checked by this `MockProver` is not satisfied.
-    ///
-    /// Any verification failures will be pretty-printed to stderr before the function
-    /// panics.
-    ///
-    /// Internally, this function uses a parallel aproach in order to verify the `MockProver` contents.
-    ///
-    /// Apart from the stderr output, this method is equivalent to:
-    /// ```ignore
-    /// assert_eq!(prover.verify_par(), Ok(()));
-    /// ```
-    pub fn assert_satisfied_par(&self) {
-        if let Err(errs) = self.verify_par() {
-            for err in errs {
-                err.emit(self);
-                eprintln!();
-            }
-            panic!("circuit was not satisfied");
-        }
-    }
+        let n = self.n as i32;
 
-    /// Panics if the circuit being checked by this `MockProver` is not satisfied.
+        // Each lookup as a pair of table and input expressions. A LogUp lookup
+        // contributes a pair for each of its input sets.
+        let lookups: Vec<(&[Expression<F>], &[Expression<F>])> = self
+            .cs
+            .lookups
+            .iter()
+            .map(|lookup| (&lookup.table_expressions[..], &lookup.input_expressions[..]))
+            .chain(self.cs.logups.iter().flat_map(|logup| {
+                logup
+                    .input_expressions
+                    .iter()
+                    .map(move |input| (&logup.table_expressions[..], &input[..]))
+            }))
+            .collect();
+
+        // The number of usable rows on which each table row and each input occurs.
+        let count = |expressions: &[Expression<F>]| {
+            let mut counts: BTreeMap<Vec<Value<F>>, usize> = BTreeMap::new();
+            for row in self.usable_rows.clone() {
+                *counts
+                    .entry(self.evaluate_row(expressions, row))
+                    .or_default() += 1;
+            }
+            counts
+        };
+        let lookup_counts: Vec<_> = lookups
+            .iter()
+            .map(|&(table, input)| (count(table), count(input)))
+            .collect();
+
+        let permutation_columns = self.cs.permutation.get_columns();
+
+        let mut prover = self.clone();
+        let mut hash: [u8; 64] = blake2b(b"Halo2-MockProver-UnderConstrained")
+            .as_bytes()
+            .try_into()
+            .unwrap();
+        let mut unconstrained: BTreeMap<(usize, ColumnMetadata), Vec<usize>> = BTreeMap::new();
+
+        for (region_index, region) in self.regions.iter().enumerate() {
+            let start = match region.rows {
+                Some((start, _)) => start,
+                None => continue,
+            };
+
+            let mut cells: Vec<_> = region
+                .cells
+                .keys()
+                .filter(|(column, _)| matches!(column.column_type(), Any::Advice(_)))
+                .copied()
+                .collect();
+            cells.sort_unstable_by_key(|(column, row)| (column.index(), *row));
+
+            for (column, row) in cells {
+                let original = self.advice[column.index()][row];
+                if !matches!(original, CellValue::Assigned(_)) {
+                    continue;
+                }
+
+                hash = blake2b(&hash).as_bytes().try_into().unwrap();
+                prover.advice[column.index()][row] = CellValue::Assigned(F::from_bytes_wide(&hash));
+
+                // Only the rows on which this cell is queried can be affected.
+                let mut rows: Vec<usize> = self
+                    .cs
+                    .advice_queries
+                    .iter()
+                    .filter(|(queried, _)| Column::<Any>::from(*queried) == column)
+                    .map(|(_, rotation)| (row as i32 - rotation.0).rem_euclid(n) as usize)
+                    .collect();
+                rows.sort_unstable();
+                rows.dedup();
+
+                if !self.detects_change(
+                    &prover,
+                    (column, row),
+                    &rows,
+                    &permutation_columns,
+                    &lookups,
+                    &lookup_counts,
+                ) {
+                    unconstrained
+                        .entry((region_index, column.into()))
+                        .or_default()
+                        .push(row - start);
+                }
+
+                prover.advice[column.index()][row] = original;
+            }
+        }
+
+        if unconstrained.is_empty() {
+            Ok(())
+        } else {
+            Err(unconstrained
+                .into_iter()
+                .map(|((region_index, column), offsets)| {
+                    let region = &self.regions[region_index];
+                    VerifyFailure::UnderConstrained {
+                        region: (
+                            region_index,
+                            region.name.clone(),
+                            region.annotations.clone(),
+                        )
+                            .into(),
+                        column,
+                        offsets,
+                    }
+                })
+                .collect())
+        }
+    }
+
+    /// Evaluates `expression` at `row`.
+    fn evaluate_at(&self, expression: &Expression<F>, row: usize) -> Value<F> {
+        let n = self.n as i32;
+        let row = row as i32 + n;
+        expression.evaluate_lazy(
+            &|scalar| Value::Real(scalar),
+            &|_| panic!("virtual selectors are removed during optimization"),
+            &util::load(n, row, &self.cs.fixed_queries, &self.fixed),
+            &util::load(n, row, &self.cs.advice_queries, &self.advice),
+            &util::load_instance(n, row, &self.cs.instance_queries, &self.instance),
+            &|challenge| Value::Real(self.challenges[challenge.index()]),
+            &|a| -a,
+            &|a, b| a + b,
+            &|a, b| a * b,
+            &|a, scalar| a * scalar,
+            &Value::Real(F::zero()),
+        )
+    }
+
+    /// Evaluates each of `expressions` at `row`.
+    fn evaluate_row(&self, expressions: &[Expression<F>], row: usize) -> Vec<Value<F>> {
+        expressions
+            .iter()
+            .map(|expression| self.evaluate_at(expression, row))
+            .collect()
+    }
+
+    /// Returns whether `perturbed`, which differs from this satisfied `MockProver` only
+    /// in the advice cell `cell`, violates a constraint. `rows` are the rows that query
+    /// `cell`, and `lookup_counts` holds the counts of the table rows and inputs of each
+    /// of `lookups` in this `MockProver`.
+    #[allow(clippy::type_complexity)]
+    fn detects_change(
+        &self,
+        perturbed: &Self,
+        cell: (Column<Any>, usize),
+        rows: &[usize],
+        permutation_columns: &[Column<Any>],
+        lookups: &[(&[Expression<F>], &[Expression<F>])],
+        lookup_counts: &[(
+            BTreeMap<Vec<Value<F>>, usize>,
+            BTreeMap<Vec<Value<F>>, usize>,
+        )],
+    ) -> bool {
+        let (column, row) = cell;
+
+        // Gates are checked on every row, including the blinding rows.
+        for gate in self.cs.gates.iter() {
+            for poly in gate.polynomials() {
+                for &gate_row in rows {
+                    match perturbed.evaluate_at(poly, gate_row) {
+                        Value::Real(x) if x.is_zero_vartime() => (),
+                        _ => return true,
+                    }
+                }
+            }
+        }
+
+        // Every cell of a copy cycle holds the same value, so the cycle is broken if
+        // and only if the next cell of the cycle no longer holds the value of `cell`.
+        if let Some(index) = permutation_columns.iter().position(|c| *c == column) {
+            let (next_index, next_row) = self.permutation.mapping()[index][row];
+            if (next_index, next_row) != (index, row) {
+                let next = permutation_columns[next_index];
+                let next_value = match next.column_type() {
+                    Any::Advice(_) => perturbed.advice[next.index()][next_row],
+                    Any::Fixed => perturbed.fixed[next.index()][next_row],
+                    Any::Instance => {
+                        CellValue::Assigned(perturbed.instance[next.index()][next_row])
+                    }
+                };
+                if next_value != perturbed.advice[column.index()][row] {
+                    return true;
+                }
+            }
+        }
+
+        // Lookups and shuffles are only enforced on the usable rows.
+        let rows: Vec<usize> = rows
+            .iter()
+            .copied()
+            .filter(|row| self.usable_rows.contains(row))
+            .collect();
+        if rows.is_empty() {
+            return false;
+        }
+
+        for (&(table, input), (table_counts, input_counts)) in lookups.iter().zip(lookup_counts) {
+            // The change in the number of occurrences of each table row.
+            let mut table_changes: BTreeMap<Vec<Value<F>>, isize> = BTreeMap::new();
+            let mut removed_inputs: BTreeMap<Vec<Value<F>>, usize> = BTreeMap::new();
+            for &row in rows.iter() {
+                let before = self.evaluate_row(table, row);
+                let after = perturbed.evaluate_row(table, row);
+                if before != after {
+                    *table_changes.entry(before).or_default() -= 1;
+                    *table_changes.entry(after).or_default() += 1;
+                }
+                *removed_inputs
+                    .entry(self.evaluate_row(input, row))
+                    .or_default() += 1;
+            }
+            let occurrences = |table_row: &Vec<Value<F>>| {
+                table_counts.get(table_row).copied().unwrap_or(0) as isize
+                    + table_changes.get(table_row).copied().unwrap_or(0)
+            };
+
+            // The inputs on the affected rows must occur in the changed table.
+            if rows
+                .iter()
+                .any(|&row| occurrences(&perturbed.evaluate_row(input, row)) == 0)
+            {
+                return true;
+            }
+
+            // So must the inputs on the other rows that matched a table row which no
+            // longer occurs.
+            if table_changes.keys().any(|table_row| {
+                occurrences(table_row) == 0
+                    && input_counts.get(table_row).copied().unwrap_or(0)
+                        > removed_inputs.get(table_row).copied().unwrap_or(0)
+            }) {
+                return true;
+            }
+        }
+
+        // The input and shuffle rows are equal as multisets before the change, so they
+        // still are if and only if the changed rows on each side make up for each other.
+        for shuffle in self.cs.shuffles.iter() {
+            let mut left = vec![];
+            let mut right = vec![];
+            for &row in rows.iter() {
+                left.push(perturbed.evaluate_row(&shuffle.input_expressions, row));
+                left.push(self.evaluate_row(&shuffle.shuffle_expressions, row));
+                right.push(perturbed.evaluate_row(&shuffle.shuffle_expressions, row));
+                right.push(self.evaluate_row(&shuffle.input_expressions, row));
+            }
+            left.sort_unstable();
+            right.sort_unstable();
+            if left != right {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Panics if the circuit being checked by this `MockProver` is not satisfied.
+    ///
+    /// Any verification failures will be pretty-printed to stderr before the function
+    /// panics.
+    ///
+    /// Apart from the stderr output, this method is equivalent to:
+    /// ```ignore
+    /// assert_eq!(prover.verify(), Ok(()));
+    /// ```
+    pub fn assert_satisfied(&self) {
+        if let Err(errs) = self.verify() {
+            for err in errs {
+                err.emit(self);
+                eprintln!();
+            }
+            panic!("circuit was not satisfied");
+        }
+    }
+
+    /// Panics if the circuit being checked by this `MockProver` is not satisfied.
+    ///
+    /// Any verification failures will be pretty-printed to stderr before the function
+    /// panics.
+    ///
+    /// Internally, this function uses a parallel aproach in order to verify the `MockProver` contents.
+    ///
+    /// Apart from the stderr output, this method is equivalent to:
+    /// ```ignore
+    /// assert_eq!(prover.verify_par(), Ok(()));
+    /// ```
+    pub fn assert_satisfied_par(&self) {
+        if let Err(errs) = self.verify_par() {
+            for err in errs {
+                err.emit(self);
+                eprintln!();
+            }
+            panic!("circuit was not satisfied");
+        }
+    }
+
+    /// Panics if the circuit being checked by this `MockProver` is not satisfied.
     ///
     /// Any verification failures will be pretty-printed to stderr before the function
     /// panics.
@@ -1472,8 +2274,9 @@ mod tests {
     use crate::{
         circuit::{Layouter, SimpleFloorPlanner, Value},
         plonk::{
-            sealed::SealedPhase, Advice, Any, Circuit, Column, ConstraintSystem, Error, Expression,
-            FirstPhase, Fixed, Instance, Selector, TableColumn,
+            sealed::SealedPhase, Advice, Any, Challenge, Circuit, Column, ConstraintSystem,
+            DynamicTable, Error, Expression, FirstPhase, Fixed, Instance, SecondPhase, Selector,
+            TableColumn,
         },
         poly::Rotation,
     };
@@ -1563,6 +2366,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn missing_witness() {
+        const K: u32 = 4;
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = Column<Advice>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                meta.advice_column()
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Faulty synthesis",
+                    |mut region| {
+                        region.assign_advice(|| "a", config, 0, || Value::known(Fp::zero()))?;
+
+                        // BUG: The witness for this cell is missing.
+                        region.assign_advice(|| "b", config, 1, Value::<Fp>::unknown)?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        match MockProver::run(K, &FaultyCircuit {}, vec![]) {
+            Err(Error::SynthesisAt {
+                region,
+                column,
+                row,
+                reason,
+            }) => {
+                assert_eq!(region.as_deref(), Some("Faulty synthesis"));
+                assert_eq!(
+                    column,
+                    Column::new(
+                        0,
+                        Any::Advice(Advice {
+                            phase: FirstPhase.to_sealed()
+                        })
+                    )
+                );
+                assert_eq!(row, 1);
+                assert_eq!(reason, "missing witness for \"b\"");
+            }
+            _ => panic!("expected a synthesis error at the missing witness"),
+        }
+    }
+
     #[test]
     fn bad_lookup_any() {
         const K: u32 = 4;
@@ -1734,7 +2597,7 @@ mod tests {
     }
 
     #[test]
-    fn bad_fixed_lookup() {
+    fn bad_logup() {
         const K: u32 = 4;
 
         #[derive(Clone)]
@@ -1754,17 +2617,15 @@ mod tests {
                 let a = meta.advice_column();
                 let q = meta.complex_selector();
                 let table = meta.lookup_table_column();
-                meta.annotate_lookup_column(table, || "Table1");
 
-                meta.lookup("lookup", |cells| {
-                    let a = cells.query_advice(a, Rotation::cur());
+                meta.lookup_logup("logup", |cells| {
+                    let a_cur = cells.query_advice(a, Rotation::cur());
+                    let a_next = cells.query_advice(a, Rotation::next());
                     let q = cells.query_selector(q);
 
-                    // If q is enabled, a must be in the table.
-                    // When q is not enabled, lookup the default value instead.
-                    let not_q = Expression::Constant(Fp::one()) - q.clone();
-                    let default = Expression::Constant(Fp::from(2));
-                    vec![(q * a + not_q * default, table)]
+                    // If q is enabled, both a and the next a must be in the table.
+                    // When q is not enabled, lookup the default value 0 instead.
+                    (vec![vec![q.clone() * a_cur], vec![q * a_next]], vec![table])
                 });
 
                 FaultyCircuitConfig { a, q, table }
@@ -1780,14 +2641,14 @@ mod tests {
                 mut layouter: impl Layouter<Fp>,
             ) -> Result<(), Error> {
                 layouter.assign_table(
-                    || "Doubling table",
+                    || "Even table",
                     |mut table| {
-                        (1..(1 << (K - 1)))
+                        (0..4)
                             .map(|i| {
                                 table.assign_cell(
                                     || format!("table[{}] = {}", i, 2 * i),
                                     config.table,
-                                    i - 1,
+                                    i,
                                     || Value::known(Fp::from(2 * i as u64)),
                                 )
                             })
@@ -1796,44 +2657,18 @@ mod tests {
                 )?;
 
                 layouter.assign_region(
-                    || "Good synthesis",
+                    || "Faulty synthesis",
                     |mut region| {
-                        // Enable the lookup on rows 0 and 1.
+                        // Enable the LogUp lookup on row 0.
                         config.q.enable(&mut region, 0)?;
-                        config.q.enable(&mut region, 1)?;
 
-                        // Assign a = 2 and a = 6.
+                        // Assign a = 2.
                         region.assign_advice(
                             || "a = 2",
                             config.a,
                             0,
                             || Value::known(Fp::from(2)),
                         )?;
-                        region.assign_advice(
-                            || "a = 6",
-                            config.a,
-                            1,
-                            || Value::known(Fp::from(6)),
-                        )?;
-
-                        Ok(())
-                    },
-                )?;
-
-                layouter.assign_region(
-                    || "Faulty synthesis",
-                    |mut region| {
-                        // Enable the lookup on rows 0 and 1.
-                        config.q.enable(&mut region, 0)?;
-                        config.q.enable(&mut region, 1)?;
-
-                        // Assign a = 4.
-                        region.assign_advice(
-                            || "a = 4",
-                            config.a,
-                            0,
-                            || Value::known(Fp::from(4)),
-                        )?;
 
                         // BUG: Assign a = 5, which doesn't exist in the table!
                         region.assign_advice(
@@ -1843,8 +2678,6 @@ mod tests {
                             || Value::known(Fp::from(5)),
                         )?;
 
-                        region.name_column(|| "Witness example", config.a);
-
                         Ok(())
                     },
                 )
@@ -1854,27 +2687,26 @@ mod tests {
         let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
         assert_eq!(
             prover.verify(),
-            Err(vec![VerifyFailure::Lookup {
-                name: "lookup",
+            Err(vec![VerifyFailure::LogUpLookup {
+                name: "logup",
                 lookup_index: 0,
                 location: FailureLocation::InRegion {
-                    region: (2, "Faulty synthesis").into(),
-                    offset: 1,
+                    region: (1, "Faulty synthesis").into(),
+                    offset: 0,
                 }
             }])
         );
+        assert_eq!(prover.verify(), prover.verify_par());
     }
 
     #[test]
-    fn contraint_unsatisfied() {
+    fn bad_shuffle() {
         const K: u32 = 4;
 
         #[derive(Clone)]
         struct FaultyCircuitConfig {
             a: Column<Advice>,
             b: Column<Advice>,
-            c: Column<Advice>,
-            d: Column<Fixed>,
             q: Selector,
         }
 
@@ -1887,22 +2719,18 @@ mod tests {
             fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
                 let a = meta.advice_column();
                 let b = meta.advice_column();
-                let c = meta.advice_column();
-                let d = meta.fixed_column();
-                let q = meta.selector();
+                let q = meta.complex_selector();
 
-                meta.create_gate("Equality check", |cells| {
+                meta.shuffle("shuffle", |cells| {
                     let a = cells.query_advice(a, Rotation::cur());
                     let b = cells.query_advice(b, Rotation::cur());
-                    let c = cells.query_advice(c, Rotation::cur());
-                    let d = cells.query_fixed(d, Rotation::cur());
                     let q = cells.query_selector(q);
 
-                    // If q is enabled, a and b must be assigned to.
-                    vec![q * (a - b) * (c - d)]
+                    // If q is enabled, the values of a must be a permutation of the values of b.
+                    vec![(q.clone() * a, q * b)]
                 });
 
-                FaultyCircuitConfig { a, b, c, d, q }
+                FaultyCircuitConfig { a, b, q }
             }
 
             fn without_witnesses(&self) -> Self {
@@ -1915,45 +2743,382 @@ mod tests {
                 mut layouter: impl Layouter<Fp>,
             ) -> Result<(), Error> {
                 layouter.assign_region(
-                    || "Correct synthesis",
+                    || "Faulty synthesis",
                     |mut region| {
-                        // Enable the equality gate.
-                        config.q.enable(&mut region, 0)?;
-
-                        // Assign a = 1.
-                        region.assign_advice(|| "a", config.a, 0, || Value::known(Fp::one()))?;
-
-                        // Assign b = 1.
-                        region.assign_advice(|| "b", config.b, 0, || Value::known(Fp::one()))?;
+                        // BUG: a = [1, 2, 3, 5] is not a permutation of b = [3, 2, 1, 4].
+                        for (offset, (a, b)) in [(1, 3), (2, 2), (3, 1), (5, 4)].iter().enumerate()
+                        {
+                            config.q.enable(&mut region, offset)?;
+                            region.assign_advice(
+                                || "a",
+                                config.a,
+                                offset,
+                                || Value::known(Fp::from(*a)),
+                            )?;
+                            region.assign_advice(
+                                || "b",
+                                config.b,
+                                offset,
+                                || Value::known(Fp::from(*b)),
+                            )?;
+                        }
 
-                        // Assign c = 5.
-                        region.assign_advice(
-                            || "c",
-                            config.c,
-                            0,
-                            || Value::known(Fp::from(5u64)),
-                        )?;
-                        // Assign d = 7.
-                        region.assign_fixed(
-                            || "d",
-                            config.d,
-                            0,
-                            || Value::known(Fp::from(7u64)),
-                        )?;
                         Ok(())
                     },
-                )?;
-                layouter.assign_region(
-                    || "Wrong synthesis",
-                    |mut region| {
-                        // Enable the equality gate.
-                        config.q.enable(&mut region, 0)?;
-
-                        // Assign a = 1.
-                        region.assign_advice(|| "a", config.a, 0, || Value::known(Fp::one()))?;
-
-                        // Assign b = 0.
-                        region.assign_advice(|| "b", config.b, 0, || Value::known(Fp::zero()))?;
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Shuffle {
+                name: "shuffle",
+                shuffle_index: 0,
+                location: FailureLocation::InRegion {
+                    region: (0, "Faulty synthesis").into(),
+                    offset: 3,
+                }
+            }])
+        );
+        assert_eq!(prover.verify(), prover.verify_par());
+    }
+
+    #[test]
+    fn bad_dynamic_lookup() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct FaultyCircuitConfig {
+            addr: Column<Advice>,
+            value: Column<Advice>,
+            q: Selector,
+            memory: DynamicTable,
+        }
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = FaultyCircuitConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let addr = meta.advice_column();
+                let value = meta.advice_column();
+                let q = meta.complex_selector();
+
+                let memory_addr = meta.advice_column();
+                let memory_value = meta.advice_column();
+                let memory = meta.dynamic_table(&[memory_addr, memory_value]);
+
+                meta.lookup_dynamic("memory read", &memory, |cells| {
+                    let addr = cells.query_advice(addr, Rotation::cur());
+                    let value = cells.query_advice(value, Rotation::cur());
+                    let q = cells.query_selector(q);
+
+                    // If q is enabled, (addr, value) must be a row of the memory table.
+                    (q, vec![(addr, memory_addr), (value, memory_value)])
+                });
+
+                FaultyCircuitConfig {
+                    addr,
+                    value,
+                    q,
+                    memory,
+                }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let rows: Vec<_> = [(0, 10), (1, 20), (2, 30)]
+                    .iter()
+                    .map(|&(addr, value)| {
+                        vec![Value::known(Fp::from(addr)), Value::known(Fp::from(value))]
+                    })
+                    .collect();
+                layouter.assign_dynamic_table(|| "memory", &config.memory, &rows)?;
+
+                layouter.assign_region(
+                    || "Faulty synthesis",
+                    |mut region| {
+                        // Read (1, 20), which is in memory.
+                        config.q.enable(&mut region, 0)?;
+                        region.assign_advice(
+                            || "addr",
+                            config.addr,
+                            0,
+                            || Value::known(Fp::from(1)),
+                        )?;
+                        region.assign_advice(
+                            || "value",
+                            config.value,
+                            0,
+                            || Value::known(Fp::from(20)),
+                        )?;
+
+                        // BUG: Read (0, 0), which only matches rows outside of the table.
+                        config.q.enable(&mut region, 1)?;
+                        region.assign_advice(
+                            || "addr",
+                            config.addr,
+                            1,
+                            || Value::known(Fp::zero()),
+                        )?;
+                        region.assign_advice(
+                            || "value",
+                            config.value,
+                            1,
+                            || Value::known(Fp::zero()),
+                        )?;
+
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                name: "memory read",
+                lookup_index: 0,
+                location: FailureLocation::InRegion {
+                    region: (1, "Faulty synthesis").into(),
+                    offset: 1,
+                }
+            }])
+        );
+    }
+
+    #[test]
+    fn bad_fixed_lookup() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct FaultyCircuitConfig {
+            a: Column<Advice>,
+            q: Selector,
+            table: TableColumn,
+        }
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = FaultyCircuitConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let q = meta.complex_selector();
+                let table = meta.lookup_table_column();
+                meta.annotate_lookup_column(table, || "Table1");
+
+                meta.lookup("lookup", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let q = cells.query_selector(q);
+
+                    // If q is enabled, a must be in the table.
+                    // When q is not enabled, lookup the default value instead.
+                    let not_q = Expression::Constant(Fp::one()) - q.clone();
+                    let default = Expression::Constant(Fp::from(2));
+                    vec![(q * a + not_q * default, table)]
+                });
+
+                FaultyCircuitConfig { a, q, table }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_table(
+                    || "Doubling table",
+                    |mut table| {
+                        (1..(1 << (K - 1)))
+                            .map(|i| {
+                                table.assign_cell(
+                                    || format!("table[{}] = {}", i, 2 * i),
+                                    config.table,
+                                    i - 1,
+                                    || Value::known(Fp::from(2 * i as u64)),
+                                )
+                            })
+                            .fold(Ok(()), |acc, res| acc.and(res))
+                    },
+                )?;
+
+                layouter.assign_region(
+                    || "Good synthesis",
+                    |mut region| {
+                        // Enable the lookup on rows 0 and 1.
+                        config.q.enable(&mut region, 0)?;
+                        config.q.enable(&mut region, 1)?;
+
+                        // Assign a = 2 and a = 6.
+                        region.assign_advice(
+                            || "a = 2",
+                            config.a,
+                            0,
+                            || Value::known(Fp::from(2)),
+                        )?;
+                        region.assign_advice(
+                            || "a = 6",
+                            config.a,
+                            1,
+                            || Value::known(Fp::from(6)),
+                        )?;
+
+                        Ok(())
+                    },
+                )?;
+
+                layouter.assign_region(
+                    || "Faulty synthesis",
+                    |mut region| {
+                        // Enable the lookup on rows 0 and 1.
+                        config.q.enable(&mut region, 0)?;
+                        config.q.enable(&mut region, 1)?;
+
+                        // Assign a = 4.
+                        region.assign_advice(
+                            || "a = 4",
+                            config.a,
+                            0,
+                            || Value::known(Fp::from(4)),
+                        )?;
+
+                        // BUG: Assign a = 5, which doesn't exist in the table!
+                        region.assign_advice(
+                            || "a = 5",
+                            config.a,
+                            1,
+                            || Value::known(Fp::from(5)),
+                        )?;
+
+                        region.name_column(|| "Witness example", config.a);
+
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                name: "lookup",
+                lookup_index: 0,
+                location: FailureLocation::InRegion {
+                    region: (2, "Faulty synthesis").into(),
+                    offset: 1,
+                }
+            }])
+        );
+    }
+
+    #[test]
+    fn contraint_unsatisfied() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct FaultyCircuitConfig {
+            a: Column<Advice>,
+            b: Column<Advice>,
+            c: Column<Advice>,
+            d: Column<Fixed>,
+            q: Selector,
+        }
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = FaultyCircuitConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let c = meta.advice_column();
+                let d = meta.fixed_column();
+                let q = meta.selector();
+
+                meta.create_gate("Equality check", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let b = cells.query_advice(b, Rotation::cur());
+                    let c = cells.query_advice(c, Rotation::cur());
+                    let d = cells.query_fixed(d, Rotation::cur());
+                    let q = cells.query_selector(q);
+
+                    // If q is enabled, a and b must be assigned to.
+                    vec![q * (a - b) * (c - d)]
+                });
+
+                FaultyCircuitConfig { a, b, c, d, q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Correct synthesis",
+                    |mut region| {
+                        // Enable the equality gate.
+                        config.q.enable(&mut region, 0)?;
+
+                        // Assign a = 1.
+                        region.assign_advice(|| "a", config.a, 0, || Value::known(Fp::one()))?;
+
+                        // Assign b = 1.
+                        region.assign_advice(|| "b", config.b, 0, || Value::known(Fp::one()))?;
+
+                        // Assign c = 5.
+                        region.assign_advice(
+                            || "c",
+                            config.c,
+                            0,
+                            || Value::known(Fp::from(5u64)),
+                        )?;
+                        // Assign d = 7.
+                        region.assign_fixed(
+                            || "d",
+                            config.d,
+                            0,
+                            || Value::known(Fp::from(7u64)),
+                        )?;
+                        Ok(())
+                    },
+                )?;
+                layouter.assign_region(
+                    || "Wrong synthesis",
+                    |mut region| {
+                        // Enable the equality gate.
+                        config.q.enable(&mut region, 0)?;
+
+                        // Assign a = 1.
+                        region.assign_advice(|| "a", config.a, 0, || Value::known(Fp::one()))?;
+
+                        // Assign b = 0.
+                        region.assign_advice(|| "b", config.b, 0, || Value::known(Fp::zero()))?;
 
                         // Name Column a
                         region.name_column(|| "This is Advice!", config.a);
@@ -2045,4 +3210,292 @@ mod tests {
             },])
         )
     }
+
+    #[test]
+    fn under_constrained_cell() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct MulConfig {
+            a: Column<Advice>,
+            b: Column<Advice>,
+            c: Column<Advice>,
+            q: Selector,
+        }
+
+        struct MulCircuit {}
+
+        impl Circuit<Fp> for MulCircuit {
+            type Config = MulConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let c = meta.advice_column();
+                let q = meta.selector();
+
+                meta.create_gate("mul", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let b = cells.query_advice(b, Rotation::cur());
+                    let c = cells.query_advice(c, Rotation::cur());
+                    let q = cells.query_selector(q);
+
+                    vec![q * (a * b - c)]
+                });
+
+                MulConfig { a, b, c, q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "mul",
+                    |mut region| {
+                        config.q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "a", config.a, 0, || Value::known(Fp::from(2)))?;
+                        region.assign_advice(|| "b", config.b, 0, || Value::known(Fp::from(3)))?;
+                        region.assign_advice(|| "c", config.c, 0, || Value::known(Fp::from(6)))?;
+
+                        // The gate is not enabled on this row, so nothing constrains it.
+                        region.assign_advice(|| "a", config.a, 1, || Value::known(Fp::from(4)))?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &MulCircuit {}, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        assert_eq!(
+            prover.verify_constrained(),
+            Err(vec![VerifyFailure::UnderConstrained {
+                region: (0, "mul").into(),
+                column: (Any::advice(), 0).into(),
+                offsets: vec![1],
+            }])
+        );
+    }
+
+    #[test]
+    fn constrained_cells_are_not_reported() {
+        const K: u32 = 5;
+
+        #[derive(Clone)]
+        struct CellsConfig {
+            a: Column<Advice>,
+            b: Column<Advice>,
+            t: Column<Advice>,
+            u: Column<Advice>,
+            d: Column<Advice>,
+            c: Column<Fixed>,
+            table: TableColumn,
+        }
+
+        struct CellsCircuit {}
+
+        impl Circuit<Fp> for CellsCircuit {
+            type Config = CellsConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let t = meta.advice_column();
+                let u = meta.advice_column();
+                let d = meta.advice_column();
+                let c = meta.fixed_column();
+                let table = meta.lookup_table_column();
+                meta.enable_equality(b);
+                meta.enable_equality(c);
+
+                meta.lookup("a in table", |cells| {
+                    vec![(cells.query_advice(a, Rotation::cur()), table)]
+                });
+                meta.lookup_any("u in t", |cells| {
+                    vec![(
+                        cells.query_advice(u, Rotation::cur()),
+                        cells.query_advice(t, Rotation::cur()),
+                    )]
+                });
+
+                CellsConfig {
+                    a,
+                    b,
+                    t,
+                    u,
+                    d,
+                    c,
+                    table,
+                }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_table(
+                    || "table",
+                    |mut table| {
+                        for i in 0..8 {
+                            table.assign_cell(
+                                || "table",
+                                config.table,
+                                i,
+                                || Value::known(Fp::from(i as u64)),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )?;
+
+                layouter.assign_region(
+                    || "cells",
+                    |mut region| {
+                        for i in 0..4 {
+                            let value = Value::known(Fp::from(i as u64 + 1));
+                            // A lookup input, and the rows of an advice table that are
+                            // each matched by a single input.
+                            region.assign_advice(|| "a", config.a, i, || value)?;
+                            region.assign_advice(|| "t", config.t, i, || value)?;
+                            region.assign_advice(|| "u", config.u, i, || value)?;
+                        }
+
+                        // A cell copied from a fixed cell.
+                        let value = Value::known(Fp::from(7));
+                        let c = region.assign_fixed(|| "c", config.c, 0, || value)?;
+                        let b = region.assign_advice(|| "b", config.b, 0, || value)?;
+                        region.constrain_equal(c.cell(), b.cell())?;
+
+                        // Nothing constrains this cell.
+                        region.assign_advice(|| "d", config.d, 0, || value)?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &CellsCircuit {}, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        assert_eq!(
+            prover.verify_constrained(),
+            Err(vec![VerifyFailure::UnderConstrained {
+                region: (1, "cells").into(),
+                column: (Any::advice(), 4).into(),
+                offsets: vec![0],
+            }])
+        );
+    }
+
+    #[test]
+    fn second_phase_with_challenges() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct RlcConfig {
+            a: Column<Advice>,
+            b: Column<Advice>,
+            q: Selector,
+            r: Challenge,
+        }
+
+        struct RlcCircuit {
+            offset: u64,
+        }
+
+        impl Circuit<Fp> for RlcCircuit {
+            type Config = RlcConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column_in(SecondPhase);
+                let q = meta.selector();
+                let r = meta.challenge_usable_after(FirstPhase);
+
+                meta.create_gate("rlc", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let b = cells.query_advice(b, Rotation::cur());
+                    let r = cells.query_challenge(r);
+                    let q = cells.query_selector(q);
+
+                    vec![q * (b - a * r)]
+                });
+
+                RlcConfig { a, b, q, r }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {
+                    offset: self.offset,
+                }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let r = layouter.get_challenge(config.r);
+                layouter.assign_region(
+                    || "rlc",
+                    |mut region| {
+                        config.q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "a", config.a, 0, || Value::known(Fp::from(3)))?;
+                        region.assign_advice(
+                            || "b",
+                            config.b,
+                            0,
+                            || r * Value::known(Fp::from(3)) + Value::known(Fp::from(self.offset)),
+                        )?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run_with_challenges(
+            K,
+            &RlcCircuit { offset: 0 },
+            vec![],
+            vec![Fp::from(7)],
+        )
+        .unwrap();
+        assert_eq!(prover.verify_per_phase(), Ok(()));
+        assert_eq!(
+            prover.advice[1][0],
+            super::CellValue::Assigned(Fp::from(21))
+        );
+
+        let prover = MockProver::run_with_challenges(
+            K,
+            &RlcCircuit { offset: 1 },
+            vec![],
+            vec![Fp::from(7)],
+        )
+        .unwrap();
+        let failures = prover.verify_per_phase().unwrap_err();
+        assert_eq!(failures.keys().copied().collect::<Vec<_>>(), vec![1]);
+        assert!(matches!(
+            failures[&1][..],
+            [VerifyFailure::ConstraintNotSatisfied { .. }]
+        ));
+
+        assert!(matches!(
+            MockProver::run_with_challenges(K, &RlcCircuit { offset: 0 }, vec![], vec![]),
+            Err(Error::InvalidChallenges)
+        ));
+    }
 }
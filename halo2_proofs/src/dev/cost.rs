@@ -34,6 +34,10 @@ pub struct CircuitCost<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> {
     fixed_queries: usize,
     /// Number of lookup arguments.
     lookups: usize,
+    /// Number of LogUp lookup arguments.
+    logups: usize,
+    /// Number of shuffle arguments.
+    shuffles: usize,
     /// Number of columns in the global permutation.
     permutation_cols: usize,
     /// Number of distinct sets of points in the multiopening argument.
@@ -197,6 +201,13 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
         point_sets.insert(vec![-1, 0]); // permuted_input_poly
         point_sets.insert(vec![0]); // permuted_table_poly
 
+        // Include LogUp lookup polynomials in point sets:
+        point_sets.insert(vec![0]); // multiplicity_poly
+        point_sets.insert(vec![0, 1]); // running_sum_poly
+
+        // Include shuffle polynomials in point sets:
+        point_sets.insert(vec![0, 1]); // product_poly
+
         // Include permutation polynomials in point sets.
         point_sets.insert(vec![0, 1]); // permutation_product_poly
         let max_deg = cs.degree();
@@ -214,6 +225,8 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             advice_queries: cs.advice_queries.len(),
             fixed_queries: cs.fixed_queries.len(),
             lookups: cs.lookups.len(),
+            logups: cs.logups.len(),
+            shuffles: cs.shuffles.len(),
             permutation_cols,
             point_sets: point_sets.len(),
             _marker: PhantomData::default(),
@@ -242,6 +255,16 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             // - 5 evals per lookup argument per instance
             lookups: ProofContribution::new(3 * self.lookups, 5 * self.lookups),
 
+            // LogUp lookup arguments:
+            // - 2 commitments per LogUp lookup argument per instance
+            // - 3 evals per LogUp lookup argument per instance
+            logups: ProofContribution::new(2 * self.logups, 3 * self.logups),
+
+            // Shuffle arguments:
+            // - 1 commitment per shuffle argument per instance
+            // - 2 evals per shuffle argument per instance
+            shuffles: ProofContribution::new(self.shuffles, 2 * self.shuffles),
+
             // Global permutation argument:
             // - chunks commitments per instance
             // - 2*chunks + (chunks - 1) evals per instance
@@ -267,6 +290,14 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             // - marginal cost per instance
             lookups: marginal.lookups * instances,
 
+            // LogUp lookup arguments:
+            // - marginal cost per instance
+            logups: marginal.logups * instances,
+
+            // Shuffle arguments:
+            // - marginal cost per instance
+            shuffles: marginal.shuffles * instances,
+
             // Global permutation argument:
             // - marginal cost per instance
             // - 1 eval per column
@@ -343,6 +374,8 @@ pub struct MarginalProofSize<G: PrimeGroup> {
     instance: ProofContribution,
     advice: ProofContribution,
     lookups: ProofContribution,
+    logups: ProofContribution,
+    shuffles: ProofContribution,
     equality: ProofContribution,
     _marker: PhantomData<G>,
 }
@@ -355,6 +388,8 @@ impl<G: PrimeGroup> From<MarginalProofSize<G>> for usize {
         proof.instance.len(point, scalar)
             + proof.advice.len(point, scalar)
             + proof.lookups.len(point, scalar)
+            + proof.logups.len(point, scalar)
+            + proof.shuffles.len(point, scalar)
             + proof.equality.len(point, scalar)
     }
 }
@@ -366,6 +401,8 @@ pub struct ProofSize<G: PrimeGroup> {
     advice: ProofContribution,
     fixed: ProofContribution,
     lookups: ProofContribution,
+    logups: ProofContribution,
+    shuffles: ProofContribution,
     equality: ProofContribution,
     vanishing: ProofContribution,
     multiopen: ProofContribution,
@@ -382,6 +419,8 @@ impl<G: PrimeGroup> From<ProofSize<G>> for usize {
             + proof.advice.len(point, scalar)
             + proof.fixed.len(point, scalar)
             + proof.lookups.len(point, scalar)
+            + proof.logups.len(point, scalar)
+            + proof.shuffles.len(point, scalar)
             + proof.equality.len(point, scalar)
             + proof.vanishing.len(point, scalar)
             + proof.multiopen.len(point, scalar)
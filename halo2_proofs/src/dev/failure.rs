@@ -179,6 +179,28 @@ pub enum VerifyFailure {
         ///   lookup is active on a row adjacent to an unrelated region.
         location: FailureLocation,
     },
+    /// A LogUp lookup input did not exist in its corresponding table.
+    LogUpLookup {
+        /// The name of the LogUp lookup that is not satisfied.
+        name: &'static str,
+        /// The index of the LogUp lookup that is not satisfied. These indices are
+        /// assigned in the order in which `ConstraintSystem::lookup_logup` is called
+        /// during `Circuit::configure`.
+        lookup_index: usize,
+        /// The location at which the LogUp lookup is not satisfied.
+        location: FailureLocation,
+    },
+    /// A shuffle input did not match the corresponding shuffle row.
+    Shuffle {
+        /// The name of the shuffle that is not satisfied.
+        name: &'static str,
+        /// The index of the shuffle that is not satisfied. These indices are assigned in
+        /// the order in which `ConstraintSystem::shuffle` is called during
+        /// `Circuit::configure`.
+        shuffle_index: usize,
+        /// The location at which the shuffle is not satisfied.
+        location: FailureLocation,
+    },
     /// A permutation did not preserve the original value of a cell.
     Permutation {
         /// The column in which this permutation is not satisfied.
@@ -186,6 +208,17 @@ pub enum VerifyFailure {
         /// The location at which the permutation is not satisfied.
         location: FailureLocation,
     },
+    /// Advice cells whose values can be changed without violating any constraint.
+    ///
+    /// This is only reported by [`MockProver::verify_constrained`].
+    UnderConstrained {
+        /// The region in which the cells were assigned.
+        region: metadata::Region,
+        /// The column containing the cells.
+        column: metadata::Column,
+        /// The offsets (relative to the start of the region) of the cells.
+        offsets: Vec<usize>,
+    },
 }
 
 impl fmt::Display for VerifyFailure {
@@ -242,6 +275,28 @@ impl fmt::Display for VerifyFailure {
                     name, lookup_index, location
                 )
             }
+            Self::LogUpLookup {
+                name,
+                lookup_index,
+                location,
+            } => {
+                write!(
+                    f,
+                    "LogUp lookup {}(index: {}) is not satisfied {}",
+                    name, lookup_index, location
+                )
+            }
+            Self::Shuffle {
+                name,
+                shuffle_index,
+                location,
+            } => {
+                write!(
+                    f,
+                    "Shuffle {}(index: {}) is not satisfied {}",
+                    name, shuffle_index, location
+                )
+            }
             Self::Permutation { column, location } => {
                 write!(
                     f,
@@ -250,6 +305,19 @@ impl fmt::Display for VerifyFailure {
                     location
                 )
             }
+            Self::UnderConstrained {
+                region,
+                column,
+                offsets,
+            } => {
+                write!(
+                    f,
+                    "Cells of {} in {} at offsets {:?} are not constrained",
+                    DebugColumn::from((*column, region.column_annotations.as_ref())),
+                    region,
+                    offsets
+                )
+            }
         }
     }
 }
@@ -0,0 +1,863 @@
+//! Static checks for common circuit configuration mistakes.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use ff::Field;
+
+use crate::{
+    circuit::Value,
+    dev::metadata,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+        Expression, Fixed, FloorPlanner, Instance, Selector,
+    },
+};
+
+/// A lookup argument referred to by a [`LintIssue`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LookupArgument {
+    /// A lookup created with `ConstraintSystem::lookup`, `lookup_any` or
+    /// `lookup_dynamic`.
+    Lookup {
+        /// The name of the lookup.
+        name: &'static str,
+        /// The index of the lookup, in the order in which it was created.
+        index: usize,
+    },
+    /// One set of inputs of a LogUp lookup.
+    LogUp {
+        /// The name of the LogUp lookup.
+        name: &'static str,
+        /// The index of the LogUp lookup, in the order in which it was created.
+        index: usize,
+        /// The index of the input set within the LogUp lookup.
+        input_index: usize,
+    },
+}
+
+impl fmt::Display for LookupArgument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lookup { name, index } => write!(f, "Lookup {}(index: {})", name, index),
+            Self::LogUp {
+                name,
+                index,
+                input_index,
+            } => write!(
+                f,
+                "LogUp lookup {}(index: {}, input: {})",
+                name, index, input_index
+            ),
+        }
+    }
+}
+
+/// A potential problem found by [`CircuitLint`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum LintIssue {
+    /// A constraint does not vanish when every selector and fixed column is zero, so it
+    /// is active on every row (including the unusable rows at the end of the circuit).
+    ///
+    /// `MockProver` reports this as `VerifyFailure::ConstraintPoisoned`.
+    MissingSelector {
+        /// The constraint that is always active.
+        constraint: metadata::Constraint,
+    },
+    /// A column was allocated but is never queried and is not part of the permutation
+    /// argument.
+    UnusedColumn {
+        /// The unused column.
+        column: metadata::Column,
+        /// The annotation of the column, if any.
+        annotation: Option<String>,
+    },
+    /// A column was queried while configuring the circuit, but does not appear in any
+    /// gate, lookup or shuffle, and is not part of the permutation argument.
+    UnconstrainedColumn {
+        /// The unconstrained column.
+        column: metadata::Column,
+        /// The annotation of the column, if any.
+        annotation: Option<String>,
+    },
+    /// The inputs of a lookup still depend on witness values when its selectors and
+    /// fixed columns are zero, so disabled rows are not guaranteed to be in the table.
+    UngatedLookupInput {
+        /// The lookup whose inputs are not gated.
+        lookup: LookupArgument,
+    },
+    /// The value taken by the inputs of a lookup when it is disabled does not appear in
+    /// its table.
+    ///
+    /// This is only checked by [`CircuitLint::analyze_with_layout`], and only for tables
+    /// that are built from fixed columns and selectors.
+    DefaultNotInTable {
+        /// The lookup whose default inputs are missing from the table.
+        lookup: LookupArgument,
+    },
+    /// An advice column is queried at a rotation larger than the number of blinding
+    /// factors, so a gate enabled near the last usable row reads past the blinding rows.
+    RotationBeyondBlindingFactors {
+        /// The queried column.
+        column: metadata::Column,
+        /// The annotation of the column, if any.
+        annotation: Option<String>,
+        /// The rotation at which the column is queried.
+        rotation: i32,
+        /// The number of blinding factors of the circuit.
+        blinding_factors: usize,
+    },
+}
+
+struct ColumnName<'a>(&'a metadata::Column, &'a Option<String>);
+
+impl fmt::Display for ColumnName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            Some(annotation) => write!(
+                f,
+                "Column('{:?}', {} - {})",
+                self.0.column_type, self.0.index, annotation
+            ),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingSelector { constraint } => write!(
+                f,
+                "{} is active on every row - missing selector?",
+                constraint
+            ),
+            Self::UnusedColumn { column, annotation } => {
+                write!(f, "{} is never used", ColumnName(column, annotation))
+            }
+            Self::UnconstrainedColumn { column, annotation } => write!(
+                f,
+                "{} is queried but never constrained",
+                ColumnName(column, annotation)
+            ),
+            Self::UngatedLookupInput { lookup } => write!(
+                f,
+                "{} has inputs that are not disabled by a selector",
+                lookup
+            ),
+            Self::DefaultNotInTable { lookup } => write!(
+                f,
+                "{} has disabled inputs that do not exist in its table",
+                lookup
+            ),
+            Self::RotationBeyondBlindingFactors {
+                column,
+                annotation,
+                rotation,
+                blinding_factors,
+            } => write!(
+                f,
+                "{} is queried at rotation {}, beyond the {} blinding factors",
+                ColumnName(column, annotation),
+                rotation,
+                blinding_factors
+            ),
+        }
+    }
+}
+
+/// A static analysis pass over a circuit's [`ConstraintSystem`], which reports mistakes
+/// that `MockProver` only catches when a witness happens to trigger them.
+///
+/// # Examples
+///
+/// ```
+/// use ff::Field;
+/// use halo2_proofs::{
+///     circuit::{Layouter, SimpleFloorPlanner},
+///     dev::{CircuitLint, LintIssue},
+///     plonk::{Circuit, ConstraintSystem, Error},
+///     poly::Rotation,
+/// };
+/// use halo2curves::pasta::Fp;
+///
+/// #[derive(Clone, Default)]
+/// struct MyCircuit {}
+///
+/// impl<F: Field> Circuit<F> for MyCircuit {
+///     type Config = ();
+///     type FloorPlanner = SimpleFloorPlanner;
+///
+///     fn without_witnesses(&self) -> Self {
+///         Self::default()
+///     }
+///
+///     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+///         let a = meta.advice_column();
+///         let b = meta.advice_column();
+///
+///         // Oops: this gate applies to every row.
+///         meta.create_gate("square", |meta| {
+///             let a = meta.query_advice(a, Rotation::cur());
+///             let b = meta.query_advice(b, Rotation::cur());
+///             Some(a.clone() * a - b)
+///         });
+///     }
+///
+///     fn synthesize(&self, _: (), _: impl Layouter<F>) -> Result<(), Error> {
+///         Ok(())
+///     }
+/// }
+///
+/// let lint = CircuitLint::analyze::<Fp, MyCircuit>();
+/// assert!(matches!(lint.issues(), [LintIssue::MissingSelector { .. }]));
+/// ```
+#[derive(Debug)]
+pub struct CircuitLint {
+    issues: Vec<LintIssue>,
+}
+
+impl CircuitLint {
+    /// Analyzes the constraint system produced by `C::configure`.
+    pub fn analyze<F: Field, C: Circuit<F>>() -> Self {
+        let mut cs = ConstraintSystem::default();
+        let _ = C::configure(&mut cs);
+
+        let annotations = cs.general_column_annotations.clone();
+        let mut lint = Linter {
+            cs: &cs,
+            annotations,
+            issues: vec![],
+        };
+        lint.check_selectors();
+        lint.check_columns();
+        lint.check_lookup_inputs(None);
+        lint.check_rotations();
+
+        CircuitLint {
+            issues: lint.issues,
+        }
+    }
+
+    /// Analyzes the constraint system produced by `C::configure`, and additionally lays
+    /// out `circuit` in `2^k` rows.
+    ///
+    /// The layout pass picks up column annotations made within regions, and checks that
+    /// the default inputs of lookups into fixed tables exist in those tables.
+    pub fn analyze_with_layout<F: Field, C: Circuit<F>>(
+        k: u32,
+        circuit: &C,
+    ) -> Result<Self, Error> {
+        let n = 1 << k;
+
+        let mut cs = ConstraintSystem::default();
+        let config = C::configure(&mut cs);
+
+        let mut layout = Layout {
+            n,
+            selectors: vec![vec![false; n]; cs.num_selectors],
+            fixed: vec![vec![F::zero(); n]; cs.num_fixed_columns],
+            annotations: cs.general_column_annotations.clone(),
+        };
+        C::FloorPlanner::synthesize(&mut layout, circuit, config, cs.constants.clone())?;
+
+        let mut lint = Linter {
+            cs: &cs,
+            annotations: layout.annotations.clone(),
+            issues: vec![],
+        };
+        lint.check_selectors();
+        lint.check_columns();
+        lint.check_lookup_inputs(Some(&layout));
+        lint.check_rotations();
+
+        Ok(CircuitLint {
+            issues: lint.issues,
+        })
+    }
+
+    /// Returns the issues that were found.
+    pub fn issues(&self) -> &[LintIssue] {
+        &self.issues
+    }
+
+    /// Returns `true` if no issues were found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for CircuitLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "- {}", issue)?;
+        }
+        writeln!(f, "Total issues: {}", self.issues.len())
+    }
+}
+
+struct Linter<'a, F: Field> {
+    cs: &'a ConstraintSystem<F>,
+    annotations: HashMap<metadata::Column, String>,
+    issues: Vec<LintIssue>,
+}
+
+impl<F: Field> Linter<'_, F> {
+    fn annotation(&self, column: &metadata::Column) -> Option<String> {
+        self.annotations.get(column).cloned()
+    }
+
+    fn check_selectors(&mut self) {
+        for (gate_index, gate) in self.cs.gates.iter().enumerate() {
+            for (poly_index, poly) in gate.polynomials().iter().enumerate() {
+                if disabled_value(poly) != Some(F::zero()) {
+                    self.issues.push(LintIssue::MissingSelector {
+                        constraint: (
+                            (gate_index, gate.name()).into(),
+                            poly_index,
+                            gate.constraint_name(poly_index),
+                        )
+                            .into(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn check_columns(&mut self) {
+        let cs = self.cs;
+
+        let all: Vec<metadata::Column> = cs
+            .advice_column_phase
+            .iter()
+            .enumerate()
+            .map(|(index, &phase)| (Any::Advice(Advice { phase }), index).into())
+            .chain((0..cs.num_fixed_columns).map(|index| (Any::Fixed, index).into()))
+            .chain((0..cs.num_instance_columns).map(|index| (Any::Instance, index).into()))
+            .collect();
+
+        let queried: BTreeSet<metadata::Column> = cs
+            .advice_queries
+            .iter()
+            .map(|(column, _)| Column::<Any>::from(*column).into())
+            .chain(
+                cs.fixed_queries
+                    .iter()
+                    .map(|(column, _)| Column::<Any>::from(*column).into()),
+            )
+            .chain(
+                cs.instance_queries
+                    .iter()
+                    .map(|(column, _)| Column::<Any>::from(*column).into()),
+            )
+            .collect();
+
+        let permuted: BTreeSet<metadata::Column> = cs
+            .permutation
+            .get_columns()
+            .into_iter()
+            .map(metadata::Column::from)
+            .collect();
+
+        let mut constrained = BTreeSet::new();
+        for gate in cs.gates.iter() {
+            for poly in gate.polynomials() {
+                constrained.extend(queried_columns(poly));
+            }
+        }
+        for lookup in cs.lookups.iter() {
+            for expr in lookup
+                .input_expressions
+                .iter()
+                .chain(&lookup.table_expressions)
+            {
+                constrained.extend(queried_columns(expr));
+            }
+        }
+        for logup in cs.logups.iter() {
+            for expr in logup
+                .input_expressions
+                .iter()
+                .flatten()
+                .chain(&logup.table_expressions)
+            {
+                constrained.extend(queried_columns(expr));
+            }
+        }
+        for shuffle in cs.shuffles.iter() {
+            for expr in shuffle
+                .input_expressions
+                .iter()
+                .chain(&shuffle.shuffle_expressions)
+            {
+                constrained.extend(queried_columns(expr));
+            }
+        }
+
+        for column in all {
+            if permuted.contains(&column) {
+                continue;
+            }
+            if !queried.contains(&column) {
+                self.issues.push(LintIssue::UnusedColumn {
+                    column,
+                    annotation: self.annotation(&column),
+                });
+            } else if !constrained.contains(&column) {
+                self.issues.push(LintIssue::UnconstrainedColumn {
+                    column,
+                    annotation: self.annotation(&column),
+                });
+            }
+        }
+    }
+
+    fn check_lookup_inputs(&mut self, layout: Option<&Layout<F>>) {
+        let cs = self.cs;
+
+        let lookups = cs.lookups.iter().enumerate().map(|(index, lookup)| {
+            (
+                LookupArgument::Lookup {
+                    name: lookup.name,
+                    index,
+                },
+                &lookup.input_expressions,
+                &lookup.table_expressions,
+            )
+        });
+        let logups = cs.logups.iter().enumerate().flat_map(|(index, logup)| {
+            logup
+                .input_expressions
+                .iter()
+                .enumerate()
+                .map(move |(input_index, inputs)| {
+                    (
+                        LookupArgument::LogUp {
+                            name: logup.name,
+                            index,
+                            input_index,
+                        },
+                        inputs,
+                        &logup.table_expressions,
+                    )
+                })
+        });
+
+        for (lookup, inputs, table) in lookups.chain(logups) {
+            let default: Option<Vec<F>> = inputs.iter().map(disabled_value).collect();
+            let default = match default {
+                Some(default) => default,
+                None => {
+                    self.issues.push(LintIssue::UngatedLookupInput { lookup });
+                    continue;
+                }
+            };
+
+            if let Some(layout) = layout {
+                let usable_rows = layout.n - (cs.blinding_factors() + 1);
+                let rows: Option<Vec<Vec<F>>> = (0..usable_rows)
+                    .map(|row| {
+                        table
+                            .iter()
+                            .map(|expr| layout.evaluate(expr, row))
+                            .collect()
+                    })
+                    .collect();
+                // Tables that depend on witness values can't be checked here.
+                if let Some(rows) = rows {
+                    if !rows.contains(&default) {
+                        self.issues.push(LintIssue::DefaultNotInTable { lookup });
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_rotations(&mut self) {
+        let blinding_factors = self.cs.blinding_factors();
+        for (column, rotation) in self.cs.advice_queries.iter() {
+            if rotation.0.unsigned_abs() as usize > blinding_factors {
+                let column: metadata::Column = Column::<Any>::from(*column).into();
+                self.issues.push(LintIssue::RotationBeyondBlindingFactors {
+                    column,
+                    annotation: self.annotation(&column),
+                    rotation: rotation.0,
+                    blinding_factors,
+                });
+            }
+        }
+    }
+}
+
+/// Evaluates `expr` on a row where every selector and fixed column is zero, returning
+/// `None` if the result still depends on witness values or challenges.
+fn disabled_value<F: Field>(expr: &Expression<F>) -> Option<F> {
+    expr.evaluate(
+        &|scalar| Some(scalar),
+        &|_| Some(F::zero()),
+        &|_| Some(F::zero()),
+        &|_| None,
+        &|_| None,
+        &|_| None,
+        &|a| a.map(|a| -a),
+        &|a, b| match (a, b) {
+            (Some(a), Some(b)) => Some(a + b),
+            _ => None,
+        },
+        &|a, b| match (a, b) {
+            (Some(a), Some(b)) => Some(a * b),
+            (Some(zero), _) | (_, Some(zero)) if zero.is_zero_vartime() => Some(F::zero()),
+            _ => None,
+        },
+        &|a, scalar| {
+            if scalar.is_zero_vartime() {
+                Some(F::zero())
+            } else {
+                a.map(|a| a * scalar)
+            }
+        },
+    )
+}
+
+fn queried_columns<F: Field>(expr: &Expression<F>) -> BTreeSet<metadata::Column> {
+    expr.evaluate(
+        &|_| BTreeSet::new(),
+        &|_| BTreeSet::new(),
+        &|query| BTreeSet::from([(Any::Fixed, query.column_index).into()]),
+        &|query| {
+            BTreeSet::from([(
+                Any::Advice(Advice { phase: query.phase }),
+                query.column_index,
+            )
+                .into()])
+        },
+        &|query| BTreeSet::from([(Any::Instance, query.column_index).into()]),
+        &|_| BTreeSet::new(),
+        &|a| a,
+        &|mut a, mut b| {
+            a.append(&mut b);
+            a
+        },
+        &|mut a, mut b| {
+            a.append(&mut b);
+            a
+        },
+        &|a, _| a,
+    )
+}
+
+/// Records the fixed values, selectors and column annotations of a circuit.
+struct Layout<F: Field> {
+    n: usize,
+    selectors: Vec<Vec<bool>>,
+    fixed: Vec<Vec<F>>,
+    annotations: HashMap<metadata::Column, String>,
+}
+
+impl<F: Field> Layout<F> {
+    /// Evaluates `expr` at `row`, returning `None` if it depends on witness values or
+    /// challenges.
+    fn evaluate(&self, expr: &Expression<F>, row: usize) -> Option<F> {
+        let rotate = |rotation: i32| (((row as i32) + rotation).rem_euclid(self.n as i32)) as usize;
+        expr.evaluate(
+            &|scalar| Some(scalar),
+            &|selector| {
+                Some(if self.selectors[selector.0][row] {
+                    F::one()
+                } else {
+                    F::zero()
+                })
+            },
+            &|query| Some(self.fixed[query.column_index][rotate(query.rotation.0)]),
+            &|_| None,
+            &|_| None,
+            &|_| None,
+            &|a| a.map(|a| -a),
+            &|a, b| Some(a? + b?),
+            &|a, b| Some(a? * b?),
+            &|a, scalar| a.map(|a| a * scalar),
+        )
+    }
+}
+
+impl<F: Field> Assignment<F> for Layout<F> {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn annotate_column<A, AR>(&mut self, annotation: A, column: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.annotations.insert(column.into(), annotation().into());
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        *self
+            .selectors
+            .get_mut(selector.0)
+            .and_then(|v| v.get_mut(row))
+            .ok_or(Error::BoundsFailure)? = true;
+
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        _: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        *self
+            .fixed
+            .get_mut(column.index())
+            .and_then(|v| v.get_mut(row))
+            .ok_or(Error::BoundsFailure)? = to().into_field().evaluate().assign()?;
+
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        from_row: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        for row in from_row..self.n {
+            self.assign_fixed(|| "", column, row, || to)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::Fp;
+
+    use super::{CircuitLint, LintIssue, LookupArgument};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::metadata,
+        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error, Selector, TableColumn},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct LookupConfig {
+        a: Column<Advice>,
+        q: Selector,
+        table: TableColumn,
+    }
+
+    /// A circuit that looks up `q * (a + 1)` in a table that may or may not contain 0.
+    struct LookupCircuit {
+        table_start: u64,
+    }
+
+    impl Circuit<Fp> for LookupCircuit {
+        type Config = LookupConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            LookupCircuit {
+                table_start: self.table_start,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let q = meta.complex_selector();
+            let table = meta.lookup_table_column();
+            meta.annotate_lookup_column(table, || "range");
+
+            meta.lookup("range check", |cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let q = cells.query_selector(q);
+                vec![(q * (a + Fp::one()), table)]
+            });
+
+            LookupConfig { a, q, table }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_table(
+                || "range",
+                |mut table| {
+                    for i in 0..8 {
+                        table.assign_cell(
+                            || "value",
+                            config.table,
+                            i,
+                            || Value::known(Fp::from(self.table_start + i as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+
+            layouter.assign_region(
+                || "input",
+                |mut region| {
+                    config.q.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", config.a, 0, || Value::known(Fp::from(2)))
+                },
+            )?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn lookup_default_in_table() {
+        let lint = CircuitLint::analyze::<Fp, LookupCircuit>();
+        assert!(lint.is_clean(), "{}", lint);
+
+        let lint = CircuitLint::analyze_with_layout(5, &LookupCircuit { table_start: 0 }).unwrap();
+        assert!(lint.is_clean(), "{}", lint);
+
+        let lint = CircuitLint::analyze_with_layout(5, &LookupCircuit { table_start: 1 }).unwrap();
+        assert_eq!(
+            lint.issues(),
+            &[LintIssue::DefaultNotInTable {
+                lookup: LookupArgument::Lookup {
+                    name: "range check",
+                    index: 0,
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn configuration_mistakes() {
+        struct FaultyCircuit;
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = ();
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                FaultyCircuit
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let unused = meta.advice_column();
+                let table = meta.lookup_table_column();
+
+                meta.create_gate("no selector", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let _ = cells.query_advice(b, Rotation::cur());
+                    Some(a.clone() * a)
+                });
+
+                meta.lookup("ungated", |cells| {
+                    let a = cells.query_advice(a, Rotation(10));
+                    vec![(a, table)]
+                });
+
+                let _ = unused;
+            }
+
+            fn synthesize(&self, _: (), _: impl Layouter<Fp>) -> Result<(), Error> {
+                Ok(())
+            }
+        }
+
+        let lint = CircuitLint::analyze::<Fp, FaultyCircuit>();
+        let blinding_factors = match lint.issues().last() {
+            Some(LintIssue::RotationBeyondBlindingFactors {
+                blinding_factors, ..
+            }) => *blinding_factors,
+            _ => panic!("{}", lint),
+        };
+        let column = |index| metadata::Column::from((Any::advice(), index));
+        assert_eq!(
+            lint.issues(),
+            &[
+                LintIssue::MissingSelector {
+                    constraint: ((0, "no selector").into(), 0, "").into(),
+                },
+                LintIssue::UnconstrainedColumn {
+                    column: column(1),
+                    annotation: None,
+                },
+                LintIssue::UnusedColumn {
+                    column: column(2),
+                    annotation: None,
+                },
+                LintIssue::UngatedLookupInput {
+                    lookup: LookupArgument::Lookup {
+                        name: "ungated",
+                        index: 0,
+                    },
+                },
+                LintIssue::RotationBeyondBlindingFactors {
+                    column: column(0),
+                    annotation: None,
+                    rotation: 10,
+                    blinding_factors,
+                },
+            ]
+        );
+    }
+}
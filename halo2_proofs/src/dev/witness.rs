@@ -0,0 +1,540 @@
+//! Export and replay of the assignment held by a [`MockProver`].
+//!
+//! [`MockProver::write`] records everything that [`MockProver::verify`] checks, so that
+//! a failing witness can be handed to someone else, or archived as a regression test,
+//! and checked again with [`MockProver::read`] without the circuit's synthesis code.
+//!
+//! # Format
+//!
+//! Integers are big-endian `u32`s, and sequences are prefixed with their length as a
+//! `u32`. Field elements, and the constraint system, are written according to the
+//! [`SerdeFormat`] passed to [`MockProver::write`]. Columns are written as in the
+//! serialized constraint system: a type byte (`0` advice, followed by its phase; `1`
+//! fixed; `2` instance) and an index.
+//!
+//! | Field           | Contents                                                          |
+//! |-----------------|-------------------------------------------------------------------|
+//! | magic           | the bytes `halo2wit`                                              |
+//! | version         | [`WITNESS_VERSION`]                                               |
+//! | `k`             | the circuit size is `2^k` rows                                    |
+//! | `cs`            | the constraint system, with compressed selectors                 |
+//! | metadata        | the names of the gates, constraints and arguments, the selectors and cells queried by each gate, and the column annotations |
+//! | usable rows     | the number of rows before the blinding rows                      |
+//! | challenges      | a sequence of field elements                                      |
+//! | instance        | a sequence of columns, each `2^k` field elements                 |
+//! | fixed, advice   | two sequences of columns, each `2^k` cells                       |
+//! | selectors       | a sequence of selectors, each `2^k` bits packed into bytes       |
+//! | permutation     | a sequence of permutation columns, each `2^k` `(column, row)` pairs mapping every cell to the next cell in its cycle |
+//! | regions         | a sequence of regions                                             |
+//!
+//! A cell is a byte `0` if it is unassigned, `1` followed by a field element if it is
+//! assigned, or `2` followed by a row if it is poisoned. A selector is its index and a
+//! byte `1` if it is simple or `0` otherwise. A region is its name (a length-prefixed
+//! UTF-8 string), its rows (a byte `0`, or `1` followed by the first and last row), the
+//! columns it uses, its enabled selectors as `(selector, rows)` pairs, its column
+//! annotations as `(column, string)` pairs, and its assigned cells as
+//! `(column, row, assignment count)` triples.
+//!
+//! A replayed `MockProver` reports the same failures as the one that was written.
+
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+use halo2curves::serde::SerdeObject;
+
+use super::{metadata, CellValue, MockProver, Region};
+use crate::{
+    arithmetic::FieldExt,
+    helpers::{
+        invalid_data, pack, read_string, read_u8, read_usize, read_vec, unpack, write_string,
+        write_usize, write_vec, SerdePrimeField,
+    },
+    plonk::{permutation, sealed::SealedPhase, Column, ConstraintSystem, FirstPhase},
+    SerdeFormat,
+};
+
+/// The version of the witness format written by [`MockProver::write`].
+pub const WITNESS_VERSION: u32 = 1;
+
+const MAGIC: &[u8; 8] = b"halo2wit";
+
+fn read_row<R: io::Read>(reader: &mut R, n: usize) -> io::Result<usize> {
+    let row = read_usize(reader)?;
+    if row >= n {
+        return Err(invalid_data("row out of bounds"));
+    }
+    Ok(row)
+}
+
+fn write_annotation<F: SerdePrimeField, W: io::Write>(
+    writer: &mut W,
+    (column, annotation): (&metadata::Column, &String),
+) -> io::Result<()> {
+    ConstraintSystem::<F>::write_column(writer, Column::new(column.index, column.column_type))?;
+    write_string(writer, annotation)
+}
+
+fn read_annotation<F: SerdePrimeField, R: io::Read>(
+    cs: &ConstraintSystem<F>,
+    reader: &mut R,
+) -> io::Result<(metadata::Column, String)> {
+    let column = metadata::Column::from(cs.read_column(reader)?);
+    Ok((column, read_string(reader)?))
+}
+
+impl<F: FieldExt + SerdeObject> MockProver<F> {
+    /// Writes the full assignment of this `MockProver`, together with its constraint
+    /// system, in the format described in the [module documentation](crate::dev::witness).
+    ///
+    /// Field elements are written according to `format`.
+    pub fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&WITNESS_VERSION.to_be_bytes())?;
+        writer.write_all(&self.k.to_be_bytes())?;
+        self.cs.write(writer, format)?;
+        self.cs.write_metadata(writer)?;
+        let mut annotations: Vec<_> = self.cs.general_column_annotations.iter().collect();
+        annotations.sort();
+        write_vec(writer, annotations.into_iter(), write_annotation::<F, W>)?;
+        write_usize(writer, self.usable_rows.end)?;
+
+        write_vec(writer, self.challenges.iter(), |writer, challenge| {
+            challenge.write(writer, format)
+        })?;
+        write_vec(writer, self.instance.iter(), |writer, column| {
+            column
+                .iter()
+                .try_for_each(|value| value.write(writer, format))
+        })?;
+        for cells in [&self.fixed, &self.advice] {
+            write_vec(writer, cells.iter(), |writer, column| {
+                column.iter().try_for_each(|cell| match cell {
+                    CellValue::Unassigned => writer.write_all(&[0]),
+                    CellValue::Assigned(value) => {
+                        writer.write_all(&[1])?;
+                        value.write(writer, format)
+                    }
+                    CellValue::Poison(row) => {
+                        writer.write_all(&[2])?;
+                        write_usize(writer, *row)
+                    }
+                })
+            })?;
+        }
+        write_vec(writer, self.selectors.iter(), |writer, selector| {
+            selector
+                .chunks(8)
+                .try_for_each(|bits| writer.write_all(&[pack(bits)]))
+        })?;
+
+        write_vec(
+            writer,
+            self.permutation.mapping().iter(),
+            |writer, column| {
+                column.iter().try_for_each(|(column, row)| {
+                    write_usize(writer, *column)?;
+                    write_usize(writer, *row)
+                })
+            },
+        )?;
+
+        write_vec(writer, self.regions.iter(), |writer, region| {
+            write_string(writer, &region.name)?;
+            match region.rows {
+                Some((start, end)) => {
+                    writer.write_all(&[1])?;
+                    write_usize(writer, start)?;
+                    write_usize(writer, end)?;
+                }
+                None => writer.write_all(&[0])?,
+            }
+
+            let mut columns: Vec<_> = region.columns.iter().copied().collect();
+            columns.sort();
+            write_vec(writer, columns.into_iter(), |writer, column| {
+                ConstraintSystem::<F>::write_column(writer, column)
+            })?;
+
+            let mut enabled_selectors: Vec<_> = region.enabled_selectors.iter().collect();
+            enabled_selectors.sort_by_key(|(selector, _)| selector.0);
+            write_vec(
+                writer,
+                enabled_selectors.into_iter(),
+                |writer, (selector, rows)| {
+                    ConstraintSystem::<F>::write_selector(writer, selector)?;
+                    write_vec(writer, rows.iter(), |writer, row| write_usize(writer, *row))
+                },
+            )?;
+
+            let mut annotations: Vec<_> = region.annotations.iter().collect();
+            annotations.sort();
+            write_vec(writer, annotations.into_iter(), write_annotation::<F, W>)?;
+
+            let mut cells: Vec<_> = region.cells.iter().collect();
+            cells.sort();
+            write_vec(
+                writer,
+                cells.into_iter(),
+                |writer, ((column, row), count)| {
+                    ConstraintSystem::<F>::write_column(writer, *column)?;
+                    write_usize(writer, *row)?;
+                    write_usize(writer, *count)
+                },
+            )
+        })
+    }
+
+    /// Reads an assignment written with [`MockProver::write`].
+    ///
+    /// The returned `MockProver` can be verified as if it had been created by
+    /// [`MockProver::run`], without access to the circuit. Field elements are read
+    /// according to `format`, which must match the one the assignment was written with.
+    pub fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a MockProver witness"));
+        }
+        if read_usize(reader)? as u32 != WITNESS_VERSION {
+            return Err(invalid_data("unsupported witness version"));
+        }
+
+        // Check `k` before anything of size `2^k` is allocated.
+        let k = read_usize(reader)? as u32;
+        if k > F::S {
+            return Err(invalid_data("circuit size is too large"));
+        }
+        let n = 1usize << k;
+        let mut cs = ConstraintSystem::<F>::read(reader, format)?;
+        cs.read_metadata(reader)?;
+        cs.general_column_annotations = read_vec(reader, |reader| read_annotation(&cs, reader))?
+            .into_iter()
+            .collect();
+        let usable_rows = read_usize(reader)?;
+        if usable_rows > n {
+            return Err(invalid_data("usable rows exceed the circuit size"));
+        }
+
+        let challenges = read_vec(reader, |reader| F::read(reader, format))?;
+        let instance: Vec<Vec<F>> = read_vec(reader, |reader| {
+            (0..n).map(|_| F::read(reader, format)).collect()
+        })?;
+        let mut cells = || {
+            read_vec(reader, |reader| {
+                (0..n)
+                    .map(|_| match read_u8(reader)? {
+                        0 => Ok(CellValue::Unassigned),
+                        1 => Ok(CellValue::Assigned(F::read(reader, format)?)),
+                        2 => Ok(CellValue::Poison(read_row(reader, n)?)),
+                        _ => Err(invalid_data("invalid cell")),
+                    })
+                    .collect()
+            })
+        };
+        let fixed: Vec<Vec<CellValue<F>>> = cells()?;
+        let advice: Vec<Vec<CellValue<F>>> = cells()?;
+        let selectors = read_vec(reader, |reader| {
+            // Grow each selector as it is read, so that a truncated reader fails
+            // before all `n` bits are allocated.
+            let mut selector = vec![];
+            while selector.len() < n {
+                let mut bits = [false; 8];
+                let len = (n - selector.len()).min(8);
+                unpack(read_u8(reader)?, &mut bits[..len]);
+                selector.extend_from_slice(&bits[..len]);
+            }
+            Ok(selector)
+        })?;
+        if challenges.len() != cs.num_challenges
+            || instance.len() != cs.num_instance_columns
+            || fixed.len() != cs.num_fixed_columns
+            || advice.len() != cs.num_advice_columns
+            || selectors.len() != cs.num_selectors
+        {
+            return Err(invalid_data(
+                "assignment does not match the constraint system",
+            ));
+        }
+
+        let mapping = read_vec(reader, |reader| {
+            (0..n)
+                .map(|_| Ok((read_usize(reader)?, read_row(reader, n)?)))
+                .collect()
+        })?;
+        let permutation = permutation::keygen::Assembly::from_mapping(&cs.permutation, mapping)
+            .ok_or_else(|| invalid_data("invalid permutation"))?;
+
+        let regions = read_vec(reader, |reader| {
+            let name = read_string(reader)?;
+            let rows = match read_u8(reader)? {
+                0 => None,
+                1 => Some((read_row(reader, n)?, read_row(reader, n)?)),
+                _ => return Err(invalid_data("invalid region rows")),
+            };
+            let columns: HashSet<_> = read_vec(reader, |reader| cs.read_column(reader))?
+                .into_iter()
+                .collect();
+            let enabled_selectors: HashMap<_, _> = read_vec(reader, |reader| {
+                let selector = cs.read_selector(reader)?;
+                Ok((selector, read_vec(reader, |reader| read_row(reader, n))?))
+            })?
+            .into_iter()
+            .collect();
+            let annotations: HashMap<_, _> =
+                read_vec(reader, |reader| read_annotation(&cs, reader))?
+                    .into_iter()
+                    .collect();
+            let cells: HashMap<_, _> = read_vec(reader, |reader| {
+                let column = cs.read_column(reader)?;
+                let row = read_row(reader, n)?;
+                Ok(((column, row), read_usize(reader)?))
+            })?
+            .into_iter()
+            .collect();
+
+            Ok(Region {
+                name,
+                columns,
+                rows,
+                enabled_selectors,
+                annotations,
+                cells,
+            })
+        })?;
+
+        Ok(MockProver {
+            k,
+            n: n as u32,
+            cs,
+            regions,
+            current_region: None,
+            fixed,
+            advice,
+            instance,
+            selectors,
+            challenges,
+            permutation,
+            usable_rows: 0..usable_rows,
+            current_phase: FirstPhase.to_sealed(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::bn256::Fr;
+
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::{MockProver, VerifyFailure},
+        plonk::{
+            Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector, TableColumn,
+        },
+        poly::Rotation,
+        SerdeFormat,
+    };
+
+    #[derive(Clone)]
+    struct MulConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        c: Column<Advice>,
+        instance: Column<Instance>,
+        q: Selector,
+        table: TableColumn,
+    }
+
+    struct MulCircuit {
+        a: u64,
+        c: u64,
+        assign_b: bool,
+    }
+
+    impl MulCircuit {
+        fn new(c: u64) -> Self {
+            MulCircuit {
+                a: 2,
+                c,
+                assign_b: true,
+            }
+        }
+    }
+
+    impl Circuit<Fr> for MulCircuit {
+        type Config = MulConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            MulCircuit {
+                a: self.a,
+                c: self.c,
+                assign_b: self.assign_b,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let c = meta.advice_column();
+            let instance = meta.instance_column();
+            let q = meta.selector();
+            let table = meta.lookup_table_column();
+            meta.enable_equality(c);
+            meta.enable_equality(instance);
+            meta.annotate_lookup_column(table, || "small values");
+
+            meta.create_gate("mul", |cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let b = cells.query_advice(b, Rotation::cur());
+                let c = cells.query_advice(c, Rotation::cur());
+                let q = cells.query_selector(q);
+                vec![("a * b = c", q * (a * b - c))]
+            });
+            meta.lookup("a is small", |cells| {
+                vec![(cells.query_advice(a, Rotation::cur()), table)]
+            });
+
+            MulConfig {
+                a,
+                b,
+                c,
+                instance,
+                q,
+                table,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_table(
+                || "small values",
+                |mut table| {
+                    for i in 0..4 {
+                        table.assign_cell(
+                            || "value",
+                            config.table,
+                            i,
+                            || Value::known(Fr::from(i as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+
+            let c = layouter.assign_region(
+                || "mul",
+                |mut region| {
+                    region.name_column(|| "product", config.c);
+                    config.q.enable(&mut region, 0)?;
+                    let a = Value::known(Fr::from(self.a));
+                    region.assign_advice(|| "a", config.a, 0, || a)?;
+                    if self.assign_b {
+                        region.assign_advice(|| "b", config.b, 0, || Value::known(Fr::from(3)))?;
+                    }
+                    region.assign_advice(|| "c", config.c, 0, || Value::known(Fr::from(self.c)))
+                },
+            )?;
+            layouter.constrain_instance(c.cell(), config.instance, 0)
+        }
+    }
+
+    fn replay(prover: &MockProver<Fr>) -> MockProver<Fr> {
+        let mut bytes = vec![];
+        prover.write(&mut bytes, SerdeFormat::Processed).unwrap();
+        MockProver::read(&mut &bytes[..], SerdeFormat::Processed).unwrap()
+    }
+
+    #[test]
+    fn replay_witness() {
+        let prover = MockProver::run(4, &MulCircuit::new(6), vec![vec![Fr::from(6)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        assert_eq!(replay(&prover).verify(), Ok(()));
+
+        // The equality constraint with the instance is broken.
+        let prover = MockProver::run(4, &MulCircuit::new(6), vec![vec![Fr::from(7)]]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert!(matches!(
+            failures[..],
+            [VerifyFailure::Permutation { .. }, ..]
+        ));
+        assert_eq!(replay(&prover).verify(), Err(failures));
+    }
+
+    #[test]
+    fn replay_named_failures() {
+        // The gate is not satisfied, and is reported with its name, the name of its
+        // constraint and the values of the cells it queries.
+        let prover = MockProver::run(4, &MulCircuit::new(7), vec![vec![Fr::from(7)]]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        match &failures[..] {
+            [VerifyFailure::ConstraintNotSatisfied { constraint, .. }] => {
+                assert_eq!(constraint, &((0, "mul").into(), 0, "a * b = c").into());
+            }
+            failures => panic!("unexpected failures: {:?}", failures),
+        }
+        let replayed = replay(&prover);
+        assert_eq!(replayed.verify(), Err(failures));
+
+        // The written witness reproduces the failure when it is written again.
+        let mut bytes = vec![];
+        prover.write(&mut bytes, SerdeFormat::Processed).unwrap();
+        let mut replayed_bytes = vec![];
+        replayed
+            .write(&mut replayed_bytes, SerdeFormat::Processed)
+            .unwrap();
+        assert_eq!(replayed_bytes, bytes);
+
+        // The lookup is reported with its name.
+        let circuit = MulCircuit {
+            a: 5,
+            c: 15,
+            assign_b: true,
+        };
+        let prover = MockProver::run(4, &circuit, vec![vec![Fr::from(15)]]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert!(matches!(
+            failures[..],
+            [VerifyFailure::Lookup {
+                name: "a is small",
+                ..
+            }]
+        ));
+        assert_eq!(replay(&prover).verify(), Err(failures));
+
+        // Unassigned cells queried by an enabled gate are reported.
+        let circuit = MulCircuit {
+            a: 2,
+            c: 0,
+            assign_b: false,
+        };
+        let prover = MockProver::run(4, &circuit, vec![vec![Fr::from(0)]]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert!(matches!(
+            failures[..],
+            [VerifyFailure::CellNotAssigned { .. }, ..]
+        ));
+        assert_eq!(replay(&prover).verify(), Err(failures));
+    }
+
+    #[test]
+    fn reject_garbage() {
+        assert!(
+            MockProver::<Fr>::read(&mut &b"not a witness"[..], SerdeFormat::Processed).is_err()
+        );
+    }
+
+    #[test]
+    fn reject_large_circuit() {
+        let prover = MockProver::run(4, &MulCircuit::new(6), vec![vec![Fr::from(6)]]).unwrap();
+        let mut bytes = vec![];
+        prover.write(&mut bytes, SerdeFormat::Processed).unwrap();
+
+        // `k` follows the magic bytes and the version.
+        bytes[12..16].copy_from_slice(&40u32.to_be_bytes());
+        let error = MockProver::<Fr>::read(&mut &bytes[..], SerdeFormat::Processed).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
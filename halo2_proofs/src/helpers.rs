@@ -4,7 +4,8 @@ use halo2curves::{pairing::Engine, serde::SerdeObject, CurveAffine};
 use std::io;
 
 /// This enum specifies how various types are serialized and deserialized.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SerdeFormat {
     /// Curve elements are serialized in compressed form.
     /// Field elements are serialized in standard form, with endianness specified by the
@@ -93,6 +94,77 @@ pub trait SerdePrimeField: PrimeField + SerdeObject {
 }
 impl<F: PrimeField + SerdeObject> SerdePrimeField for F {}
 
+/// Returns an [`io::ErrorKind::InvalidData`] error with the given message.
+pub(crate) fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+pub(crate) fn write_u8<W: io::Write>(writer: &mut W, value: u8) -> io::Result<()> {
+    writer.write_all(&[value])
+}
+
+pub(crate) fn read_u8<R: io::Read>(reader: &mut R) -> io::Result<u8> {
+    let mut bytes = [0u8; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+/// Writes `value` as a big-endian `u32`.
+pub(crate) fn write_usize<W: io::Write>(writer: &mut W, value: usize) -> io::Result<()> {
+    writer.write_all(&(value as u32).to_be_bytes())
+}
+
+/// Reads a big-endian `u32` written with [`write_usize`].
+pub(crate) fn read_usize<R: io::Read>(reader: &mut R) -> io::Result<usize> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_be_bytes(bytes) as usize)
+}
+
+/// Writes a length-prefixed sequence of items.
+pub(crate) fn write_vec<W: io::Write, T, I>(
+    writer: &mut W,
+    items: I,
+    mut write_item: impl FnMut(&mut W, T) -> io::Result<()>,
+) -> io::Result<()>
+where
+    I: IntoIterator<Item = T>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let items = items.into_iter();
+    write_usize(writer, items.len())?;
+    for item in items {
+        write_item(writer, item)?;
+    }
+    Ok(())
+}
+
+/// Reads a length-prefixed sequence of items written with [`write_vec`].
+pub(crate) fn read_vec<R: io::Read, T>(
+    reader: &mut R,
+    mut read_item: impl FnMut(&mut R) -> io::Result<T>,
+) -> io::Result<Vec<T>> {
+    let len = read_usize(reader)?;
+    // Don't trust `len` for preallocation; the reader may be truncated.
+    let mut items = vec![];
+    for _ in 0..len {
+        items.push(read_item(reader)?);
+    }
+    Ok(items)
+}
+
+/// Writes a length-prefixed UTF-8 string.
+pub(crate) fn write_string<W: io::Write>(writer: &mut W, string: &str) -> io::Result<()> {
+    write_usize(writer, string.len())?;
+    writer.write_all(string.as_bytes())
+}
+
+/// Reads a string written with [`write_string`].
+pub(crate) fn read_string<R: io::Read>(reader: &mut R) -> io::Result<String> {
+    let bytes = read_vec(reader, read_u8)?;
+    String::from_utf8(bytes).map_err(|_| invalid_data("invalid UTF-8 string"))
+}
+
 /// Convert a slice of `bool` into a `u8`.
 ///
 /// Panics if the slice has length greater than 8.
@@ -138,9 +210,3 @@ pub(crate) fn write_polynomial_slice<W: io::Write, F: SerdePrimeField, B>(
     }
     Ok(())
 }
-
-/// Gets the total number of bytes of a slice of polynomials, assuming all polynomials are the same length
-pub(crate) fn polynomial_slice_byte_length<F: PrimeField, B>(slice: &[Polynomial<F, B>]) -> usize {
-    let field_len = F::default().to_repr().as_ref().len();
-    4 + slice.len() * (4 + field_len * slice.get(0).map(|poly| poly.len()).unwrap_or(0))
-}
@@ -11,23 +11,30 @@ use group::ff::Field;
 
 use crate::arithmetic::{CurveAffine, FieldExt};
 use crate::helpers::{
-    polynomial_slice_byte_length, read_polynomial_vec, write_polynomial_slice, SerdeCurveAffine,
-    SerdePrimeField,
+    read_polynomial_vec, write_polynomial_slice, SerdeCurveAffine, SerdePrimeField,
 };
 use crate::poly::{
-    commitment::Params, Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff,
-    PinnedEvaluationDomain, Polynomial,
+    commitment::Params, EvaluationDomain, LagrangeCoeff, PinnedEvaluationDomain, Polynomial,
 };
 use crate::transcript::{ChallengeScalar, EncodedChallenge, Transcript};
 use crate::SerdeFormat;
 
 mod assigned;
 mod circuit;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod envelope;
 mod error;
 mod evaluation;
+#[cfg(feature = "evm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "evm")))]
+pub mod evm;
 mod keygen;
+mod logup;
 mod lookup;
 pub mod permutation;
+mod shuffle;
+mod store;
 mod vanishing;
 
 mod prover;
@@ -38,10 +45,13 @@ pub use circuit::*;
 pub use error::*;
 pub use keygen::*;
 pub use prover::*;
+pub use store::*;
 pub use verifier::*;
 
 use evaluation::Evaluator;
 use std::io;
+use std::path::Path;
+use std::sync::Arc;
 
 /// This is a verifying key which allows for the verification of proofs for a
 /// particular circuit.
@@ -73,6 +83,30 @@ where
     /// WITHOUT performing the expensive Montgomery reduction.
     pub fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
         writer.write_all(&self.domain.k().to_be_bytes())?;
+        self.write_commitments(writer, format)
+    }
+
+    /// Writes a verifying key, including its constraint system, to a buffer.
+    ///
+    /// Unlike [`Self::write`], the output can be read back with
+    /// [`Self::read_with_cs`] without access to the circuit type. Curve and
+    /// field elements are written according to `format` as in [`Self::write`].
+    pub fn write_with_cs<W: io::Write>(
+        &self,
+        writer: &mut W,
+        format: SerdeFormat,
+    ) -> io::Result<()> {
+        writer.write_all(&self.domain.k().to_be_bytes())?;
+        writer.write_all(&self.domain.j().to_be_bytes())?;
+        self.cs.write(writer, format)?;
+        self.write_commitments(writer, format)
+    }
+
+    fn write_commitments<W: io::Write>(
+        &self,
+        writer: &mut W,
+        format: SerdeFormat,
+    ) -> io::Result<()> {
         writer.write_all(&(self.fixed_commitments.len() as u32).to_be_bytes())?;
         for commitment in &self.fixed_commitments {
             commitment.write(writer, format)?;
@@ -102,11 +136,96 @@ where
     pub fn read<R: io::Read, ConcreteCircuit: Circuit<C::Scalar>>(
         reader: &mut R,
         format: SerdeFormat,
+    ) -> Result<Self, Error> {
+        Self::read_io::<R, ConcreteCircuit>(reader, format)
+            .map_err(Error::VerifyingKeySerialization)
+    }
+
+    fn read_io<R: io::Read, ConcreteCircuit: Circuit<C::Scalar>>(
+        reader: &mut R,
+        format: SerdeFormat,
     ) -> io::Result<Self> {
         let mut k = [0u8; 4];
         reader.read_exact(&mut k)?;
         let k = u32::from_be_bytes(k);
+        if k > C::Scalar::S {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "domain is larger than the two-adicity of the scalar field",
+            ));
+        }
         let (domain, cs, _) = keygen::create_domain::<C, ConcreteCircuit>(k);
+        let (fixed_commitments, permutation, selectors) =
+            Self::read_commitments(reader, k, &cs, format)?;
+        let (cs, _) = cs.compress_selectors(selectors.clone());
+
+        Ok(Self::from_parts(
+            domain,
+            fixed_commitments,
+            permutation,
+            cs,
+            selectors,
+        ))
+    }
+
+    /// Reads a verification key written with [`Self::write_with_cs`].
+    ///
+    /// The constraint system is taken from the buffer rather than rebuilt from
+    /// a circuit, so this can load keys for circuits the caller does not link.
+    /// Elements are parsed according to `format` as in [`Self::read`].
+    pub fn read_with_cs<R: io::Read>(reader: &mut R, format: SerdeFormat) -> Result<Self, Error> {
+        Self::read_with_cs_io(reader, format).map_err(Error::VerifyingKeySerialization)
+    }
+
+    fn read_with_cs_io<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self> {
+        let mut k = [0u8; 4];
+        reader.read_exact(&mut k)?;
+        let k = u32::from_be_bytes(k);
+        let mut j = [0u8; 4];
+        reader.read_exact(&mut j)?;
+        let j = u32::from_be_bytes(j);
+        if k > C::Scalar::S {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "domain is larger than the two-adicity of the scalar field",
+            ));
+        }
+        let cs = ConstraintSystem::read(reader, format)?;
+        // Key generation always sizes the extended domain by the degree of the
+        // constraint system, so any other value of `j` is malformed.
+        if j as usize != cs.degree() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "domain does not match the degree of the constraint system",
+            ));
+        }
+        let extended_k = k + (u64::from(j) - 1).next_power_of_two().trailing_zeros();
+        if extended_k > C::Scalar::S {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "extended domain is larger than the two-adicity of the scalar field",
+            ));
+        }
+        let domain = EvaluationDomain::new(j, k);
+        let (fixed_commitments, permutation, selectors) =
+            Self::read_commitments(reader, k, &cs, format)?;
+
+        Ok(Self::from_parts(
+            domain,
+            fixed_commitments,
+            permutation,
+            cs,
+            selectors,
+        ))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn read_commitments<R: io::Read>(
+        reader: &mut R,
+        k: u32,
+        cs: &ConstraintSystem<C::Scalar>,
+        format: SerdeFormat,
+    ) -> io::Result<(Vec<C>, permutation::VerifyingKey<C>, Vec<Vec<bool>>)> {
         let mut num_fixed_columns = [0u8; 4];
         reader.read_exact(&mut num_fixed_columns)?;
         let num_fixed_columns = u32::from_be_bytes(num_fixed_columns);
@@ -118,9 +237,9 @@ where
         let permutation = permutation::VerifyingKey::read(reader, &cs.permutation, format)?;
 
         // read selectors
-        let selectors: Vec<Vec<bool>> = vec![vec![false; 1 << k]; cs.num_selectors]
-            .into_iter()
-            .map(|mut selector| {
+        let selectors: Vec<Vec<bool>> = (0..cs.num_selectors)
+            .map(|_| {
+                let mut selector = vec![false; 1 << k];
                 let mut selector_bytes = vec![0u8; (selector.len() + 7) / 8];
                 reader.read_exact(&mut selector_bytes)?;
                 for (bits, byte) in selector.chunks_mut(8).into_iter().zip(selector_bytes) {
@@ -129,15 +248,8 @@ where
                 Ok(selector)
             })
             .collect::<io::Result<_>>()?;
-        let (cs, _) = cs.compress_selectors(selectors.clone());
 
-        Ok(Self::from_parts(
-            domain,
-            fixed_commitments,
-            permutation,
-            cs,
-            selectors,
-        ))
+        Ok((fixed_commitments, permutation, selectors))
     }
 
     /// Writes a verifying key to a vector of bytes using [`Self::write`].
@@ -151,9 +263,21 @@ where
     pub fn from_bytes<ConcreteCircuit: Circuit<C::Scalar>>(
         mut bytes: &[u8],
         format: SerdeFormat,
-    ) -> io::Result<Self> {
+    ) -> Result<Self, Error> {
         Self::read::<_, ConcreteCircuit>(&mut bytes, format)
     }
+
+    /// Writes a verifying key to a vector of bytes using [`Self::write_with_cs`].
+    pub fn to_bytes_with_cs(&self, format: SerdeFormat) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::<u8>::with_capacity(self.bytes_length());
+        Self::write_with_cs(self, &mut bytes, format)?;
+        Ok(bytes)
+    }
+
+    /// Reads a verification key from a slice of bytes using [`Self::read_with_cs`].
+    pub fn from_bytes_with_cs(mut bytes: &[u8], format: SerdeFormat) -> Result<Self, Error> {
+        Self::read_with_cs(&mut bytes, format)
+    }
 }
 
 impl<C: CurveAffine> VerifyingKey<C> {
@@ -258,35 +382,53 @@ pub struct PinnedVerificationKey<'a, C: CurveAffine> {
 }
 /// This is a proving key which allows for the creation of proofs for a
 /// particular circuit.
+///
+/// The polynomials of the key are held by a [`PolynomialStore`]. Keys created
+/// with [`keygen_pk`] or read with [`ProvingKey::read`] keep them in memory;
+/// [`ProvingKey::read_file_backed`] loads them from disk as the prover needs them.
 #[derive(Clone, Debug)]
 pub struct ProvingKey<C: CurveAffine> {
     vk: VerifyingKey<C>,
-    l0: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
-    l_last: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
-    l_active_row: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
-    fixed_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
-    fixed_polys: Vec<Polynomial<C::Scalar, Coeff>>,
-    fixed_cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
-    permutation: permutation::ProvingKey<C>,
+    store: Arc<dyn PolynomialStore<C>>,
     ev: Evaluator<C>,
 }
 
 impl<C: CurveAffine> ProvingKey<C> {
+    /// Constructs a proving key whose polynomials are held by `store`.
+    ///
+    /// The store must hold the polynomials of a proving key generated for `vk`.
+    pub fn from_store<S: PolynomialStore<C> + 'static>(vk: VerifyingKey<C>, store: S) -> Self {
+        let ev = Evaluator::new(vk.cs());
+        ProvingKey {
+            vk,
+            store: Arc::new(store),
+            ev,
+        }
+    }
+
     /// Get the underlying [`VerifyingKey`].
     pub fn get_vk(&self) -> &VerifyingKey<C> {
         &self.vk
     }
 
+    /// Get the underlying [`PolynomialStore`].
+    pub fn store(&self) -> &dyn PolynomialStore<C> {
+        self.store.as_ref()
+    }
+
     /// Gets the total number of bytes in the serialization of `self`
     fn bytes_length(&self) -> usize {
         let scalar_len = C::Scalar::default().to_repr().as_ref().len();
+        let n = 1usize << self.vk.domain.k();
+        let extended_len = self.vk.domain.extended_len();
+        let polynomial_len = |len: usize| 4 + scalar_len * len;
+        // Each group is written as three slices, in Lagrange, coefficient and extended form.
+        let slices_len =
+            |count: usize| 12 + count * (polynomial_len(n) * 2 + polynomial_len(extended_len));
         self.vk.bytes_length()
-            + 12
-            + scalar_len * (self.l0.len() + self.l_last.len() + self.l_active_row.len())
-            + polynomial_slice_byte_length(&self.fixed_values)
-            + polynomial_slice_byte_length(&self.fixed_polys)
-            + polynomial_slice_byte_length(&self.fixed_cosets)
-            + self.permutation.bytes_length()
+            + 3 * polynomial_len(extended_len)
+            + slices_len(self.vk.fixed_commitments.len())
+            + slices_len(self.vk.cs.permutation.columns.len())
     }
 }
 
@@ -306,13 +448,15 @@ where
     /// Does so by first writing the verifying key and then serializing the rest of the data (in the form of field polynomials)
     pub fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
         self.vk.write(writer, format)?;
-        self.l0.write(writer, format)?;
-        self.l_last.write(writer, format)?;
-        self.l_active_row.write(writer, format)?;
-        write_polynomial_slice(&self.fixed_values, writer, format)?;
-        write_polynomial_slice(&self.fixed_polys, writer, format)?;
-        write_polynomial_slice(&self.fixed_cosets, writer, format)?;
-        self.permutation.write(writer, format)?;
+        self.store.l0()?.write(writer, format)?;
+        self.store.l_last()?.write(writer, format)?;
+        self.store.l_active_row()?.write(writer, format)?;
+        write_polynomial_slice(&self.store.fixed_values()?, writer, format)?;
+        write_polynomial_slice(&self.store.fixed_polys()?, writer, format)?;
+        write_polynomial_slice(&self.store.fixed_cosets()?, writer, format)?;
+        write_polynomial_slice(&self.store.permutations()?, writer, format)?;
+        write_polynomial_slice(&self.store.permutation_polys()?, writer, format)?;
+        write_polynomial_slice(&self.store.permutation_cosets()?, writer, format)?;
         Ok(())
     }
 
@@ -330,8 +474,15 @@ where
     pub fn read<R: io::Read, ConcreteCircuit: Circuit<C::Scalar>>(
         reader: &mut R,
         format: SerdeFormat,
+    ) -> Result<Self, Error> {
+        Self::read_io::<R, ConcreteCircuit>(reader, format).map_err(Error::ProvingKeySerialization)
+    }
+
+    fn read_io<R: io::Read, ConcreteCircuit: Circuit<C::Scalar>>(
+        reader: &mut R,
+        format: SerdeFormat,
     ) -> io::Result<Self> {
-        let vk = VerifyingKey::<C>::read::<R, ConcreteCircuit>(reader, format)?;
+        let vk = VerifyingKey::<C>::read_io::<R, ConcreteCircuit>(reader, format)?;
         let l0 = Polynomial::read(reader, format)?;
         let l_last = Polynomial::read(reader, format)?;
         let l_active_row = Polynomial::read(reader, format)?;
@@ -339,18 +490,35 @@ where
         let fixed_polys = read_polynomial_vec(reader, format)?;
         let fixed_cosets = read_polynomial_vec(reader, format)?;
         let permutation = permutation::ProvingKey::read(reader, format)?;
-        let ev = Evaluator::new(vk.cs());
-        Ok(Self {
+        Ok(Self::from_store(
             vk,
-            l0,
-            l_last,
-            l_active_row,
-            fixed_values,
-            fixed_polys,
-            fixed_cosets,
-            permutation,
-            ev,
-        })
+            InMemoryStore {
+                l0,
+                l_last,
+                l_active_row,
+                fixed_values,
+                fixed_polys,
+                fixed_cosets,
+                permutation,
+            },
+        ))
+    }
+
+    /// Opens a proving key file written with [`Self::write`] without loading
+    /// its polynomials into memory.
+    ///
+    /// Only the verifying key is read up front; the returned key is backed by a
+    /// [`FileStore`], which reads each group of polynomials from the file when
+    /// the prover needs it. This bounds the memory taken by the proving key at
+    /// the cost of reading the file once per proof. The file is parsed according
+    /// to `format` as in [`Self::read`].
+    pub fn read_file_backed<ConcreteCircuit: Circuit<C::Scalar>>(
+        path: impl AsRef<Path>,
+        format: SerdeFormat,
+    ) -> Result<Self, Error> {
+        let (vk, store) = FileStore::open::<ConcreteCircuit>(path.as_ref(), format)
+            .map_err(Error::ProvingKeySerialization)?;
+        Ok(Self::from_store(vk, store))
     }
 
     /// Writes a proving key to a vector of bytes using [`Self::write`].
@@ -364,7 +532,7 @@ where
     pub fn from_bytes<ConcreteCircuit: Circuit<C::Scalar>>(
         mut bytes: &[u8],
         format: SerdeFormat,
-    ) -> io::Result<Self> {
+    ) -> Result<Self, Error> {
         Self::read::<_, ConcreteCircuit>(&mut bytes, format)
     }
 }
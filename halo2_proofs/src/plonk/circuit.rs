@@ -7,7 +7,7 @@ use std::{
     ops::{Neg, Sub},
 };
 
-use super::{lookup, permutation, Assigned, Error};
+use super::{logup, lookup, permutation, shuffle, Assigned, Error};
 use crate::dev::metadata;
 use crate::{
     circuit::{Layouter, Region, Value},
@@ -16,6 +16,7 @@ use crate::{
 use sealed::SealedPhase;
 
 mod compress_selectors;
+mod serialization;
 
 /// A column type
 pub trait ColumnType:
@@ -31,7 +32,6 @@ pub struct Column<C: ColumnType> {
 }
 
 impl<C: ColumnType> Column<C> {
-    #[cfg(test)]
     pub(crate) fn new(index: usize, column_type: C) -> Self {
         Column { index, column_type }
     }
@@ -498,6 +498,43 @@ impl TableColumn {
     }
 }
 
+/// A dynamic lookup table, whose rows are assigned to advice columns at synthesis
+/// time.
+///
+/// Every dynamic table is gated by its own fixed "tag" column, which is set to one on
+/// each row that belongs to the table and is zero everywhere else. Lookups created
+/// with [`ConstraintSystem::lookup_dynamic`] are matched against `tag * column`, so
+/// rows that were never added to the table cannot satisfy an enabled lookup.
+///
+/// Rows are added to the table with [`Layouter::assign_dynamic_table`] or
+/// [`Region::add_to_dynamic_table`].
+///
+/// [`Layouter::assign_dynamic_table`]: crate::circuit::Layouter::assign_dynamic_table
+/// [`Region::add_to_dynamic_table`]: crate::circuit::Region::add_to_dynamic_table
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct DynamicTable {
+    /// The fixed column holding this table's tag.
+    ///
+    /// # Security
+    ///
+    /// This tag column MUST NOT be exposed in the public API, or else chip developers
+    /// can assign to it outside of the dynamic table APIs, silently adding rows to
+    /// the table.
+    tag: Column<Fixed>,
+    columns: Vec<Column<Advice>>,
+}
+
+impl DynamicTable {
+    pub(crate) fn tag(&self) -> Column<Fixed> {
+        self.tag
+    }
+
+    /// Returns the advice columns backing this table.
+    pub fn columns(&self) -> &[Column<Advice>] {
+        &self.columns
+    }
+}
+
 /// A challenge squeezed from transcript after advice columns at the phase have been committed.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub struct Challenge {
@@ -1389,6 +1426,14 @@ pub struct ConstraintSystem<F: Field> {
     // input expressions and a sequence of table expressions involved in the lookup.
     pub(crate) lookups: Vec<lookup::Argument<F>>,
 
+    // Vector of LogUp lookup arguments, where each corresponds to a sequence of
+    // inputs and a sequence of table expressions shared by all of those inputs.
+    pub(crate) logups: Vec<logup::Argument<F>>,
+
+    // Vector of shuffle arguments, where each corresponds to a sequence of
+    // input expressions and a sequence of shuffle expressions involved in the shuffle.
+    pub(crate) shuffles: Vec<shuffle::Argument<F>>,
+
     // List of indexes of Fixed columns which are associated to a circuit-general Column tied to their annotation.
     pub(crate) general_column_annotations: HashMap<metadata::Column, String>,
 
@@ -1415,6 +1460,8 @@ pub struct PinnedConstraintSystem<'a, F: Field> {
     fixed_queries: &'a Vec<(Column<Fixed>, Rotation)>,
     permutation: &'a permutation::Argument,
     lookups: &'a Vec<lookup::Argument<F>>,
+    logups: &'a Vec<logup::Argument<F>>,
+    shuffles: &'a Vec<shuffle::Argument<F>>,
     constants: &'a Vec<Column<Fixed>>,
     minimum_degree: &'a Option<usize>,
 }
@@ -1440,7 +1487,16 @@ impl<'a, F: Field> std::fmt::Debug for PinnedConstraintSystem<'a, F> {
             .field("instance_queries", self.instance_queries)
             .field("fixed_queries", self.fixed_queries)
             .field("permutation", self.permutation)
-            .field("lookups", self.lookups)
+            .field("lookups", self.lookups);
+        // Only show logups field if it's used.
+        if !self.logups.is_empty() {
+            debug_struct.field("logups", self.logups);
+        }
+        // Only show shuffles field if it's used.
+        if !self.shuffles.is_empty() {
+            debug_struct.field("shuffles", self.shuffles);
+        }
+        debug_struct
             .field("constants", self.constants)
             .field("minimum_degree", self.minimum_degree);
         debug_struct.finish()
@@ -1475,6 +1531,8 @@ impl<F: Field> Default for ConstraintSystem<F> {
             instance_queries: Vec::new(),
             permutation: permutation::Argument::new(),
             lookups: Vec::new(),
+            logups: Vec::new(),
+            shuffles: Vec::new(),
             general_column_annotations: HashMap::new(),
             constants: vec![],
             minimum_degree: None,
@@ -1501,6 +1559,8 @@ impl<F: Field> ConstraintSystem<F> {
             instance_queries: &self.instance_queries,
             permutation: &self.permutation,
             lookups: &self.lookups,
+            logups: &self.logups,
+            shuffles: &self.shuffles,
             constants: &self.constants,
             minimum_degree: &self.minimum_degree,
         }
@@ -1574,6 +1634,136 @@ impl<F: Field> ConstraintSystem<F> {
         index
     }
 
+    /// Add a lookup argument for some input expressions into a dynamic table.
+    ///
+    /// `table_map` returns an expression `q` gating the lookup, and a map between input
+    /// expressions and the advice columns of `table` they need to match. On every row
+    /// where `q` is one, the inputs must appear together in a single row of the table;
+    /// internally this looks up `(q, q * input_0, ...)` in `(tag, tag * column_0, ...)`.
+    /// Table columns that do not appear in `table_map` are unconstrained.
+    ///
+    /// `q` should evaluate to zero or one, and the table must leave at least one usable
+    /// row untagged so that disabled lookups can be satisfied.
+    pub fn lookup_dynamic(
+        &mut self,
+        name: &'static str,
+        table: &DynamicTable,
+        table_map: impl FnOnce(
+            &mut VirtualCells<'_, F>,
+        ) -> (Expression<F>, Vec<(Expression<F>, Column<Advice>)>),
+    ) -> usize {
+        let mut cells = VirtualCells::new(self);
+        let (selector, table_map) = table_map(&mut cells);
+        if selector.contains_simple_selector() {
+            panic!("expression containing simple selector supplied to lookup argument");
+        }
+
+        let tag = cells.query_fixed(table.tag, Rotation::cur());
+        let mut lookup_map = vec![(selector.clone(), tag.clone())];
+        for (input, column) in table_map {
+            if input.contains_simple_selector() {
+                panic!("expression containing simple selector supplied to lookup argument");
+            }
+            if !table.columns.contains(&column) {
+                panic!("column {:?} is not part of the dynamic table", column);
+            }
+
+            let column = cells.query_advice(column, Rotation::cur());
+            lookup_map.push((selector.clone() * input, tag.clone() * column));
+        }
+
+        let index = self.lookups.len();
+
+        self.lookups.push(lookup::Argument::new(name, lookup_map));
+
+        index
+    }
+
+    /// Add a LogUp lookup argument for some inputs and table columns.
+    ///
+    /// `table_map` returns a list of inputs, each a tuple of expressions, and the
+    /// table columns that every input needs to match. All inputs share a single
+    /// multiplicity column, but each additional input raises the degree of the
+    /// argument by the degree of that input.
+    pub fn lookup_logup(
+        &mut self,
+        name: &'static str,
+        table_map: impl FnOnce(&mut VirtualCells<'_, F>) -> (Vec<Vec<Expression<F>>>, Vec<TableColumn>),
+    ) -> usize {
+        let mut cells = VirtualCells::new(self);
+        let (inputs, table) = table_map(&mut cells);
+        for input in inputs.iter().flatten() {
+            if input.contains_simple_selector() {
+                panic!("expression containing simple selector supplied to lookup argument");
+            }
+        }
+        let table = table
+            .into_iter()
+            .map(|table| cells.query_fixed(table.inner(), Rotation::cur()))
+            .collect();
+
+        let index = self.logups.len();
+
+        self.logups.push(logup::Argument::new(name, inputs, table));
+
+        index
+    }
+
+    /// Add a LogUp lookup argument for some inputs and table expressions.
+    ///
+    /// `table_map` returns a list of inputs, each a tuple of expressions, and the
+    /// table expressions that every input needs to match.
+    pub fn lookup_any_logup(
+        &mut self,
+        name: &'static str,
+        table_map: impl FnOnce(
+            &mut VirtualCells<'_, F>,
+        ) -> (Vec<Vec<Expression<F>>>, Vec<Expression<F>>),
+    ) -> usize {
+        let mut cells = VirtualCells::new(self);
+        let (inputs, table) = table_map(&mut cells);
+        for input in inputs.iter().flatten() {
+            if input.contains_simple_selector() {
+                panic!("expression containing simple selector supplied to lookup argument");
+            }
+        }
+
+        let index = self.logups.len();
+
+        self.logups.push(logup::Argument::new(name, inputs, table));
+
+        index
+    }
+
+    /// Add a shuffle argument for some input expressions and shuffle expressions.
+    ///
+    /// `shuffle_map` returns a map between input expressions and the shuffle expressions
+    /// they need to be a permutation of.
+    pub fn shuffle(
+        &mut self,
+        name: &'static str,
+        shuffle_map: impl FnOnce(&mut VirtualCells<'_, F>) -> Vec<(Expression<F>, Expression<F>)>,
+    ) -> usize {
+        let mut cells = VirtualCells::new(self);
+        let shuffle_map = shuffle_map(&mut cells)
+            .into_iter()
+            .map(|(input, shuffle)| {
+                if input.contains_simple_selector() || shuffle.contains_simple_selector() {
+                    panic!("expression containing simple selector supplied to shuffle argument");
+                }
+
+                (input, shuffle)
+            })
+            .collect();
+
+        let index = self.shuffles.len();
+
+        self.shuffles
+            .push(shuffle::Argument::new(name, shuffle_map));
+
+        index
+    }
+
     fn query_fixed_index(&mut self, column: Column<Fixed>, at: Rotation) -> usize {
         // Return existing query, if it exists
         for (index, fixed_query) in self.fixed_queries.iter().enumerate() {
@@ -1832,6 +2022,29 @@ impl<F: Field> ConstraintSystem<F> {
             replace_selectors(expr, &selector_replacements, true);
         }
 
+        // Substitute non-simple selectors for the real fixed columns in all
+        // LogUp lookup expressions
+        for expr in self.logups.iter_mut().flat_map(|logup| {
+            logup
+                .input_expressions
+                .iter_mut()
+                .flatten()
+                .chain(logup.table_expressions.iter_mut())
+        }) {
+            replace_selectors(expr, &selector_replacements, true);
+        }
+
+        // Substitute non-simple selectors for the real fixed columns in all
+        // shuffle expressions
+        for expr in self.shuffles.iter_mut().flat_map(|shuffle| {
+            shuffle
+                .input_expressions
+                .iter_mut()
+                .chain(shuffle.shuffle_expressions.iter_mut())
+        }) {
+            replace_selectors(expr, &selector_replacements, true);
+        }
+
         (self, polys)
     }
 
@@ -1860,6 +2073,20 @@ impl<F: Field> ConstraintSystem<F> {
         }
     }
 
+    /// Allocates a new dynamic lookup table backed by the given advice columns, along
+    /// with the fixed tag column that gates it.
+    pub fn dynamic_table(&mut self, columns: &[Column<Advice>]) -> DynamicTable {
+        assert!(
+            !columns.is_empty(),
+            "a dynamic table needs at least one column"
+        );
+
+        DynamicTable {
+            tag: self.fixed_column(),
+            columns: columns.to_vec(),
+        }
+    }
+
     /// Annotate a Lookup column.
     pub fn annotate_lookup_column<A, AR>(&mut self, column: TableColumn, annotation: A)
     where
@@ -1993,6 +2220,28 @@ impl<F: Field> ConstraintSystem<F> {
                 .unwrap_or(1),
         );
 
+        // The LogUp lookup argument also serves alongside the gates and must be
+        // accounted for.
+        degree = std::cmp::max(
+            degree,
+            self.logups
+                .iter()
+                .map(|l| l.required_degree())
+                .max()
+                .unwrap_or(1),
+        );
+
+        // The shuffle argument also serves alongside the gates and must be accounted
+        // for.
+        degree = std::cmp::max(
+            degree,
+            self.shuffles
+                .iter()
+                .map(|l| l.required_degree())
+                .max()
+                .unwrap_or(1),
+        );
+
         // Account for each gate to ensure our quotient polynomial is the
         // correct degree and that our extended domain is the right size.
         degree = std::cmp::max(
@@ -2017,6 +2266,10 @@ impl<F: Field> ConstraintSystem<F> {
         // - The permutation argument witness polynomials are evaluated at most 3 times.
         // - Each lookup argument has independent witness polynomials, and they are
         //   evaluated at most 2 times.
+        // - Each LogUp lookup argument has independent multiplicity and running sum
+        //   polynomials, and they are evaluated at most 2 times.
+        // - Each shuffle argument has an independent product polynomial, and it is
+        //   evaluated at most 2 times.
         let factors = std::cmp::max(3, factors);
 
         // Each polynomial is evaluated at most an additional time during
@@ -2111,6 +2364,16 @@ impl<F: Field> ConstraintSystem<F> {
         &self.lookups
     }
 
+    /// Returns LogUp lookup arguments
+    pub fn logups(&self) -> &Vec<logup::Argument<F>> {
+        &self.logups
+    }
+
+    /// Returns shuffle arguments
+    pub fn shuffles(&self) -> &Vec<shuffle::Argument<F>> {
+        &self.shuffles
+    }
+
     /// Returns constants
     pub fn constants(&self) -> &Vec<Column<Fixed>> {
         &self.constants
@@ -0,0 +1,664 @@
+//! Serialization of a compressed [`ConstraintSystem`], so that a verifying key
+//! can be read without the concrete circuit type.
+
+use std::io;
+
+use super::{
+    sealed, Advice, AdviceQuery, Any, Challenge, Column, ColumnType, ConstraintSystem, Expression,
+    FixedQuery, Gate, InstanceQuery, Selector, VirtualCell,
+};
+use crate::{
+    helpers::{
+        invalid_data, read_string, read_u8, read_usize, read_vec, write_string, write_u8,
+        write_usize, write_vec, SerdePrimeField,
+    },
+    plonk::{logup, lookup, permutation, shuffle},
+    poly::Rotation,
+    SerdeFormat,
+};
+
+/// The maximum nesting depth of an expression that is read.
+///
+/// Expressions are read recursively, so the depth is bounded to keep a
+/// malicious verifying key from exhausting the stack. Writing is not bounded.
+const MAX_EXPRESSION_DEPTH: usize = 1024;
+
+fn write_rotation<W: io::Write>(writer: &mut W, rotation: Rotation) -> io::Result<()> {
+    writer.write_all(&rotation.0.to_be_bytes())
+}
+
+fn read_rotation<R: io::Read>(reader: &mut R) -> io::Result<Rotation> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(Rotation(i32::from_be_bytes(bytes)))
+}
+
+/// Reads a name of a gate, constraint or argument.
+///
+/// These names are `&'static str`s, as passed to [`ConstraintSystem`] in
+/// `Circuit::configure`, so the names that are read are leaked.
+fn read_name<R: io::Read>(reader: &mut R) -> io::Result<&'static str> {
+    Ok(Box::leak(read_string(reader)?.into_boxed_str()))
+}
+
+fn read_index<R: io::Read>(reader: &mut R, bound: usize) -> io::Result<usize> {
+    let index = read_usize(reader)?;
+    if index >= bound {
+        return Err(invalid_data("index out of bounds"));
+    }
+    Ok(index)
+}
+
+fn read_phase<R: io::Read>(reader: &mut R) -> io::Result<sealed::Phase> {
+    Ok(sealed::Phase(read_u8(reader)?))
+}
+
+fn write_column<W: io::Write>(writer: &mut W, column: &Column<Any>) -> io::Result<()> {
+    match column.column_type {
+        Any::Advice(advice) => {
+            write_u8(writer, 0)?;
+            write_u8(writer, advice.phase.0)?;
+        }
+        Any::Fixed => write_u8(writer, 1)?,
+        Any::Instance => write_u8(writer, 2)?,
+    }
+    write_usize(writer, column.index)
+}
+
+fn write_query<W: io::Write, C: ColumnType>(
+    writer: &mut W,
+    (column, rotation): &(Column<C>, Rotation),
+) -> io::Result<()>
+where
+    Column<C>: Into<Column<Any>>,
+{
+    write_column(writer, &(*column).into())?;
+    write_rotation(writer, *rotation)
+}
+
+impl<F: SerdePrimeField> ConstraintSystem<F> {
+    /// Writes a column of this constraint system.
+    pub(crate) fn write_column<W: io::Write>(
+        writer: &mut W,
+        column: Column<Any>,
+    ) -> io::Result<()> {
+        write_column(writer, &column)
+    }
+
+    /// Reads a column written with [`ConstraintSystem::write_column`], checking that it
+    /// exists in this constraint system.
+    pub(crate) fn read_column<R: io::Read>(&self, reader: &mut R) -> io::Result<Column<Any>> {
+        let (column_type, bound) = match read_u8(reader)? {
+            0 => (
+                Any::Advice(Advice {
+                    phase: read_phase(reader)?,
+                }),
+                self.num_advice_columns,
+            ),
+            1 => (Any::Fixed, self.num_fixed_columns),
+            2 => (Any::Instance, self.num_instance_columns),
+            _ => return Err(invalid_data("invalid column type")),
+        };
+        let index = read_index(reader, bound)?;
+        if let Any::Advice(advice) = column_type {
+            if self.advice_column_phase[index] != advice.phase {
+                return Err(invalid_data("inconsistent advice column phase"));
+            }
+        }
+        Ok(Column { index, column_type })
+    }
+
+    fn read_typed_column<R: io::Read, C: ColumnType>(&self, reader: &mut R) -> io::Result<Column<C>>
+    where
+        Column<C>: TryFrom<Column<Any>>,
+    {
+        let column = self.read_column(reader)?;
+        Column::<C>::try_from(column).map_err(|_| invalid_data("unexpected column type"))
+    }
+
+    /// Writes an expression in prefix order.
+    ///
+    /// The expression is traversed with an explicit stack, so that writing never
+    /// fails because of its depth. Only [`Self::read_expression`] bounds the depth.
+    fn write_expression<W: io::Write>(
+        expression: &Expression<F>,
+        writer: &mut W,
+        format: SerdeFormat,
+    ) -> io::Result<()> {
+        enum Item<'a, F> {
+            Expression(&'a Expression<F>),
+            Scalar(&'a F),
+        }
+
+        let mut stack = vec![Item::Expression(expression)];
+        while let Some(item) = stack.pop() {
+            let expression = match item {
+                Item::Expression(expression) => expression,
+                Item::Scalar(scalar) => {
+                    scalar.write(writer, format)?;
+                    continue;
+                }
+            };
+            match expression {
+                Expression::Constant(scalar) => {
+                    write_u8(writer, 0)?;
+                    scalar.write(writer, format)?;
+                }
+                Expression::Selector(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "virtual selectors must be compressed before serialization",
+                    ))
+                }
+                Expression::Fixed(query) => {
+                    write_u8(writer, 1)?;
+                    write_usize(writer, query.index)?;
+                }
+                Expression::Advice(query) => {
+                    write_u8(writer, 2)?;
+                    write_usize(writer, query.index)?;
+                }
+                Expression::Instance(query) => {
+                    write_u8(writer, 3)?;
+                    write_usize(writer, query.index)?;
+                }
+                Expression::Challenge(challenge) => {
+                    write_u8(writer, 4)?;
+                    write_usize(writer, challenge.index)?;
+                }
+                Expression::Negated(a) => {
+                    write_u8(writer, 5)?;
+                    stack.push(Item::Expression(a));
+                }
+                Expression::Sum(a, b) => {
+                    write_u8(writer, 6)?;
+                    stack.push(Item::Expression(b));
+                    stack.push(Item::Expression(a));
+                }
+                Expression::Product(a, b) => {
+                    write_u8(writer, 7)?;
+                    stack.push(Item::Expression(b));
+                    stack.push(Item::Expression(a));
+                }
+                Expression::Scaled(a, scalar) => {
+                    write_u8(writer, 8)?;
+                    stack.push(Item::Scalar(scalar));
+                    stack.push(Item::Expression(a));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads an expression. Queries are written as indices into the query
+    /// vectors of the constraint system, which must already have been read.
+    fn read_expression<R: io::Read>(
+        &self,
+        reader: &mut R,
+        format: SerdeFormat,
+        depth: usize,
+    ) -> io::Result<Expression<F>> {
+        if depth >= MAX_EXPRESSION_DEPTH {
+            return Err(invalid_data("expression is nested too deeply"));
+        }
+        Ok(match read_u8(reader)? {
+            0 => Expression::Constant(F::read(reader, format)?),
+            1 => {
+                let index = read_index(reader, self.fixed_queries.len())?;
+                let (column, rotation) = self.fixed_queries[index];
+                Expression::Fixed(FixedQuery {
+                    index,
+                    column_index: column.index,
+                    rotation,
+                })
+            }
+            2 => {
+                let index = read_index(reader, self.advice_queries.len())?;
+                let (column, rotation) = self.advice_queries[index];
+                Expression::Advice(AdviceQuery {
+                    index,
+                    column_index: column.index,
+                    rotation,
+                    phase: column.column_type.phase,
+                })
+            }
+            3 => {
+                let index = read_index(reader, self.instance_queries.len())?;
+                let (column, rotation) = self.instance_queries[index];
+                Expression::Instance(InstanceQuery {
+                    index,
+                    column_index: column.index,
+                    rotation,
+                })
+            }
+            4 => {
+                let index = read_index(reader, self.num_challenges)?;
+                Expression::Challenge(Challenge {
+                    index,
+                    phase: self.challenge_phase[index],
+                })
+            }
+            5 => Expression::Negated(Box::new(self.read_expression(reader, format, depth + 1)?)),
+            6 => Expression::Sum(
+                Box::new(self.read_expression(reader, format, depth + 1)?),
+                Box::new(self.read_expression(reader, format, depth + 1)?),
+            ),
+            7 => Expression::Product(
+                Box::new(self.read_expression(reader, format, depth + 1)?),
+                Box::new(self.read_expression(reader, format, depth + 1)?),
+            ),
+            8 => {
+                let a = self.read_expression(reader, format, depth + 1)?;
+                Expression::Scaled(Box::new(a), F::read(reader, format)?)
+            }
+            _ => return Err(invalid_data("invalid expression")),
+        })
+    }
+
+    fn write_expressions<W: io::Write>(
+        expressions: &[Expression<F>],
+        writer: &mut W,
+        format: SerdeFormat,
+    ) -> io::Result<()> {
+        write_vec(writer, expressions, |writer, expression| {
+            Self::write_expression(expression, writer, format)
+        })
+    }
+
+    fn read_expressions<R: io::Read>(
+        &self,
+        reader: &mut R,
+        format: SerdeFormat,
+    ) -> io::Result<Vec<Expression<F>>> {
+        read_vec(reader, |reader| self.read_expression(reader, format, 0))
+    }
+
+    /// Writes a selector of this constraint system.
+    pub(crate) fn write_selector<W: io::Write>(
+        writer: &mut W,
+        selector: &Selector,
+    ) -> io::Result<()> {
+        write_usize(writer, selector.0)?;
+        write_u8(writer, selector.1 as u8)
+    }
+
+    /// Reads a selector written with [`ConstraintSystem::write_selector`], checking that
+    /// it exists in this constraint system.
+    pub(crate) fn read_selector<R: io::Read>(&self, reader: &mut R) -> io::Result<Selector> {
+        let index = read_index(reader, self.num_selectors)?;
+        match read_u8(reader)? {
+            0 => Ok(Selector(index, false)),
+            1 => Ok(Selector(index, true)),
+            _ => Err(invalid_data("invalid selector")),
+        }
+    }
+
+    /// Writes the names of the gates, constraints and arguments, and the selectors and
+    /// cells queried by each gate, which are only used by the developer tooling and so
+    /// are not written by [`ConstraintSystem::write`].
+    pub(crate) fn write_metadata<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_vec(writer, &self.gates, |writer, gate| {
+            write_string(writer, gate.name)?;
+            write_vec(writer, &gate.constraint_names, |writer, name| {
+                write_string(writer, name)
+            })?;
+            write_vec(writer, &gate.queried_selectors, Self::write_selector)?;
+            write_vec(writer, &gate.queried_cells, |writer, cell| {
+                write_column(writer, &cell.column)?;
+                write_rotation(writer, cell.rotation)
+            })
+        })?;
+        write_vec(writer, &self.lookups, |writer, lookup| {
+            write_string(writer, lookup.name)
+        })?;
+        write_vec(writer, &self.logups, |writer, logup| {
+            write_string(writer, logup.name)
+        })?;
+        write_vec(writer, &self.shuffles, |writer, shuffle| {
+            write_string(writer, shuffle.name)
+        })
+    }
+
+    /// Reads the metadata written with [`ConstraintSystem::write_metadata`] into this
+    /// constraint system, which must have been read with [`ConstraintSystem::read`].
+    pub(crate) fn read_metadata<R: io::Read>(&mut self, reader: &mut R) -> io::Result<()> {
+        let mismatch = || invalid_data("metadata does not match the constraint system");
+
+        let gates = read_vec(reader, |reader| {
+            let name = read_name(reader)?;
+            let constraint_names = read_vec(reader, read_name)?;
+            let queried_selectors = read_vec(reader, |reader| self.read_selector(reader))?;
+            let queried_cells = read_vec(reader, |reader| {
+                Ok(VirtualCell {
+                    column: self.read_column(reader)?,
+                    rotation: read_rotation(reader)?,
+                })
+            })?;
+            Ok((name, constraint_names, queried_selectors, queried_cells))
+        })?;
+        if gates.len() != self.gates.len() {
+            return Err(mismatch());
+        }
+        for (gate, (name, constraint_names, queried_selectors, queried_cells)) in
+            self.gates.iter_mut().zip(gates)
+        {
+            if constraint_names.len() != gate.polys.len() {
+                return Err(mismatch());
+            }
+            gate.name = name;
+            gate.constraint_names = constraint_names;
+            gate.queried_selectors = queried_selectors;
+            gate.queried_cells = queried_cells;
+        }
+
+        let mut read_names = |len: usize| -> io::Result<Vec<&'static str>> {
+            let names = read_vec(reader, read_name)?;
+            if names.len() == len {
+                Ok(names)
+            } else {
+                Err(mismatch())
+            }
+        };
+        let names = read_names(self.lookups.len())?;
+        for (lookup, name) in self.lookups.iter_mut().zip(names) {
+            lookup.name = name;
+        }
+        let names = read_names(self.logups.len())?;
+        for (logup, name) in self.logups.iter_mut().zip(names) {
+            logup.name = name;
+        }
+        let names = read_names(self.shuffles.len())?;
+        for (shuffle, name) in self.shuffles.iter_mut().zip(names) {
+            shuffle.name = name;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a constraint system whose selectors have been compressed.
+    ///
+    /// Everything the verifier depends on is written; names and annotations,
+    /// which are only used by the developer tooling, are not.
+    pub(crate) fn write<W: io::Write>(
+        &self,
+        writer: &mut W,
+        format: SerdeFormat,
+    ) -> io::Result<()> {
+        write_usize(writer, self.num_fixed_columns)?;
+        write_usize(writer, self.num_advice_columns)?;
+        write_usize(writer, self.num_instance_columns)?;
+        write_usize(writer, self.num_selectors)?;
+        write_usize(writer, self.num_challenges)?;
+        write_vec(writer, &self.advice_column_phase, |writer, phase| {
+            write_u8(writer, phase.0)
+        })?;
+        write_vec(writer, &self.challenge_phase, |writer, phase| {
+            write_u8(writer, phase.0)
+        })?;
+        write_vec(writer, &self.selector_map, |writer, column| {
+            write_column(writer, &(*column).into())
+        })?;
+
+        write_vec(writer, &self.advice_queries, write_query)?;
+        write_vec(writer, &self.instance_queries, write_query)?;
+        write_vec(writer, &self.fixed_queries, write_query)?;
+
+        write_vec(writer, &self.gates, |writer, gate| {
+            Self::write_expressions(&gate.polys, writer, format)
+        })?;
+        write_vec(writer, &self.permutation.columns, write_column)?;
+        write_vec(writer, &self.lookups, |writer, lookup| {
+            Self::write_expressions(&lookup.input_expressions, writer, format)?;
+            Self::write_expressions(&lookup.table_expressions, writer, format)
+        })?;
+        write_vec(writer, &self.logups, |writer, logup| {
+            write_vec(writer, &logup.input_expressions, |writer, input| {
+                Self::write_expressions(input, writer, format)
+            })?;
+            Self::write_expressions(&logup.table_expressions, writer, format)
+        })?;
+        write_vec(writer, &self.shuffles, |writer, shuffle| {
+            Self::write_expressions(&shuffle.input_expressions, writer, format)?;
+            Self::write_expressions(&shuffle.shuffle_expressions, writer, format)
+        })?;
+        write_vec(writer, &self.constants, |writer, column| {
+            write_column(writer, &(*column).into())
+        })?;
+        match self.minimum_degree {
+            Some(degree) => {
+                write_u8(writer, 1)?;
+                write_usize(writer, degree)
+            }
+            None => write_u8(writer, 0),
+        }
+    }
+
+    /// Reads a constraint system written with [`ConstraintSystem::write`].
+    pub(crate) fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self> {
+        let mut cs = ConstraintSystem {
+            num_fixed_columns: read_usize(reader)?,
+            num_advice_columns: read_usize(reader)?,
+            num_instance_columns: read_usize(reader)?,
+            num_selectors: read_usize(reader)?,
+            num_challenges: read_usize(reader)?,
+            advice_column_phase: read_vec(reader, read_phase)?,
+            challenge_phase: read_vec(reader, read_phase)?,
+            ..Default::default()
+        };
+        if cs.advice_column_phase.len() != cs.num_advice_columns
+            || cs.challenge_phase.len() != cs.num_challenges
+        {
+            return Err(invalid_data("inconsistent number of phases"));
+        }
+        cs.selector_map = read_vec(reader, |reader| cs.read_typed_column(reader))?;
+
+        cs.advice_queries = read_vec(reader, |reader| {
+            Ok((cs.read_typed_column(reader)?, read_rotation(reader)?))
+        })?;
+        cs.num_advice_queries = vec![0; cs.num_advice_columns];
+        for (column, _) in cs.advice_queries.iter() {
+            cs.num_advice_queries[column.index] += 1;
+        }
+        cs.instance_queries = read_vec(reader, |reader| {
+            Ok((cs.read_typed_column(reader)?, read_rotation(reader)?))
+        })?;
+        cs.fixed_queries = read_vec(reader, |reader| {
+            Ok((cs.read_typed_column(reader)?, read_rotation(reader)?))
+        })?;
+
+        cs.gates = read_vec(reader, |reader| {
+            let polys = cs.read_expressions(reader, format)?;
+            Ok(Gate {
+                name: "",
+                constraint_names: vec![""; polys.len()],
+                polys,
+                queried_selectors: vec![],
+                queried_cells: vec![],
+            })
+        })?;
+        cs.permutation = permutation::Argument {
+            columns: read_vec(reader, |reader| cs.read_column(reader))?,
+        };
+        cs.lookups = read_vec(reader, |reader| {
+            let input_expressions = cs.read_expressions(reader, format)?;
+            let table_expressions = cs.read_expressions(reader, format)?;
+            if input_expressions.len() != table_expressions.len() {
+                return Err(invalid_data("inconsistent lookup argument"));
+            }
+            Ok(lookup::Argument {
+                name: "",
+                input_expressions,
+                table_expressions,
+            })
+        })?;
+        cs.logups = read_vec(reader, |reader| {
+            let input_expressions = read_vec(reader, |reader| cs.read_expressions(reader, format))?;
+            let table_expressions = cs.read_expressions(reader, format)?;
+            if input_expressions.is_empty()
+                || input_expressions
+                    .iter()
+                    .any(|input| input.len() != table_expressions.len())
+            {
+                return Err(invalid_data("inconsistent LogUp argument"));
+            }
+            Ok(logup::Argument {
+                name: "",
+                input_expressions,
+                table_expressions,
+            })
+        })?;
+        cs.shuffles = read_vec(reader, |reader| {
+            let input_expressions = cs.read_expressions(reader, format)?;
+            let shuffle_expressions = cs.read_expressions(reader, format)?;
+            if input_expressions.len() != shuffle_expressions.len() {
+                return Err(invalid_data("inconsistent shuffle argument"));
+            }
+            Ok(shuffle::Argument {
+                name: "",
+                input_expressions,
+                shuffle_expressions,
+            })
+        })?;
+        cs.constants = read_vec(reader, |reader| cs.read_typed_column(reader))?;
+        cs.minimum_degree = match read_u8(reader)? {
+            0 => None,
+            1 => Some(read_usize(reader)?),
+            _ => return Err(invalid_data("invalid minimum degree")),
+        };
+
+        Ok(cs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use halo2curves::pasta::Fp;
+
+    use super::super::{
+        Advice, Column, ConstraintSystem, Expression, Fixed, Instance, TableColumn,
+    };
+    use super::MAX_EXPRESSION_DEPTH;
+    use crate::{plonk::FirstPhase, poly::Rotation, SerdeFormat};
+
+    #[test]
+    fn constraint_system_roundtrip() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let a: Column<Advice> = meta.advice_column();
+        let b = meta.advice_column_in(FirstPhase);
+        let f: Column<Fixed> = meta.fixed_column();
+        let i: Column<Instance> = meta.instance_column();
+        let t: TableColumn = meta.lookup_table_column();
+        let s = meta.selector();
+        let c = meta.challenge_usable_after(FirstPhase);
+        meta.enable_equality(a);
+        meta.enable_constant(f);
+
+        meta.create_gate("gate", |meta| {
+            let s = meta.query_selector(s);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::next());
+            let f = meta.query_fixed(f, Rotation::prev());
+            let i = meta.query_instance(i, Rotation::cur());
+            let c = meta.query_challenge(c);
+            vec![s * (a * b - f * c + -i) * Fp::from(3)]
+        });
+        meta.lookup("lookup", |meta| {
+            vec![(meta.query_advice(a, Rotation::cur()), t)]
+        });
+        meta.shuffle("shuffle", |meta| {
+            vec![(
+                meta.query_advice(a, Rotation::cur()),
+                meta.query_advice(b, Rotation::cur()),
+            )]
+        });
+
+        let (meta, _) = meta.compress_selectors(vec![vec![false, true, true, false]]);
+
+        for format in [SerdeFormat::Processed, SerdeFormat::RawBytes] {
+            let mut bytes = vec![];
+            meta.write(&mut bytes, format).unwrap();
+            let read = ConstraintSystem::<Fp>::read(&mut &bytes[..], format).unwrap();
+
+            assert_eq!(
+                format!("{:?}", read.pinned()),
+                format!("{:?}", meta.pinned())
+            );
+            assert_eq!(read.blinding_factors(), meta.blinding_factors());
+            assert_eq!(read.degree(), meta.degree());
+        }
+    }
+
+    #[test]
+    fn malformed_expressions() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let a: Column<Advice> = meta.advice_column();
+        meta.create_gate("gate", |meta| vec![meta.query_advice(a, Rotation::cur())]);
+
+        let read = |bytes: &[u8]| {
+            meta.read_expression(&mut &bytes[..], SerdeFormat::RawBytes, 0)
+                .map_err(|e| e.kind())
+        };
+        assert!(read(&[2, 0, 0, 0, 0]).is_ok());
+        // Unknown tag
+        assert_eq!(read(&[9]).unwrap_err(), io::ErrorKind::InvalidData);
+        // Query index out of bounds
+        assert_eq!(
+            read(&[2, 0, 0, 0, 1]).unwrap_err(),
+            io::ErrorKind::InvalidData
+        );
+        // Challenge that does not exist
+        assert_eq!(
+            read(&[4, 0, 0, 0, 0]).unwrap_err(),
+            io::ErrorKind::InvalidData
+        );
+        // Truncated operand
+        assert_eq!(
+            read(&[6, 2, 0, 0, 0, 0]).unwrap_err(),
+            io::ErrorKind::UnexpectedEof
+        );
+        // Nesting beyond the limit is rejected before the operand is read
+        let mut nested = vec![5; MAX_EXPRESSION_DEPTH];
+        nested.extend([2, 0, 0, 0, 0]);
+        assert_eq!(read(&nested).unwrap_err(), io::ErrorKind::InvalidData);
+        assert!(read(&nested[1..]).is_ok());
+
+        // Writing does not bound the depth, but reading the result back does
+        let mut expression = meta
+            .read_expression(&mut &[2, 0, 0, 0, 0][..], SerdeFormat::RawBytes, 0)
+            .unwrap();
+        for _ in 0..MAX_EXPRESSION_DEPTH {
+            expression = Expression::Negated(Box::new(expression));
+        }
+        let mut bytes = vec![];
+        ConstraintSystem::write_expression(&expression, &mut bytes, SerdeFormat::RawBytes).unwrap();
+        assert_eq!(bytes, nested);
+        assert_eq!(read(&bytes).unwrap_err(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn malformed_constraint_system() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let a: Column<Advice> = meta.advice_column();
+        meta.create_gate("gate", |meta| vec![meta.query_advice(a, Rotation::cur())]);
+        let mut bytes = vec![];
+        meta.write(&mut bytes, SerdeFormat::RawBytes).unwrap();
+
+        let read = |bytes: &[u8]| {
+            ConstraintSystem::<Fp>::read(&mut &bytes[..], SerdeFormat::RawBytes)
+                .map_err(|e| e.kind())
+        };
+        assert!(read(&bytes).is_ok());
+        for len in 0..bytes.len() {
+            assert_eq!(
+                read(&bytes[..len]).unwrap_err(),
+                io::ErrorKind::UnexpectedEof
+            );
+        }
+        // A huge number of advice columns without matching phases
+        let mut huge = bytes.clone();
+        huge[4..8].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(read(&huge).unwrap_err(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,758 @@
+//! Versioned, self-describing envelopes for keys and proofs.
+//!
+//! The binary layouts written by [`VerifyingKey::write`] and
+//! [`ProvingKey::write`] carry no information about the circuit or proof
+//! system they were created for, so a stale key is at best rejected with an
+//! opaque parse error and at worst silently misparsed. An [`Envelope`] wraps
+//! such a payload together with an [`EnvelopeHeader`] describing it, and the
+//! header is checked against the expected configuration before the payload is
+//! parsed.
+
+use std::fmt;
+use std::io;
+
+use blake2b_simd::Params as Blake2bParams;
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+
+use super::{Circuit, ConstraintSystem, Error, ProvingKey, VerifyingKey};
+use crate::arithmetic::CurveAffine;
+use crate::helpers::{SerdeCurveAffine, SerdePrimeField};
+use crate::SerdeFormat;
+
+/// The version of the envelope format written by this crate.
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// The commitment scheme a key or proof was created for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommitmentSchemeKind {
+    /// The inner product argument commitment scheme.
+    Ipa,
+    /// The KZG commitment scheme with the GWC multi-opening argument.
+    KzgGwc,
+    /// The KZG commitment scheme with the SHPLONK multi-opening argument.
+    KzgShplonk,
+}
+
+/// The transcript a key or proof was created for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TranscriptKind {
+    /// [`Blake2bWrite`](crate::transcript::Blake2bWrite) and
+    /// [`Blake2bRead`](crate::transcript::Blake2bRead).
+    Blake2b,
+    /// [`Keccak256Write`](crate::transcript::Keccak256Write) and
+    /// [`Keccak256Read`](crate::transcript::Keccak256Read).
+    Keccak256,
+}
+
+/// The kind of payload carried by an [`Envelope`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PayloadKind {
+    /// A verifying key written with the given format.
+    VerifyingKey(SerdeFormat),
+    /// A proving key written with the given format.
+    ProvingKey(SerdeFormat),
+    /// A proof.
+    Proof,
+}
+
+/// The header of an [`Envelope`], describing its payload.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvelopeHeader {
+    /// The envelope format version.
+    pub version: u32,
+    /// The modulus of the base field of the curve.
+    pub base_modulus: String,
+    /// The modulus of the scalar field of the curve.
+    pub scalar_modulus: String,
+    /// The size parameter of the circuit.
+    pub k: u32,
+    /// The commitment scheme.
+    pub scheme: CommitmentSchemeKind,
+    /// The transcript.
+    pub transcript: TranscriptKind,
+    /// A digest of the circuit's constraint system.
+    pub cs_digest: [u8; 32],
+    /// The kind of the payload.
+    pub payload: PayloadKind,
+}
+
+/// A key or proof together with a header describing it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope {
+    /// The header describing the payload.
+    pub header: EnvelopeHeader,
+    /// The serialized key or proof.
+    pub payload: Vec<u8>,
+}
+
+/// An error that occurred while opening an [`Envelope`].
+#[derive(Debug)]
+pub enum EnvelopeError {
+    /// The envelope was written with an unsupported format version.
+    UnsupportedVersion {
+        /// The version found in the envelope.
+        found: u32,
+    },
+    /// The envelope was created for a different curve.
+    CurveMismatch {
+        /// The base and scalar field moduli found in the envelope.
+        found: (String, String),
+        /// The base and scalar field moduli of the expected curve.
+        expected: (String, String),
+    },
+    /// The envelope was created for a different commitment scheme.
+    SchemeMismatch {
+        /// The commitment scheme found in the envelope.
+        found: CommitmentSchemeKind,
+        /// The expected commitment scheme.
+        expected: CommitmentSchemeKind,
+    },
+    /// The envelope was created for a different transcript.
+    TranscriptMismatch {
+        /// The transcript found in the envelope.
+        found: TranscriptKind,
+        /// The expected transcript.
+        expected: TranscriptKind,
+    },
+    /// The envelope does not carry the expected kind of payload.
+    PayloadMismatch {
+        /// The payload kind found in the envelope.
+        found: PayloadKind,
+    },
+    /// The envelope was created for a circuit of a different size.
+    KMismatch {
+        /// The value of `k` found in the envelope.
+        found: u32,
+        /// The expected value of `k`.
+        expected: u32,
+    },
+    /// The envelope was created for a different constraint system, for example
+    /// because the circuit has changed since the key was generated.
+    ConstraintSystemMismatch,
+    /// The payload could not be parsed.
+    Payload(Error),
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::UnsupportedVersion { found } => write!(
+                f,
+                "Unsupported envelope version {} (expected {})",
+                found, ENVELOPE_VERSION
+            ),
+            EnvelopeError::CurveMismatch { found, expected } => write!(
+                f,
+                "Envelope is for the curve with moduli {:?}, expected {:?}",
+                found, expected
+            ),
+            EnvelopeError::SchemeMismatch { found, expected } => write!(
+                f,
+                "Envelope is for the {:?} commitment scheme, expected {:?}",
+                found, expected
+            ),
+            EnvelopeError::TranscriptMismatch { found, expected } => write!(
+                f,
+                "Envelope is for the {:?} transcript, expected {:?}",
+                found, expected
+            ),
+            EnvelopeError::PayloadMismatch { found } => {
+                write!(f, "Envelope carries an unexpected payload {:?}", found)
+            }
+            EnvelopeError::KMismatch { found, expected } => write!(
+                f,
+                "Envelope is for a circuit with k = {}, expected k = {}",
+                found, expected
+            ),
+            EnvelopeError::ConstraintSystemMismatch => write!(
+                f,
+                "Envelope is for a different constraint system; the key is stale"
+            ),
+            EnvelopeError::Payload(e) => write!(f, "Envelope payload is invalid: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for EnvelopeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EnvelopeError::Payload(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Computes a digest of the pinned representation of a constraint system.
+fn cs_digest<F: PrimeField>(cs: &ConstraintSystem<F>) -> [u8; 32] {
+    let mut hasher = Blake2bParams::new()
+        .hash_length(32)
+        .personal(b"Halo2-CS-Digest")
+        .to_state();
+
+    let s = format!("{:?}", cs.pinned());
+
+    hasher.update(&(s.len() as u64).to_le_bytes());
+    hasher.update(s.as_bytes());
+
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(hasher.finalize().as_bytes());
+    digest
+}
+
+impl EnvelopeHeader {
+    fn new<C: CurveAffine>(
+        vk: &VerifyingKey<C>,
+        scheme: CommitmentSchemeKind,
+        transcript: TranscriptKind,
+        payload: PayloadKind,
+    ) -> Self {
+        EnvelopeHeader {
+            version: ENVELOPE_VERSION,
+            base_modulus: C::Base::MODULUS.to_string(),
+            scalar_modulus: C::Scalar::MODULUS.to_string(),
+            k: vk.domain.k(),
+            scheme,
+            transcript,
+            cs_digest: cs_digest(&vk.cs),
+            payload,
+        }
+    }
+
+    /// Checks the parts of the header that do not depend on the payload.
+    fn check<C: CurveAffine>(
+        &self,
+        scheme: CommitmentSchemeKind,
+        transcript: TranscriptKind,
+    ) -> Result<(), EnvelopeError> {
+        if self.version != ENVELOPE_VERSION {
+            return Err(EnvelopeError::UnsupportedVersion {
+                found: self.version,
+            });
+        }
+
+        let expected = (C::Base::MODULUS.to_string(), C::Scalar::MODULUS.to_string());
+        if (&self.base_modulus, &self.scalar_modulus) != (&expected.0, &expected.1) {
+            return Err(EnvelopeError::CurveMismatch {
+                found: (self.base_modulus.clone(), self.scalar_modulus.clone()),
+                expected,
+            });
+        }
+
+        if self.scheme != scheme {
+            return Err(EnvelopeError::SchemeMismatch {
+                found: self.scheme,
+                expected: scheme,
+            });
+        }
+
+        if self.transcript != transcript {
+            return Err(EnvelopeError::TranscriptMismatch {
+                found: self.transcript,
+                expected: transcript,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the header describes the circuit of the given verifying key.
+    fn check_circuit<C: CurveAffine>(&self, vk: &VerifyingKey<C>) -> Result<(), EnvelopeError> {
+        if self.k != vk.domain.k() {
+            return Err(EnvelopeError::KMismatch {
+                found: self.k,
+                expected: vk.domain.k(),
+            });
+        }
+
+        if self.cs_digest != cs_digest(&vk.cs) {
+            return Err(EnvelopeError::ConstraintSystemMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+impl Envelope {
+    /// Wraps a proof created with the given verifying key's circuit.
+    pub fn for_proof<C: CurveAffine>(
+        vk: &VerifyingKey<C>,
+        scheme: CommitmentSchemeKind,
+        transcript: TranscriptKind,
+        proof: Vec<u8>,
+    ) -> Self {
+        Envelope {
+            header: EnvelopeHeader::new(vk, scheme, transcript, PayloadKind::Proof),
+            payload: proof,
+        }
+    }
+
+    /// Checks that this envelope carries a proof for the given verifying key,
+    /// commitment scheme and transcript, and returns the proof.
+    pub fn open_proof<C: CurveAffine>(
+        &self,
+        vk: &VerifyingKey<C>,
+        scheme: CommitmentSchemeKind,
+        transcript: TranscriptKind,
+    ) -> Result<&[u8], EnvelopeError> {
+        self.header.check::<C>(scheme, transcript)?;
+        if self.header.payload != PayloadKind::Proof {
+            return Err(EnvelopeError::PayloadMismatch {
+                found: self.header.payload,
+            });
+        }
+        self.header.check_circuit(vk)?;
+
+        Ok(&self.payload)
+    }
+}
+
+impl<C: SerdeCurveAffine> VerifyingKey<C>
+where
+    C::Scalar: SerdePrimeField,
+{
+    /// Wraps this verifying key into an [`Envelope`], writing it with the
+    /// given format. **This requires the `serde` crate feature to be enabled.**
+    pub fn to_envelope(
+        &self,
+        scheme: CommitmentSchemeKind,
+        transcript: TranscriptKind,
+        format: SerdeFormat,
+    ) -> Envelope {
+        Envelope {
+            header: EnvelopeHeader::new(
+                self,
+                scheme,
+                transcript,
+                PayloadKind::VerifyingKey(format),
+            ),
+            payload: self.to_bytes(format),
+        }
+    }
+
+    /// Reads a verifying key from an [`Envelope`], checking that it was
+    /// created for `ConcreteCircuit` with the given commitment scheme and
+    /// transcript. **This requires the `serde` crate feature to be enabled.**
+    pub fn from_envelope<ConcreteCircuit: Circuit<C::Scalar>>(
+        envelope: &Envelope,
+        scheme: CommitmentSchemeKind,
+        transcript: TranscriptKind,
+    ) -> Result<Self, EnvelopeError> {
+        envelope.header.check::<C>(scheme, transcript)?;
+        let format = match envelope.header.payload {
+            PayloadKind::VerifyingKey(format) => format,
+            found => return Err(EnvelopeError::PayloadMismatch { found }),
+        };
+        check_payload_k(
+            &envelope.header,
+            &envelope.payload,
+            Error::VerifyingKeySerialization,
+        )?;
+
+        let vk = Self::from_bytes::<ConcreteCircuit>(&envelope.payload, format)
+            .map_err(EnvelopeError::Payload)?;
+        envelope.header.check_circuit(&vk)?;
+
+        Ok(vk)
+    }
+}
+
+impl<C: SerdeCurveAffine> ProvingKey<C>
+where
+    C::Scalar: SerdePrimeField,
+{
+    /// Wraps this proving key into an [`Envelope`], writing it with the given
+    /// format. **This requires the `serde` crate feature to be enabled.**
+    pub fn to_envelope(
+        &self,
+        scheme: CommitmentSchemeKind,
+        transcript: TranscriptKind,
+        format: SerdeFormat,
+    ) -> Envelope {
+        Envelope {
+            header: EnvelopeHeader::new(
+                &self.vk,
+                scheme,
+                transcript,
+                PayloadKind::ProvingKey(format),
+            ),
+            payload: self.to_bytes(format),
+        }
+    }
+
+    /// Reads a proving key from an [`Envelope`], checking that it was created
+    /// for `ConcreteCircuit` with the given commitment scheme and transcript.
+    /// **This requires the `serde` crate feature to be enabled.**
+    pub fn from_envelope<ConcreteCircuit: Circuit<C::Scalar>>(
+        envelope: &Envelope,
+        scheme: CommitmentSchemeKind,
+        transcript: TranscriptKind,
+    ) -> Result<Self, EnvelopeError> {
+        envelope.header.check::<C>(scheme, transcript)?;
+        let format = match envelope.header.payload {
+            PayloadKind::ProvingKey(format) => format,
+            found => return Err(EnvelopeError::PayloadMismatch { found }),
+        };
+        check_payload_k(
+            &envelope.header,
+            &envelope.payload,
+            Error::ProvingKeySerialization,
+        )?;
+
+        let pk = Self::from_bytes::<ConcreteCircuit>(&envelope.payload, format)
+            .map_err(EnvelopeError::Payload)?;
+        envelope.header.check_circuit(&pk.vk)?;
+
+        Ok(pk)
+    }
+}
+
+/// Checks that a serialized key starts with the `k` recorded in the header,
+/// before the key is parsed and the circuit is configured for that `k`.
+fn check_payload_k(
+    header: &EnvelopeHeader,
+    payload: &[u8],
+    serialization_error: fn(io::Error) -> Error,
+) -> Result<(), EnvelopeError> {
+    let k = payload
+        .get(..4)
+        .map(|k| u32::from_be_bytes(k.try_into().unwrap()))
+        .ok_or_else(|| {
+            EnvelopeError::Payload(serialization_error(io::ErrorKind::UnexpectedEof.into()))
+        })?;
+    if k != header.k {
+        return Err(EnvelopeError::KMismatch {
+            found: k,
+            expected: header.k,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::{EqAffine, Fp};
+
+    use assert_matches::assert_matches;
+    use rand_core::OsRng;
+
+    use super::{
+        CommitmentSchemeKind, Envelope, EnvelopeError, PayloadKind, TranscriptKind,
+        ENVELOPE_VERSION,
+    };
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{
+            create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
+            ConstraintSystem, Error, ProvingKey, VerifyingKey,
+        },
+        poly::{
+            commitment::ParamsProver,
+            ipa::{
+                commitment::{IPACommitmentScheme, ParamsIPA},
+                multiopen::{ProverIPA, VerifierIPA},
+                strategy::SingleStrategy,
+            },
+            Rotation,
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+        SerdeFormat,
+    };
+
+    /// Sends an envelope through bincode, as it would be stored or transmitted.
+    fn round_trip(envelope: &Envelope) -> Envelope {
+        let bytes = bincode::serialize(envelope).unwrap();
+        bincode::deserialize(&bytes).unwrap()
+    }
+
+    #[derive(Default)]
+    struct MyCircuit<const GATES: usize>;
+
+    impl<const GATES: usize> Circuit<Fp> for MyCircuit<GATES> {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            for _ in 0..GATES {
+                meta.create_gate("a is zero", |meta| {
+                    vec![meta.query_advice(a, Rotation::cur())]
+                });
+            }
+            a
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "a",
+                |mut region| region.assign_advice(|| "a", config, 0, || Value::known(Fp::zero())),
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn envelope_rejects_stale_keys() {
+        let params = ParamsIPA::<EqAffine>::new(3);
+        let vk = keygen_vk(&params, &MyCircuit::<1>).unwrap();
+
+        let envelope = vk.to_envelope(
+            CommitmentSchemeKind::Ipa,
+            TranscriptKind::Blake2b,
+            SerdeFormat::Processed,
+        );
+
+        // The key can be read back for the circuit it was created for.
+        let read = VerifyingKey::<EqAffine>::from_envelope::<MyCircuit<1>>(
+            &envelope,
+            CommitmentSchemeKind::Ipa,
+            TranscriptKind::Blake2b,
+        )
+        .unwrap();
+        assert_eq!(format!("{:?}", read.pinned()), format!("{:?}", vk.pinned()));
+
+        // It is rejected for a different configuration...
+        assert!(matches!(
+            VerifyingKey::<EqAffine>::from_envelope::<MyCircuit<1>>(
+                &envelope,
+                CommitmentSchemeKind::KzgShplonk,
+                TranscriptKind::Blake2b,
+            ),
+            Err(EnvelopeError::SchemeMismatch { .. })
+        ));
+
+        // ...and for a circuit that has changed since the key was created.
+        assert!(matches!(
+            VerifyingKey::<EqAffine>::from_envelope::<MyCircuit<2>>(
+                &envelope,
+                CommitmentSchemeKind::Ipa,
+                TranscriptKind::Blake2b,
+            ),
+            Err(EnvelopeError::ConstraintSystemMismatch)
+        ));
+
+        // A stale version is rejected before the payload is parsed.
+        let mut stale = envelope;
+        stale.header.version += 1;
+        assert!(matches!(
+            VerifyingKey::<EqAffine>::from_envelope::<MyCircuit<1>>(
+                &stale,
+                CommitmentSchemeKind::Ipa,
+                TranscriptKind::Blake2b,
+            ),
+            Err(EnvelopeError::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn verifying_key_envelope_round_trip() {
+        let params = ParamsIPA::<EqAffine>::new(3);
+        let vk = keygen_vk(&params, &MyCircuit::<1>).unwrap();
+
+        for format in [
+            SerdeFormat::Processed,
+            SerdeFormat::RawBytes,
+            SerdeFormat::RawBytesUnchecked,
+        ] {
+            let envelope = round_trip(&vk.to_envelope(
+                CommitmentSchemeKind::Ipa,
+                TranscriptKind::Blake2b,
+                format,
+            ));
+            assert_eq!(envelope.header.payload, PayloadKind::VerifyingKey(format));
+
+            let read = VerifyingKey::<EqAffine>::from_envelope::<MyCircuit<1>>(
+                &envelope,
+                CommitmentSchemeKind::Ipa,
+                TranscriptKind::Blake2b,
+            )
+            .unwrap();
+            assert_eq!(format!("{:?}", read.pinned()), format!("{:?}", vk.pinned()));
+        }
+
+        let envelope = vk.to_envelope(
+            CommitmentSchemeKind::Ipa,
+            TranscriptKind::Blake2b,
+            SerdeFormat::Processed,
+        );
+
+        // A truncated serialization is rejected by the serde format itself.
+        let bytes = bincode::serialize(&envelope).unwrap();
+        assert!(bincode::deserialize::<Envelope>(&bytes[..bytes.len() - 1]).is_err());
+
+        // A newer version survives the round trip, and is then rejected.
+        let mut newer = envelope.clone();
+        newer.header.version = ENVELOPE_VERSION + 1;
+        assert_matches!(
+            VerifyingKey::<EqAffine>::from_envelope::<MyCircuit<1>>(
+                &round_trip(&newer),
+                CommitmentSchemeKind::Ipa,
+                TranscriptKind::Blake2b,
+            ),
+            Err(EnvelopeError::UnsupportedVersion { found }) if found == ENVELOPE_VERSION + 1
+        );
+
+        // A payload that disagrees with its header about k is rejected before it
+        // is parsed.
+        let mut corrupted = envelope.clone();
+        corrupted.payload[3] ^= 1;
+        assert_matches!(
+            VerifyingKey::<EqAffine>::from_envelope::<MyCircuit<1>>(
+                &round_trip(&corrupted),
+                CommitmentSchemeKind::Ipa,
+                TranscriptKind::Blake2b,
+            ),
+            Err(EnvelopeError::KMismatch {
+                found: 2,
+                expected: 3
+            })
+        );
+
+        // A truncated payload fails to parse.
+        let mut corrupted = envelope;
+        corrupted.payload.pop();
+        assert_matches!(
+            VerifyingKey::<EqAffine>::from_envelope::<MyCircuit<1>>(
+                &round_trip(&corrupted),
+                CommitmentSchemeKind::Ipa,
+                TranscriptKind::Blake2b,
+            ),
+            Err(EnvelopeError::Payload(Error::VerifyingKeySerialization(_)))
+        );
+    }
+
+    #[test]
+    fn proving_key_envelope_round_trip() {
+        let params = ParamsIPA::<EqAffine>::new(3);
+        let vk = keygen_vk(&params, &MyCircuit::<1>).unwrap();
+        let pk = keygen_pk(&params, vk, &MyCircuit::<1>).unwrap();
+
+        let envelope = round_trip(&pk.to_envelope(
+            CommitmentSchemeKind::Ipa,
+            TranscriptKind::Blake2b,
+            SerdeFormat::RawBytes,
+        ));
+        let read = ProvingKey::<EqAffine>::from_envelope::<MyCircuit<1>>(
+            &envelope,
+            CommitmentSchemeKind::Ipa,
+            TranscriptKind::Blake2b,
+        )
+        .unwrap();
+        assert_eq!(read.to_bytes(SerdeFormat::RawBytes), envelope.payload);
+
+        // A proving key cannot be read as a verifying key.
+        assert_matches!(
+            VerifyingKey::<EqAffine>::from_envelope::<MyCircuit<1>>(
+                &envelope,
+                CommitmentSchemeKind::Ipa,
+                TranscriptKind::Blake2b,
+            ),
+            Err(EnvelopeError::PayloadMismatch {
+                found: PayloadKind::ProvingKey(SerdeFormat::RawBytes)
+            })
+        );
+
+        // A truncated payload fails to parse.
+        let mut corrupted = envelope;
+        corrupted.payload.truncate(corrupted.payload.len() / 2);
+        assert_matches!(
+            ProvingKey::<EqAffine>::from_envelope::<MyCircuit<1>>(
+                &round_trip(&corrupted),
+                CommitmentSchemeKind::Ipa,
+                TranscriptKind::Blake2b,
+            ),
+            Err(EnvelopeError::Payload(Error::ProvingKeySerialization(_)))
+        );
+    }
+
+    #[test]
+    fn proof_envelope_round_trip() {
+        let params = ParamsIPA::<EqAffine>::new(3);
+        let vk = keygen_vk(&params, &MyCircuit::<1>).unwrap();
+        let pk = keygen_pk(&params, vk, &MyCircuit::<1>).unwrap();
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof::<IPACommitmentScheme<_>, ProverIPA<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &[MyCircuit::<1>],
+            &[&[]],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+
+        let envelope = round_trip(&Envelope::for_proof(
+            pk.get_vk(),
+            CommitmentSchemeKind::Ipa,
+            TranscriptKind::Blake2b,
+            proof.clone(),
+        ));
+        let opened = envelope
+            .open_proof(
+                pk.get_vk(),
+                CommitmentSchemeKind::Ipa,
+                TranscriptKind::Blake2b,
+            )
+            .unwrap()
+            .to_vec();
+        assert_eq!(opened, proof);
+
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&opened[..]);
+        assert!(
+            verify_proof::<IPACommitmentScheme<_>, VerifierIPA<_>, _, _, _>(
+                &params,
+                pk.get_vk(),
+                SingleStrategy::new(&params),
+                &[&[]],
+                &mut transcript,
+            )
+            .is_ok()
+        );
+
+        // The proof is rejected for another transcript or circuit.
+        assert_matches!(
+            envelope.open_proof(
+                pk.get_vk(),
+                CommitmentSchemeKind::Ipa,
+                TranscriptKind::Keccak256,
+            ),
+            Err(EnvelopeError::TranscriptMismatch {
+                found: TranscriptKind::Blake2b,
+                expected: TranscriptKind::Keccak256,
+            })
+        );
+        let other_vk = keygen_vk(&params, &MyCircuit::<2>).unwrap();
+        assert_matches!(
+            envelope.open_proof(
+                &other_vk,
+                CommitmentSchemeKind::Ipa,
+                TranscriptKind::Blake2b,
+            ),
+            Err(EnvelopeError::ConstraintSystemMismatch)
+        );
+
+        // A newer version is rejected.
+        let mut newer = envelope;
+        newer.header.version = ENVELOPE_VERSION + 1;
+        assert_matches!(
+            round_trip(&newer).open_proof(
+                pk.get_vk(),
+                CommitmentSchemeKind::Ipa,
+                TranscriptKind::Blake2b,
+            ),
+            Err(EnvelopeError::UnsupportedVersion { .. })
+        );
+    }
+}
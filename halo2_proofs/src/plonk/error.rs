@@ -5,23 +5,81 @@ use std::io;
 
 use super::{Any, Column};
 
+/// An argument of the proof system, used to report where a failure occurred.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgumentKind {
+    /// The vanishing argument, which enforces the custom gates.
+    Vanishing,
+    /// The permutation argument, which enforces the copy constraints.
+    Permutation,
+    /// The lookup argument with the given index.
+    Lookup(usize),
+    /// The LogUp lookup argument with the given index.
+    LogUp(usize),
+    /// The shuffle argument with the given index.
+    Shuffle(usize),
+    /// The multi-opening argument. As the verifier batches the checks of every
+    /// other argument into the multi-opening, failures of the final check are
+    /// reported against it.
+    Multiopen,
+}
+
+impl fmt::Display for ArgumentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgumentKind::Vanishing => write!(f, "vanishing argument"),
+            ArgumentKind::Permutation => write!(f, "permutation argument"),
+            ArgumentKind::Lookup(index) => write!(f, "lookup argument {}", index),
+            ArgumentKind::LogUp(index) => write!(f, "LogUp lookup argument {}", index),
+            ArgumentKind::Shuffle(index) => write!(f, "shuffle argument {}", index),
+            ArgumentKind::Multiopen => write!(f, "multi-opening argument"),
+        }
+    }
+}
+
 /// This is an error that could occur during proving or circuit synthesis.
-// TODO: these errors need to be cleaned up
 #[derive(Debug)]
 pub enum Error {
     /// This is an error that can occur during synthesis of the circuit, for
     /// example, when the witness is not present.
     Synthesis,
+    /// An error that occurred during synthesis of a specific cell.
+    SynthesisAt {
+        /// The name of the region the cell belongs to, if known.
+        region: Option<String>,
+        /// The column of the cell.
+        column: Column<Any>,
+        /// The absolute row of the cell.
+        row: usize,
+        /// A description of what went wrong.
+        reason: String,
+    },
     /// The provided instances do not match the circuit parameters.
     InvalidInstances,
-    /// The constraint system is not satisfied.
-    ConstraintSystemFailure,
+    /// The provided challenge values do not match the circuit's challenges.
+    InvalidChallenges,
+    /// The constraint system is not satisfied by the given argument.
+    ConstraintSystemFailure(ArgumentKind),
     /// Out of bounds index passed to a backend
     BoundsFailure,
-    /// Opening error
-    Opening,
+    /// The opening proof of the given argument was invalid.
+    Opening(ArgumentKind),
     /// Transcript error
     Transcript(io::Error),
+    /// The proof of an argument could not be read from the transcript.
+    ArgumentTranscript {
+        /// The argument whose proof was being read.
+        argument: ArgumentKind,
+        /// The error returned by the transcript.
+        source: io::Error,
+    },
+    /// An error occurred while reading or writing a verifying key.
+    VerifyingKeySerialization(io::Error),
+    /// An error occurred while reading or writing a proving key, or while loading
+    /// polynomials from its [`PolynomialStore`](super::PolynomialStore).
+    ProvingKeySerialization(io::Error),
+    /// An error occurred while reading or writing commitment scheme parameters.
+    ParamsSerialization(io::Error),
     /// `k` is too small for the given circuit.
     NotEnoughRowsAvailable {
         /// The current value of `k` being used.
@@ -41,7 +99,8 @@ pub enum Error {
 
 impl From<io::Error> for Error {
     fn from(error: io::Error) -> Self {
-        // The only place we can get io::Error from is the transcript.
+        // Key and parameter serialization map their errors explicitly, so any
+        // remaining io::Error comes from the transcript.
         Error::Transcript(error)
     }
 }
@@ -51,17 +110,55 @@ impl Error {
     pub(crate) fn not_enough_rows_available(current_k: u32) -> Self {
         Error::NotEnoughRowsAvailable { current_k }
     }
+
+    /// Attributes a transcript error, raised while the verifier was reading the
+    /// proof of `argument`, to that argument.
+    pub(crate) fn in_argument(self, argument: ArgumentKind) -> Self {
+        match self {
+            Error::Transcript(source) => Error::ArgumentTranscript { argument, source },
+            error => error,
+        }
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::Synthesis => write!(f, "General synthesis error"),
+            Error::SynthesisAt {
+                region,
+                column,
+                row,
+                reason,
+            } => {
+                write!(f, "Synthesis error at column {:?}, row {}", column, row)?;
+                if let Some(region) = region {
+                    write!(f, " in region \"{}\"", region)?;
+                }
+                write!(f, ": {}", reason)
+            }
             Error::InvalidInstances => write!(f, "Provided instances do not match the circuit"),
-            Error::ConstraintSystemFailure => write!(f, "The constraint system is not satisfied"),
+            Error::InvalidChallenges => write!(f, "Provided challenges do not match the circuit"),
+            Error::ConstraintSystemFailure(argument) => write!(
+                f,
+                "The constraint system is not satisfied by the {}",
+                argument
+            ),
             Error::BoundsFailure => write!(f, "An out-of-bounds index was passed to the backend"),
-            Error::Opening => write!(f, "Multi-opening proof was invalid"),
+            Error::Opening(argument) => write!(f, "Opening proof of the {} was invalid", argument),
             Error::Transcript(e) => write!(f, "Transcript error: {}", e),
+            Error::ArgumentTranscript { argument, source } => write!(
+                f,
+                "Transcript error while reading the proof of the {}: {}",
+                argument, source
+            ),
+            Error::VerifyingKeySerialization(e) => {
+                write!(f, "Verifying key serialization error: {}", e)
+            }
+            Error::ProvingKeySerialization(e) => {
+                write!(f, "Proving key serialization error: {}", e)
+            }
+            Error::ParamsSerialization(e) => write!(f, "Parameters serialization error: {}", e),
             Error::NotEnoughRowsAvailable { current_k } => write!(
                 f,
                 "k = {} is too small for the given circuit. Try using a larger value of k",
@@ -86,7 +183,11 @@ impl fmt::Display for Error {
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
-            Error::Transcript(e) => Some(e),
+            Error::Transcript(e)
+            | Error::VerifyingKeySerialization(e)
+            | Error::ProvingKeySerialization(e)
+            | Error::ParamsSerialization(e)
+            | Error::ArgumentTranscript { source: e, .. } => Some(e),
             _ => None,
         }
     }
@@ -1,7 +1,10 @@
 use crate::multicore;
 use crate::plonk::lookup::prover::Committed;
 use crate::plonk::permutation::Argument;
-use crate::plonk::{lookup, permutation, AdviceQuery, Any, FixedQuery, InstanceQuery, ProvingKey};
+use crate::plonk::{
+    logup, lookup, permutation, shuffle, AdviceQuery, Any, Error, FixedQuery, InstanceQuery,
+    ProvingKey,
+};
 use crate::poly::Basis;
 use crate::{
     arithmetic::{eval_polynomial, parallelize, CurveAffine, FieldExt},
@@ -21,7 +24,8 @@ use std::convert::TryInto;
 use std::num::ParseIntError;
 use std::slice;
 use std::{
-    collections::BTreeMap,
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
     iter,
     ops::{Index, Mul, MulAssign},
 };
@@ -33,6 +37,39 @@ fn get_rotation_idx(idx: usize, rot: i32, rot_scale: i32, isize: i32) -> usize {
     (((idx as i32) + (rot * rot_scale)).rem_euclid(isize)) as usize
 }
 
+/// Fixed columns in the extended domain, of which only some are loaded.
+type FixedCosets<'a, F> = Vec<Option<Cow<'a, Polynomial<F, ExtendedLagrangeCoeff>>>>;
+
+/// Loads the given fixed columns of the proving key in the extended domain,
+/// leaving the other columns unloaded.
+fn load_fixed_cosets<C: CurveAffine>(
+    pk: &ProvingKey<C>,
+    columns: impl IntoIterator<Item = usize>,
+) -> Result<FixedCosets<'_, C::Scalar>, Error> {
+    let mut cosets: FixedCosets<_> = iter::repeat_with(|| None)
+        .take(pk.vk.cs.num_fixed_columns)
+        .collect();
+    for column in columns {
+        if cosets[column].is_none() {
+            cosets[column] = Some(
+                pk.store
+                    .fixed_coset(column)
+                    .map_err(Error::ProvingKeySerialization)?,
+            );
+        }
+    }
+    Ok(cosets)
+}
+
+/// Returns the values of each fixed column, which are empty for the columns
+/// that are not loaded.
+fn fixed_slices<'a, F: Field>(cosets: &'a FixedCosets<'_, F>) -> Vec<&'a [F]> {
+    cosets
+        .iter()
+        .map(|coset| coset.as_deref().map_or(&[][..], |coset| &coset[..]))
+        .collect()
+}
+
 /// Value used in a calculation
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd)]
 pub enum ValueSource {
@@ -73,7 +110,7 @@ impl ValueSource {
         rotations: &[usize],
         constants: &[F],
         intermediates: &[F],
-        fixed_values: &[Polynomial<F, B>],
+        fixed_values: &[&[F]],
         advice_values: &[Polynomial<F, B>],
         instance_values: &[Polynomial<F, B>],
         challenges: &[F],
@@ -133,7 +170,7 @@ impl Calculation {
         rotations: &[usize],
         constants: &[F],
         intermediates: &[F],
-        fixed_values: &[Polynomial<F, B>],
+        fixed_values: &[&[F]],
         advice_values: &[Polynomial<F, B>],
         instance_values: &[Polynomial<F, B>],
         challenges: &[F],
@@ -186,6 +223,10 @@ pub struct Evaluator<C: CurveAffine> {
     pub custom_gates: GraphEvaluator<C>,
     ///  Lookups evalution
     pub lookups: Vec<GraphEvaluator<C>>,
+    ///  LogUp lookups evalution, as (inputs, table) graphs per argument
+    pub logups: Vec<(Vec<GraphEvaluator<C>>, GraphEvaluator<C>)>,
+    ///  Shuffles evalution, two graphs (input and shuffle) per argument
+    pub shuffles: Vec<GraphEvaluator<C>>,
 }
 
 /// GraphEvaluator
@@ -273,10 +314,60 @@ impl<C: CurveAffine> Evaluator<C> {
             ev.lookups.push(graph);
         }
 
+        // LogUp lookups
+        for logup in cs.logups.iter() {
+            let evaluate_lc = |expressions: &Vec<Expression<_>>| {
+                let mut graph = GraphEvaluator::default();
+                let parts = expressions
+                    .iter()
+                    .map(|expr| graph.add_expression(expr))
+                    .collect();
+                let compressed = graph.add_calculation(Calculation::Horner(
+                    ValueSource::Constant(0),
+                    parts,
+                    ValueSource::Theta(),
+                ));
+                // \theta^{m-1} e_0(X) + ... + e_{m-1}(X) + \beta
+                graph.add_calculation(Calculation::Add(compressed, ValueSource::Beta()));
+                graph
+            };
+
+            ev.logups.push((
+                logup.input_expressions.iter().map(&evaluate_lc).collect(),
+                evaluate_lc(&logup.table_expressions),
+            ));
+        }
+
+        // Shuffles
+        for shuffle in cs.shuffles.iter() {
+            let evaluate_lc = |expressions: &Vec<Expression<_>>| {
+                let mut graph = GraphEvaluator::default();
+                let parts = expressions
+                    .iter()
+                    .map(|expr| graph.add_expression(expr))
+                    .collect();
+                let compressed = graph.add_calculation(Calculation::Horner(
+                    ValueSource::Constant(0),
+                    parts,
+                    ValueSource::Theta(),
+                ));
+                // \theta^{m-1} e_0(X) + ... + e_{m-1}(X) + \gamma
+                graph.add_calculation(Calculation::Add(compressed, ValueSource::Gamma()));
+                graph
+            };
+
+            ev.shuffles.push(evaluate_lc(&shuffle.input_expressions));
+            ev.shuffles.push(evaluate_lc(&shuffle.shuffle_expressions));
+        }
+
         ev
     }
 
     /// Evaluate h poly
+    ///
+    /// The fixed and permutation columns, in the extended domain, are loaded
+    /// from the proving key's store separately for the custom gates and for
+    /// each argument, and only the columns that they query are loaded.
     pub(in crate::plonk) fn evaluate_h(
         &self,
         pk: &ProvingKey<C>,
@@ -288,18 +379,16 @@ impl<C: CurveAffine> Evaluator<C> {
         gamma: C::ScalarExt,
         theta: C::ScalarExt,
         lookups: &[Vec<lookup::prover::Committed<C>>],
+        logups: &[Vec<logup::prover::Committed<C>>],
+        shuffles: &[Vec<shuffle::prover::Committed<C>>],
         permutations: &[permutation::prover::Committed<C>],
-    ) -> Polynomial<C::ScalarExt, ExtendedLagrangeCoeff> {
+    ) -> Result<Polynomial<C::ScalarExt, ExtendedLagrangeCoeff>, Error> {
         let domain = &pk.vk.domain;
         let size = domain.extended_len();
         let rot_scale = 1 << (domain.extended_k() - domain.k());
-        let fixed = &pk.fixed_cosets[..];
         let extended_omega = domain.get_extended_omega();
         let isize = size as i32;
         let one = C::ScalarExt::one();
-        let l0 = &pk.l0;
-        let l_last = &pk.l_last;
-        let l_active_row = &pk.l_active_row;
         let p = &pk.vk.cs.permutation;
 
         // Calculate the advice and instance cosets
@@ -326,40 +415,55 @@ impl<C: CurveAffine> Evaluator<C> {
 
         // Core expression evaluations
         let num_threads = multicore::current_num_threads();
-        for (((advice, instance), lookups), permutation) in advice
+        for (((((advice, instance), lookups), logups), shuffles), permutation) in advice
             .iter()
             .zip(instance.iter())
             .zip(lookups.iter())
+            .zip(logups.iter())
+            .zip(shuffles.iter())
             .zip(permutations.iter())
         {
             // Custom gates
-            multicore::scope(|scope| {
-                let chunk_size = (size + num_threads - 1) / num_threads;
-                for (thread_idx, values) in values.chunks_mut(chunk_size).enumerate() {
-                    let start = thread_idx * chunk_size;
-                    scope.spawn(move |_| {
-                        let mut eval_data = self.custom_gates.instance();
-                        for (i, value) in values.iter_mut().enumerate() {
-                            let idx = start + i;
-                            *value = self.custom_gates.evaluate(
-                                &mut eval_data,
-                                fixed,
-                                advice,
-                                instance,
-                                challenges,
-                                &beta,
-                                &gamma,
-                                &theta,
-                                &y,
-                                value,
-                                idx,
-                                rot_scale,
-                                isize,
-                            );
-                        }
-                    });
-                }
-            });
+            {
+                let fixed_cosets = load_fixed_cosets(pk, self.custom_gates.fixed_columns())?;
+                let fixed = &fixed_slices(&fixed_cosets)[..];
+                multicore::scope(|scope| {
+                    let chunk_size = (size + num_threads - 1) / num_threads;
+                    for (thread_idx, values) in values.chunks_mut(chunk_size).enumerate() {
+                        let start = thread_idx * chunk_size;
+                        scope.spawn(move |_| {
+                            let mut eval_data = self.custom_gates.instance();
+                            for (i, value) in values.iter_mut().enumerate() {
+                                let idx = start + i;
+                                *value = self.custom_gates.evaluate(
+                                    &mut eval_data,
+                                    fixed,
+                                    advice,
+                                    instance,
+                                    challenges,
+                                    &beta,
+                                    &gamma,
+                                    &theta,
+                                    &y,
+                                    value,
+                                    idx,
+                                    rot_scale,
+                                    isize,
+                                );
+                            }
+                        });
+                    }
+                });
+            }
+
+            // The arguments below are all restricted to the usable rows.
+            let l0 = pk.store.l0().map_err(Error::ProvingKeySerialization)?;
+            let l_last = pk.store.l_last().map_err(Error::ProvingKeySerialization)?;
+            let l_active_row = pk
+                .store
+                .l_active_row()
+                .map_err(Error::ProvingKeySerialization)?;
+            let (l0, l_last, l_active_row) = (&*l0, &*l_last, &*l_active_row);
 
             // Permutations
             let sets = &permutation.sets;
@@ -374,10 +478,8 @@ impl<C: CurveAffine> Evaluator<C> {
 
                 // Permutation constraints
                 parallelize(&mut values, |values, start| {
-                    let mut beta_term = extended_omega.pow_vartime(&[start as u64, 0, 0, 0]);
                     for (i, value) in values.iter_mut().enumerate() {
                         let idx = start + i;
-                        let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
                         let r_last = get_rotation_idx(idx, last_rotation.0, rot_scale, isize);
 
                         // Enforce only for the first set.
@@ -402,49 +504,79 @@ impl<C: CurveAffine> Evaluator<C> {
                                         * l0[idx]);
                             }
                         }
-                        // And for all the sets we enforce:
-                        // (1 - (l_last(X) + l_blind(X))) * (
-                        //   z_i(\omega X) \prod_j (p(X) + \beta s_j(X) + \gamma)
-                        // - z_i(X) \prod_j (p(X) + \delta^j \beta X + \gamma)
-                        // )
-                        let mut current_delta = delta_start * beta_term;
-                        for ((set, columns), cosets) in sets
+                    }
+                });
+
+                // And for all the sets we enforce:
+                // (1 - (l_last(X) + l_blind(X))) * (
+                //   z_i(\omega X) \prod_j (p(X) + \beta s_j(X) + \gamma)
+                // - z_i(X) \prod_j (p(X) + \delta^j \beta X + \gamma)
+                // )
+                // Each set only queries its own columns, so they are loaded one
+                // set at a time.
+                let mut set_delta_start = delta_start;
+                for (set_idx, (set, columns)) in
+                    sets.iter().zip(p.columns.chunks(chunk_len)).enumerate()
+                {
+                    let fixed_cosets = load_fixed_cosets(
+                        pk,
+                        columns
                             .iter()
-                            .zip(p.columns.chunks(chunk_len))
-                            .zip(pk.permutation.cosets.chunks(chunk_len))
-                        {
+                            .filter(|column| matches!(column.column_type(), Any::Fixed))
+                            .map(|column| column.index()),
+                    )?;
+                    let fixed = fixed_slices(&fixed_cosets);
+                    let columns: Vec<&[C::Scalar]> = columns
+                        .iter()
+                        .map(|&column| match column.column_type() {
+                            Any::Advice(_) => &advice[column.index()][..],
+                            Any::Fixed => fixed[column.index()],
+                            Any::Instance => &instance[column.index()][..],
+                        })
+                        .collect();
+                    let permutation_cosets = (set_idx * chunk_len..)
+                        .take(columns.len())
+                        .map(|column| pk.store.permutation_coset(column))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(Error::ProvingKeySerialization)?;
+
+                    parallelize(&mut values, |values, start| {
+                        let mut beta_term = extended_omega.pow_vartime(&[start as u64, 0, 0, 0]);
+                        for (i, value) in values.iter_mut().enumerate() {
+                            let idx = start + i;
+                            let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
+
                             let mut left = set.permutation_product_coset[r_next];
-                            for (values, permutation) in columns
-                                .iter()
-                                .map(|&column| match column.column_type() {
-                                    Any::Advice(_) => &advice[column.index()],
-                                    Any::Fixed => &fixed[column.index()],
-                                    Any::Instance => &instance[column.index()],
-                                })
-                                .zip(cosets.iter())
+                            for (values, permutation) in
+                                columns.iter().zip(permutation_cosets.iter())
                             {
                                 left *= values[idx] + beta * permutation[idx] + gamma;
                             }
 
                             let mut right = set.permutation_product_coset[idx];
-                            for values in columns.iter().map(|&column| match column.column_type() {
-                                Any::Advice(_) => &advice[column.index()],
-                                Any::Fixed => &fixed[column.index()],
-                                Any::Instance => &instance[column.index()],
-                            }) {
+                            let mut current_delta = set_delta_start * beta_term;
+                            for values in columns.iter() {
                                 right *= values[idx] + current_delta + gamma;
                                 current_delta *= &C::Scalar::DELTA;
                             }
 
                             *value = *value * y + ((left - right) * l_active_row[idx]);
+                            beta_term *= &extended_omega;
                         }
-                        beta_term *= &extended_omega;
+                    });
+
+                    for _ in columns.iter() {
+                        set_delta_start *= &C::Scalar::DELTA;
                     }
-                });
+                }
             }
 
             // Lookups
             for (n, lookup) in lookups.iter().enumerate() {
+                let lookup_evaluator = &self.lookups[n];
+                let fixed_cosets = load_fixed_cosets(pk, lookup_evaluator.fixed_columns())?;
+                let fixed = &fixed_slices(&fixed_cosets)[..];
+
                 // Polynomials required for this lookup.
                 // Calculated here so these only have to be kept in memory for the short time
                 // they are actually needed.
@@ -460,7 +592,6 @@ impl<C: CurveAffine> Evaluator<C> {
 
                 // Lookup constraints
                 parallelize(&mut values, |values, start| {
-                    let lookup_evaluator = &self.lookups[n];
                     let mut eval_data = lookup_evaluator.instance();
                     for (i, value) in values.iter_mut().enumerate() {
                         let idx = start + i;
@@ -517,8 +648,192 @@ impl<C: CurveAffine> Evaluator<C> {
                     }
                 });
             }
+
+            // LogUp lookups
+            for (n, logup) in logups.iter().enumerate() {
+                let (input_evaluators, table_evaluator) = &self.logups[n];
+                let fixed_cosets = load_fixed_cosets(
+                    pk,
+                    input_evaluators
+                        .iter()
+                        .chain(iter::once(table_evaluator))
+                        .flat_map(GraphEvaluator::fixed_columns),
+                )?;
+                let fixed = &fixed_slices(&fixed_cosets)[..];
+
+                // Polynomials required for this LogUp lookup.
+                // Calculated here so these only have to be kept in memory for the short time
+                // they are actually needed.
+                let multiplicity_coset = pk
+                    .vk
+                    .domain
+                    .coeff_to_extended(logup.multiplicity_poly.clone());
+                let running_sum_coset = pk
+                    .vk
+                    .domain
+                    .coeff_to_extended(logup.running_sum_poly.clone());
+
+                // LogUp lookup constraints
+                parallelize(&mut values, |values, start| {
+                    let mut input_eval_data: Vec<_> = input_evaluators
+                        .iter()
+                        .map(|input_evaluator| input_evaluator.instance())
+                        .collect();
+                    let mut table_eval_data = table_evaluator.instance();
+                    for (i, value) in values.iter_mut().enumerate() {
+                        let idx = start + i;
+
+                        // f_j(X) + \beta
+                        let input_values: Vec<_> = input_evaluators
+                            .iter()
+                            .zip(input_eval_data.iter_mut())
+                            .map(|(input_evaluator, eval_data)| {
+                                input_evaluator.evaluate(
+                                    eval_data,
+                                    fixed,
+                                    advice,
+                                    instance,
+                                    challenges,
+                                    &beta,
+                                    &gamma,
+                                    &theta,
+                                    &y,
+                                    &C::ScalarExt::zero(),
+                                    idx,
+                                    rot_scale,
+                                    isize,
+                                )
+                            })
+                            .collect();
+
+                        // t(X) + \beta
+                        let table_value = table_evaluator.evaluate(
+                            &mut table_eval_data,
+                            fixed,
+                            advice,
+                            instance,
+                            challenges,
+                            &beta,
+                            &gamma,
+                            &theta,
+                            &y,
+                            &C::ScalarExt::zero(),
+                            idx,
+                            rot_scale,
+                            isize,
+                        );
+
+                        let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
+
+                        // \prod_j (f_j(X) + \beta)
+                        let inputs_product = input_values
+                            .iter()
+                            .fold(one, |acc, input_value| acc * input_value);
+                        // \sum_j \prod_{k \neq j} (f_k(X) + \beta)
+                        let inputs_sum = (0..input_values.len())
+                            .map(|j| {
+                                input_values
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(k, _)| *k != j)
+                                    .fold(one, |acc, (_, input_value)| acc * input_value)
+                            })
+                            .fold(C::ScalarExt::zero(), |acc, term| acc + term);
+
+                        // l_0(X) * \phi(X) = 0
+                        *value = *value * y + (running_sum_coset[idx] * l0[idx]);
+                        // l_last(X) * \phi(X) = 0
+                        *value = *value * y + (running_sum_coset[idx] * l_last[idx]);
+                        // (1 - (l_last(X) + l_blind(X))) * (
+                        //   (\phi(\omega X) - \phi(X)) \prod_j (f_j(X) + \beta) (t(X) + \beta)
+                        //   - (t(X) + \beta) \sum_j \prod_{k \neq j} (f_k(X) + \beta)
+                        //   + m(X) \prod_j (f_j(X) + \beta)
+                        // ) = 0
+                        *value = *value * y
+                            + (((running_sum_coset[r_next] - running_sum_coset[idx])
+                                * inputs_product
+                                * table_value
+                                - table_value * inputs_sum
+                                + multiplicity_coset[idx] * inputs_product)
+                                * l_active_row[idx]);
+                    }
+                });
+            }
+
+            // Shuffles
+            for (n, shuffle) in shuffles.iter().enumerate() {
+                let input_evaluator = &self.shuffles[2 * n];
+                let shuffle_evaluator = &self.shuffles[2 * n + 1];
+                let fixed_cosets = load_fixed_cosets(
+                    pk,
+                    input_evaluator
+                        .fixed_columns()
+                        .into_iter()
+                        .chain(shuffle_evaluator.fixed_columns()),
+                )?;
+                let fixed = &fixed_slices(&fixed_cosets)[..];
+
+                let product_coset = pk.vk.domain.coeff_to_extended(shuffle.product_poly.clone());
+
+                // Shuffle constraints
+                parallelize(&mut values, |values, start| {
+                    let mut eval_data_input = input_evaluator.instance();
+                    let mut eval_data_shuffle = shuffle_evaluator.instance();
+                    for (i, value) in values.iter_mut().enumerate() {
+                        let idx = start + i;
+
+                        let input_value = input_evaluator.evaluate(
+                            &mut eval_data_input,
+                            fixed,
+                            advice,
+                            instance,
+                            challenges,
+                            &beta,
+                            &gamma,
+                            &theta,
+                            &y,
+                            &C::ScalarExt::zero(),
+                            idx,
+                            rot_scale,
+                            isize,
+                        );
+
+                        let shuffle_value = shuffle_evaluator.evaluate(
+                            &mut eval_data_shuffle,
+                            fixed,
+                            advice,
+                            instance,
+                            challenges,
+                            &beta,
+                            &gamma,
+                            &theta,
+                            &y,
+                            &C::ScalarExt::zero(),
+                            idx,
+                            rot_scale,
+                            isize,
+                        );
+
+                        let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
+
+                        // l_0(X) * (1 - z(X)) = 0
+                        *value = *value * y + ((one - product_coset[idx]) * l0[idx]);
+                        // l_last(X) * (z(X)^2 - z(X)) = 0
+                        *value = *value * y
+                            + ((product_coset[idx] * product_coset[idx] - product_coset[idx])
+                                * l_last[idx]);
+                        // (1 - (l_last(X) + l_blind(X))) * (
+                        //   z(\omega X) (s(X) + \gamma) - z(X) (a(X) + \gamma)
+                        // ) = 0
+                        *value = *value * y
+                            + l_active_row[idx]
+                                * (product_coset[r_next] * shuffle_value
+                                    - product_coset[idx] * input_value);
+                    }
+                });
+            }
         }
-        values
+        Ok(values)
     }
 }
 
@@ -689,6 +1004,18 @@ impl<C: CurveAffine> GraphEvaluator<C> {
         }
     }
 
+    /// Returns the fixed columns queried by this graph.
+    fn fixed_columns(&self) -> BTreeSet<usize> {
+        // Fixed columns are only ever read through a `Store`, see `add_expression`.
+        self.calculations
+            .iter()
+            .filter_map(|info| match info.calculation {
+                Calculation::Store(ValueSource::Fixed(column, _)) => Some(column),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Creates a new evaluation structure
     pub fn instance(&self) -> EvaluationData<C> {
         EvaluationData {
@@ -700,7 +1027,7 @@ impl<C: CurveAffine> GraphEvaluator<C> {
     pub fn evaluate<B: Basis>(
         &self,
         data: &mut EvaluationData<C>,
-        fixed: &[Polynomial<C::ScalarExt, B>],
+        fixed: &[&[C::ScalarExt]],
         advice: &[Polynomial<C::ScalarExt, B>],
         instance: &[Polynomial<C::ScalarExt, B>],
         challenges: &[C::ScalarExt],
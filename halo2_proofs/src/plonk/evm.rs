@@ -0,0 +1,1186 @@
+//! Generation of Solidity verifiers for proofs over BN254.
+//!
+//! A [`SolidityGenerator`] renders a contract that checks a single proof for a
+//! fixed verifying key, as [`verify_proof`] does with
+//! [`KZGCommitmentScheme<Bn256>`], the [`Keccak256Read`] transcript and the
+//! chosen multi-opening argument. The contract exposes
+//!
+//! ```text
+//! function verifyProof(bytes calldata proof, uint256[] calldata instances)
+//!     external view returns (bool)
+//! ```
+//!
+//! where `instances` are the values of the instance columns, in column order.
+//! The number of values in each instance column is fixed when the contract is
+//! generated.
+//!
+//! Decompressing points is expensive on the EVM, so the contract reads proofs in
+//! a different encoding than [`Keccak256Write`] produces: points are written as
+//! their uncompressed coordinates and every word is big-endian. The transcript
+//! still hashes the same bytes, so the challenges are unchanged.
+//! [`SolidityGenerator::encode_calldata`] converts a proof into this encoding
+//! and ABI-encodes the call.
+//!
+//! [`verify_proof`]: super::verify_proof
+//! [`KZGCommitmentScheme<Bn256>`]: crate::poly::kzg::commitment::KZGCommitmentScheme
+//! [`Keccak256Read`]: crate::transcript::Keccak256Read
+//! [`Keccak256Write`]: crate::transcript::Keccak256Write
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+
+use ff::{Field, PrimeField};
+use group::GroupEncoding;
+use halo2curves::{
+    bn256::{Bn256, Fr, G1Affine},
+    Coordinates, CurveAffine, FieldExt,
+};
+use sha3::{Digest, Keccak256};
+
+use super::{circuit::Any, Error, Expression, VerifyingKey};
+use crate::poly::{kzg::commitment::ParamsKZG, Rotation};
+
+mod program;
+mod yul;
+
+use program::{Point, Program, ProofItem, Scalar};
+
+/// The multi-opening argument a proof was created with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultiopenScheme {
+    /// [`ProverGWC`](crate::poly::kzg::multiopen::ProverGWC) and
+    /// [`VerifierGWC`](crate::poly::kzg::multiopen::VerifierGWC).
+    Gwc,
+    /// [`ProverSHPLONK`](crate::poly::kzg::multiopen::ProverSHPLONK) and
+    /// [`VerifierSHPLONK`](crate::poly::kzg::multiopen::VerifierSHPLONK).
+    Shplonk,
+}
+
+/// A generator of Solidity verifiers for a verifying key.
+#[derive(Clone, Debug)]
+pub struct SolidityGenerator {
+    num_instances: Vec<usize>,
+    program: Program,
+}
+
+impl SolidityGenerator {
+    /// Creates a generator for proofs of `vk` created with `params` and
+    /// `scheme`, where instance column `i` holds `num_instances[i]` values.
+    pub fn new(
+        params: &ParamsKZG<Bn256>,
+        vk: &VerifyingKey<G1Affine>,
+        scheme: MultiopenScheme,
+        num_instances: &[usize],
+    ) -> Result<Self, Error> {
+        if num_instances.len() != vk.cs.num_instance_columns {
+            return Err(Error::InvalidInstances);
+        }
+        let usable_rows = (1 << vk.domain.k()) - (vk.cs.blinding_factors() + 1);
+        if num_instances.iter().any(|&len| len > usable_rows) {
+            return Err(Error::InstanceTooLarge);
+        }
+
+        Ok(SolidityGenerator {
+            num_instances: num_instances.to_vec(),
+            program: compile(params, vk, scheme, num_instances)?,
+        })
+    }
+
+    /// Renders the source of the `Halo2Verifier` contract.
+    pub fn render(&self) -> String {
+        yul::render(&self.program)
+    }
+
+    /// Returns the calldata of a `verifyProof` call for a proof written with
+    /// [`Keccak256Write`](crate::transcript::Keccak256Write), with the given
+    /// instance column values.
+    pub fn encode_calldata(&self, instances: &[&[Fr]], proof: &[u8]) -> Result<Vec<u8>, Error> {
+        if instances.len() != self.num_instances.len()
+            || instances
+                .iter()
+                .zip(self.num_instances.iter())
+                .any(|(instance, len)| instance.len() != *len)
+        {
+            return Err(Error::InvalidInstances);
+        }
+
+        let mut reader = proof;
+        let mut encoded_proof = Vec::with_capacity(self.program.proof_len());
+        for item in self.program.proof.iter() {
+            match item {
+                ProofItem::Point => {
+                    let mut compressed = <G1Affine as GroupEncoding>::Repr::default();
+                    io::Read::read_exact(&mut reader, compressed.as_mut())?;
+                    let point: G1Affine = Option::from(G1Affine::from_bytes(&compressed))
+                        .ok_or_else(|| {
+                            io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof")
+                        })?;
+                    let coordinates: Coordinates<G1Affine> = Option::from(point.coordinates())
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::Other,
+                                "proof contains the point at infinity",
+                            )
+                        })?;
+                    encoded_proof.extend(be_bytes(coordinates.x()));
+                    encoded_proof.extend(be_bytes(coordinates.y()));
+                }
+                ProofItem::Scalar => {
+                    let mut repr = <Fr as PrimeField>::Repr::default();
+                    io::Read::read_exact(&mut reader, repr.as_mut())?;
+                    let scalar: Fr = Option::from(Fr::from_repr(repr)).ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::Other,
+                            "invalid field element encoding in proof",
+                        )
+                    })?;
+                    encoded_proof.extend(be_bytes(&scalar));
+                }
+            }
+        }
+
+        let selector = Keccak256::digest(b"verifyProof(bytes,uint256[])");
+        let mut calldata = selector[..4].to_vec();
+        calldata.extend(usize_word(0x40));
+        calldata.extend(usize_word(0x60 + encoded_proof.len()));
+        calldata.extend(usize_word(encoded_proof.len()));
+        calldata.extend(encoded_proof);
+        calldata.extend(usize_word(self.program.num_instances));
+        for value in instances.iter().flat_map(|instance| instance.iter()) {
+            calldata.extend(be_bytes(value));
+        }
+
+        Ok(calldata)
+    }
+}
+
+fn be_bytes<F: PrimeField<Repr = [u8; 32]>>(value: &F) -> [u8; 32] {
+    let mut repr = value.to_repr();
+    repr.reverse();
+    repr
+}
+
+fn usize_word(value: usize) -> [u8; 32] {
+    let mut word = [0; 32];
+    word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+/// A commitment opened by the multi-opening argument.
+#[derive(Clone, Debug)]
+enum Commitment {
+    Point(Point),
+    Msm(Vec<(Scalar, Point)>),
+}
+
+/// An opening of the commitment with index `commitment` at $x \omega^{rotation}$.
+#[derive(Clone, Copy, Debug)]
+struct Query {
+    commitment: usize,
+    rotation: i32,
+    eval: Scalar,
+}
+
+/// The openings checked by the multi-opening argument. Commitments are
+/// identified by their index, as the verifier identifies them by reference.
+#[derive(Debug, Default)]
+struct Queries {
+    commitments: Vec<Commitment>,
+    queries: Vec<Query>,
+}
+
+impl Queries {
+    fn commitment(&mut self, commitment: Commitment) -> usize {
+        self.commitments.push(commitment);
+        self.commitments.len() - 1
+    }
+
+    fn open(&mut self, commitment: usize, rotation: i32, eval: Scalar) {
+        self.queries.push(Query {
+            commitment,
+            rotation,
+            eval,
+        });
+    }
+
+    /// Appends the terms of `coeff` times commitment `commitment` to `terms`.
+    fn scaled_terms(
+        &self,
+        program: &mut Program,
+        commitment: usize,
+        coeff: Scalar,
+        terms: &mut Vec<(Scalar, Point)>,
+    ) {
+        match &self.commitments[commitment] {
+            Commitment::Point(point) => terms.push((coeff, *point)),
+            Commitment::Msm(msm) => {
+                for (scalar, point) in msm.iter() {
+                    let scalar = program.mul(coeff, *scalar);
+                    terms.push((scalar, *point));
+                }
+            }
+        }
+    }
+}
+
+struct Evaluations<'a> {
+    advice: &'a [Scalar],
+    fixed: &'a [Scalar],
+    instance: &'a [Scalar],
+    challenges: &'a [Scalar],
+}
+
+fn evaluate(
+    program: &mut Program,
+    expression: &Expression<Fr>,
+    evals: &Evaluations,
+) -> Result<Scalar, Error> {
+    Ok(match expression {
+        Expression::Constant(scalar) => Scalar::Constant(*scalar),
+        Expression::Selector(_) => {
+            return Err(Error::VerifyingKeySerialization(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "virtual selectors must be compressed before generating a verifier",
+            )))
+        }
+        Expression::Fixed(query) => evals.fixed[query.index],
+        Expression::Advice(query) => evals.advice[query.index],
+        Expression::Instance(query) => evals.instance[query.index],
+        Expression::Challenge(challenge) => evals.challenges[challenge.index()],
+        Expression::Negated(a) => {
+            let a = evaluate(program, a, evals)?;
+            program.neg(a)
+        }
+        Expression::Sum(a, b) => {
+            let a = evaluate(program, a, evals)?;
+            let b = evaluate(program, b, evals)?;
+            program.add(a, b)
+        }
+        Expression::Product(a, b) => {
+            let a = evaluate(program, a, evals)?;
+            let b = evaluate(program, b, evals)?;
+            program.mul(a, b)
+        }
+        Expression::Scaled(a, scalar) => {
+            let a = evaluate(program, a, evals)?;
+            program.mul(a, Scalar::Constant(*scalar))
+        }
+    })
+}
+
+/// Computes $\sum_i \theta^{m - 1 - i} e_i$ for the evaluations $e_i$ of
+/// `expressions`.
+fn compress(
+    program: &mut Program,
+    expressions: &[Expression<Fr>],
+    theta: Scalar,
+    evals: &Evaluations,
+) -> Result<Scalar, Error> {
+    let mut acc = Scalar::Constant(Fr::zero());
+    for expression in expressions.iter() {
+        let eval = evaluate(program, expression, evals)?;
+        acc = program.mul(acc, theta);
+        acc = program.add(acc, eval);
+    }
+    Ok(acc)
+}
+
+fn multiply_all(program: &mut Program, values: impl IntoIterator<Item = Scalar>) -> Scalar {
+    values
+        .into_iter()
+        .fold(Scalar::Constant(Fr::one()), |acc, value| {
+            program.mul(acc, value)
+        })
+}
+
+fn add_all(program: &mut Program, values: impl IntoIterator<Item = Scalar>) -> Scalar {
+    values
+        .into_iter()
+        .fold(Scalar::Constant(Fr::zero()), |acc, value| {
+            program.add(acc, value)
+        })
+}
+
+/// Builds the program that checks a proof, mirroring [`verify_proof`] for a
+/// single proof with instances that are not committed to.
+///
+/// [`verify_proof`]: super::verify_proof
+fn compile(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    scheme: MultiopenScheme,
+    num_instances: &[usize],
+) -> Result<Program, Error> {
+    let cs = &vk.cs;
+    let domain = &vk.domain;
+    let one = Scalar::Constant(Fr::one());
+    let omega = |rotation: i32| domain.rotate_omega(Fr::one(), Rotation(rotation));
+    let mut program = Program::default();
+
+    program.common_scalar(Scalar::Constant(vk.transcript_repr));
+    let mut instances = vec![];
+    for &len in num_instances.iter() {
+        let mut column = vec![];
+        for _ in 0..len {
+            let value = program.read_instance();
+            program.common_scalar(value);
+            column.push(value);
+        }
+        instances.push(column);
+    }
+
+    let mut advice_commitments = vec![None; cs.num_advice_columns];
+    let mut challenges = vec![Scalar::Constant(Fr::zero()); cs.num_challenges];
+    for current_phase in cs.phases() {
+        for (phase, commitment) in cs
+            .advice_column_phase
+            .iter()
+            .zip(advice_commitments.iter_mut())
+        {
+            if current_phase == *phase {
+                *commitment = Some(program.read_point());
+            }
+        }
+        for (phase, challenge) in cs.challenge_phase.iter().zip(challenges.iter_mut()) {
+            if current_phase == *phase {
+                *challenge = program.squeeze();
+            }
+        }
+    }
+    let advice_commitments: Vec<Point> = advice_commitments
+        .into_iter()
+        .map(|commitment| commitment.expect("every advice column has a phase"))
+        .collect();
+
+    let theta = program.squeeze();
+    let lookup_permuted: Vec<(Point, Point)> = cs
+        .lookups
+        .iter()
+        .map(|_| (program.read_point(), program.read_point()))
+        .collect();
+    let logup_multiplicities: Vec<Point> = cs.logups.iter().map(|_| program.read_point()).collect();
+
+    let beta = program.squeeze();
+    let gamma = program.squeeze();
+
+    let chunk_len = vk.cs_degree - 2;
+    let permutation_products: Vec<Point> = cs
+        .permutation
+        .columns
+        .chunks(chunk_len)
+        .map(|_| program.read_point())
+        .collect();
+    let lookup_products: Vec<Point> = cs.lookups.iter().map(|_| program.read_point()).collect();
+    let logup_running_sums: Vec<Point> = cs.logups.iter().map(|_| program.read_point()).collect();
+    let shuffle_products: Vec<Point> = cs.shuffles.iter().map(|_| program.read_point()).collect();
+
+    let random_poly_commitment = program.read_point();
+    let y = program.squeeze();
+    let h_commitments: Vec<Point> = (0..domain.get_quotient_poly_degree())
+        .map(|_| program.read_point())
+        .collect();
+    let x = program.squeeze();
+
+    let advice_evals: Vec<Scalar> = cs
+        .advice_queries
+        .iter()
+        .map(|_| program.read_scalar())
+        .collect();
+    let fixed_evals: Vec<Scalar> = cs
+        .fixed_queries
+        .iter()
+        .map(|_| program.read_scalar())
+        .collect();
+    let random_eval = program.read_scalar();
+    let permutation_common_evals: Vec<Scalar> = vk
+        .permutation
+        .commitments()
+        .iter()
+        .map(|_| program.read_scalar())
+        .collect();
+    // (eval, next_eval, last_eval) of each permutation product.
+    let mut permutation_evals = vec![];
+    for i in 0..permutation_products.len() {
+        let eval = program.read_scalar();
+        let next_eval = program.read_scalar();
+        let last_eval = if i + 1 < permutation_products.len() {
+            Some(program.read_scalar())
+        } else {
+            None
+        };
+        permutation_evals.push((eval, next_eval, last_eval));
+    }
+    // [product, product_next, permuted_input, permuted_input_inv, permuted_table]
+    let lookup_evals: Vec<Vec<Scalar>> = cs
+        .lookups
+        .iter()
+        .map(|_| (0..5).map(|_| program.read_scalar()).collect())
+        .collect();
+    // [multiplicity, running_sum, running_sum_next]
+    let logup_evals: Vec<Vec<Scalar>> = cs
+        .logups
+        .iter()
+        .map(|_| (0..3).map(|_| program.read_scalar()).collect())
+        .collect();
+    // [product, product_next]
+    let shuffle_evals: Vec<Vec<Scalar>> = cs
+        .shuffles
+        .iter()
+        .map(|_| (0..2).map(|_| program.read_scalar()).collect())
+        .collect();
+
+    // Evaluate every Lagrange basis polynomial that is needed at x, with a
+    // single inversion.
+    let mut xn = x;
+    for _ in 0..domain.k() {
+        xn = program.square(xn);
+    }
+    let blinding_factors = cs.blinding_factors() as i32;
+    let mut rotations: BTreeSet<i32> = (-(blinding_factors + 1)..=0).collect();
+    for (column, rotation) in cs.instance_queries.iter() {
+        rotations.extend((0..num_instances[column.index()] as i32).map(|j| j - rotation.0));
+    }
+    let xn_minus_one = program.sub(xn, one);
+    let mut denominators: Vec<Scalar> = rotations
+        .iter()
+        .map(|&rotation| program.sub(x, Scalar::Constant(omega(rotation))))
+        .collect();
+    denominators.push(xn_minus_one);
+    let mut inverses = program.batch_invert(&denominators);
+    let xn_minus_one_inv = inverses.pop().unwrap();
+    let n_inv = Fr::from(1u64 << domain.k()).invert().unwrap();
+    let common = program.mul(xn_minus_one, Scalar::Constant(n_inv));
+    let mut lagrange = BTreeMap::new();
+    for (rotation, inverse) in rotations.iter().zip(inverses) {
+        let l_i = program.mul(inverse, common);
+        let l_i = program.mul(l_i, Scalar::Constant(omega(*rotation)));
+        lagrange.insert(*rotation, l_i);
+    }
+
+    let mut instance_evals = vec![];
+    for (column, rotation) in cs.instance_queries.iter() {
+        let mut terms = vec![];
+        for (j, value) in instances[column.index()].iter().enumerate() {
+            terms.push(program.mul(*value, lagrange[&(j as i32 - rotation.0)]));
+        }
+        instance_evals.push(add_all(&mut program, terms));
+    }
+
+    let l_last = lagrange[&-(blinding_factors + 1)];
+    let l_blind = add_all(
+        &mut program,
+        (-blinding_factors..0).map(|rotation| lagrange[&rotation]),
+    );
+    let l_0 = lagrange[&0];
+    let l_last_or_blind = program.add(l_last, l_blind);
+    let active_rows = program.sub(one, l_last_or_blind);
+
+    let evals = Evaluations {
+        advice: &advice_evals,
+        fixed: &fixed_evals,
+        instance: &instance_evals,
+        challenges: &challenges,
+    };
+
+    let mut expressions = vec![];
+    for gate in cs.gates.iter() {
+        for poly in gate.polynomials() {
+            expressions.push(evaluate(&mut program, poly, &evals)?);
+        }
+    }
+
+    if let Some((first_eval, _, _)) = permutation_evals.first() {
+        // l_0(X) * (1 - z_0(X)) = 0
+        let e = program.sub(one, *first_eval);
+        expressions.push(program.mul(l_0, e));
+    }
+    if let Some((last_eval, _, _)) = permutation_evals.last() {
+        // l_last(X) * (z_l(X)^2 - z_l(X)) = 0
+        let e = program.square(*last_eval);
+        let e = program.sub(e, *last_eval);
+        expressions.push(program.mul(e, l_last));
+    }
+    for window in permutation_evals.windows(2) {
+        // l_0(X) * (z_i(X) - z_{i-1}(\omega^(last) X)) = 0
+        let e = program.sub(window[1].0, window[0].2.unwrap());
+        expressions.push(program.mul(e, l_0));
+    }
+    let permutation_columns = &cs.permutation.columns;
+    for (chunk_index, ((set, columns), sigma_evals)) in permutation_evals
+        .iter()
+        .zip(permutation_columns.chunks(chunk_len))
+        .zip(permutation_common_evals.chunks(chunk_len))
+        .enumerate()
+    {
+        let column_evals: Vec<Scalar> = columns
+            .iter()
+            .map(|&column| {
+                let index = cs.get_any_query_index(column, Rotation::cur());
+                match column.column_type() {
+                    Any::Advice(_) => advice_evals[index],
+                    Any::Fixed => fixed_evals[index],
+                    Any::Instance => instance_evals[index],
+                }
+            })
+            .collect();
+
+        let mut left = set.1;
+        for (eval, sigma_eval) in column_evals.iter().zip(sigma_evals.iter()) {
+            let term = program.mul(beta, *sigma_eval);
+            let term = program.add(*eval, term);
+            let term = program.add(term, gamma);
+            left = program.mul(left, term);
+        }
+
+        let mut right = set.0;
+        let delta_power = Fr::DELTA.pow_vartime(&[(chunk_index * chunk_len) as u64]);
+        let beta_x = program.mul(beta, x);
+        let mut current_delta = program.mul(beta_x, Scalar::Constant(delta_power));
+        for eval in column_evals.iter() {
+            let term = program.add(*eval, current_delta);
+            let term = program.add(term, gamma);
+            right = program.mul(right, term);
+            current_delta = program.mul(current_delta, Scalar::Constant(Fr::DELTA));
+        }
+
+        let e = program.sub(left, right);
+        expressions.push(program.mul(e, active_rows));
+    }
+
+    for (argument, lookup) in cs.lookups.iter().zip(lookup_evals.iter()) {
+        let (product, product_next, input, input_inv, table) =
+            (lookup[0], lookup[1], lookup[2], lookup[3], lookup[4]);
+
+        // l_0(X) * (1 - z(X)) = 0
+        let e = program.sub(one, product);
+        expressions.push(program.mul(l_0, e));
+        // l_last(X) * (z(X)^2 - z(X)) = 0
+        let e = program.square(product);
+        let e = program.sub(e, product);
+        expressions.push(program.mul(l_last, e));
+        // (1 - (l_last(X) + l_blind(X))) * (
+        //   z(\omega X) (a'(X) + \beta) (s'(X) + \gamma)
+        //   - z(X) (\theta^{m-1} a_0(X) + ... + \beta) (\theta^{m-1} s_0(X) + ... + \gamma)
+        // ) = 0
+        let input_beta = program.add(input, beta);
+        let table_gamma = program.add(table, gamma);
+        let left = program.mul(product_next, input_beta);
+        let left = program.mul(left, table_gamma);
+        let compressed_input = compress(&mut program, &argument.input_expressions, theta, &evals)?;
+        let compressed_table = compress(&mut program, &argument.table_expressions, theta, &evals)?;
+        let input_beta = program.add(compressed_input, beta);
+        let table_gamma = program.add(compressed_table, gamma);
+        let right = program.mul(product, input_beta);
+        let right = program.mul(right, table_gamma);
+        let e = program.sub(left, right);
+        expressions.push(program.mul(e, active_rows));
+        // l_0(X) * (a'(X) - s'(X)) = 0
+        let input_minus_table = program.sub(input, table);
+        expressions.push(program.mul(l_0, input_minus_table));
+        // (1 - (l_last(X) + l_blind(X))) * (a′(X) − s′(X))⋅(a′(X) − a′(\omega^{-1} X)) = 0
+        let e = program.sub(input, input_inv);
+        let e = program.mul(input_minus_table, e);
+        expressions.push(program.mul(e, active_rows));
+    }
+
+    for (argument, logup) in cs.logups.iter().zip(logup_evals.iter()) {
+        let (multiplicity, running_sum, running_sum_next) = (logup[0], logup[1], logup[2]);
+
+        // l_0(X) * \phi(X) = 0
+        expressions.push(program.mul(l_0, running_sum));
+        // l_last(X) * \phi(X) = 0
+        expressions.push(program.mul(l_last, running_sum));
+        // (1 - (l_last(X) + l_blind(X))) * (
+        //   (\phi(\omega X) - \phi(X)) \prod_j (f_j(X) + \beta) (t(X) + \beta)
+        //   - (t(X) + \beta) \sum_j \prod_{k \neq j} (f_k(X) + \beta)
+        //   + m(X) \prod_j (f_j(X) + \beta)
+        // ) = 0
+        let inputs: Vec<Scalar> = argument
+            .input_expressions
+            .iter()
+            .map(|input| {
+                let compressed = compress(&mut program, input, theta, &evals)?;
+                Ok(program.add(compressed, beta))
+            })
+            .collect::<Result<_, Error>>()?;
+        let compressed_table = compress(&mut program, &argument.table_expressions, theta, &evals)?;
+        let table = program.add(compressed_table, beta);
+        let inputs_product = multiply_all(&mut program, inputs.iter().copied());
+        let mut inputs_sum = Scalar::Constant(Fr::zero());
+        for j in 0..inputs.len() {
+            let others = inputs
+                .iter()
+                .enumerate()
+                .filter(|(k, _)| *k != j)
+                .map(|(_, input)| *input);
+            let term = multiply_all(&mut program, others);
+            inputs_sum = program.add(inputs_sum, term);
+        }
+        let left = program.sub(running_sum_next, running_sum);
+        let left = program.mul(left, inputs_product);
+        let left = program.mul(left, table);
+        let right = program.mul(table, inputs_sum);
+        let multiplicity_product = program.mul(multiplicity, inputs_product);
+        let right = program.sub(right, multiplicity_product);
+        let e = program.sub(left, right);
+        expressions.push(program.mul(e, active_rows));
+    }
+
+    for (argument, shuffle) in cs.shuffles.iter().zip(shuffle_evals.iter()) {
+        let (product, product_next) = (shuffle[0], shuffle[1]);
+
+        // l_0(X) * (1 - z(X)) = 0
+        let e = program.sub(one, product);
+        expressions.push(program.mul(l_0, e));
+        // l_last(X) * (z(X)^2 - z(X)) = 0
+        let e = program.square(product);
+        let e = program.sub(e, product);
+        expressions.push(program.mul(l_last, e));
+        // (1 - (l_last(X) + l_blind(X))) * (z(\omega X) (s(X) + \gamma) - z(X) (a(X) + \gamma)) = 0
+        let compressed_shuffle =
+            compress(&mut program, &argument.shuffle_expressions, theta, &evals)?;
+        let compressed_input = compress(&mut program, &argument.input_expressions, theta, &evals)?;
+        let shuffle_gamma = program.add(compressed_shuffle, gamma);
+        let input_gamma = program.add(compressed_input, gamma);
+        let left = program.mul(product_next, shuffle_gamma);
+        let right = program.mul(product, input_gamma);
+        let e = program.sub(left, right);
+        expressions.push(program.mul(e, active_rows));
+    }
+
+    let mut h_eval = Scalar::Constant(Fr::zero());
+    for expression in expressions {
+        h_eval = program.mul(h_eval, y);
+        h_eval = program.add(h_eval, expression);
+    }
+    let expected_h_eval = program.mul(h_eval, xn_minus_one_inv);
+
+    // Collect the openings in the order the verifier does.
+    let mut queries = Queries::default();
+    let advice_ids: Vec<usize> = advice_commitments
+        .iter()
+        .map(|commitment| queries.commitment(Commitment::Point(*commitment)))
+        .collect();
+    for ((column, rotation), eval) in cs.advice_queries.iter().zip(advice_evals.iter()) {
+        queries.open(advice_ids[column.index()], rotation.0, *eval);
+    }
+
+    let permutation_ids: Vec<usize> = permutation_products
+        .iter()
+        .map(|commitment| queries.commitment(Commitment::Point(*commitment)))
+        .collect();
+    for (id, (eval, next_eval, _)) in permutation_ids.iter().zip(permutation_evals.iter()) {
+        queries.open(*id, 0, *eval);
+        queries.open(*id, 1, *next_eval);
+    }
+    for (id, (_, _, last_eval)) in permutation_ids
+        .iter()
+        .zip(permutation_evals.iter())
+        .rev()
+        .skip(1)
+    {
+        queries.open(*id, -(blinding_factors + 1), last_eval.unwrap());
+    }
+
+    for ((&(input, table), product), evals) in lookup_permuted
+        .iter()
+        .zip(lookup_products.iter())
+        .zip(lookup_evals.iter())
+    {
+        let product = queries.commitment(Commitment::Point(*product));
+        let input = queries.commitment(Commitment::Point(input));
+        let table = queries.commitment(Commitment::Point(table));
+        queries.open(product, 0, evals[0]);
+        queries.open(input, 0, evals[2]);
+        queries.open(table, 0, evals[4]);
+        queries.open(input, -1, evals[3]);
+        queries.open(product, 1, evals[1]);
+    }
+
+    for ((multiplicity, running_sum), evals) in logup_multiplicities
+        .iter()
+        .zip(logup_running_sums.iter())
+        .zip(logup_evals.iter())
+    {
+        let multiplicity = queries.commitment(Commitment::Point(*multiplicity));
+        let running_sum = queries.commitment(Commitment::Point(*running_sum));
+        queries.open(multiplicity, 0, evals[0]);
+        queries.open(running_sum, 0, evals[1]);
+        queries.open(running_sum, 1, evals[2]);
+    }
+
+    for (product, evals) in shuffle_products.iter().zip(shuffle_evals.iter()) {
+        let product = queries.commitment(Commitment::Point(*product));
+        queries.open(product, 0, evals[0]);
+        queries.open(product, 1, evals[1]);
+    }
+
+    let fixed_ids: Vec<usize> = vk
+        .fixed_commitments
+        .iter()
+        .map(|commitment| queries.commitment(Commitment::Point(Point::Constant(*commitment))))
+        .collect();
+    for ((column, rotation), eval) in cs.fixed_queries.iter().zip(fixed_evals.iter()) {
+        queries.open(fixed_ids[column.index()], rotation.0, *eval);
+    }
+
+    for (commitment, eval) in vk
+        .permutation
+        .commitments()
+        .iter()
+        .zip(permutation_common_evals.iter())
+    {
+        let id = queries.commitment(Commitment::Point(Point::Constant(*commitment)));
+        queries.open(id, 0, *eval);
+    }
+
+    let xn_powers = program.powers(xn, h_commitments.len());
+    let h_commitment = queries.commitment(Commitment::Msm(
+        xn_powers.into_iter().zip(h_commitments).collect(),
+    ));
+    queries.open(h_commitment, 0, expected_h_eval);
+    let random_poly_commitment = queries.commitment(Commitment::Point(random_poly_commitment));
+    queries.open(random_poly_commitment, 0, random_eval);
+
+    let (left, right) = match scheme {
+        MultiopenScheme::Gwc => gwc(&mut program, params, &queries, x, &omega),
+        MultiopenScheme::Shplonk => shplonk(&mut program, params, &queries, x, &omega),
+    };
+    program.pairing(left, right, params.g2, params.s_g2);
+
+    Ok(program)
+}
+
+/// Mirrors [`VerifierGWC`](crate::poly::kzg::multiopen::VerifierGWC), returning
+/// the slots of the two points of the pairing check.
+fn gwc(
+    program: &mut Program,
+    params: &ParamsKZG<Bn256>,
+    queries: &Queries,
+    x: Scalar,
+    omega: &impl Fn(i32) -> Fr,
+) -> (usize, usize) {
+    let v = program.squeeze();
+
+    let mut groups: Vec<(i32, Vec<&Query>)> = vec![];
+    for query in queries.queries.iter() {
+        match groups
+            .iter_mut()
+            .find(|(rotation, _)| *rotation == query.rotation)
+        {
+            Some((_, group)) => group.push(query),
+            None => groups.push((query.rotation, vec![query])),
+        }
+    }
+
+    let witnesses: Vec<Point> = groups.iter().map(|_| program.read_point()).collect();
+    let u = program.squeeze();
+
+    let mut left = vec![];
+    let mut right = vec![];
+    let mut eval_multi = Scalar::Constant(Fr::zero());
+    let u_powers = program.powers(u, groups.len());
+    for (((rotation, group), witness), power_of_u) in groups.iter().zip(witnesses).zip(u_powers) {
+        let v_powers = program.powers(v, group.len());
+        let mut eval_batch = Scalar::Constant(Fr::zero());
+        for (query, power_of_v) in group.iter().zip(v_powers) {
+            let coeff = program.mul(power_of_u, power_of_v);
+            queries.scaled_terms(program, query.commitment, coeff, &mut right);
+            let eval = program.mul(power_of_v, query.eval);
+            eval_batch = program.add(eval_batch, eval);
+        }
+        let eval_batch = program.mul(power_of_u, eval_batch);
+        eval_multi = program.add(eval_multi, eval_batch);
+
+        let z = program.mul(x, Scalar::Constant(omega(*rotation)));
+        let z = program.mul(power_of_u, z);
+        right.push((z, witness));
+        left.push((power_of_u, witness));
+    }
+    let eval_multi = program.neg(eval_multi);
+    right.push((eval_multi, Point::Constant(params.g[0])));
+
+    (program.msm(&left), program.msm(&right))
+}
+
+/// Mirrors [`VerifierSHPLONK`](crate::poly::kzg::multiopen::VerifierSHPLONK),
+/// returning the slots of the two points of the pairing check.
+fn shplonk(
+    program: &mut Program,
+    params: &ParamsKZG<Bn256>,
+    queries: &Queries,
+    x: Scalar,
+    omega: &impl Fn(i32) -> Fr,
+) -> (usize, usize) {
+    // Group the commitments by the set of points they are opened at, in the
+    // order the verifier does.
+    let mut super_point_set = BTreeSet::new();
+    let mut commitment_rotations: Vec<(usize, BTreeSet<i32>)> = vec![];
+    for query in queries.queries.iter() {
+        super_point_set.insert(query.rotation);
+        match commitment_rotations
+            .iter_mut()
+            .find(|(commitment, _)| *commitment == query.commitment)
+        {
+            Some((_, rotations)) => {
+                rotations.insert(query.rotation);
+            }
+            None => commitment_rotations
+                .push((query.commitment, std::iter::once(query.rotation).collect())),
+        }
+    }
+    let mut rotation_sets: Vec<(Vec<i32>, Vec<usize>)> = vec![];
+    for (commitment, rotations) in commitment_rotations {
+        let rotations: Vec<i32> = rotations.into_iter().collect();
+        match rotation_sets.iter_mut().find(|(set, _)| *set == rotations) {
+            Some((_, commitments)) => commitments.push(commitment),
+            None => rotation_sets.push((rotations, vec![commitment])),
+        }
+    }
+    let eval_at = |commitment: usize, rotation: i32| {
+        queries
+            .queries
+            .iter()
+            .find(|query| query.commitment == commitment && query.rotation == rotation)
+            .unwrap()
+            .eval
+    };
+
+    let y = program.squeeze();
+    let v = program.squeeze();
+    let h1 = program.read_point();
+    let u = program.squeeze();
+    let h2 = program.read_point();
+
+    let mut points = BTreeMap::new();
+    let mut diffs = BTreeMap::new();
+    for &rotation in super_point_set.iter() {
+        let point = program.mul(x, Scalar::Constant(omega(rotation)));
+        diffs.insert(rotation, program.sub(u, point));
+        points.insert(rotation, point);
+    }
+
+    // Invert the vanishing polynomial of the points missing from the first set,
+    // and the denominators of the Lagrange bases of every set, at once.
+    let z_diffs: Vec<Scalar> = rotation_sets
+        .iter()
+        .map(|(set, _)| {
+            let missing = super_point_set
+                .iter()
+                .filter(|rotation| !set.contains(*rotation))
+                .map(|rotation| diffs[rotation]);
+            multiply_all(program, missing)
+        })
+        .collect();
+    let mut to_invert = vec![z_diffs[0]];
+    for (set, _) in rotation_sets.iter() {
+        for &k in set.iter() {
+            let factors: Vec<Scalar> = set
+                .iter()
+                .filter(|&&l| l != k)
+                .map(|l| program.sub(points[&k], points[l]))
+                .collect();
+            to_invert.push(multiply_all(program, factors));
+        }
+    }
+    let mut inverses = program.batch_invert(&to_invert).into_iter();
+    let z_0_diff_inverse = inverses.next().unwrap();
+    let z_0 = multiply_all(
+        program,
+        rotation_sets[0].0.iter().map(|rotation| diffs[rotation]),
+    );
+
+    let mut outer = vec![];
+    let mut r_outer = Scalar::Constant(Fr::zero());
+    let v_powers = program.powers(v, rotation_sets.len());
+    for (i, ((set, commitments), power_of_v)) in rotation_sets.iter().zip(v_powers).enumerate() {
+        let z_diff = if i == 0 {
+            Scalar::Constant(Fr::one())
+        } else {
+            program.mul(z_diffs[i], z_0_diff_inverse)
+        };
+
+        // The Lagrange basis of the set, evaluated at u.
+        let bases: Vec<Scalar> = set
+            .iter()
+            .map(|&k| {
+                let numerators: Vec<Scalar> =
+                    set.iter().filter(|&&l| l != k).map(|l| diffs[l]).collect();
+                let numerator = multiply_all(program, numerators);
+                program.mul(numerator, inverses.next().unwrap())
+            })
+            .collect();
+
+        let scale = program.mul(power_of_v, z_diff);
+        let mut r_inner = Scalar::Constant(Fr::zero());
+        let y_powers = program.powers(y, commitments.len());
+        for (commitment, power_of_y) in commitments.iter().zip(y_powers) {
+            let mut r_eval = Scalar::Constant(Fr::zero());
+            for (&rotation, basis) in set.iter().zip(bases.iter()) {
+                let term = program.mul(eval_at(*commitment, rotation), *basis);
+                r_eval = program.add(r_eval, term);
+            }
+            let r_eval = program.mul(power_of_y, r_eval);
+            r_inner = program.add(r_inner, r_eval);
+
+            let coeff = program.mul(scale, power_of_y);
+            queries.scaled_terms(program, *commitment, coeff, &mut outer);
+        }
+        let r_inner = program.mul(scale, r_inner);
+        r_outer = program.add(r_outer, r_inner);
+    }
+
+    let r_outer = program.neg(r_outer);
+    outer.push((r_outer, Point::Constant(params.g[0])));
+    let z_0 = program.neg(z_0);
+    outer.push((z_0, h1));
+    outer.push((u, h2));
+
+    let left = program.msm(&[(Scalar::Constant(Fr::one()), h2)]);
+    (left, program.msm(&outer))
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::OsRng;
+
+    use super::{evaluate, Evaluations, MultiopenScheme, Program, ProofItem, SolidityGenerator};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{
+            create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
+            ConstraintSystem, Error, Expression, Instance, Selector, TableColumn,
+        },
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverGWC, ProverSHPLONK, VerifierGWC, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+            Rotation,
+        },
+        transcript::{
+            Challenge255, Keccak256Read, Keccak256Write, TranscriptReadBuffer,
+            TranscriptWriterBuffer,
+        },
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        c: Column<Advice>,
+        s: Selector,
+        table: TableColumn,
+        instance: Column<Instance>,
+    }
+
+    /// Proves that `a * b = c` for small `a` and `b`, exposing `c`.
+    #[derive(Clone, Default)]
+    struct TestCircuit {
+        a: Value<Fr>,
+        b: Value<Fr>,
+    }
+
+    impl Circuit<Fr> for TestCircuit {
+        type Config = TestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> TestConfig {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let c = meta.advice_column();
+            let s = meta.selector();
+            let table = meta.lookup_table_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(c);
+            meta.enable_equality(instance);
+
+            meta.create_gate("mul", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let c = meta.query_advice(c, Rotation::cur());
+                vec![s * (a * b - c)]
+            });
+            meta.lookup("a is small", |meta| {
+                vec![(meta.query_advice(a, Rotation::cur()), table)]
+            });
+            meta.lookup_logup("b is small", |meta| {
+                (
+                    vec![vec![meta.query_advice(b, Rotation::cur())]],
+                    vec![table],
+                )
+            });
+
+            TestConfig {
+                a,
+                b,
+                c,
+                s,
+                table,
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: TestConfig,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_table(
+                || "table",
+                |mut table| {
+                    for i in 0..16 {
+                        table.assign_cell(
+                            || "value",
+                            config.table,
+                            i,
+                            || Value::known(Fr::from(i as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+
+            let c = layouter.assign_region(
+                || "mul",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", config.a, 0, || self.a)?;
+                    region.assign_advice(|| "b", config.b, 0, || self.b)?;
+                    region.assign_advice(|| "c", config.c, 0, || self.a * self.b)
+                },
+            )?;
+
+            layouter.constrain_instance(c.cell(), config.instance, 0)
+        }
+    }
+
+    fn check(scheme: MultiopenScheme) {
+        const K: u32 = 5;
+        let params = ParamsKZG::<Bn256>::new(K);
+        let circuit = TestCircuit {
+            a: Value::known(Fr::from(3)),
+            b: Value::known(Fr::from(5)),
+        };
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk, &circuit).unwrap();
+        let instances = [Fr::from(15)];
+
+        let mut transcript = Keccak256Write::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        match scheme {
+            MultiopenScheme::Gwc => {
+                create_proof::<KZGCommitmentScheme<Bn256>, ProverGWC<_>, _, _, _, _>(
+                    &params,
+                    &pk,
+                    &[circuit],
+                    &[&[&instances]],
+                    OsRng,
+                    &mut transcript,
+                )
+            }
+            MultiopenScheme::Shplonk => {
+                create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+                    &params,
+                    &pk,
+                    &[circuit],
+                    &[&[&instances]],
+                    OsRng,
+                    &mut transcript,
+                )
+            }
+        }
+        .unwrap();
+        let proof = transcript.finalize();
+
+        let mut transcript = Keccak256Read::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+        let strategy = SingleStrategy::new(&params);
+        match scheme {
+            MultiopenScheme::Gwc => verify_proof::<_, VerifierGWC<_>, _, _, _>(
+                &params,
+                pk.get_vk(),
+                strategy,
+                &[&[&instances]],
+                &mut transcript,
+            ),
+            MultiopenScheme::Shplonk => verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+                &params,
+                pk.get_vk(),
+                strategy,
+                &[&[&instances]],
+                &mut transcript,
+            ),
+        }
+        .unwrap();
+
+        let generator = SolidityGenerator::new(&params, pk.get_vk(), scheme, &[1]).unwrap();
+        let source = generator.render();
+        assert!(source.contains("contract Halo2Verifier"));
+        assert!(source
+            .contains("function verifyProof(bytes calldata proof, uint256[] calldata instances)"));
+
+        let calldata = generator.encode_calldata(&[&instances], &proof).unwrap();
+        assert_eq!(
+            calldata.len(),
+            4 + 32 * 4 + generator.program.proof_len() + 32 * instances.len()
+        );
+        assert!(generator.program.execute(&calldata));
+
+        // A proof for different instances is rejected.
+        let wrong_instances = generator
+            .encode_calldata(&[&[Fr::from(16)]], &proof)
+            .unwrap();
+        assert!(!generator.program.execute(&wrong_instances));
+
+        // So is a proof with a tampered evaluation.
+        let mut tampered = calldata.clone();
+        let first_scalar: usize = generator
+            .program
+            .proof
+            .iter()
+            .take_while(|item| **item == ProofItem::Point)
+            .map(ProofItem::encoded_len)
+            .sum();
+        tampered[4 + 32 * 3 + first_scalar + 31] ^= 1;
+        assert!(!generator.program.execute(&tampered));
+
+        // And the wrong number of instances.
+        assert!(matches!(
+            generator.encode_calldata(&[&[]], &proof),
+            Err(Error::InvalidInstances)
+        ));
+    }
+
+    #[test]
+    fn gwc_verifier() {
+        check(MultiopenScheme::Gwc);
+    }
+
+    #[test]
+    fn shplonk_verifier() {
+        check(MultiopenScheme::Shplonk);
+    }
+
+    #[test]
+    fn virtual_selector() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let selector = Expression::Selector(meta.selector());
+        let evals = Evaluations {
+            advice: &[],
+            fixed: &[],
+            instance: &[],
+            challenges: &[],
+        };
+        assert!(matches!(
+            evaluate(&mut Program::default(), &selector, &evals),
+            Err(Error::VerifyingKeySerialization(_))
+        ));
+    }
+}
@@ -0,0 +1,437 @@
+use ff::Field;
+use halo2curves::bn256::{Fr, G1Affine, G2Affine};
+
+/// The index of a memory word. A point occupies two consecutive words.
+pub(super) type Slot = usize;
+
+/// A scalar that is either computed at runtime or known when the verifier is
+/// generated.
+#[derive(Clone, Copy, Debug)]
+pub(super) enum Scalar {
+    Slot(Slot),
+    Constant(Fr),
+}
+
+/// A point that is either computed at runtime or known when the verifier is
+/// generated.
+#[derive(Clone, Copy, Debug)]
+pub(super) enum Point {
+    Slot(Slot),
+    Constant(G1Affine),
+}
+
+/// An item of the proof, in the order it is read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum ProofItem {
+    Point,
+    Scalar,
+}
+
+impl ProofItem {
+    /// The length of the item in the EVM encoding of the proof: points are
+    /// written as uncompressed big-endian coordinates, and scalars as a single
+    /// big-endian word.
+    pub(super) fn encoded_len(&self) -> usize {
+        match self {
+            ProofItem::Point => 0x40,
+            ProofItem::Scalar => 0x20,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(super) enum Statement {
+    /// Reads the instance at `index`, failing if it is not a canonical scalar.
+    ReadInstance {
+        dst: Slot,
+        index: usize,
+    },
+    /// Reads the scalar at `offset` in the EVM encoding of the proof, failing
+    /// if it is not canonical.
+    ReadScalar {
+        dst: Slot,
+        offset: usize,
+    },
+    /// Reads the point at `offset` in the EVM encoding of the proof, failing if
+    /// it is not on the curve.
+    ReadPoint {
+        dst: Slot,
+        offset: usize,
+    },
+    AbsorbScalar(Scalar),
+    AbsorbPoint(Point),
+    Squeeze {
+        dst: Slot,
+    },
+    Add {
+        dst: Slot,
+        a: Scalar,
+        b: Scalar,
+    },
+    Sub {
+        dst: Slot,
+        a: Scalar,
+        b: Scalar,
+    },
+    Mul {
+        dst: Slot,
+        a: Scalar,
+        b: Scalar,
+    },
+    Invert {
+        dst: Slot,
+        a: Scalar,
+    },
+    /// Sets the point at `dst` to the identity.
+    ZeroPoint {
+        dst: Slot,
+    },
+    /// Adds `scalar * point` to the point at `dst`.
+    MulAdd {
+        dst: Slot,
+        point: Point,
+        scalar: Scalar,
+    },
+    /// Fails unless $e(lhs, [s]_2) \cdot e(rhs, -[1]_2) = 1$.
+    Pairing {
+        lhs: Slot,
+        rhs: Slot,
+        g2: G2Affine,
+        s_g2: G2Affine,
+    },
+}
+
+/// A straight-line program that verifies a single proof.
+///
+/// Scalar arithmetic with constant operands is folded while the program is
+/// built, so only operations on runtime values become statements.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Program {
+    pub(super) statements: Vec<Statement>,
+    pub(super) num_slots: usize,
+    pub(super) num_instances: usize,
+    pub(super) proof: Vec<ProofItem>,
+}
+
+impl Program {
+    fn alloc(&mut self, words: usize) -> Slot {
+        let slot = self.num_slots;
+        self.num_slots += words;
+        slot
+    }
+
+    /// Returns the length of the EVM encoding of the proof.
+    pub(super) fn proof_len(&self) -> usize {
+        self.proof.iter().map(ProofItem::encoded_len).sum()
+    }
+
+    pub(super) fn read_instance(&mut self) -> Scalar {
+        let dst = self.alloc(1);
+        self.statements.push(Statement::ReadInstance {
+            dst,
+            index: self.num_instances,
+        });
+        self.num_instances += 1;
+        Scalar::Slot(dst)
+    }
+
+    pub(super) fn read_scalar(&mut self) -> Scalar {
+        let dst = self.alloc(1);
+        let offset = self.proof_len();
+        self.proof.push(ProofItem::Scalar);
+        self.statements.push(Statement::ReadScalar { dst, offset });
+        self.common_scalar(Scalar::Slot(dst));
+        Scalar::Slot(dst)
+    }
+
+    pub(super) fn read_point(&mut self) -> Point {
+        let dst = self.alloc(2);
+        let offset = self.proof_len();
+        self.proof.push(ProofItem::Point);
+        self.statements.push(Statement::ReadPoint { dst, offset });
+        self.statements
+            .push(Statement::AbsorbPoint(Point::Slot(dst)));
+        Point::Slot(dst)
+    }
+
+    pub(super) fn common_scalar(&mut self, scalar: Scalar) {
+        self.statements.push(Statement::AbsorbScalar(scalar));
+    }
+
+    pub(super) fn squeeze(&mut self) -> Scalar {
+        let dst = self.alloc(1);
+        self.statements.push(Statement::Squeeze { dst });
+        Scalar::Slot(dst)
+    }
+
+    pub(super) fn add(&mut self, a: Scalar, b: Scalar) -> Scalar {
+        match (a, b) {
+            (Scalar::Constant(a), Scalar::Constant(b)) => Scalar::Constant(a + b),
+            (Scalar::Constant(zero), other) | (other, Scalar::Constant(zero))
+                if bool::from(zero.is_zero()) =>
+            {
+                other
+            }
+            _ => {
+                let dst = self.alloc(1);
+                self.statements.push(Statement::Add { dst, a, b });
+                Scalar::Slot(dst)
+            }
+        }
+    }
+
+    pub(super) fn sub(&mut self, a: Scalar, b: Scalar) -> Scalar {
+        match (a, b) {
+            (Scalar::Constant(a), Scalar::Constant(b)) => Scalar::Constant(a - b),
+            (other, Scalar::Constant(zero)) if bool::from(zero.is_zero()) => other,
+            _ => {
+                let dst = self.alloc(1);
+                self.statements.push(Statement::Sub { dst, a, b });
+                Scalar::Slot(dst)
+            }
+        }
+    }
+
+    pub(super) fn mul(&mut self, a: Scalar, b: Scalar) -> Scalar {
+        match (a, b) {
+            (Scalar::Constant(a), Scalar::Constant(b)) => Scalar::Constant(a * b),
+            (Scalar::Constant(zero), _) | (_, Scalar::Constant(zero))
+                if bool::from(zero.is_zero()) =>
+            {
+                Scalar::Constant(Fr::zero())
+            }
+            (Scalar::Constant(one), other) | (other, Scalar::Constant(one)) if one == Fr::one() => {
+                other
+            }
+            _ => {
+                let dst = self.alloc(1);
+                self.statements.push(Statement::Mul { dst, a, b });
+                Scalar::Slot(dst)
+            }
+        }
+    }
+
+    pub(super) fn neg(&mut self, a: Scalar) -> Scalar {
+        self.sub(Scalar::Constant(Fr::zero()), a)
+    }
+
+    pub(super) fn square(&mut self, a: Scalar) -> Scalar {
+        self.mul(a, a)
+    }
+
+    /// Inverts `a`. Zero is mapped to zero.
+    pub(super) fn invert(&mut self, a: Scalar) -> Scalar {
+        match a {
+            Scalar::Constant(a) => Scalar::Constant(a.invert().unwrap_or(Fr::zero())),
+            Scalar::Slot(_) => {
+                let dst = self.alloc(1);
+                self.statements.push(Statement::Invert { dst, a });
+                Scalar::Slot(dst)
+            }
+        }
+    }
+
+    /// Inverts every element of `values` with a single inversion.
+    pub(super) fn batch_invert(&mut self, values: &[Scalar]) -> Vec<Scalar> {
+        if values.is_empty() {
+            return vec![];
+        }
+
+        let mut products = Vec::with_capacity(values.len());
+        let mut acc = values[0];
+        products.push(acc);
+        for value in &values[1..] {
+            acc = self.mul(acc, *value);
+            products.push(acc);
+        }
+
+        let mut inverse = self.invert(acc);
+        let mut results = vec![Scalar::Constant(Fr::zero()); values.len()];
+        for i in (1..values.len()).rev() {
+            results[i] = self.mul(inverse, products[i - 1]);
+            inverse = self.mul(inverse, values[i]);
+        }
+        results[0] = inverse;
+
+        results
+    }
+
+    /// Returns `1, base, base^2, ...` up to `base^(n - 1)`.
+    pub(super) fn powers(&mut self, base: Scalar, n: usize) -> Vec<Scalar> {
+        let mut acc = Scalar::Constant(Fr::one());
+        (0..n)
+            .map(|_| {
+                let power = acc;
+                acc = self.mul(acc, base);
+                power
+            })
+            .collect()
+    }
+
+    /// Computes $\sum_i s_i P_i$.
+    pub(super) fn msm(&mut self, terms: &[(Scalar, Point)]) -> Slot {
+        let dst = self.alloc(2);
+        self.statements.push(Statement::ZeroPoint { dst });
+        for &(scalar, point) in terms {
+            self.statements
+                .push(Statement::MulAdd { dst, point, scalar });
+        }
+        dst
+    }
+
+    pub(super) fn pairing(&mut self, lhs: Slot, rhs: Slot, g2: G2Affine, s_g2: G2Affine) {
+        self.statements
+            .push(Statement::Pairing { lhs, rhs, g2, s_g2 });
+    }
+}
+
+#[cfg(test)]
+mod interpreter {
+    use std::collections::HashMap;
+
+    use ff::{Field, PrimeField};
+    use group::{prime::PrimeCurveAffine, Curve, Group};
+    use halo2curves::{
+        bn256::{Bn256, Fq, Fr, G1Affine, G1},
+        pairing::{MillerLoopResult, MultiMillerLoop},
+        CurveAffine, FieldExt,
+    };
+    use sha3::{Digest, Keccak256};
+
+    use super::{Point, Program, Scalar, Statement};
+
+    fn read_word(calldata: &[u8], offset: usize) -> Option<[u8; 32]> {
+        calldata
+            .get(offset..offset + 32)
+            .map(|word| word.try_into().unwrap())
+    }
+
+    fn word_to_usize(word: [u8; 32]) -> usize {
+        word[24..]
+            .iter()
+            .fold(0, |acc, byte| (acc << 8) | *byte as usize)
+    }
+
+    fn word_to_field<F: PrimeField<Repr = [u8; 32]>>(mut word: [u8; 32]) -> Option<F> {
+        word.reverse();
+        F::from_repr(word).into()
+    }
+
+    impl Program {
+        /// Runs the program on the ABI-encoded arguments of `verifyProof`,
+        /// checking the same conditions as the rendered contract.
+        pub(in crate::plonk::evm) fn execute(&self, calldata: &[u8]) -> bool {
+            let array = |argument: usize| -> Option<(usize, usize)> {
+                let offset = 4 + word_to_usize(read_word(calldata, 4 + 32 * argument)?);
+                Some((offset + 32, word_to_usize(read_word(calldata, offset)?)))
+            };
+            let (proof_offset, proof_len) = array(0).unwrap();
+            let (instances_offset, num_instances) = array(1).unwrap();
+            if proof_len != self.proof_len() || num_instances != self.num_instances {
+                return false;
+            }
+
+            let mut scalars: HashMap<usize, Fr> = HashMap::new();
+            let mut points: HashMap<usize, G1> = HashMap::new();
+            let mut transcript = b"Halo2-Transcript".to_vec();
+            let mut success = true;
+
+            let scalar = |scalars: &HashMap<usize, Fr>, scalar: Scalar| match scalar {
+                Scalar::Slot(slot) => scalars[&slot],
+                Scalar::Constant(value) => value,
+            };
+            let point = |points: &HashMap<usize, G1>, point: Point| match point {
+                Point::Slot(slot) => points[&slot],
+                Point::Constant(value) => value.to_curve(),
+            };
+
+            for statement in self.statements.iter() {
+                match *statement {
+                    Statement::ReadInstance { dst, index } => {
+                        let word = read_word(calldata, instances_offset + 32 * index).unwrap();
+                        let value = word_to_field::<Fr>(word);
+                        success &= value.is_some();
+                        scalars.insert(dst, value.unwrap_or_else(Fr::zero));
+                    }
+                    Statement::ReadScalar { dst, offset } => {
+                        let word = read_word(calldata, proof_offset + offset).unwrap();
+                        let value = word_to_field::<Fr>(word);
+                        success &= value.is_some();
+                        scalars.insert(dst, value.unwrap_or_else(Fr::zero));
+                    }
+                    Statement::ReadPoint { dst, offset } => {
+                        let x = read_word(calldata, proof_offset + offset).unwrap();
+                        let y = read_word(calldata, proof_offset + offset + 32).unwrap();
+                        let value = word_to_field::<Fq>(x)
+                            .zip(word_to_field::<Fq>(y))
+                            .filter(|(x, y)| !bool::from(x.is_zero() & y.is_zero()))
+                            .and_then(|(x, y)| Option::from(G1Affine::from_xy(x, y)));
+                        success &= value.is_some();
+                        points.insert(dst, value.unwrap_or_else(G1Affine::identity).to_curve());
+                    }
+                    Statement::AbsorbScalar(value) => {
+                        transcript.push(2);
+                        transcript.extend_from_slice(&scalar(&scalars, value).to_repr());
+                    }
+                    Statement::AbsorbPoint(value) => {
+                        let value = point(&points, value).to_affine();
+                        let coordinates = value.coordinates().unwrap();
+                        transcript.push(1);
+                        transcript.extend_from_slice(&coordinates.x().to_repr());
+                        transcript.extend_from_slice(&coordinates.y().to_repr());
+                    }
+                    Statement::Squeeze { dst } => {
+                        transcript.push(0);
+                        let mut wide = [0u8; 64];
+                        for (suffix, half) in [10u8, 11].iter().zip(wide.chunks_mut(32)) {
+                            let mut hasher = Keccak256::new();
+                            hasher.update(&transcript);
+                            hasher.update(&[*suffix]);
+                            half.copy_from_slice(hasher.finalize().as_slice());
+                        }
+                        scalars.insert(dst, Fr::from_bytes_wide(&wide));
+                    }
+                    Statement::Add { dst, a, b } => {
+                        let value = scalar(&scalars, a) + scalar(&scalars, b);
+                        scalars.insert(dst, value);
+                    }
+                    Statement::Sub { dst, a, b } => {
+                        let value = scalar(&scalars, a) - scalar(&scalars, b);
+                        scalars.insert(dst, value);
+                    }
+                    Statement::Mul { dst, a, b } => {
+                        let value = scalar(&scalars, a) * scalar(&scalars, b);
+                        scalars.insert(dst, value);
+                    }
+                    Statement::Invert { dst, a } => {
+                        let value = scalar(&scalars, a).invert().unwrap_or(Fr::zero());
+                        scalars.insert(dst, value);
+                    }
+                    Statement::ZeroPoint { dst } => {
+                        points.insert(dst, G1::identity());
+                    }
+                    Statement::MulAdd {
+                        dst,
+                        point: value,
+                        scalar: by,
+                    } => {
+                        let term = point(&points, value) * scalar(&scalars, by);
+                        *points.get_mut(&dst).unwrap() += term;
+                    }
+                    Statement::Pairing { lhs, rhs, g2, s_g2 } => {
+                        let s_g2 = <Bn256 as MultiMillerLoop>::G2Prepared::from(s_g2);
+                        let n_g2 = <Bn256 as MultiMillerLoop>::G2Prepared::from(-g2);
+                        let (lhs, rhs) = (points[&lhs].to_affine(), points[&rhs].to_affine());
+                        success &= bool::from(
+                            Bn256::multi_miller_loop(&[(&lhs, &s_g2), (&rhs, &n_g2)])
+                                .final_exponentiation()
+                                .is_identity(),
+                        );
+                    }
+                }
+            }
+
+            success
+        }
+    }
+}
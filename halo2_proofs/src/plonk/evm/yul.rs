@@ -0,0 +1,345 @@
+use std::fmt::Write;
+
+use ff::PrimeField;
+use halo2curves::{
+    bn256::{Fq, Fq2, Fr, G1Affine, G2Affine},
+    Coordinates, CurveAffine, FieldExt,
+};
+
+use super::program::{Point, Program, Scalar, Slot, Statement};
+
+/// Memory used as input and output of precompile calls.
+const SCRATCH: usize = 0x00;
+/// The start of the transcript buffer, after the scratch space.
+const TRANSCRIPT: usize = 0x180;
+
+fn word<F: PrimeField<Repr = [u8; 32]>>(value: &F) -> String {
+    let mut repr = value.to_repr();
+    repr.reverse();
+    let digits: String = repr.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("0x{}", digits)
+}
+
+fn point_words(point: &G1Affine) -> (String, String) {
+    let coordinates: Option<Coordinates<G1Affine>> = point.coordinates().into();
+    let (x, y) = coordinates
+        .map(|coordinates| (*coordinates.x(), *coordinates.y()))
+        .unwrap_or_default();
+    (word(&x), word(&y))
+}
+
+/// Returns the words of a G2 point in the order the pairing precompile expects.
+fn g2_words(point: &G2Affine) -> [String; 4] {
+    let coordinates: Coordinates<G2Affine> = point.coordinates().unwrap();
+    let (x, y): (&Fq2, &Fq2) = (coordinates.x(), coordinates.y());
+    [word(&x.c1), word(&x.c0), word(&y.c1), word(&y.c0)]
+}
+
+struct Renderer {
+    slots: usize,
+    transcript_len: usize,
+    body: String,
+}
+
+impl Renderer {
+    fn new(program: &Program) -> Self {
+        // Every absorbed item is written after a one-byte prefix, and every
+        // squeeze appends a prefix and temporarily writes a suffix.
+        let transcript_len = 16
+            + program
+                .statements
+                .iter()
+                .map(|statement| match statement {
+                    Statement::AbsorbScalar(_) => 33,
+                    Statement::AbsorbPoint(_) => 65,
+                    Statement::Squeeze { .. } => 1,
+                    _ => 0,
+                })
+                .sum::<usize>();
+        let slots = (TRANSCRIPT + transcript_len + 1 + 31) / 32 * 32;
+
+        Renderer {
+            slots,
+            transcript_len: 16,
+            body: String::new(),
+        }
+    }
+
+    fn slot(&self, slot: Slot) -> String {
+        format!("{:#x}", self.slots + 32 * slot)
+    }
+
+    fn scalar(&self, scalar: &Scalar) -> String {
+        match scalar {
+            Scalar::Slot(slot) => format!("mload({})", self.slot(*slot)),
+            Scalar::Constant(value) => word(value),
+        }
+    }
+
+    fn point(&self, point: &Point) -> (String, String) {
+        match point {
+            Point::Slot(slot) => (
+                format!("mload({})", self.slot(*slot)),
+                format!("mload({})", self.slot(*slot + 1)),
+            ),
+            Point::Constant(value) => point_words(value),
+        }
+    }
+
+    fn line(&mut self, line: String) {
+        writeln!(self.body, "            {}", line).unwrap();
+    }
+
+    /// Returns the address of the next `len` bytes of the transcript.
+    fn append(&mut self, len: usize) -> usize {
+        let address = TRANSCRIPT + self.transcript_len;
+        self.transcript_len += len;
+        address
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::ReadInstance { dst, index } => {
+                let line = format!(
+                    "success := and(success, read_scalar(add(instances.offset, {:#x}), {}))",
+                    32 * index,
+                    self.slot(*dst)
+                );
+                self.line(line);
+            }
+            Statement::ReadScalar { dst, offset } => {
+                let line = format!(
+                    "success := and(success, read_scalar(add(proof.offset, {:#x}), {}))",
+                    offset,
+                    self.slot(*dst)
+                );
+                self.line(line);
+            }
+            Statement::ReadPoint { dst, offset } => {
+                let line = format!(
+                    "success := and(success, read_point(add(proof.offset, {:#x}), {}))",
+                    offset,
+                    self.slot(*dst)
+                );
+                self.line(line);
+            }
+            Statement::AbsorbScalar(scalar) => {
+                let address = self.append(33);
+                let value = self.scalar(scalar);
+                self.line(format!("mstore8({:#x}, 2)", address));
+                self.line(format!("mstore({:#x}, reverse({}))", address + 1, value));
+            }
+            Statement::AbsorbPoint(point) => {
+                let address = self.append(65);
+                let (x, y) = self.point(point);
+                self.line(format!("mstore8({:#x}, 1)", address));
+                self.line(format!("mstore({:#x}, reverse({}))", address + 1, x));
+                self.line(format!("mstore({:#x}, reverse({}))", address + 33, y));
+            }
+            Statement::Squeeze { dst } => {
+                let address = self.append(1);
+                self.line(format!("mstore8({:#x}, 0)", address));
+                let line = format!(
+                    "mstore({}, squeeze({:#x}))",
+                    self.slot(*dst),
+                    self.transcript_len
+                );
+                self.line(line);
+            }
+            Statement::Add { dst, a, b } => {
+                let line = format!(
+                    "mstore({}, addmod({}, {}, {}))",
+                    self.slot(*dst),
+                    self.scalar(a),
+                    self.scalar(b),
+                    Fr::MODULUS
+                );
+                self.line(line);
+            }
+            Statement::Sub { dst, a, b } => {
+                let line = format!(
+                    "mstore({}, addmod({}, sub({r}, {}), {r}))",
+                    self.slot(*dst),
+                    self.scalar(a),
+                    self.scalar(b),
+                    r = Fr::MODULUS
+                );
+                self.line(line);
+            }
+            Statement::Mul { dst, a, b } => {
+                let line = format!(
+                    "mstore({}, mulmod({}, {}, {}))",
+                    self.slot(*dst),
+                    self.scalar(a),
+                    self.scalar(b),
+                    Fr::MODULUS
+                );
+                self.line(line);
+            }
+            Statement::Invert { dst, a } => {
+                let line = format!(
+                    "success := and(success, invert({}, {}))",
+                    self.scalar(a),
+                    self.slot(*dst)
+                );
+                self.line(line);
+            }
+            Statement::ZeroPoint { dst } => {
+                let line = format!(
+                    "mstore({}, 0) mstore({}, 0)",
+                    self.slot(*dst),
+                    self.slot(*dst + 1)
+                );
+                self.line(line);
+            }
+            Statement::MulAdd { dst, point, scalar } => {
+                let (x, y) = self.point(point);
+                let line = format!(
+                    "success := and(success, mul_add({}, {}, {}, {}))",
+                    self.slot(*dst),
+                    x,
+                    y,
+                    self.scalar(scalar)
+                );
+                self.line(line);
+            }
+            Statement::Pairing { lhs, rhs, g2, s_g2 } => {
+                let mut words = vec![
+                    format!("mload({})", self.slot(*lhs)),
+                    format!("mload({})", self.slot(*lhs + 1)),
+                ];
+                words.extend(g2_words(s_g2));
+                words.push(format!("mload({})", self.slot(*rhs)));
+                words.push(format!("mload({})", self.slot(*rhs + 1)));
+                words.extend(g2_words(&-*g2));
+                for (i, word) in words.into_iter().enumerate() {
+                    self.line(format!("mstore({:#x}, {})", SCRATCH + 32 * i, word));
+                }
+                self.line(format!(
+                    "success := and(success, staticcall(gas(), 0x08, {:#x}, 0x180, {:#x}, 0x20))",
+                    SCRATCH, SCRATCH
+                ));
+                self.line(format!("success := and(success, mload({:#x}))", SCRATCH));
+            }
+        }
+    }
+}
+
+/// Renders `program` as the `Halo2Verifier` contract.
+pub(super) fn render(program: &Program) -> String {
+    let mut renderer = Renderer::new(program);
+    for statement in program.statements.iter() {
+        renderer.statement(statement);
+    }
+
+    let r = Fr::MODULUS;
+    let q = Fq::MODULUS;
+    let r_minus_two = word(&-Fr::from(2));
+    let mut wide = [0u8; 64];
+    wide[32] = 1;
+    let two_256 = word(&Fr::from_bytes_wide(&wide));
+    let mut domain = [0u8; 32];
+    domain[..16].copy_from_slice(b"Halo2-Transcript");
+    let domain: String = domain.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    format!(
+        r#"// SPDX-License-Identifier: MIT
+// This file was generated by halo2_proofs. Do not edit.
+
+pragma solidity ^0.8.0;
+
+contract Halo2Verifier {{
+    /// Verifies a proof created with the Keccak256 transcript, whose points
+    /// are uncompressed and whose points and scalars are big-endian words.
+    function verifyProof(bytes calldata proof, uint256[] calldata instances)
+        external
+        view
+        returns (bool)
+    {{
+        assembly {{
+            // Reverses the bytes of a word.
+            function reverse(v) -> r {{
+                r := v
+                r := or(shr(8, and(r, 0xff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00)), shl(8, and(r, 0x00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff)))
+                r := or(shr(16, and(r, 0xffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000)), shl(16, and(r, 0x0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff)))
+                r := or(shr(32, and(r, 0xffffffff00000000ffffffff00000000ffffffff00000000ffffffff00000000)), shl(32, and(r, 0x00000000ffffffff00000000ffffffff00000000ffffffff00000000ffffffff)))
+                r := or(shr(64, and(r, 0xffffffffffffffff0000000000000000ffffffffffffffff0000000000000000)), shl(64, and(r, 0x0000000000000000ffffffffffffffff0000000000000000ffffffffffffffff)))
+                r := or(shr(128, r), shl(128, r))
+            }}
+
+            // Copies a scalar from calldata to memory, checking that it is canonical.
+            function read_scalar(cd, dst) -> ok {{
+                let v := calldataload(cd)
+                mstore(dst, v)
+                ok := lt(v, {r})
+            }}
+
+            // Copies a point from calldata to memory, checking that it is on the curve.
+            function read_point(cd, dst) -> ok {{
+                let q := {q}
+                let x := calldataload(cd)
+                let y := calldataload(add(cd, 0x20))
+                mstore(dst, x)
+                mstore(add(dst, 0x20), y)
+                ok := and(lt(x, q), lt(y, q))
+                ok := and(ok, eq(mulmod(y, y, q), addmod(mulmod(x, mulmod(x, x, q), q), 3, q)))
+            }}
+
+            // Squeezes a challenge from the first `len` bytes of the transcript.
+            function squeeze(len) -> c {{
+                let end := add({transcript:#x}, len)
+                mstore8(end, 10)
+                let lo := keccak256({transcript:#x}, add(len, 1))
+                mstore8(end, 11)
+                let hi := keccak256({transcript:#x}, add(len, 1))
+                c := addmod(reverse(lo), mulmod(reverse(hi), {two_256}, {r}), {r})
+            }}
+
+            // Writes the inverse of `a` to `dst`.
+            function invert(a, dst) -> ok {{
+                mstore({scratch:#x}, 0x20)
+                mstore({scratch_1:#x}, 0x20)
+                mstore({scratch_2:#x}, 0x20)
+                mstore({scratch_3:#x}, a)
+                mstore({scratch_4:#x}, {r_minus_two})
+                mstore({scratch_5:#x}, {r})
+                ok := staticcall(gas(), 0x05, {scratch:#x}, 0xc0, dst, 0x20)
+            }}
+
+            // Adds `s * (x, y)` to the point at `acc`.
+            function mul_add(acc, x, y, s) -> ok {{
+                mstore({scratch:#x}, x)
+                mstore({scratch_1:#x}, y)
+                mstore({scratch_2:#x}, s)
+                ok := staticcall(gas(), 0x07, {scratch:#x}, 0x60, {scratch:#x}, 0x40)
+                mstore({scratch_2:#x}, mload(acc))
+                mstore({scratch_3:#x}, mload(add(acc, 0x20)))
+                ok := and(ok, staticcall(gas(), 0x06, {scratch:#x}, 0x80, acc, 0x40))
+            }}
+
+            let success := and(eq(proof.length, {proof_len:#x}), eq(instances.length, {num_instances:#x}))
+            mstore({transcript:#x}, 0x{domain})
+{body}
+            mstore(0x00, success)
+            return(0x00, 0x20)
+        }}
+    }}
+}}
+"#,
+        r = r,
+        q = q,
+        two_256 = two_256,
+        r_minus_two = r_minus_two,
+        transcript = TRANSCRIPT,
+        scratch = SCRATCH,
+        scratch_1 = SCRATCH + 0x20,
+        scratch_2 = SCRATCH + 0x40,
+        scratch_3 = SCRATCH + 0x60,
+        scratch_4 = SCRATCH + 0x80,
+        scratch_5 = SCRATCH + 0xa0,
+        proof_len = program.proof_len(),
+        num_instances = program.num_instances,
+        domain = domain,
+        body = renderer.body,
+    )
+}
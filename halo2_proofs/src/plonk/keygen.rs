@@ -10,9 +10,8 @@ use super::{
         Advice, Any, Assignment, Circuit, Column, ConstraintSystem, Fixed, FloorPlanner, Instance,
         Selector,
     },
-    evaluation::Evaluator,
-    permutation, Assigned, Challenge, Error, Expression, LagrangeCoeff, Polynomial, ProvingKey,
-    VerifyingKey,
+    permutation, Assigned, Challenge, Error, Expression, InMemoryStore, LagrangeCoeff, Polynomial,
+    ProvingKey, VerifyingKey,
 };
 use crate::{
     arithmetic::{parallelize, CurveAffine},
@@ -45,6 +44,167 @@ where
     (domain, cs, config)
 }
 
+/// Assignment that measures the number of rows used by a circuit, without any
+/// bound on the number of rows available.
+#[derive(Debug)]
+struct Measurement {
+    /// One more than the largest row used so far.
+    rows: usize,
+}
+
+impl Measurement {
+    fn use_row(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+}
+
+impl<F: Field> Assignment<F> for Measurement {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about regions in this context.
+    }
+
+    fn exit_region(&mut self) {
+        // Do nothing; we don't care about regions in this context.
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        // The row is accounted for when the instance cell is copied into the circuit.
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        _: Column<Any>,
+        left_row: usize,
+        _: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.use_row(left_row);
+        self.use_row(right_row);
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        from_row: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        // Filling requires at least one row to fill.
+        self.use_row(from_row);
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn annotate_column<A, AR>(&mut self, _annotation: A, _column: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // Do nothing
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+}
+
+/// Returns the smallest `k` for which `circuit` can be synthesized with instance
+/// columns of the given lengths.
+///
+/// This runs the circuit's floor planner without any bound on the number of rows,
+/// and then accounts for the rows reserved for blinding factors. The result can be
+/// passed to [`MockProver::run`], [`keygen_vk`] and [`ParamsProver::new`]; a single
+/// set of parameters for a large `k` can also be reused for smaller circuits with
+/// [`Params::downsized`].
+///
+/// [`MockProver::run`]: crate::dev::MockProver::run
+/// [`ParamsProver::new`]: crate::poly::commitment::ParamsProver::new
+pub fn minimum_k<F, ConcreteCircuit>(
+    circuit: &ConcreteCircuit,
+    instance_lengths: &[usize],
+) -> Result<u32, Error>
+where
+    F: Field,
+    ConcreteCircuit: Circuit<F>,
+{
+    let mut cs = ConstraintSystem::default();
+    let config = ConcreteCircuit::configure(&mut cs);
+
+    let mut measurement = Measurement { rows: 0 };
+    ConcreteCircuit::FloorPlanner::synthesize(
+        &mut measurement,
+        circuit,
+        config,
+        cs.constants.clone(),
+    )?;
+
+    let usable_rows = instance_lengths
+        .iter()
+        .fold(measurement.rows, |rows, len| rows.max(*len));
+    let rows = (usable_rows + cs.blinding_factors() + 1).max(cs.minimum_rows());
+
+    Ok(rows.next_power_of_two().trailing_zeros())
+}
+
 /// Assembly to be used in circuit synthesis.
 #[derive(Debug)]
 struct Assembly<F: Field> {
@@ -350,18 +510,16 @@ where
         }
     });
 
-    // Compute the optimized evaluation data structure
-    let ev = Evaluator::new(&vk.cs);
-
-    Ok(ProvingKey {
+    Ok(ProvingKey::from_store(
         vk,
-        l0,
-        l_last,
-        l_active_row,
-        fixed_values: fixed,
-        fixed_polys,
-        fixed_cosets,
-        permutation: permutation_pk,
-        ev,
-    })
+        InMemoryStore {
+            l0,
+            l_last,
+            l_active_row,
+            fixed_values: fixed,
+            fixed_polys,
+            fixed_cosets,
+            permutation: permutation_pk,
+        },
+    ))
 }
@@ -0,0 +1,93 @@
+use super::circuit::Expression;
+use ff::Field;
+use std::fmt::{self, Debug};
+
+pub(crate) mod prover;
+pub(crate) mod verifier;
+
+/// A lookup argument based on logarithmic derivatives ("LogUp").
+///
+/// Any number of input tuples can be looked up in the same table. Instead of
+/// the permuted input and table columns of the lookup argument, the prover
+/// commits to a single multiplicity column `m(X)` counting how often each
+/// table row is looked up, and a running sum `\phi(X)` of the fractions
+/// `\sum_j 1/(f_j(X) + \beta) - m(X)/(t(X) + \beta)`.
+#[derive(Clone)]
+pub struct Argument<F: Field> {
+    pub(crate) name: &'static str,
+    pub(crate) input_expressions: Vec<Vec<Expression<F>>>,
+    pub(crate) table_expressions: Vec<Expression<F>>,
+}
+
+impl<F: Field> Debug for Argument<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Argument")
+            .field("input_expressions", &self.input_expressions)
+            .field("table_expressions", &self.table_expressions)
+            .finish()
+    }
+}
+
+impl<F: Field> Argument<F> {
+    /// Constructs a new LogUp lookup argument.
+    ///
+    /// Each entry of `input_expressions` is a tuple of expressions that must
+    /// appear as a row of `table_expressions`.
+    pub fn new(
+        name: &'static str,
+        input_expressions: Vec<Vec<Expression<F>>>,
+        table_expressions: Vec<Expression<F>>,
+    ) -> Self {
+        assert!(!input_expressions.is_empty());
+        for input in input_expressions.iter() {
+            assert_eq!(input.len(), table_expressions.len());
+        }
+
+        Argument {
+            name,
+            input_expressions,
+            table_expressions,
+        }
+    }
+
+    pub(crate) fn required_degree(&self) -> usize {
+        // The running sum should start at zero.
+        // degree 2:
+        // l_0(X) * \phi(X) = 0
+        //
+        // The running sum over all usable rows should be zero.
+        // degree 2:
+        // l_last(X) * \phi(X) = 0
+        //
+        // Enable the running sum argument for only the rows involved.
+        // degree (2 + input_degree + table_degree), where input_degree is the
+        // sum of the degrees of all inputs:
+        // (1 - (l_last(X) + l_blind(X))) * (
+        //   (\phi(\omega X) - \phi(X)) \prod_j (f_j(X) + \beta) (t(X) + \beta)
+        //   - (t(X) + \beta) \sum_j \prod_{k \neq j} (f_k(X) + \beta)
+        //   + m(X) \prod_j (f_j(X) + \beta)
+        // ) = 0
+        let input_degree: usize = self
+            .input_expressions
+            .iter()
+            .map(|input| input.iter().map(|expr| expr.degree()).max().unwrap_or(1))
+            .map(|degree| std::cmp::max(degree, 1))
+            .sum();
+        let mut table_degree = 1;
+        for expr in self.table_expressions.iter() {
+            table_degree = std::cmp::max(table_degree, expr.degree());
+        }
+
+        2 + input_degree + table_degree
+    }
+
+    /// Returns inputs of this argument
+    pub fn input_expressions(&self) -> &Vec<Vec<Expression<F>>> {
+        &self.input_expressions
+    }
+
+    /// Returns table of this argument
+    pub fn table_expressions(&self) -> &Vec<Expression<F>> {
+        &self.table_expressions
+    }
+}
@@ -0,0 +1,323 @@
+use super::super::{
+    circuit::Expression, ArgumentKind, ChallengeBeta, ChallengeTheta, ChallengeX, Error, ProvingKey,
+};
+use super::Argument;
+use crate::plonk::evaluation::evaluate;
+use crate::{
+    arithmetic::{eval_polynomial, parallelize, CurveAffine, FieldExt},
+    poly::{
+        commitment::{Blind, Params},
+        Coeff, EvaluationDomain, LagrangeCoeff, Polynomial, ProverQuery, Rotation,
+    },
+    transcript::{EncodedChallenge, TranscriptWrite},
+};
+use group::{
+    ff::{BatchInvert, Field},
+    Curve,
+};
+use rand_core::RngCore;
+use std::{
+    collections::BTreeMap,
+    iter,
+    ops::{Mul, MulAssign},
+};
+
+#[derive(Debug)]
+pub(in crate::plonk) struct Multiplicities<C: CurveAffine> {
+    compressed_input_expressions: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
+    compressed_table_expression: Polynomial<C::Scalar, LagrangeCoeff>,
+    multiplicity_expression: Polynomial<C::Scalar, LagrangeCoeff>,
+    multiplicity_poly: Polynomial<C::Scalar, Coeff>,
+    multiplicity_blind: Blind<C::Scalar>,
+}
+
+#[derive(Debug)]
+pub(in crate::plonk) struct Committed<C: CurveAffine> {
+    pub(in crate::plonk) multiplicity_poly: Polynomial<C::Scalar, Coeff>,
+    multiplicity_blind: Blind<C::Scalar>,
+    pub(in crate::plonk) running_sum_poly: Polynomial<C::Scalar, Coeff>,
+    running_sum_blind: Blind<C::Scalar>,
+}
+
+pub(in crate::plonk) struct Evaluated<C: CurveAffine> {
+    constructed: Committed<C>,
+}
+
+impl<F: FieldExt> Argument<F> {
+    /// Given a LogUp lookup with inputs [A^j_0, A^j_1, ..., A^j_{m-1}] and table expressions
+    /// [S_0, S_1, ..., S_{m-1}], this method
+    /// - constructs A^j_compressed = \theta^{m-1} A^j_0 + ... + A^j_{m-1} for each input j
+    ///   and S_compressed = \theta^{m-1} S_0 + ... + S_{m-1},
+    /// - counts how many times each row of S_compressed occurs across all of the
+    ///   compressed inputs, obtaining the multiplicity column m, and
+    /// - commits to m.
+    /// The Multiplicities<C> struct is then used to build the running sum.
+    pub(in crate::plonk) fn commit_multiplicities<
+        'a,
+        'params: 'a,
+        C,
+        P: Params<'params, C>,
+        E: EncodedChallenge<C>,
+        R: RngCore,
+        T: TranscriptWrite<C, E>,
+    >(
+        &self,
+        index: usize,
+        pk: &ProvingKey<C>,
+        params: &P,
+        domain: &EvaluationDomain<C::Scalar>,
+        theta: ChallengeTheta<C>,
+        advice_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
+        fixed_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
+        instance_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
+        challenges: &'a [C::Scalar],
+        mut rng: R,
+        transcript: &mut T,
+    ) -> Result<Multiplicities<C>, Error>
+    where
+        C: CurveAffine<ScalarExt = F>,
+        C::Curve: Mul<F, Output = C::Curve> + MulAssign<F>,
+    {
+        // Closure to get values of expressions and compress them
+        let compress_expressions = |expressions: &[Expression<C::Scalar>]| {
+            let compressed_expression = expressions
+                .iter()
+                .map(|expression| {
+                    pk.vk.domain.lagrange_from_vec(evaluate(
+                        expression,
+                        params.n() as usize,
+                        1,
+                        fixed_values,
+                        advice_values,
+                        instance_values,
+                        challenges,
+                    ))
+                })
+                .fold(domain.empty_lagrange(), |acc, expression| {
+                    acc * *theta + &expression
+                });
+            compressed_expression
+        };
+
+        // Get values of input expressions involved in the lookup and compress them
+        let compressed_input_expressions: Vec<_> = self
+            .input_expressions
+            .iter()
+            .map(|input| compress_expressions(input))
+            .collect();
+
+        // Get values of table expressions involved in the lookup and compress them
+        let compressed_table_expression = compress_expressions(&self.table_expressions);
+
+        let blinding_factors = pk.vk.cs.blinding_factors();
+        let usable_rows = params.n() as usize - (blinding_factors + 1);
+
+        // Map each table value to the first usable row it appears in.
+        let mut table_rows: BTreeMap<C::Scalar, usize> = BTreeMap::new();
+        for (row, value) in compressed_table_expression
+            .iter()
+            .take(usable_rows)
+            .enumerate()
+        {
+            table_rows.entry(*value).or_insert(row);
+        }
+
+        // Count how many times each table row is looked up across all inputs.
+        let mut multiplicities = vec![0u64; usable_rows];
+        for compressed_input_expression in compressed_input_expressions.iter() {
+            for value in compressed_input_expression.iter().take(usable_rows) {
+                // Return error if input value not found
+                let row = table_rows
+                    .get(value)
+                    .ok_or(Error::ConstraintSystemFailure(ArgumentKind::LogUp(index)))?;
+                multiplicities[*row] += 1;
+            }
+        }
+
+        // Pad the multiplicities with random blinding factors.
+        let multiplicity_expression = pk.vk.domain.lagrange_from_vec(
+            multiplicities
+                .into_iter()
+                .map(C::Scalar::from)
+                .chain((0..(blinding_factors + 1)).map(|_| C::Scalar::random(&mut rng)))
+                .collect(),
+        );
+
+        let multiplicity_blind = Blind(C::Scalar::random(&mut rng));
+        let multiplicity_commitment = params
+            .commit_lagrange(&multiplicity_expression, multiplicity_blind)
+            .to_affine();
+        let multiplicity_poly = pk
+            .vk
+            .domain
+            .lagrange_to_coeff(multiplicity_expression.clone());
+
+        // Hash multiplicity commitment
+        transcript.write_point(multiplicity_commitment)?;
+
+        Ok(Multiplicities {
+            compressed_input_expressions,
+            compressed_table_expression,
+            multiplicity_expression,
+            multiplicity_poly,
+            multiplicity_blind,
+        })
+    }
+}
+
+impl<C: CurveAffine> Multiplicities<C> {
+    /// Given a LogUp lookup with its multiplicity column m, this method constructs
+    /// the running sum
+    ///   \phi(\omega^{i+1}) = \phi(\omega^i) + \sum_j 1/(f_j(\omega^i) + \beta) - m(\omega^i)/(t(\omega^i) + \beta)
+    /// starting at \phi(1) = 0. The running sum is used to populate the
+    /// Committed<C> struct which is returned.
+    pub(in crate::plonk) fn commit_running_sum<
+        'params,
+        P: Params<'params, C>,
+        E: EncodedChallenge<C>,
+        R: RngCore,
+        T: TranscriptWrite<C, E>,
+    >(
+        self,
+        pk: &ProvingKey<C>,
+        params: &P,
+        beta: ChallengeBeta<C>,
+        mut rng: R,
+        transcript: &mut T,
+    ) -> Result<Committed<C>, Error> {
+        let blinding_factors = pk.vk.cs.blinding_factors();
+        let usable_rows = params.n() as usize - (blinding_factors + 1);
+
+        // Closure to compute 1/(value + \beta) over the usable rows
+        let invert_shifted = |values: &Polynomial<C::Scalar, LagrangeCoeff>| {
+            let mut inverted = values[..usable_rows].to_vec();
+            parallelize(&mut inverted, |inverted, _| {
+                for value in inverted.iter_mut() {
+                    *value += &*beta;
+                }
+            });
+            inverted.iter_mut().batch_invert();
+            inverted
+        };
+
+        // -m(\omega^i) / (t(\omega^i) + \beta)
+        let mut summands = invert_shifted(&self.compressed_table_expression);
+        parallelize(&mut summands, |summands, start| {
+            for (summand, multiplicity) in summands
+                .iter_mut()
+                .zip(self.multiplicity_expression[start..].iter())
+            {
+                *summand = -(*summand * multiplicity);
+            }
+        });
+
+        // + \sum_j 1 / (f_j(\omega^i) + \beta)
+        for compressed_input_expression in self.compressed_input_expressions.iter() {
+            let inverted = invert_shifted(compressed_input_expression);
+            parallelize(&mut summands, |summands, start| {
+                for (summand, inverted) in summands.iter_mut().zip(inverted[start..].iter()) {
+                    *summand += inverted;
+                }
+            });
+        }
+
+        // Compute the evaluations of the running sum over our domain,
+        // starting with \phi[0] = 0
+        let phi = iter::once(C::Scalar::zero())
+            .chain(summands)
+            .scan(C::Scalar::zero(), |state, cur| {
+                *state += &cur;
+                Some(*state)
+            })
+            // Take all rows including the "last" row which should
+            // be zero for a valid lookup
+            .take(usable_rows + 1)
+            // Chain random blinding factors.
+            .chain((0..blinding_factors).map(|_| C::Scalar::random(&mut rng)))
+            .collect::<Vec<_>>();
+        assert_eq!(phi.len(), params.n() as usize);
+        let phi = pk.vk.domain.lagrange_from_vec(phi);
+
+        #[cfg(feature = "sanity-checks")]
+        // This test works only with intermediate representations in this method.
+        // It can be used for debugging purposes.
+        {
+            // l_0(X) * \phi(X) = 0
+            assert_eq!(phi[0], C::Scalar::zero());
+
+            // l_last(X) * \phi(X) = 0
+            assert_eq!(phi[usable_rows], C::Scalar::zero());
+        }
+
+        let running_sum_blind = Blind(C::Scalar::random(rng));
+        let running_sum_commitment = params.commit_lagrange(&phi, running_sum_blind).to_affine();
+        let running_sum_poly = pk.vk.domain.lagrange_to_coeff(phi);
+
+        // Hash running sum commitment
+        transcript.write_point(running_sum_commitment)?;
+
+        Ok(Committed::<C> {
+            multiplicity_poly: self.multiplicity_poly,
+            multiplicity_blind: self.multiplicity_blind,
+            running_sum_poly,
+            running_sum_blind,
+        })
+    }
+}
+
+impl<C: CurveAffine> Committed<C> {
+    pub(in crate::plonk) fn evaluate<E: EncodedChallenge<C>, T: TranscriptWrite<C, E>>(
+        self,
+        pk: &ProvingKey<C>,
+        x: ChallengeX<C>,
+        transcript: &mut T,
+    ) -> Result<Evaluated<C>, Error> {
+        let domain = &pk.vk.domain;
+        let x_next = domain.rotate_omega(*x, Rotation::next());
+
+        let multiplicity_eval = eval_polynomial(&self.multiplicity_poly, *x);
+        let running_sum_eval = eval_polynomial(&self.running_sum_poly, *x);
+        let running_sum_next_eval = eval_polynomial(&self.running_sum_poly, x_next);
+
+        // Hash each evaluation
+        for eval in iter::empty()
+            .chain(Some(multiplicity_eval))
+            .chain(Some(running_sum_eval))
+            .chain(Some(running_sum_next_eval))
+        {
+            transcript.write_scalar(eval)?;
+        }
+
+        Ok(Evaluated { constructed: self })
+    }
+}
+
+impl<C: CurveAffine> Evaluated<C> {
+    pub(in crate::plonk) fn open<'a>(
+        &'a self,
+        pk: &'a ProvingKey<C>,
+        x: ChallengeX<C>,
+    ) -> impl Iterator<Item = ProverQuery<'a, C>> + Clone {
+        let x_next = pk.vk.domain.rotate_omega(*x, Rotation::next());
+
+        iter::empty()
+            // Open multiplicity commitment at x
+            .chain(Some(ProverQuery {
+                point: *x,
+                poly: &self.constructed.multiplicity_poly,
+                blind: self.constructed.multiplicity_blind,
+            }))
+            // Open running sum commitment at x
+            .chain(Some(ProverQuery {
+                point: *x,
+                poly: &self.constructed.running_sum_poly,
+                blind: self.constructed.running_sum_blind,
+            }))
+            // Open running sum commitment at \omega x
+            .chain(Some(ProverQuery {
+                point: x_next,
+                poly: &self.constructed.running_sum_poly,
+                blind: self.constructed.running_sum_blind,
+            }))
+    }
+}
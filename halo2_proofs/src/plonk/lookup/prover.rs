@@ -1,6 +1,6 @@
 use super::super::{
-    circuit::Expression, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX, Error,
-    ProvingKey,
+    circuit::Expression, ArgumentKind, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX,
+    Error, ProvingKey,
 };
 use super::Argument;
 use crate::plonk::evaluation::evaluate;
@@ -71,6 +71,7 @@ impl<F: FieldExt> Argument<F> {
         T: TranscriptWrite<C, E>,
     >(
         &self,
+        index: usize,
         pk: &ProvingKey<C>,
         params: &P,
         domain: &EvaluationDomain<C::Scalar>,
@@ -115,6 +116,7 @@ impl<F: FieldExt> Argument<F> {
 
         // Permute compressed (InputExpression, TableExpression) pair
         let (permuted_input_expression, permuted_table_expression) = permute_expression_pair(
+            index,
             pk,
             params,
             domain,
@@ -389,6 +391,7 @@ type ExpressionPair<F> = (Polynomial<F, LagrangeCoeff>, Polynomial<F, LagrangeCo
 ///   that has the corresponding value in S'.
 /// This method returns (A', S') if no errors are encountered.
 fn permute_expression_pair<'params, C: CurveAffine, P: Params<'params, C>, R: RngCore>(
+    index: usize,
     pk: &ProvingKey<C>,
     params: &P,
     domain: &EvaluationDomain<C::Scalar>,
@@ -430,7 +433,9 @@ fn permute_expression_pair<'params, C: CurveAffine, P: Params<'params, C>, R: Rn
                     None
                 } else {
                     // Return error if input_value not found
-                    Some(Err(Error::ConstraintSystemFailure))
+                    Some(Err(Error::ConstraintSystemFailure(ArgumentKind::Lookup(
+                        index,
+                    ))))
                 }
             // If input value is repeated
             } else {
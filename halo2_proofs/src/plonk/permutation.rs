@@ -3,10 +3,7 @@
 use super::circuit::{Any, Column};
 use crate::{
     arithmetic::CurveAffine,
-    helpers::{
-        polynomial_slice_byte_length, read_polynomial_vec, write_polynomial_slice,
-        SerdeCurveAffine, SerdePrimeField,
-    },
+    helpers::{read_polynomial_vec, SerdeCurveAffine, SerdePrimeField},
     poly::{Coeff, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial},
     SerdeFormat,
 };
@@ -126,9 +123,9 @@ impl<C: CurveAffine> VerifyingKey<C> {
 /// The proving key for a single permutation argument.
 #[derive(Clone, Debug)]
 pub(crate) struct ProvingKey<C: CurveAffine> {
-    permutations: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
-    polys: Vec<Polynomial<C::Scalar, Coeff>>,
-    pub(super) cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
+    pub(crate) permutations: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
+    pub(crate) polys: Vec<Polynomial<C::Scalar, Coeff>>,
+    pub(crate) cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
 }
 
 impl<C: SerdeCurveAffine> ProvingKey<C>
@@ -146,25 +143,4 @@ where
             cosets,
         })
     }
-
-    /// Writes proving key for a single permutation argument to buffer using `Polynomial::write`.  
-    pub(super) fn write<W: io::Write>(
-        &self,
-        writer: &mut W,
-        format: SerdeFormat,
-    ) -> io::Result<()> {
-        write_polynomial_slice(&self.permutations, writer, format)?;
-        write_polynomial_slice(&self.polys, writer, format)?;
-        write_polynomial_slice(&self.cosets, writer, format)?;
-        Ok(())
-    }
-}
-
-impl<C: CurveAffine> ProvingKey<C> {
-    /// Gets the total number of bytes in the serialization of `self`
-    pub(super) fn bytes_length(&self) -> usize {
-        polynomial_slice_byte_length(&self.permutations)
-            + polynomial_slice_byte_length(&self.polys)
-            + polynomial_slice_byte_length(&self.cosets)
-    }
 }
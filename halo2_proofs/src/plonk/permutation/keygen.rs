@@ -45,6 +45,56 @@ impl Assembly {
         }
     }
 
+    /// Rebuilds an assembly from the mapping of another assembly for the same argument.
+    ///
+    /// Returns `None` if `mapping` is not a permutation of the cells of the argument.
+    pub(crate) fn from_mapping(p: &Argument, mapping: Vec<Vec<(usize, usize)>>) -> Option<Self> {
+        if mapping.len() != p.columns.len() {
+            return None;
+        }
+        let n = mapping.first().map(|column| column.len()).unwrap_or(0);
+        if mapping.iter().any(|column| column.len() != n) {
+            return None;
+        }
+
+        // Walk each cycle, making its first cell the distinguished element.
+        let mut aux = vec![vec![None; n]; mapping.len()];
+        let mut sizes = vec![vec![1usize; n]; mapping.len()];
+        for column in 0..mapping.len() {
+            for row in 0..n {
+                if aux[column][row].is_some() {
+                    continue;
+                }
+                let start = (column, row);
+                let mut size = 0;
+                let mut i = start;
+                loop {
+                    match aux.get_mut(i.0).and_then(|aux| aux.get_mut(i.1)) {
+                        Some(cell) if cell.is_none() => *cell = Some(start),
+                        // Either out of bounds, or a cell with two preimages.
+                        _ => return None,
+                    }
+                    size += 1;
+                    i = mapping[i.0][i.1];
+                    if i == start {
+                        break;
+                    }
+                }
+                sizes[column][row] = size;
+            }
+        }
+
+        Some(Assembly {
+            columns: p.columns.clone(),
+            mapping,
+            aux: aux
+                .into_iter()
+                .map(|column| column.into_iter().map(Option::unwrap).collect())
+                .collect(),
+            sizes,
+        })
+    }
+
     pub(crate) fn copy(
         &mut self,
         left_column: Column<Any>,
@@ -6,7 +6,7 @@ use rand_core::RngCore;
 use std::iter::{self, ExactSizeIterator};
 
 use super::super::{circuit::Any, ChallengeBeta, ChallengeGamma, ChallengeX};
-use super::{Argument, ProvingKey};
+use super::Argument;
 use crate::{
     arithmetic::{eval_polynomial, parallelize, CurveAffine, FieldExt},
     plonk::{self, Error},
@@ -53,7 +53,7 @@ impl Argument {
         &self,
         params: &P,
         pk: &plonk::ProvingKey<C>,
-        pkey: &ProvingKey<C>,
+        permutations: &[Polynomial<C::Scalar, LagrangeCoeff>],
         advice: &[Polynomial<C::Scalar, LagrangeCoeff>],
         fixed: &[Polynomial<C::Scalar, LagrangeCoeff>],
         instance: &[Polynomial<C::Scalar, LagrangeCoeff>],
@@ -83,7 +83,7 @@ impl Argument {
         for (columns, permutations) in self
             .columns
             .chunks(chunk_len)
-            .zip(pkey.permutations.chunks(chunk_len))
+            .zip(permutations.chunks(chunk_len))
         {
             // Goal is to compute the products of fractions
             //
@@ -206,30 +206,34 @@ impl<C: CurveAffine> Committed<C> {
     }
 }
 
-impl<C: CurveAffine> super::ProvingKey<C> {
-    pub(in crate::plonk) fn open(
-        &self,
-        x: ChallengeX<C>,
-    ) -> impl Iterator<Item = ProverQuery<'_, C>> + Clone {
-        self.polys.iter().map(move |poly| ProverQuery {
-            point: *x,
-            poly,
-            blind: Blind::default(),
-        })
-    }
-
-    pub(in crate::plonk) fn evaluate<E: EncodedChallenge<C>, T: TranscriptWrite<C, E>>(
-        &self,
-        x: ChallengeX<C>,
-        transcript: &mut T,
-    ) -> Result<(), Error> {
-        // Hash permutation evals
-        for eval in self.polys.iter().map(|poly| eval_polynomial(poly, *x)) {
-            transcript.write_scalar(eval)?;
-        }
+/// Opens the permutation polynomials, which are shared by all circuit instances.
+pub(in crate::plonk) fn open_common<C: CurveAffine>(
+    polys: &[Polynomial<C::Scalar, Coeff>],
+    x: ChallengeX<C>,
+) -> impl Iterator<Item = ProverQuery<'_, C>> + Clone {
+    polys.iter().map(move |poly| ProverQuery {
+        point: *x,
+        poly,
+        blind: Blind::default(),
+    })
+}
 
-        Ok(())
+/// Evaluates the permutation polynomials, which are shared by all circuit instances.
+pub(in crate::plonk) fn evaluate_common<
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    T: TranscriptWrite<C, E>,
+>(
+    polys: &[Polynomial<C::Scalar, Coeff>],
+    x: ChallengeX<C>,
+    transcript: &mut T,
+) -> Result<(), Error> {
+    // Hash permutation evals
+    for eval in polys.iter().map(|poly| eval_polynomial(poly, *x)) {
+        transcript.write_scalar(eval)?;
     }
+
+    Ok(())
 }
 
 impl<C: CurveAffine> Constructed<C> {
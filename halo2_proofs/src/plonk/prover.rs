@@ -15,8 +15,8 @@ use super::{
         Advice, Any, Assignment, Challenge, Circuit, Column, ConstraintSystem, FirstPhase, Fixed,
         FloorPlanner, Instance, Selector,
     },
-    lookup, permutation, vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX,
-    ChallengeY, Error, Expression, ProvingKey,
+    logup, lookup, permutation, shuffle, vanishing, ArgumentKind, ChallengeBeta, ChallengeGamma,
+    ChallengeTheta, ChallengeX, ChallengeY, Error, Expression, ProvingKey,
 };
 use crate::{
     arithmetic::{eval_polynomial, CurveAffine, FieldExt},
@@ -144,20 +144,22 @@ pub fn create_proof<
         challenges: &'a HashMap<usize, F>,
         instances: &'a [&'a [F]],
         usable_rows: RangeTo<usize>,
+        // The name of the region being assigned, reported in synthesis errors.
+        current_region: Option<String>,
         _marker: std::marker::PhantomData<F>,
     }
 
     impl<'a, F: Field> Assignment<F> for WitnessCollection<'a, F> {
-        fn enter_region<NR, N>(&mut self, _: N)
+        fn enter_region<NR, N>(&mut self, name: N)
         where
             NR: Into<String>,
             N: FnOnce() -> NR,
         {
-            // Do nothing; we don't care about regions in this context.
+            self.current_region = Some(name().into());
         }
 
         fn exit_region(&mut self) {
-            // Do nothing; we don't care about regions in this context.
+            self.current_region = None;
         }
 
         fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
@@ -192,7 +194,7 @@ pub fn create_proof<
 
         fn assign_advice<V, VR, A, AR>(
             &mut self,
-            _: A,
+            annotation: A,
             column: Column<Advice>,
             row: usize,
             to: V,
@@ -212,11 +214,18 @@ pub fn create_proof<
                 return Err(Error::not_enough_rows_available(self.k));
             }
 
+            let value = to().into_field().assign().map_err(|_| Error::SynthesisAt {
+                region: self.current_region.clone(),
+                column: column.into(),
+                row,
+                reason: format!("missing witness for \"{}\"", annotation().into()),
+            })?;
+
             *self
                 .advice
                 .get_mut(column.index())
                 .and_then(|v| v.get_mut(row))
-                .ok_or(Error::BoundsFailure)? = to().into_field().assign()?;
+                .ok_or(Error::BoundsFailure)? = value;
 
             Ok(())
         }
@@ -320,6 +329,7 @@ pub fn create_proof<
                     // number of blinding factors and an extra row for use in the
                     // permutation argument.
                     usable_rows: ..unusable_rows_start,
+                    current_region: None,
                     _marker: std::marker::PhantomData,
                 };
 
@@ -403,6 +413,12 @@ pub fn create_proof<
     // Sample theta challenge for keeping lookup columns linearly independent
     let theta: ChallengeTheta<_> = transcript.squeeze_challenge_scalar();
 
+    // The fixed column values are needed until every argument has been committed to.
+    let fixed_values = pk
+        .store
+        .fixed_values()
+        .map_err(Error::ProvingKeySerialization)?;
+
     let lookups: Vec<Vec<lookup::prover::Permuted<Scheme::Curve>>> = instance
         .iter()
         .zip(advice.iter())
@@ -412,14 +428,45 @@ pub fn create_proof<
                 .cs
                 .lookups
                 .iter()
-                .map(|lookup| {
+                .enumerate()
+                .map(|(index, lookup)| {
                     lookup.commit_permuted(
+                        index,
                         pk,
                         params,
                         domain,
                         theta,
                         &advice.advice_polys,
-                        &pk.fixed_values,
+                        &fixed_values,
+                        &instance.instance_values,
+                        &challenges,
+                        &mut rng,
+                        transcript,
+                    )
+                })
+                .collect()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let logups: Vec<Vec<logup::prover::Multiplicities<Scheme::Curve>>> = instance
+        .iter()
+        .zip(advice.iter())
+        .map(|(instance, advice)| -> Result<Vec<_>, Error> {
+            // Construct and commit to multiplicities for each LogUp lookup
+            pk.vk
+                .cs
+                .logups
+                .iter()
+                .enumerate()
+                .map(|(index, logup)| {
+                    logup.commit_multiplicities(
+                        index,
+                        pk,
+                        params,
+                        domain,
+                        theta,
+                        &advice.advice_polys,
+                        &fixed_values,
                         &instance.instance_values,
                         &challenges,
                         &mut rng,
@@ -437,6 +484,10 @@ pub fn create_proof<
     let gamma: ChallengeGamma<_> = transcript.squeeze_challenge_scalar();
 
     // Commit to permutations.
+    let permutation_values = pk
+        .store
+        .permutations()
+        .map_err(Error::ProvingKeySerialization)?;
     let permutations: Vec<permutation::prover::Committed<Scheme::Curve>> = instance
         .iter()
         .zip(advice.iter())
@@ -444,9 +495,9 @@ pub fn create_proof<
             pk.vk.cs.permutation.commit(
                 params,
                 pk,
-                &pk.permutation,
+                &permutation_values,
                 &advice.advice_polys,
-                &pk.fixed_values,
+                &fixed_values,
                 &instance.instance_values,
                 beta,
                 gamma,
@@ -455,6 +506,7 @@ pub fn create_proof<
             )
         })
         .collect::<Result<Vec<_>, _>>()?;
+    drop(permutation_values);
 
     let lookups: Vec<Vec<lookup::prover::Committed<Scheme::Curve>>> = lookups
         .into_iter()
@@ -467,6 +519,47 @@ pub fn create_proof<
         })
         .collect::<Result<Vec<_>, _>>()?;
 
+    let logups: Vec<Vec<logup::prover::Committed<Scheme::Curve>>> = logups
+        .into_iter()
+        .map(|logups| -> Result<Vec<_>, _> {
+            // Construct and commit to running sums for each LogUp lookup
+            logups
+                .into_iter()
+                .map(|logup| logup.commit_running_sum(pk, params, beta, &mut rng, transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let shuffles: Vec<Vec<shuffle::prover::Committed<Scheme::Curve>>> = instance
+        .iter()
+        .zip(advice.iter())
+        .map(|(instance, advice)| -> Result<Vec<_>, _> {
+            // Compress expressions for each shuffle
+            pk.vk
+                .cs
+                .shuffles
+                .iter()
+                .map(|shuffle| {
+                    shuffle.commit_product(
+                        pk,
+                        params,
+                        domain,
+                        theta,
+                        gamma,
+                        &advice.advice_polys,
+                        &fixed_values,
+                        &instance.instance_values,
+                        &challenges,
+                        &mut rng,
+                        transcript,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    drop(fixed_values);
+
     // Commit to the vanishing argument's random polynomial for blinding h(x_3)
     let vanishing = vanishing::Argument::commit(params, domain, &mut rng, transcript)?;
 
@@ -509,8 +602,10 @@ pub fn create_proof<
         *gamma,
         *theta,
         &lookups,
+        &logups,
+        &shuffles,
         &permutations,
-    );
+    )?;
 
     // Construct the vanishing argument's h(X) commitments
     let vanishing = vanishing.construct(params, domain, h_poly, &mut rng, transcript)?;
@@ -561,11 +656,15 @@ pub fn create_proof<
     }
 
     // Compute and hash fixed evals (shared across all circuit instances)
+    let fixed_polys = pk
+        .store
+        .fixed_polys()
+        .map_err(Error::ProvingKeySerialization)?;
     let fixed_evals: Vec<_> = meta
         .fixed_queries
         .iter()
         .map(|&(column, at)| {
-            eval_polynomial(&pk.fixed_polys[column.index()], domain.rotate_omega(*x, at))
+            eval_polynomial(&fixed_polys[column.index()], domain.rotate_omega(*x, at))
         })
         .collect();
 
@@ -577,7 +676,11 @@ pub fn create_proof<
     let vanishing = vanishing.evaluate(x, xn, domain, transcript)?;
 
     // Evaluate common permutation data
-    pk.permutation.evaluate(x, transcript)?;
+    let permutation_polys = pk
+        .store
+        .permutation_polys()
+        .map_err(Error::ProvingKeySerialization)?;
+    permutation::prover::evaluate_common(&permutation_polys, x, transcript)?;
 
     // Evaluate the permutations, if any, at omega^i x.
     let permutations: Vec<permutation::prover::Evaluated<Scheme::Curve>> = permutations
@@ -596,56 +699,81 @@ pub fn create_proof<
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    let instances = instance
-        .iter()
-        .zip(advice.iter())
-        .zip(permutations.iter())
-        .zip(lookups.iter())
-        .flat_map(|(((instance, advice), permutation), lookups)| {
-            iter::empty()
-                .chain(
-                    P::QUERY_INSTANCE
-                        .then_some(pk.vk.cs.instance_queries.iter().map(move |&(column, at)| {
+    // Evaluate the LogUp lookups, if any, at omega^i x.
+    let logups: Vec<Vec<logup::prover::Evaluated<Scheme::Curve>>> = logups
+        .into_iter()
+        .map(|logups| -> Result<Vec<_>, _> {
+            logups
+                .into_iter()
+                .map(|p| p.evaluate(pk, x, transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Evaluate the shuffles, if any, at omega^i x.
+    let shuffles: Vec<Vec<shuffle::prover::Evaluated<Scheme::Curve>>> = shuffles
+        .into_iter()
+        .map(|shuffles| -> Result<Vec<_>, _> {
+            shuffles
+                .into_iter()
+                .map(|p| p.evaluate(pk, x, transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let instances =
+        instance
+            .iter()
+            .zip(advice.iter())
+            .zip(permutations.iter())
+            .zip(lookups.iter())
+            .zip(logups.iter())
+            .zip(shuffles.iter())
+            .flat_map(
+                |(((((instance, advice), permutation), lookups), logups), shuffles)| {
+                    iter::empty()
+                        .chain(
+                            P::QUERY_INSTANCE
+                                .then_some(pk.vk.cs.instance_queries.iter().map(
+                                    move |&(column, at)| ProverQuery {
+                                        point: domain.rotate_omega(*x, at),
+                                        poly: &instance.instance_polys[column.index()],
+                                        blind: Blind::default(),
+                                    },
+                                ))
+                                .into_iter()
+                                .flatten(),
+                        )
+                        .chain(pk.vk.cs.advice_queries.iter().map(move |&(column, at)| {
                             ProverQuery {
                                 point: domain.rotate_omega(*x, at),
-                                poly: &instance.instance_polys[column.index()],
-                                blind: Blind::default(),
+                                poly: &advice.advice_polys[column.index()],
+                                blind: advice.advice_blinds[column.index()],
                             }
                         }))
-                        .into_iter()
-                        .flatten(),
-                )
-                .chain(
-                    pk.vk
-                        .cs
-                        .advice_queries
-                        .iter()
-                        .map(move |&(column, at)| ProverQuery {
-                            point: domain.rotate_omega(*x, at),
-                            poly: &advice.advice_polys[column.index()],
-                            blind: advice.advice_blinds[column.index()],
-                        }),
-                )
-                .chain(permutation.open(pk, x))
-                .chain(lookups.iter().flat_map(move |p| p.open(pk, x)).into_iter())
-        })
-        .chain(
-            pk.vk
-                .cs
-                .fixed_queries
-                .iter()
-                .map(|&(column, at)| ProverQuery {
-                    point: domain.rotate_omega(*x, at),
-                    poly: &pk.fixed_polys[column.index()],
-                    blind: Blind::default(),
-                }),
-        )
-        .chain(pk.permutation.open(x))
-        // We query the h(X) polynomial at x
-        .chain(vanishing.open(x));
+                        .chain(permutation.open(pk, x))
+                        .chain(lookups.iter().flat_map(move |p| p.open(pk, x)).into_iter())
+                        .chain(logups.iter().flat_map(move |p| p.open(pk, x)).into_iter())
+                        .chain(shuffles.iter().flat_map(move |p| p.open(pk, x)).into_iter())
+                },
+            )
+            .chain(
+                pk.vk
+                    .cs
+                    .fixed_queries
+                    .iter()
+                    .map(|&(column, at)| ProverQuery {
+                        point: domain.rotate_omega(*x, at),
+                        poly: &fixed_polys[column.index()],
+                        blind: Blind::default(),
+                    }),
+            )
+            .chain(permutation::prover::open_common(&permutation_polys, x))
+            // We query the h(X) polynomial at x
+            .chain(vanishing.open(x));
 
     let prover = P::new(params);
     prover
         .create_proof(rng, transcript, instances)
-        .map_err(|_| Error::ConstraintSystemFailure)
+        .map_err(|_| Error::Opening(ArgumentKind::Multiopen))
 }
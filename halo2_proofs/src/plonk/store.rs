@@ -0,0 +1,351 @@
+//! Storage backends for the polynomials held by a [`ProvingKey`].
+//!
+//! [`ProvingKey`]: super::ProvingKey
+
+use std::borrow::Cow;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Seek, SeekFrom};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use ff::Field;
+
+use super::{permutation, Circuit, VerifyingKey};
+use crate::arithmetic::CurveAffine;
+use crate::helpers::{SerdeCurveAffine, SerdePrimeField};
+use crate::poly::{Coeff, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial};
+use crate::SerdeFormat;
+
+/// Polynomials borrowed from, or loaded by, a [`PolynomialStore`].
+pub type StoredPolys<'a, F, B> = Cow<'a, [Polynomial<F, B>]>;
+
+/// A source of the polynomials that make up a proving key.
+///
+/// The prover requests each group of polynomials when it reaches the stage of
+/// proof creation that needs it, and drops it as soon as that stage is done.
+/// When evaluating the quotient polynomial, which works in the extended domain,
+/// it requests the individual fixed and permutation columns that each gate or
+/// argument queries instead. A store that loads polynomials on demand therefore
+/// bounds the memory used by the proving key to the largest group, rather than
+/// to the whole key.
+pub trait PolynomialStore<C: CurveAffine>: fmt::Debug + Send + Sync {
+    /// Returns $l_0(X)$ in the extended evaluation domain.
+    fn l0(&self) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>>;
+
+    /// Returns $l_{last}(X)$ in the extended evaluation domain.
+    fn l_last(&self) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>>;
+
+    /// Returns $1 - (l_{last}(X) + l_{blind}(X))$ in the extended evaluation domain.
+    fn l_active_row(&self) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>>;
+
+    /// Returns the fixed columns in Lagrange form.
+    fn fixed_values(&self) -> io::Result<StoredPolys<'_, C::Scalar, LagrangeCoeff>>;
+
+    /// Returns the fixed columns in coefficient form.
+    fn fixed_polys(&self) -> io::Result<StoredPolys<'_, C::Scalar, Coeff>>;
+
+    /// Returns the fixed columns in the extended evaluation domain.
+    fn fixed_cosets(&self) -> io::Result<StoredPolys<'_, C::Scalar, ExtendedLagrangeCoeff>>;
+
+    /// Returns the fixed column `index` in the extended evaluation domain.
+    fn fixed_coset(
+        &self,
+        index: usize,
+    ) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>>;
+
+    /// Returns the permutation polynomials in Lagrange form.
+    fn permutations(&self) -> io::Result<StoredPolys<'_, C::Scalar, LagrangeCoeff>>;
+
+    /// Returns the permutation polynomials in coefficient form.
+    fn permutation_polys(&self) -> io::Result<StoredPolys<'_, C::Scalar, Coeff>>;
+
+    /// Returns the permutation polynomials in the extended evaluation domain.
+    fn permutation_cosets(&self) -> io::Result<StoredPolys<'_, C::Scalar, ExtendedLagrangeCoeff>>;
+
+    /// Returns the permutation polynomial of column `index` of the permutation
+    /// argument in the extended evaluation domain.
+    fn permutation_coset(
+        &self,
+        index: usize,
+    ) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>>;
+}
+
+fn missing_column(index: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("proving key has no column {}", index),
+    )
+}
+
+/// A [`PolynomialStore`] that keeps every polynomial in memory.
+///
+/// This is the store used by proving keys created with [`keygen_pk`] or read
+/// with [`ProvingKey::read`].
+///
+/// [`keygen_pk`]: super::keygen_pk
+/// [`ProvingKey::read`]: super::ProvingKey::read
+#[derive(Clone, Debug)]
+pub struct InMemoryStore<C: CurveAffine> {
+    pub(crate) l0: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
+    pub(crate) l_last: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
+    pub(crate) l_active_row: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
+    pub(crate) fixed_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
+    pub(crate) fixed_polys: Vec<Polynomial<C::Scalar, Coeff>>,
+    pub(crate) fixed_cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
+    pub(crate) permutation: permutation::ProvingKey<C>,
+}
+
+impl<C: CurveAffine> PolynomialStore<C> for InMemoryStore<C> {
+    fn l0(&self) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> {
+        Ok(Cow::Borrowed(&self.l0))
+    }
+
+    fn l_last(&self) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> {
+        Ok(Cow::Borrowed(&self.l_last))
+    }
+
+    fn l_active_row(&self) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> {
+        Ok(Cow::Borrowed(&self.l_active_row))
+    }
+
+    fn fixed_values(&self) -> io::Result<StoredPolys<'_, C::Scalar, LagrangeCoeff>> {
+        Ok(Cow::Borrowed(&self.fixed_values))
+    }
+
+    fn fixed_polys(&self) -> io::Result<StoredPolys<'_, C::Scalar, Coeff>> {
+        Ok(Cow::Borrowed(&self.fixed_polys))
+    }
+
+    fn fixed_cosets(&self) -> io::Result<StoredPolys<'_, C::Scalar, ExtendedLagrangeCoeff>> {
+        Ok(Cow::Borrowed(&self.fixed_cosets))
+    }
+
+    fn fixed_coset(
+        &self,
+        index: usize,
+    ) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> {
+        self.fixed_cosets
+            .get(index)
+            .map(Cow::Borrowed)
+            .ok_or_else(|| missing_column(index))
+    }
+
+    fn permutations(&self) -> io::Result<StoredPolys<'_, C::Scalar, LagrangeCoeff>> {
+        Ok(Cow::Borrowed(&self.permutation.permutations))
+    }
+
+    fn permutation_polys(&self) -> io::Result<StoredPolys<'_, C::Scalar, Coeff>> {
+        Ok(Cow::Borrowed(&self.permutation.polys))
+    }
+
+    fn permutation_cosets(&self) -> io::Result<StoredPolys<'_, C::Scalar, ExtendedLagrangeCoeff>> {
+        Ok(Cow::Borrowed(&self.permutation.cosets))
+    }
+
+    fn permutation_coset(
+        &self,
+        index: usize,
+    ) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> {
+        self.permutation
+            .cosets
+            .get(index)
+            .map(Cow::Borrowed)
+            .ok_or_else(|| missing_column(index))
+    }
+}
+
+/// Byte offsets of each polynomial within a proving key file.
+#[derive(Clone, Debug)]
+struct Offsets {
+    l0: u64,
+    l_last: u64,
+    l_active_row: u64,
+    fixed_values: Vec<u64>,
+    fixed_polys: Vec<u64>,
+    fixed_cosets: Vec<u64>,
+    permutations: Vec<u64>,
+    permutation_polys: Vec<u64>,
+    permutation_cosets: Vec<u64>,
+}
+
+/// A [`PolynomialStore`] that reads polynomials from a proving key file each
+/// time they are requested.
+///
+/// The file must have been written with [`ProvingKey::write`], and must not
+/// change while the store is in use. Use [`ProvingKey::read_file_backed`] to
+/// open one.
+///
+/// [`ProvingKey::write`]: super::ProvingKey::write
+/// [`ProvingKey::read_file_backed`]: super::ProvingKey::read_file_backed
+#[derive(Debug)]
+pub struct FileStore<C: CurveAffine> {
+    path: PathBuf,
+    format: SerdeFormat,
+    offsets: Offsets,
+    _marker: PhantomData<C>,
+}
+
+impl<C: SerdeCurveAffine> FileStore<C>
+where
+    C::Scalar: SerdePrimeField,
+{
+    /// Reads the verifying key at the start of the proving key file at `path`,
+    /// and records where each polynomial begins without loading them.
+    pub(crate) fn open<ConcreteCircuit: Circuit<C::Scalar>>(
+        path: &Path,
+        format: SerdeFormat,
+    ) -> io::Result<(VerifyingKey<C>, Self)> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let vk = VerifyingKey::read_io::<_, ConcreteCircuit>(&mut reader, format)?;
+
+        let mut element_len = vec![];
+        C::Scalar::zero().write(&mut element_len, format)?;
+        let element_len = element_len.len() as u64;
+
+        let reader = &mut reader;
+        let offsets = Offsets {
+            l0: skip_polynomial(reader, element_len)?,
+            l_last: skip_polynomial(reader, element_len)?,
+            l_active_row: skip_polynomial(reader, element_len)?,
+            fixed_values: skip_polynomial_vec(reader, element_len)?,
+            fixed_polys: skip_polynomial_vec(reader, element_len)?,
+            fixed_cosets: skip_polynomial_vec(reader, element_len)?,
+            permutations: skip_polynomial_vec(reader, element_len)?,
+            permutation_polys: skip_polynomial_vec(reader, element_len)?,
+            permutation_cosets: skip_polynomial_vec(reader, element_len)?,
+        };
+        if reader.stream_position()? > reader.get_ref().metadata()?.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "proving key file is truncated",
+            ));
+        }
+
+        Ok((
+            vk,
+            FileStore {
+                path: path.to_path_buf(),
+                format,
+                offsets,
+                _marker: PhantomData,
+            },
+        ))
+    }
+
+    fn reader_at(&self, offset: u64) -> io::Result<BufReader<File>> {
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        Ok(BufReader::new(file))
+    }
+
+    fn read_polynomial<B>(&self, offset: u64) -> io::Result<Polynomial<C::Scalar, B>> {
+        Polynomial::read(&mut self.reader_at(offset)?, self.format)
+    }
+
+    fn read_polynomial_vec<B>(&self, offsets: &[u64]) -> io::Result<Vec<Polynomial<C::Scalar, B>>> {
+        offsets
+            .iter()
+            .map(|&offset| self.read_polynomial(offset))
+            .collect()
+    }
+
+    fn read_column<B>(
+        &self,
+        offsets: &[u64],
+        index: usize,
+    ) -> io::Result<Polynomial<C::Scalar, B>> {
+        let offset = offsets.get(index).ok_or_else(|| missing_column(index))?;
+        self.read_polynomial(*offset)
+    }
+}
+
+impl<C: SerdeCurveAffine> PolynomialStore<C> for FileStore<C>
+where
+    C::Scalar: SerdePrimeField,
+{
+    fn l0(&self) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> {
+        self.read_polynomial(self.offsets.l0).map(Cow::Owned)
+    }
+
+    fn l_last(&self) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> {
+        self.read_polynomial(self.offsets.l_last).map(Cow::Owned)
+    }
+
+    fn l_active_row(&self) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> {
+        self.read_polynomial(self.offsets.l_active_row)
+            .map(Cow::Owned)
+    }
+
+    fn fixed_values(&self) -> io::Result<StoredPolys<'_, C::Scalar, LagrangeCoeff>> {
+        self.read_polynomial_vec(&self.offsets.fixed_values)
+            .map(Cow::Owned)
+    }
+
+    fn fixed_polys(&self) -> io::Result<StoredPolys<'_, C::Scalar, Coeff>> {
+        self.read_polynomial_vec(&self.offsets.fixed_polys)
+            .map(Cow::Owned)
+    }
+
+    fn fixed_cosets(&self) -> io::Result<StoredPolys<'_, C::Scalar, ExtendedLagrangeCoeff>> {
+        self.read_polynomial_vec(&self.offsets.fixed_cosets)
+            .map(Cow::Owned)
+    }
+
+    fn fixed_coset(
+        &self,
+        index: usize,
+    ) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> {
+        self.read_column(&self.offsets.fixed_cosets, index)
+            .map(Cow::Owned)
+    }
+
+    fn permutations(&self) -> io::Result<StoredPolys<'_, C::Scalar, LagrangeCoeff>> {
+        self.read_polynomial_vec(&self.offsets.permutations)
+            .map(Cow::Owned)
+    }
+
+    fn permutation_polys(&self) -> io::Result<StoredPolys<'_, C::Scalar, Coeff>> {
+        self.read_polynomial_vec(&self.offsets.permutation_polys)
+            .map(Cow::Owned)
+    }
+
+    fn permutation_cosets(&self) -> io::Result<StoredPolys<'_, C::Scalar, ExtendedLagrangeCoeff>> {
+        self.read_polynomial_vec(&self.offsets.permutation_cosets)
+            .map(Cow::Owned)
+    }
+
+    fn permutation_coset(
+        &self,
+        index: usize,
+    ) -> io::Result<Cow<'_, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> {
+        self.read_column(&self.offsets.permutation_cosets, index)
+            .map(Cow::Owned)
+    }
+}
+
+fn read_len<R: io::Read>(reader: &mut R) -> io::Result<u32> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    Ok(u32::from_be_bytes(len))
+}
+
+/// Skips over a polynomial written with `Polynomial::write`, returning the
+/// offset it starts at.
+fn skip_polynomial(reader: &mut BufReader<File>, element_len: u64) -> io::Result<u64> {
+    let offset = reader.stream_position()?;
+    let len = read_len(reader)? as u64;
+    let skip = (len * element_len)
+        .try_into()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "polynomial is too large"))?;
+    reader.seek_relative(skip)?;
+    Ok(offset)
+}
+
+/// Skips over a vector of polynomials written with `write_polynomial_slice`,
+/// returning the offset each polynomial starts at.
+fn skip_polynomial_vec(reader: &mut BufReader<File>, element_len: u64) -> io::Result<Vec<u64>> {
+    (0..read_len(reader)?)
+        .map(|_| skip_polynomial(reader, element_len))
+        .collect()
+}
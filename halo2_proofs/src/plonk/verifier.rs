@@ -4,8 +4,8 @@ use rand_core::RngCore;
 use std::iter;
 
 use super::{
-    vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX, ChallengeY, Error,
-    VerifyingKey,
+    vanishing, ArgumentKind, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX, ChallengeY,
+    Error, VerifyingKey,
 };
 use crate::arithmetic::{compute_inner_product, CurveAffine, FieldExt};
 use crate::poly::commitment::{CommitmentScheme, Verifier};
@@ -19,7 +19,7 @@ use crate::transcript::{read_n_points, read_n_scalars, EncodedChallenge, Transcr
 #[cfg(feature = "batch")]
 mod batch;
 #[cfg(feature = "batch")]
-pub use batch::BatchVerifier;
+pub use batch::{BatchAccumulator, BatchReport, BatchVerifier};
 
 use crate::poly::commitment::ParamsVerifier;
 
@@ -128,7 +128,28 @@ pub fn verify_proof<
             vk.cs
                 .lookups
                 .iter()
-                .map(|argument| argument.read_permuted_commitments(transcript))
+                .enumerate()
+                .map(|(index, argument)| {
+                    argument
+                        .read_permuted_commitments(transcript)
+                        .map_err(|e| e.in_argument(ArgumentKind::Lookup(index)))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let logups_multiplicities = (0..num_proofs)
+        .map(|_| -> Result<Vec<_>, _> {
+            // Hash each LogUp lookup multiplicity commitment
+            vk.cs
+                .logups
+                .iter()
+                .enumerate()
+                .map(|(index, argument)| {
+                    argument
+                        .read_multiplicity_commitment(transcript)
+                        .map_err(|e| e.in_argument(ArgumentKind::LogUp(index)))
+                })
                 .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
@@ -142,7 +163,10 @@ pub fn verify_proof<
     let permutations_committed = (0..num_proofs)
         .map(|_| {
             // Hash each permutation product commitment
-            vk.cs.permutation.read_product_commitments(vk, transcript)
+            vk.cs
+                .permutation
+                .read_product_commitments(vk, transcript)
+                .map_err(|e| e.in_argument(ArgumentKind::Permutation))
         })
         .collect::<Result<Vec<_>, _>>()?;
 
@@ -152,17 +176,57 @@ pub fn verify_proof<
             // Hash each lookup product commitment
             lookups
                 .into_iter()
-                .map(|lookup| lookup.read_product_commitment(transcript))
+                .enumerate()
+                .map(|(index, lookup)| {
+                    lookup
+                        .read_product_commitment(transcript)
+                        .map_err(|e| e.in_argument(ArgumentKind::Lookup(index)))
+                })
                 .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    let vanishing = vanishing::Argument::read_commitments_before_y(transcript)?;
+    let logups_committed = logups_multiplicities
+        .into_iter()
+        .map(|logups| {
+            // Hash each LogUp lookup running sum commitment
+            logups
+                .into_iter()
+                .enumerate()
+                .map(|(index, logup)| {
+                    logup
+                        .read_running_sum_commitment(transcript)
+                        .map_err(|e| e.in_argument(ArgumentKind::LogUp(index)))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let shuffles_committed = (0..num_proofs)
+        .map(|_| -> Result<Vec<_>, _> {
+            // Hash each shuffle product commitment
+            vk.cs
+                .shuffles
+                .iter()
+                .enumerate()
+                .map(|(index, argument)| {
+                    argument
+                        .read_product_commitment(transcript)
+                        .map_err(|e| e.in_argument(ArgumentKind::Shuffle(index)))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let vanishing = vanishing::Argument::read_commitments_before_y(transcript)
+        .map_err(|e| e.in_argument(ArgumentKind::Vanishing))?;
 
     // Sample y challenge, which keeps the gates linearly independent.
     let y: ChallengeY<_> = transcript.squeeze_challenge_scalar();
 
-    let vanishing = vanishing.read_commitments_after_y(vk, transcript)?;
+    let vanishing = vanishing
+        .read_commitments_after_y(vk, transcript)
+        .map_err(|e| e.in_argument(ArgumentKind::Vanishing))?;
 
     // Sample x challenge, which is used to ensure the circuit is
     // satisfied with high probability.
@@ -220,13 +284,22 @@ pub fn verify_proof<
 
     let fixed_evals = read_n_scalars(transcript, vk.cs.fixed_queries.len())?;
 
-    let vanishing = vanishing.evaluate_after_x(transcript)?;
+    let vanishing = vanishing
+        .evaluate_after_x(transcript)
+        .map_err(|e| e.in_argument(ArgumentKind::Vanishing))?;
 
-    let permutations_common = vk.permutation.evaluate(transcript)?;
+    let permutations_common = vk
+        .permutation
+        .evaluate(transcript)
+        .map_err(|e| e.in_argument(ArgumentKind::Permutation))?;
 
     let permutations_evaluated = permutations_committed
         .into_iter()
-        .map(|permutation| permutation.evaluate(transcript))
+        .map(|permutation| {
+            permutation
+                .evaluate(transcript)
+                .map_err(|e| e.in_argument(ArgumentKind::Permutation))
+        })
         .collect::<Result<Vec<_>, _>>()?;
 
     let lookups_evaluated = lookups_committed
@@ -234,7 +307,42 @@ pub fn verify_proof<
         .map(|lookups| -> Result<Vec<_>, _> {
             lookups
                 .into_iter()
-                .map(|lookup| lookup.evaluate(transcript))
+                .enumerate()
+                .map(|(index, lookup)| {
+                    lookup
+                        .evaluate(transcript)
+                        .map_err(|e| e.in_argument(ArgumentKind::Lookup(index)))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let logups_evaluated = logups_committed
+        .into_iter()
+        .map(|logups| -> Result<Vec<_>, _> {
+            logups
+                .into_iter()
+                .enumerate()
+                .map(|(index, logup)| {
+                    logup
+                        .evaluate(transcript)
+                        .map_err(|e| e.in_argument(ArgumentKind::LogUp(index)))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let shuffles_evaluated = shuffles_committed
+        .into_iter()
+        .map(|shuffles| -> Result<Vec<_>, _> {
+            shuffles
+                .into_iter()
+                .enumerate()
+                .map(|(index, shuffle)| {
+                    shuffle
+                        .evaluate(transcript)
+                        .map_err(|e| e.in_argument(ArgumentKind::Shuffle(index)))
+                })
                 .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
@@ -262,63 +370,109 @@ pub fn verify_proof<
             .zip(instance_evals.iter())
             .zip(permutations_evaluated.iter())
             .zip(lookups_evaluated.iter())
-            .flat_map(|(((advice_evals, instance_evals), permutation), lookups)| {
-                let challenges = &challenges;
-                let fixed_evals = &fixed_evals;
-                std::iter::empty()
-                    // Evaluate the circuit using the custom gates provided
-                    .chain(vk.cs.gates.iter().flat_map(move |gate| {
-                        gate.polynomials().iter().map(move |poly| {
-                            poly.evaluate(
-                                &|scalar| scalar,
-                                &|_| panic!("virtual selectors are removed during optimization"),
-                                &|query| fixed_evals[query.index],
-                                &|query| advice_evals[query.index],
-                                &|query| instance_evals[query.index],
-                                &|challenge| challenges[challenge.index()],
-                                &|a| -a,
-                                &|a, b| a + &b,
-                                &|a, b| a * &b,
-                                &|a, scalar| a * &scalar,
-                            )
-                        })
-                    }))
-                    .chain(permutation.expressions(
-                        vk,
-                        &vk.cs.permutation,
-                        &permutations_common,
-                        advice_evals,
-                        fixed_evals,
-                        instance_evals,
-                        l_0,
-                        l_last,
-                        l_blind,
-                        beta,
-                        gamma,
-                        x,
-                    ))
-                    .chain(
-                        lookups
-                            .iter()
-                            .zip(vk.cs.lookups.iter())
-                            .flat_map(move |(p, argument)| {
-                                p.expressions(
-                                    l_0,
-                                    l_last,
-                                    l_blind,
-                                    argument,
-                                    theta,
-                                    beta,
-                                    gamma,
-                                    advice_evals,
-                                    fixed_evals,
-                                    instance_evals,
-                                    challenges,
+            .zip(logups_evaluated.iter())
+            .zip(shuffles_evaluated.iter())
+            .flat_map(
+                |(((((advice_evals, instance_evals), permutation), lookups), logups), shuffles)| {
+                    let challenges = &challenges;
+                    let fixed_evals = &fixed_evals;
+                    std::iter::empty()
+                        // Evaluate the circuit using the custom gates provided
+                        .chain(vk.cs.gates.iter().flat_map(move |gate| {
+                            gate.polynomials().iter().map(move |poly| {
+                                poly.evaluate(
+                                    &|scalar| scalar,
+                                    &|_| {
+                                        panic!("virtual selectors are removed during optimization")
+                                    },
+                                    &|query| fixed_evals[query.index],
+                                    &|query| advice_evals[query.index],
+                                    &|query| instance_evals[query.index],
+                                    &|challenge| challenges[challenge.index()],
+                                    &|a| -a,
+                                    &|a, b| a + &b,
+                                    &|a, b| a * &b,
+                                    &|a, scalar| a * &scalar,
                                 )
                             })
-                            .into_iter(),
-                    )
-            });
+                        }))
+                        .chain(permutation.expressions(
+                            vk,
+                            &vk.cs.permutation,
+                            &permutations_common,
+                            advice_evals,
+                            fixed_evals,
+                            instance_evals,
+                            l_0,
+                            l_last,
+                            l_blind,
+                            beta,
+                            gamma,
+                            x,
+                        ))
+                        .chain(
+                            lookups
+                                .iter()
+                                .zip(vk.cs.lookups.iter())
+                                .flat_map(move |(p, argument)| {
+                                    p.expressions(
+                                        l_0,
+                                        l_last,
+                                        l_blind,
+                                        argument,
+                                        theta,
+                                        beta,
+                                        gamma,
+                                        advice_evals,
+                                        fixed_evals,
+                                        instance_evals,
+                                        challenges,
+                                    )
+                                })
+                                .into_iter(),
+                        )
+                        .chain(
+                            logups
+                                .iter()
+                                .zip(vk.cs.logups.iter())
+                                .flat_map(move |(p, argument)| {
+                                    p.expressions(
+                                        l_0,
+                                        l_last,
+                                        l_blind,
+                                        argument,
+                                        theta,
+                                        beta,
+                                        advice_evals,
+                                        fixed_evals,
+                                        instance_evals,
+                                        challenges,
+                                    )
+                                })
+                                .into_iter(),
+                        )
+                        .chain(
+                            shuffles
+                                .iter()
+                                .zip(vk.cs.shuffles.iter())
+                                .flat_map(move |(p, argument)| {
+                                    p.expressions(
+                                        l_0,
+                                        l_last,
+                                        l_blind,
+                                        argument,
+                                        theta,
+                                        gamma,
+                                        advice_evals,
+                                        fixed_evals,
+                                        instance_evals,
+                                        challenges,
+                                    )
+                                })
+                                .into_iter(),
+                        )
+                },
+            );
 
         vanishing.verify(params, expressions, y, xn)
     };
@@ -330,13 +484,24 @@ pub fn verify_proof<
         .zip(advice_evals.iter())
         .zip(permutations_evaluated.iter())
         .zip(lookups_evaluated.iter())
+        .zip(logups_evaluated.iter())
+        .zip(shuffles_evaluated.iter())
         .flat_map(
             |(
                 (
-                    (((instance_commitments, instance_evals), advice_commitments), advice_evals),
-                    permutation,
+                    (
+                        (
+                            (
+                                ((instance_commitments, instance_evals), advice_commitments),
+                                advice_evals,
+                            ),
+                            permutation,
+                        ),
+                        lookups,
+                    ),
+                    logups,
                 ),
-                lookups,
+                shuffles,
             )| {
                 iter::empty()
                     .chain(
@@ -369,6 +534,18 @@ pub fn verify_proof<
                             .flat_map(move |p| p.queries(vk, x))
                             .into_iter(),
                     )
+                    .chain(
+                        logups
+                            .iter()
+                            .flat_map(move |p| p.queries(vk, x))
+                            .into_iter(),
+                    )
+                    .chain(
+                        shuffles
+                            .iter()
+                            .flat_map(move |p| p.queries(vk, x))
+                            .into_iter(),
+                    )
             },
         )
         .chain(
@@ -394,6 +571,6 @@ pub fn verify_proof<
     strategy.process(|msm| {
         verifier
             .verify_proof(transcript, queries, msm)
-            .map_err(|_| Error::Opening)
+            .map_err(|_| Error::Opening(ArgumentKind::Multiopen))
     })
 }
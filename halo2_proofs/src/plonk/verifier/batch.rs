@@ -1,52 +1,143 @@
-use std::{io, marker::PhantomData};
+use std::{fmt::Debug, marker::PhantomData};
 
 use group::ff::Field;
-use halo2curves::CurveAffine;
-use rand_core::{OsRng, RngCore};
-use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use halo2curves::{pairing::MultiMillerLoop, CurveAffine};
+use rand_core::OsRng;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
 use super::{verify_proof, VerificationStrategy};
 use crate::{
-    multicore,
+    helpers::SerdeCurveAffine,
     plonk::{Error, VerifyingKey},
     poly::{
-        commitment::{Params, MSM},
+        commitment::{CommitmentScheme, Verifier, MSM},
         ipa::{
             commitment::{IPACommitmentScheme, ParamsVerifierIPA},
             msm::MSMIPA,
-            multiopen::VerifierIPA,
             strategy::GuardIPA,
         },
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            msm::DualMSM,
+            strategy::GuardKZG,
+        },
     },
-    transcript::{Blake2bRead, TranscriptReadBuffer},
+    transcript::{EncodedChallenge, TranscriptReadBuffer},
 };
 
-/// A proof verification strategy that returns the proof's MSM.
+/// An accumulator of verification equations that can be combined across
+/// proofs. **This requires the `batch` crate feature to be enabled.**
 ///
-/// `BatchVerifier` handles the accumulation of the MSMs for the batched proofs.
-#[derive(Debug)]
-struct BatchStrategy<'params, C: CurveAffine> {
-    msm: MSMIPA<'params, C>,
+/// This is implemented for [`MSMIPA`], whose accumulated MSM is checked with a
+/// single multiexp, and for [`DualMSM`], whose two accumulated MSMs are checked
+/// with a single pairing.
+pub trait BatchAccumulator<'params, Scheme: CommitmentScheme>: Sized + Clone + Send + Sync {
+    /// The guard returned by the multi-open verifier for this accumulator.
+    type Guard;
+
+    /// Creates an empty accumulator.
+    fn empty(params: &'params Scheme::ParamsVerifier) -> Self;
+
+    /// Extracts the accumulator from the guard of a single verified proof.
+    fn from_guard(guard: Self::Guard) -> Self;
+
+    /// Scales all scalars in the accumulator by some scaling factor.
+    fn scale(&mut self, factor: Scheme::Scalar);
+
+    /// Adds another accumulator into this one.
+    fn add(&mut self, other: Self);
+
+    /// Performs the final check of the accumulated equations.
+    fn check(self) -> bool;
+}
+
+impl<'params, C: CurveAffine> BatchAccumulator<'params, IPACommitmentScheme<C>>
+    for MSMIPA<'params, C>
+{
+    type Guard = GuardIPA<'params, C>;
+
+    fn empty(params: &'params ParamsVerifierIPA<C>) -> Self {
+        MSMIPA::new(params)
+    }
+
+    fn from_guard(guard: Self::Guard) -> Self {
+        guard.use_challenges()
+    }
+
+    fn scale(&mut self, factor: C::Scalar) {
+        MSM::scale(self, factor)
+    }
+
+    fn add(&mut self, other: Self) {
+        self.add_msm(&other)
+    }
+
+    fn check(self) -> bool {
+        MSM::check(&self)
+    }
 }
 
-impl<'params, C: CurveAffine>
-    VerificationStrategy<'params, IPACommitmentScheme<C>, VerifierIPA<'params, C>>
-    for BatchStrategy<'params, C>
+impl<'params, E: MultiMillerLoop + Debug> BatchAccumulator<'params, KZGCommitmentScheme<E>>
+    for DualMSM<'params, E>
+where
+    E::G1Affine: SerdeCurveAffine,
+    E::G2Affine: SerdeCurveAffine,
+    DualMSM<'params, E>: Send + Sync,
 {
-    type Output = MSMIPA<'params, C>;
+    type Guard = GuardKZG<'params, E>;
 
-    fn new(params: &'params ParamsVerifierIPA<C>) -> Self {
+    fn empty(params: &'params ParamsKZG<E>) -> Self {
+        DualMSM::new(params)
+    }
+
+    fn from_guard(guard: Self::Guard) -> Self {
+        guard.msm_accumulator
+    }
+
+    fn scale(&mut self, factor: E::Scalar) {
+        DualMSM::scale(self, factor)
+    }
+
+    fn add(&mut self, other: Self) {
+        self.add_msm(other)
+    }
+
+    fn check(self) -> bool {
+        DualMSM::check(self)
+    }
+}
+
+/// A proof verification strategy that returns the proof's accumulator.
+///
+/// `BatchVerifier` handles the accumulation of the accumulators for the
+/// batched proofs.
+struct BatchStrategy<'params, Scheme: CommitmentScheme, V: Verifier<'params, Scheme>> {
+    msm: V::MSMAccumulator,
+    _marker: PhantomData<Scheme>,
+}
+
+impl<'params, Scheme, V> VerificationStrategy<'params, Scheme, V>
+    for BatchStrategy<'params, Scheme, V>
+where
+    Scheme: CommitmentScheme,
+    V: Verifier<'params, Scheme>,
+    V::MSMAccumulator: BatchAccumulator<'params, Scheme, Guard = V::Guard>,
+{
+    type Output = V::MSMAccumulator;
+
+    fn new(params: &'params Scheme::ParamsVerifier) -> Self {
         BatchStrategy {
-            msm: MSMIPA::new(params),
+            msm: <V::MSMAccumulator as BatchAccumulator<'params, Scheme>>::empty(params),
+            _marker: PhantomData,
         }
     }
 
     fn process(
         self,
-        f: impl FnOnce(MSMIPA<'params, C>) -> Result<GuardIPA<'params, C>, Error>,
+        f: impl FnOnce(V::MSMAccumulator) -> Result<V::Guard, Error>,
     ) -> Result<Self::Output, Error> {
         let guard = f(self.msm)?;
-        Ok(guard.use_challenges())
+        Ok(<V::MSMAccumulator as BatchAccumulator<'params, Scheme>>::from_guard(guard))
     }
 
     fn finalize(self) -> bool {
@@ -55,79 +146,231 @@ impl<'params, C: CurveAffine>
 }
 
 #[derive(Debug)]
-struct BatchItem<C: CurveAffine> {
-    instances: Vec<Vec<Vec<C::ScalarExt>>>,
+struct BatchItem<Scheme: CommitmentScheme> {
+    instances: Vec<Vec<Vec<Scheme::Scalar>>>,
     proof: Vec<u8>,
 }
 
+impl<Scheme: CommitmentScheme> BatchItem<Scheme> {
+    /// Verifies this item's proof up to (but excluding) the final check, and
+    /// returns its accumulator.
+    fn accumulate<'a, 'params, V, E, T>(
+        &'a self,
+        params: &'params Scheme::ParamsVerifier,
+        vk: &VerifyingKey<Scheme::Curve>,
+    ) -> Result<V::MSMAccumulator, Error>
+    where
+        V: Verifier<'params, Scheme>,
+        V::MSMAccumulator: BatchAccumulator<'params, Scheme, Guard = V::Guard>,
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptReadBuffer<&'a [u8], Scheme::Curve, E>,
+    {
+        let instances: Vec<Vec<_>> = self
+            .instances
+            .iter()
+            .map(|i| i.iter().map(|c| &c[..]).collect())
+            .collect();
+        let instances: Vec<_> = instances.iter().map(|i| &i[..]).collect();
+
+        let strategy = BatchStrategy::<Scheme, V>::new(params);
+        let mut transcript = T::init(&self.proof[..]);
+        verify_proof::<Scheme, V, E, T, _>(params, vk, strategy, &instances, &mut transcript)
+    }
+}
+
 /// A verifier that checks multiple proofs in a batch. **This requires the
 /// `batch` crate feature to be enabled.**
-#[derive(Debug, Default)]
-pub struct BatchVerifier<C: CurveAffine> {
-    items: Vec<BatchItem<C>>,
+///
+/// The batch verifier is generic over the commitment scheme: for IPA the
+/// per-proof MSMs are combined into a single multiexp, and for KZG the
+/// per-proof [`DualMSM`]s are combined into a single pairing check.
+#[derive(Debug)]
+pub struct BatchVerifier<Scheme: CommitmentScheme> {
+    items: Vec<BatchItem<Scheme>>,
+}
+
+impl<Scheme: CommitmentScheme> Default for BatchVerifier<Scheme> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl<C: CurveAffine> BatchVerifier<C> {
+impl<Scheme: CommitmentScheme> BatchVerifier<Scheme> {
     /// Constructs a new batch verifier.
     pub fn new() -> Self {
         Self { items: vec![] }
     }
 
     /// Adds a proof to the batch.
-    pub fn add_proof(&mut self, instances: Vec<Vec<Vec<C::Scalar>>>, proof: Vec<u8>) {
+    pub fn add_proof(&mut self, instances: Vec<Vec<Vec<Scheme::Scalar>>>, proof: Vec<u8>) {
         self.items.push(BatchItem { instances, proof })
     }
 
     /// Finalizes the batch and checks its validity.
     ///
     /// Returns `false` if *some* proof was invalid. If the caller needs to identify
-    /// specific failing proofs, it must re-process the proofs separately.
+    /// specific failing proofs, it should use [`BatchVerifier::finalize_with_report`].
     ///
     /// This uses [`OsRng`] internally instead of taking an `R: RngCore` argument, because
     /// the internal parallelization requires access to a RNG that is guaranteed to not
     /// clone its internal state when shared between threads.
-    pub fn finalize(self, params: &ParamsVerifierIPA<C>, vk: &VerifyingKey<C>) -> bool {
-        fn accumulate_msm<'params, C: CurveAffine>(
-            mut acc: MSMIPA<'params, C>,
-            msm: MSMIPA<'params, C>,
-        ) -> MSMIPA<'params, C> {
-            // Scale the MSM by a random factor to ensure that if the existing MSM has
-            // `is_zero() == false` then this argument won't be able to interfere with it
-            // to make it true, with high probability.
-            acc.scale(C::Scalar::random(OsRng));
-
-            acc.add_msm(&msm);
-            acc
-        }
+    pub fn finalize<'a, 'params, V, E, T>(
+        &'a self,
+        params: &'params Scheme::ParamsVerifier,
+        vk: &VerifyingKey<Scheme::Curve>,
+    ) -> bool
+    where
+        Scheme::ParamsVerifier: Sync,
+        V: Verifier<'params, Scheme>,
+        V::MSMAccumulator: BatchAccumulator<'params, Scheme, Guard = V::Guard>,
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptReadBuffer<&'a [u8], Scheme::Curve, E>,
+    {
+        let empty_msm = || <V::MSMAccumulator as BatchAccumulator<'params, Scheme>>::empty(params);
 
         let final_msm = self
             .items
-            .into_par_iter()
+            .par_iter()
             .enumerate()
             .map(|(i, item)| {
-                let instances: Vec<Vec<_>> = item
-                    .instances
-                    .iter()
-                    .map(|i| i.iter().map(|c| &c[..]).collect())
-                    .collect();
-                let instances: Vec<_> = instances.iter().map(|i| &i[..]).collect();
-
-                let strategy = BatchStrategy::new(params);
-                let mut transcript = Blake2bRead::init(&item.proof[..]);
-                verify_proof(params, vk, strategy, &instances, &mut transcript).map_err(|e| {
+                item.accumulate::<V, E, T>(params, vk).map_err(|e| {
                     tracing::debug!("Batch item {} failed verification: {}", i, e);
                     e
                 })
             })
-            .try_fold(
-                || params.empty_msm(),
-                |msm, res| res.map(|proof_msm| accumulate_msm(msm, proof_msm)),
-            )
-            .try_reduce(|| params.empty_msm(), |a, b| Ok(accumulate_msm(a, b)));
+            .try_fold(empty_msm, |msm, res| {
+                res.map(|proof_msm| accumulate_msm::<Scheme, _>(msm, proof_msm))
+            })
+            .try_reduce(empty_msm, |a, b| Ok(accumulate_msm::<Scheme, _>(a, b)));
 
         match final_msm {
             Ok(msm) => msm.check(),
             Err(_) => false,
         }
     }
+
+    /// Finalizes the batch and reports which proofs are invalid.
+    ///
+    /// Each proof is first verified up to its final check. Proofs that fail at
+    /// this stage (for example because their transcript cannot be parsed) are
+    /// reported in [`BatchReport::rejected`] together with their error. The
+    /// accumulators of the remaining proofs are then checked together, and if
+    /// the combined check fails the batch is bisected to find the proofs whose
+    /// final check fails, which are reported in [`BatchReport::failed`].
+    ///
+    /// Unlike [`BatchVerifier::finalize`], this keeps the accumulator of every
+    /// proof in memory until the batch has been checked.
+    pub fn finalize_with_report<'a, 'params, V, E, T>(
+        &'a self,
+        params: &'params Scheme::ParamsVerifier,
+        vk: &VerifyingKey<Scheme::Curve>,
+    ) -> BatchReport
+    where
+        Scheme::ParamsVerifier: Sync,
+        V: Verifier<'params, Scheme>,
+        V::MSMAccumulator: BatchAccumulator<'params, Scheme, Guard = V::Guard>,
+        E: EncodedChallenge<Scheme::Curve>,
+        T: TranscriptReadBuffer<&'a [u8], Scheme::Curve, E>,
+    {
+        let results: Vec<_> = self
+            .items
+            .par_iter()
+            .map(|item| item.accumulate::<V, E, T>(params, vk))
+            .collect();
+
+        let mut rejected = vec![];
+        let mut msms = vec![];
+        for (i, result) in results.into_iter().enumerate() {
+            match result {
+                Ok(msm) => msms.push((i, msm)),
+                Err(e) => {
+                    tracing::debug!("Batch item {} failed verification: {}", i, e);
+                    rejected.push((i, e));
+                }
+            }
+        }
+
+        let failed = bisect::<Scheme, _>(params, &msms);
+        for i in failed.iter() {
+            tracing::debug!("Batch item {} failed the final check", i);
+        }
+
+        BatchReport { rejected, failed }
+    }
+}
+
+/// The result of [`BatchVerifier::finalize_with_report`].
+#[derive(Debug)]
+pub struct BatchReport {
+    /// Indices of the proofs that could not be processed, in increasing order,
+    /// together with the error that was encountered.
+    pub rejected: Vec<(usize, Error)>,
+    /// Indices of the proofs that were processed but failed the final check,
+    /// in increasing order.
+    pub failed: Vec<usize>,
+}
+
+impl BatchReport {
+    /// Returns `true` if every proof in the batch is valid.
+    pub fn is_valid(&self) -> bool {
+        self.rejected.is_empty() && self.failed.is_empty()
+    }
+
+    /// Returns the indices of all invalid proofs, in increasing order.
+    pub fn invalid_proofs(&self) -> Vec<usize> {
+        let mut invalid: Vec<_> = self
+            .rejected
+            .iter()
+            .map(|(i, _)| *i)
+            .chain(self.failed.iter().cloned())
+            .collect();
+        invalid.sort_unstable();
+        invalid
+    }
+}
+
+fn accumulate_msm<'params, Scheme: CommitmentScheme, A: BatchAccumulator<'params, Scheme>>(
+    mut acc: A,
+    msm: A,
+) -> A {
+    // Scale the MSM by a random factor to ensure that if the existing MSM has
+    // `is_zero() == false` then this argument won't be able to interfere with it
+    // to make it true, with high probability.
+    acc.scale(Scheme::Scalar::random(OsRng));
+
+    acc.add(msm);
+    acc
+}
+
+/// Returns the indices of the accumulators whose check fails, by recursively
+/// checking the combined accumulators of each half of `msms`.
+fn bisect<'params, Scheme: CommitmentScheme, A: BatchAccumulator<'params, Scheme>>(
+    params: &'params Scheme::ParamsVerifier,
+    msms: &[(usize, A)],
+) -> Vec<usize>
+where
+    Scheme::ParamsVerifier: Sync,
+{
+    if msms.is_empty() {
+        return vec![];
+    }
+
+    let combined = msms.iter().fold(A::empty(params), |acc, (_, msm)| {
+        accumulate_msm::<Scheme, _>(acc, msm.clone())
+    });
+    if combined.check() {
+        return vec![];
+    }
+
+    if msms.len() == 1 {
+        return vec![msms[0].0];
+    }
+
+    let (left, right) = msms.split_at(msms.len() / 2);
+    let (mut left, right) = rayon::join(
+        || bisect::<Scheme, _>(params, left),
+        || bisect::<Scheme, _>(params, right),
+    );
+    left.extend(right);
+    left
 }
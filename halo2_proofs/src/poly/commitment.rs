@@ -37,7 +37,7 @@ pub trait CommitmentScheme {
     fn new_params(k: u32) -> Self::ParamsProver;
 
     /// Wrapper for parameter reader
-    fn read_params<R: io::Read>(reader: &mut R) -> io::Result<Self::ParamsProver>;
+    fn read_params<R: io::Read>(reader: &mut R) -> Result<Self::ParamsProver, crate::plonk::Error>;
 }
 
 /// Parameters for circuit sysnthesis and prover parameters.
@@ -54,6 +54,16 @@ pub trait Params<'params, C: CurveAffine>: Sized + Clone {
     /// Downsize `Params` with smaller `k`.
     fn downsize(&mut self, k: u32);
 
+    /// Returns a copy of these `Params` downsized to a smaller `k`, so that one set
+    /// of parameters can be reused for circuits of every smaller size.
+    ///
+    /// Panics if `k` is larger than [`Params::k`].
+    fn downsized(&self, k: u32) -> Self {
+        let mut params = self.clone();
+        params.downsize(k);
+        params
+    }
+
     /// Generates an empty multiscalar multiplication struct using the
     /// appropriate params.
     fn empty_msm(&'params self) -> Self::MSM;
@@ -71,7 +81,12 @@ pub trait Params<'params, C: CurveAffine>: Sized + Clone {
     fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()>;
 
     /// Reads params from a buffer.
-    fn read<R: io::Read>(reader: &mut R) -> io::Result<Self>;
+    ///
+    /// Fails with [`Error::ParamsSerialization`] if the buffer cannot be read or
+    /// does not hold valid parameters.
+    ///
+    /// [`Error::ParamsSerialization`]: crate::plonk::Error::ParamsSerialization
+    fn read<R: io::Read>(reader: &mut R) -> Result<Self, crate::plonk::Error>;
 }
 
 /// Parameters for circuit sysnthesis and prover parameters.
@@ -365,6 +365,11 @@ impl<G: Group> EvaluationDomain<G> {
         self.k
     }
 
+    /// Get the degree bound $j$ this domain was constructed with
+    pub(crate) fn j(&self) -> u32 {
+        (self.quotient_poly_degree + 1) as u32
+    }
+
     /// Get the size of the extended domain
     pub fn extended_k(&self) -> u32 {
         self.extended_k
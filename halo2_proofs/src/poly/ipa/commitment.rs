@@ -7,6 +7,7 @@ use crate::arithmetic::{
     best_fft, best_multiexp, g_to_lagrange, parallelize, CurveAffine, CurveExt, FieldExt, Group,
 };
 use crate::helpers::CurveRead;
+use crate::plonk::Error;
 use crate::poly::commitment::{Blind, CommitmentScheme, Params, ParamsProver, ParamsVerifier, MSM};
 use crate::poly::ipa::msm::MSMIPA;
 use crate::poly::{Coeff, LagrangeCoeff, Polynomial};
@@ -52,7 +53,7 @@ impl<C: CurveAffine> CommitmentScheme for IPACommitmentScheme<C> {
         ParamsIPA::new(k)
     }
 
-    fn read_params<R: io::Read>(reader: &mut R) -> io::Result<Self::ParamsProver> {
+    fn read_params<R: io::Read>(reader: &mut R) -> Result<Self::ParamsProver, Error> {
         ParamsIPA::read(reader)
     }
 }
@@ -122,10 +123,22 @@ impl<'params, C: CurveAffine> Params<'params, C> for ParamsIPA<C> {
     }
 
     /// Reads params from a buffer.
-    fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+    fn read<R: io::Read>(reader: &mut R) -> Result<Self, Error> {
+        Self::read_io(reader).map_err(Error::ParamsSerialization)
+    }
+}
+
+impl<C: CurveAffine> ParamsIPA<C> {
+    fn read_io<R: io::Read>(reader: &mut R) -> io::Result<Self> {
         let mut k = [0u8; 4];
         reader.read_exact(&mut k[..])?;
         let k = u32::from_le_bytes(k);
+        if k > C::Scalar::S {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("k = {} is larger than the two-adicity of the field", k),
+            ));
+        }
 
         let n: u64 = 1 << k;
 
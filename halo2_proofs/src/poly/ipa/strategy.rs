@@ -7,7 +7,7 @@ use crate::poly::commitment::CommitmentScheme;
 use crate::transcript::TranscriptRead;
 use crate::{
     arithmetic::best_multiexp,
-    plonk::Error,
+    plonk::{ArgumentKind, Error},
     poly::{
         commitment::MSM,
         strategy::{Guard, VerificationStrategy},
@@ -143,7 +143,9 @@ impl<'params, C: CurveAffine>
         if msm.check() {
             Ok(())
         } else {
-            Err(Error::ConstraintSystemFailure)
+            // Every argument is checked through the multi-opening, so a failure of
+            // its final check cannot be attributed to a more specific argument.
+            Err(Error::ConstraintSystemFailure(ArgumentKind::Multiopen))
         }
     }
 
@@ -0,0 +1,218 @@
+//! Succinct accumulators for KZG opening proofs.
+//!
+//! Verifying a proof with [`AccumulatorStrategy`] reduces all of its openings to a
+//! [`DualMSM`], for which the verifier eventually checks
+//! $e(\mathsf{lhs}, [s] G_2) = e(\mathsf{rhs}, G_2)$. Evaluating the two MSMs gives a
+//! [`KZGAccumulator`] of two $G_1$ points, which can be exported from a verified
+//! proof with [`AccumulatorStrategy::extract_accumulator`], carried as public inputs
+//! of a subsequent proof with [`KZGAccumulator::to_instances`], combined with other
+//! accumulators with [`fold_accumulators`], and finally checked with [`decide`].
+//!
+//! [`AccumulatorStrategy`]: super::strategy::AccumulatorStrategy
+//! [`AccumulatorStrategy::extract_accumulator`]: super::strategy::AccumulatorStrategy::extract_accumulator
+
+use std::fmt::{self, Debug};
+use std::io;
+
+use ff::Field;
+use group::{prime::PrimeCurveAffine, Curve};
+use halo2curves::pairing::{Engine, MillerLoopResult, MultiMillerLoop};
+
+use super::{commitment::ParamsKZG, msm::DualMSM};
+use crate::{
+    arithmetic::{best_multiexp, from_limbs, num_limbs, CurveAffine},
+    poly::commitment::MSM,
+    transcript::{encode_point_coordinates, EncodedChallenge, Transcript},
+};
+
+pub use crate::arithmetic::LIMB_BITS;
+
+/// A pair of $G_1$ points $(\mathsf{lhs}, \mathsf{rhs})$ whose pairing check
+/// $e(\mathsf{lhs}, [s] G_2) = e(\mathsf{rhs}, G_2)$ has been deferred.
+pub struct KZGAccumulator<E: Engine> {
+    /// The point paired with $[s] G_2$.
+    pub lhs: E::G1Affine,
+    /// The point paired with $G_2$.
+    pub rhs: E::G1Affine,
+}
+
+impl<E: Engine> Clone for KZGAccumulator<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E: Engine> Copy for KZGAccumulator<E> {}
+
+impl<E: Engine> PartialEq for KZGAccumulator<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.lhs == other.lhs && self.rhs == other.rhs
+    }
+}
+
+impl<E: Engine> Eq for KZGAccumulator<E> {}
+
+impl<E: Engine> Debug for KZGAccumulator<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KZGAccumulator")
+            .field("lhs", &self.lhs)
+            .field("rhs", &self.rhs)
+            .finish()
+    }
+}
+
+impl<E: Engine> KZGAccumulator<E> {
+    /// Returns the accumulator as public inputs of a subsequent proof.
+    ///
+    /// Each point is encoded with [`encode_point_coordinates`]: its $x$ and $y$
+    /// coordinates are each split into little-endian limbs of [`LIMB_BITS`] bits, so
+    /// that they fit in the scalar field. The identity is encoded with both
+    /// coordinates set to zero.
+    pub fn to_instances(&self) -> Vec<E::Scalar> {
+        let mut instances = encode_point_coordinates(&self.lhs);
+        instances.extend(encode_point_coordinates(&self.rhs));
+        instances
+    }
+
+    /// Reads an accumulator from public inputs written by
+    /// [`KZGAccumulator::to_instances`].
+    ///
+    /// Returns `None` if the limbs do not encode two points on the curve.
+    pub fn from_instances(instances: &[E::Scalar]) -> Option<Self> {
+        let num_limbs = num_limbs::<E::G1Affine>();
+        if instances.len() != 4 * num_limbs {
+            return None;
+        }
+
+        let mut coordinates = instances
+            .chunks(num_limbs)
+            .map(from_limbs::<E::Scalar, <E::G1Affine as CurveAffine>::Base>);
+        let mut point = || -> Option<E::G1Affine> {
+            let x = coordinates.next()??;
+            let y = coordinates.next()??;
+            if bool::from(x.is_zero() & y.is_zero()) {
+                Some(E::G1Affine::identity())
+            } else {
+                Option::from(E::G1Affine::from_xy(x, y))
+            }
+        };
+
+        Some(KZGAccumulator {
+            lhs: point()?,
+            rhs: point()?,
+        })
+    }
+}
+
+impl<'params, E: MultiMillerLoop + Debug> DualMSM<'params, E> {
+    /// Evaluates both channels of this MSM into a succinct accumulator, deferring
+    /// the pairing check.
+    pub fn extract_accumulator(&self) -> KZGAccumulator<E> {
+        KZGAccumulator {
+            lhs: self.left.eval().to_affine(),
+            rhs: self.right.eval().to_affine(),
+        }
+    }
+}
+
+/// Folds `accumulators` into a single accumulator, which passes [`decide`] if and
+/// only if (except with negligible probability) every accumulator does.
+///
+/// The accumulators are absorbed into `transcript`, and are combined with the
+/// powers of a challenge squeezed from it. Returns an error if the transcript
+/// cannot absorb one of the points, or if `accumulators` is empty: folding nothing
+/// would give the identity pair, which passes [`decide`] without attesting to any
+/// proof.
+pub fn fold_accumulators<E, EC, T>(
+    accumulators: &[KZGAccumulator<E>],
+    transcript: &mut T,
+) -> io::Result<KZGAccumulator<E>>
+where
+    E: Engine,
+    EC: EncodedChallenge<E::G1Affine>,
+    T: Transcript<E::G1Affine, EC>,
+{
+    if accumulators.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot fold an empty set of accumulators",
+        ));
+    }
+
+    for accumulator in accumulators {
+        transcript.common_point(accumulator.lhs)?;
+        transcript.common_point(accumulator.rhs)?;
+    }
+    let r = transcript.squeeze_challenge().get_scalar();
+
+    let powers: Vec<E::Scalar> = accumulators
+        .iter()
+        .scan(E::Scalar::one(), |power, _| {
+            let current = *power;
+            *power *= r;
+            Some(current)
+        })
+        .collect();
+    let (lhs, rhs): (Vec<_>, Vec<_>) = accumulators
+        .iter()
+        .map(|accumulator| (accumulator.lhs, accumulator.rhs))
+        .unzip();
+
+    Ok(KZGAccumulator {
+        lhs: best_multiexp(&powers, &lhs).to_affine(),
+        rhs: best_multiexp(&powers, &rhs).to_affine(),
+    })
+}
+
+/// Performs the deferred pairing check of `accumulator`.
+pub fn decide<E: MultiMillerLoop + Debug>(
+    params: &ParamsKZG<E>,
+    accumulator: &KZGAccumulator<E>,
+) -> bool {
+    let s_g2_prepared = E::G2Prepared::from(params.s_g2);
+    let n_g2_prepared = E::G2Prepared::from(-params.g2);
+
+    bool::from(
+        E::multi_miller_loop(&[
+            (&accumulator.lhs, &s_g2_prepared),
+            (&accumulator.rhs, &n_g2_prepared),
+        ])
+        .final_exponentiation()
+        .is_identity(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use group::{prime::PrimeCurveAffine, Curve};
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use rand_core::OsRng;
+
+    use super::KZGAccumulator;
+    use crate::arithmetic::Field;
+
+    #[test]
+    fn instances_roundtrip() {
+        let accumulator = KZGAccumulator::<Bn256> {
+            lhs: (G1Affine::generator() * Fr::random(OsRng)).to_affine(),
+            rhs: G1Affine::identity(),
+        };
+
+        let instances = accumulator.to_instances();
+        assert_eq!(instances.len(), 16);
+        assert_eq!(
+            KZGAccumulator::<Bn256>::from_instances(&instances),
+            Some(accumulator)
+        );
+
+        // A limb that does not fit in `LIMB_BITS` bits is rejected.
+        let mut bad = instances.clone();
+        bad[0] = -Fr::one();
+        assert_eq!(KZGAccumulator::<Bn256>::from_instances(&bad), None);
+
+        // So is a point that is not on the curve.
+        let mut bad = instances;
+        bad[0] += Fr::one();
+        assert_eq!(KZGAccumulator::<Bn256>::from_instances(&bad), None);
+    }
+}
@@ -2,6 +2,7 @@ use crate::arithmetic::{
     best_fft, best_multiexp, g_to_lagrange, parallelize, CurveAffine, CurveExt, FieldExt, Group,
 };
 use crate::helpers::SerdeCurveAffine;
+use crate::plonk::Error;
 use crate::poly::commitment::{Blind, CommitmentScheme, Params, ParamsProver, ParamsVerifier, MSM};
 use crate::poly::{Coeff, LagrangeCoeff, Polynomial};
 use crate::SerdeFormat;
@@ -50,14 +51,15 @@ where
         ParamsKZG::new(k)
     }
 
-    fn read_params<R: io::Read>(reader: &mut R) -> io::Result<Self::ParamsProver> {
+    fn read_params<R: io::Read>(reader: &mut R) -> Result<Self::ParamsProver, Error> {
         ParamsKZG::read(reader)
     }
 }
 
 impl<E: Engine + Debug> ParamsKZG<E> {
     /// Initializes parameters for the curve, draws toxic secret from given rng.
-    /// MUST NOT be used in production.
+    /// MUST NOT be used in production; import the result of a trusted setup
+    /// ceremony with [`ParamsKZG::read_ptau`] instead.
     pub fn setup<R: RngCore>(k: u32, rng: R) -> Self {
         // Largest root of unity exponent of the Engine is `2^E::Scalar::S`, so we can
         // only support FFTs of polynomials below degree `2^E::Scalar::S`.
@@ -157,7 +159,15 @@ impl<E: Engine + Debug> ParamsKZG<E> {
     }
 
     /// Reads params from a buffer.
-    pub fn read_custom<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self>
+    pub fn read_custom<R: io::Read>(reader: &mut R, format: SerdeFormat) -> Result<Self, Error>
+    where
+        E::G1Affine: SerdeCurveAffine,
+        E::G2Affine: SerdeCurveAffine,
+    {
+        Self::read_custom_io(reader, format).map_err(Error::ParamsSerialization)
+    }
+
+    fn read_custom_io<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self>
     where
         E::G1Affine: SerdeCurveAffine,
         E::G2Affine: SerdeCurveAffine,
@@ -165,6 +175,12 @@ impl<E: Engine + Debug> ParamsKZG<E> {
         let mut k = [0u8; 4];
         reader.read_exact(&mut k[..])?;
         let k = u32::from_le_bytes(k);
+        if k > E::Scalar::S {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("k = {} is larger than the two-adicity of the field", k),
+            ));
+        }
         let n = 1 << k;
 
         let (g, g_lagrange) = match format {
@@ -297,7 +313,7 @@ where
     }
 
     /// Reads params from a buffer.
-    fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+    fn read<R: io::Read>(reader: &mut R) -> Result<Self, Error> {
         Self::read_custom(reader, SerdeFormat::RawBytes)
     }
 }
@@ -410,4 +426,30 @@ mod test {
         assert_eq!(params0.g2, params1.g2);
         assert_eq!(params0.s_g2, params1.s_g2);
     }
+
+    #[test]
+    fn test_parameter_deserialisation_errors() {
+        use assert_matches::assert_matches;
+
+        use super::super::commitment::Params;
+        use crate::halo2curves::bn256::Bn256;
+        use crate::plonk::Error;
+
+        let params = ParamsKZG::<Bn256>::new(3);
+        let mut data = vec![];
+        <ParamsKZG<_> as Params<_>>::write(&params, &mut data).unwrap();
+
+        // Truncated parameters
+        assert_matches!(
+            <ParamsKZG<Bn256> as Params<_>>::read(&mut &data[..data.len() - 1]),
+            Err(Error::ParamsSerialization(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof
+        );
+
+        // A k larger than the field supports
+        data[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_matches!(
+            <ParamsKZG<Bn256> as Params<_>>::read(&mut &data[..]),
+            Err(Error::ParamsSerialization(e)) if e.kind() == std::io::ErrorKind::InvalidData
+        );
+    }
 }
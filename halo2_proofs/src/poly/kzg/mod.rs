@@ -1,3 +1,5 @@
+/// Accumulation of KZG opening proofs
+pub mod accumulator;
 /// KZG commitment scheme
 pub mod commitment;
 /// Multiscalar multiplication engines
@@ -6,3 +8,5 @@ pub mod msm;
 pub mod multiopen;
 /// Strategies used with KZG scheme
 pub mod strategy;
+
+mod ptau;
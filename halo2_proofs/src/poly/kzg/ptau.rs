@@ -0,0 +1,350 @@
+//! Import of KZG parameters from powers-of-tau ceremony transcripts.
+//!
+//! The `.ptau` layout is the one produced by snarkjs and used by the Perpetual
+//! Powers of Tau ceremony. A file starts with the magic bytes `ptau`, a version
+//! and a section count (all little-endian `u32`s), followed by sections of the
+//! form `(type: u32, size: u64, data)`. Only three sections are needed here:
+//!
+//! - 1: header, holding the byte length `n8` and value of the base field
+//!   modulus, the `power` of the transcript and the power of the ceremony.
+//! - 2: $[\tau^i] G_1$ for $i < 2^{power + 1} - 1$.
+//! - 3: $[\tau^i] G_2$ for $i < 2^{power}$.
+//!
+//! Coordinates are written in Montgomery form as little-endian bytes, which is
+//! the raw encoding of the curves in `halo2curves`.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use ff::{Field, PrimeField};
+use group::{prime::PrimeCurveAffine, Curve};
+use halo2curves::pairing::{MillerLoopResult, MultiMillerLoop};
+use rand_core::OsRng;
+
+use super::commitment::ParamsKZG;
+use crate::arithmetic::{best_multiexp, g_to_lagrange, CurveAffine};
+use crate::helpers::{invalid_data, SerdeCurveAffine};
+use crate::plonk::Error;
+use crate::SerdeFormat;
+
+const MAGIC: &[u8; 4] = b"ptau";
+const HEADER_SECTION: u32 = 1;
+const TAU_G1_SECTION: u32 = 2;
+const TAU_G2_SECTION: u32 = 3;
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Returns the byte length of the raw encoding of `C`.
+fn raw_len<C: SerdeCurveAffine>() -> io::Result<u64> {
+    let mut bytes = vec![];
+    C::identity().write(&mut bytes, SerdeFormat::RawBytes)?;
+    Ok(bytes.len() as u64)
+}
+
+/// Formats a little-endian integer the way `PrimeField::MODULUS` is written.
+fn modulus_hex(le_bytes: &[u8]) -> String {
+    let digits: String = le_bytes
+        .iter()
+        .rev()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("0x{}", digits)
+}
+
+impl<E: MultiMillerLoop + Debug> ParamsKZG<E>
+where
+    E::G1Affine: SerdeCurveAffine,
+    E::G2Affine: SerdeCurveAffine,
+{
+    /// Reads parameters for $2^k$ rows from a powers-of-tau transcript in the
+    /// `.ptau` format used by snarkjs and the Perpetual Powers of Tau ceremony.
+    ///
+    /// The first $2^k$ powers of $\tau$ in $G_1$ are taken from the transcript
+    /// and the Lagrange basis is derived from them. Before the parameters are
+    /// returned, the imported powers are checked to be consistent with
+    /// $[\tau] G_2$ with a randomized pairing check; transcripts that fail the
+    /// check, are for a different curve, or are too small for `k` are rejected
+    /// with an [`Error::ParamsSerialization`] of kind [`io::ErrorKind::InvalidData`].
+    pub fn read_ptau<R: Read + Seek>(reader: &mut R, k: u32) -> Result<Self, Error> {
+        Self::read_ptau_io(reader, k).map_err(Error::ParamsSerialization)
+    }
+
+    fn read_ptau_io<R: Read + Seek>(reader: &mut R, k: u32) -> io::Result<Self> {
+        if k > E::Scalar::S {
+            return Err(invalid_data(format!(
+                "k = {} exceeds the two-adicity of the scalar field",
+                k
+            )));
+        }
+        let n: u64 = 1 << k;
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a ptau file"));
+        }
+        let _version = read_u32(reader)?;
+        let num_sections = read_u32(reader)?;
+
+        // Sections may appear in any order, so find them all first.
+        let mut sections = HashMap::new();
+        for _ in 0..num_sections {
+            let section_type = read_u32(reader)?;
+            let size = read_u64(reader)?;
+            let offset = reader.stream_position()?;
+            sections.entry(section_type).or_insert((offset, size));
+            reader.seek(SeekFrom::Start(offset + size))?;
+        }
+        let seek_section = |reader: &mut R, section_type: u32| -> io::Result<u64> {
+            let (offset, size) = *sections
+                .get(&section_type)
+                .ok_or_else(|| invalid_data(format!("missing ptau section {}", section_type)))?;
+            reader.seek(SeekFrom::Start(offset))?;
+            Ok(size)
+        };
+
+        // Header
+        seek_section(reader, HEADER_SECTION)?;
+        let n8 = read_u32(reader)?;
+        let g1_len = raw_len::<E::G1Affine>()?;
+        if g1_len != 2 * u64::from(n8) {
+            return Err(invalid_data("ptau file is for a different curve"));
+        }
+        let mut modulus = vec![0u8; n8 as usize];
+        reader.read_exact(&mut modulus)?;
+        if !modulus_hex(&modulus)
+            .eq_ignore_ascii_case(<<E::G1Affine as CurveAffine>::Base as PrimeField>::MODULUS)
+        {
+            return Err(invalid_data("ptau file is for a different curve"));
+        }
+        let power = read_u32(reader)?;
+        if power < k {
+            return Err(invalid_data(format!(
+                "ptau file supports k up to {}, but k = {} was requested",
+                power, k
+            )));
+        }
+
+        // Powers of tau in G1
+        let size = seek_section(reader, TAU_G1_SECTION)?;
+        if size < n * g1_len {
+            return Err(invalid_data("malformed powers of tau in G1"));
+        }
+        let g = (0..n)
+            .map(|_| E::G1Affine::read(reader, SerdeFormat::RawBytes))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        // The G2 generator and tau in G2
+        let g2_len = raw_len::<E::G2Affine>()?;
+        let size = seek_section(reader, TAU_G2_SECTION)?;
+        if g2_len != 4 * n8 as u64 || size < 2 * g2_len {
+            return Err(invalid_data("malformed powers of tau in G2"));
+        }
+        let g2 = E::G2Affine::read(reader, SerdeFormat::RawBytes)?;
+        let s_g2 = E::G2Affine::read(reader, SerdeFormat::RawBytes)?;
+
+        let params = ParamsKZG {
+            k,
+            n,
+            g_lagrange: g_to_lagrange(g.iter().map(|g| g.to_curve()).collect(), k),
+            g,
+            g2,
+            s_g2,
+        };
+        if !params.is_consistent() {
+            return Err(invalid_data("ptau powers of tau are inconsistent"));
+        }
+
+        Ok(params)
+    }
+
+    /// Checks that `g` holds successive powers of the secret committed to by
+    /// `s_g2`, over the standard generators.
+    ///
+    /// For random $r_i$, this checks that
+    /// $e(\sum_i r_i g_{i+1}, G_2) = e(\sum_i r_i g_i, [\tau] G_2)$,
+    /// which only holds with negligible probability unless every
+    /// $g_{i+1} = [\tau] g_i$.
+    fn is_consistent(&self) -> bool {
+        if self.g[0] != E::G1Affine::generator()
+            || self.g2 != E::G2Affine::generator()
+            || bool::from(self.s_g2.is_identity())
+        {
+            return false;
+        }
+        if self.g.len() < 2 {
+            return true;
+        }
+
+        let r: Vec<E::Scalar> = (1..self.g.len())
+            .map(|_| E::Scalar::random(OsRng))
+            .collect();
+        let shifted = best_multiexp(&r, &self.g[1..]).to_affine();
+        let unshifted = best_multiexp(&r, &self.g[..self.g.len() - 1]).to_affine();
+
+        let g2_prepared = E::G2Prepared::from(self.g2);
+        let s_g2_prepared = E::G2Prepared::from(-self.s_g2);
+        bool::from(
+            E::multi_miller_loop(&[(&shifted, &g2_prepared), (&unshifted, &s_g2_prepared)])
+                .final_exponentiation()
+                .is_identity(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor};
+
+    use ff::{Field, PrimeField};
+    use group::{prime::PrimeCurveAffine, Curve};
+    use halo2curves::bn256::{Bn256, Fq, Fr, G1Affine, G2Affine};
+    use rand_core::OsRng;
+
+    use super::ParamsKZG;
+    use crate::helpers::{invalid_data, SerdeCurveAffine};
+    use crate::plonk::Error;
+    use crate::poly::commitment::{Blind, Params, ParamsProver};
+    use crate::poly::EvaluationDomain;
+    use crate::SerdeFormat;
+
+    fn section(out: &mut Vec<u8>, section_type: u32, data: &[u8]) {
+        out.extend_from_slice(&section_type.to_le_bytes());
+        out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        out.extend_from_slice(data);
+    }
+
+    /// Writes a `.ptau` transcript for the secret `tau`.
+    fn ptau(power: u32, tau: Fr) -> Vec<u8> {
+        let modulus = Fq::MODULUS.trim_start_matches("0x");
+        let mut header = 32u32.to_le_bytes().to_vec();
+        header.extend(
+            (0..32)
+                .rev()
+                .map(|i| u8::from_str_radix(&modulus[2 * i..2 * i + 2], 16).unwrap()),
+        );
+        header.extend_from_slice(&power.to_le_bytes());
+        header.extend_from_slice(&power.to_le_bytes());
+
+        let mut tau_g1 = vec![];
+        let mut current = Fr::one();
+        for _ in 0..(1 << (power + 1)) - 1 {
+            (G1Affine::generator() * current)
+                .to_affine()
+                .write(&mut tau_g1, SerdeFormat::RawBytes)
+                .unwrap();
+            current *= tau;
+        }
+
+        let mut tau_g2 = vec![];
+        let mut current = Fr::one();
+        for _ in 0..1 << power {
+            (G2Affine::generator() * current)
+                .to_affine()
+                .write(&mut tau_g2, SerdeFormat::RawBytes)
+                .unwrap();
+            current *= tau;
+        }
+
+        let mut out = b"ptau".to_vec();
+        out.extend_from_slice(&1u32.to_le_bytes());
+        out.extend_from_slice(&3u32.to_le_bytes());
+        // Sections need not be in order.
+        section(&mut out, 2, &tau_g1);
+        section(&mut out, 1, &header);
+        section(&mut out, 3, &tau_g2);
+        out
+    }
+
+    /// A power 4 transcript in the layout written by
+    /// `snarkjs powersoftau new bn128 4`, before any contribution: every power
+    /// of $\tau$, $\alpha \tau$ and $\beta \tau$ is the generator. Unlike
+    /// [`ptau`], it also holds the sections that are not read here.
+    const POT4_0000: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/data/pot4_0000.ptau"
+    ));
+
+    fn read_error(bytes: &[u8], k: u32) -> io::ErrorKind {
+        match ParamsKZG::<Bn256>::read_ptau(&mut Cursor::new(bytes), k) {
+            Err(Error::ParamsSerialization(e)) => e.kind(),
+            result => panic!("unexpected result {:?}", result.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn read_snarkjs_ptau() {
+        for k in 1..=4 {
+            let params = ParamsKZG::<Bn256>::read_ptau(&mut Cursor::new(POT4_0000), k).unwrap();
+            assert_eq!(params.k(), k);
+            assert_eq!(params.s_g2(), G2Affine::generator());
+        }
+        assert_eq!(read_error(POT4_0000, 5), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn read_malformed_ptau() {
+        // k beyond the two-adicity of the scalar field
+        assert_eq!(read_error(POT4_0000, 64), io::ErrorKind::InvalidData);
+
+        // The header starts after the file header and the section header.
+        let n8 = 12 + 12;
+        for bad_n8 in [0u32, 48, u32::MAX] {
+            let mut bytes = POT4_0000.to_vec();
+            bytes[n8..n8 + 4].copy_from_slice(&bad_n8.to_le_bytes());
+            assert_eq!(read_error(&bytes, 4), io::ErrorKind::InvalidData);
+        }
+
+        // A different base field modulus
+        let mut bytes = POT4_0000.to_vec();
+        bytes[n8 + 4] ^= 1;
+        assert_eq!(read_error(&bytes, 4), io::ErrorKind::InvalidData);
+
+        // Truncated
+        assert_eq!(
+            read_error(&POT4_0000[..POT4_0000.len() / 2], 4),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn read_ptau() {
+        const K: u32 = 4;
+
+        let tau = Fr::random(OsRng);
+        let bytes = ptau(K + 1, tau);
+        let params = ParamsKZG::<Bn256>::read_ptau(&mut Cursor::new(&bytes), K).unwrap();
+        assert_eq!(params.k(), K);
+        assert_eq!(params.s_g2(), (G2Affine::generator() * tau).to_affine());
+
+        let domain = EvaluationDomain::new(1, K);
+        let mut a = domain.empty_lagrange();
+        for (i, a) in a.iter_mut().enumerate() {
+            *a = Fr::from(i as u64);
+        }
+        let b = domain.lagrange_to_coeff(a.clone());
+        let alpha = Blind(Fr::random(OsRng));
+        assert_eq!(params.commit(&b, alpha), params.commit_lagrange(&a, alpha));
+
+        // k larger than the transcript supports
+        assert!(ParamsKZG::<Bn256>::read_ptau(&mut Cursor::new(&bytes), K + 2).is_err());
+
+        // A tampered power of tau
+        let mut tampered = bytes.clone();
+        let point_len = 64;
+        let offset = 12 + 12 + 3 * point_len;
+        tampered[offset..offset + point_len].copy_from_slice(&bytes[offset - point_len..offset]);
+        assert!(ParamsKZG::<Bn256>::read_ptau(&mut Cursor::new(&tampered), K).is_err());
+    }
+}
@@ -1,13 +1,14 @@
 use std::{fmt::Debug, marker::PhantomData};
 
 use super::{
+    accumulator::{decide, KZGAccumulator},
     commitment::{KZGCommitmentScheme, ParamsKZG},
     msm::{DualMSM, MSMKZG},
     multiopen::VerifierGWC,
 };
 use crate::{
     helpers::SerdeCurveAffine,
-    plonk::Error,
+    plonk::{ArgumentKind, Error},
     poly::{
         commitment::{Verifier, MSM},
         ipa::msm::MSMIPA,
@@ -64,6 +65,12 @@ impl<'params, E: MultiMillerLoop + Debug> AccumulatorStrategy<'params, E> {
     pub fn with(msm_accumulator: DualMSM<'params, E>) -> Self {
         AccumulatorStrategy { msm_accumulator }
     }
+
+    /// Evaluates the openings accumulated so far into a succinct accumulator,
+    /// deferring the final pairing check to [`decide`].
+    pub fn extract_accumulator(&self) -> KZGAccumulator<E> {
+        self.msm_accumulator.extract_accumulator()
+    }
 }
 
 /// A verifier that checks a single proof
@@ -149,7 +156,9 @@ where
         if msm.check() {
             Ok(())
         } else {
-            Err(Error::ConstraintSystemFailure)
+            // Every argument is checked through the multi-opening, so a failure of
+            // its final check cannot be attributed to a more specific argument.
+            Err(Error::ConstraintSystemFailure(ArgumentKind::Multiopen))
         }
     }
 
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod test {
     use crate::arithmetic::{eval_polynomial, FieldExt};
-    use crate::plonk::Error;
+    use crate::plonk::{ArgumentKind, Error};
     use crate::poly::commitment::ParamsProver;
     use crate::poly::commitment::{Blind, ParamsVerifier, MSM};
     use crate::poly::query::PolynomialPointer;
@@ -217,7 +217,7 @@ mod test {
                 .process(|msm_accumulator| {
                     verifier
                         .verify_proof(&mut transcript, queries.clone(), msm_accumulator)
-                        .map_err(|_| Error::Opening)
+                        .map_err(|_| Error::Opening(ArgumentKind::Multiopen))
                 })
                 .unwrap();
 
@@ -0,0 +1,301 @@
+//! Transcripts built on a Poseidon sponge over the scalar field.
+//!
+//! Unlike [`Blake2bRead`] and [`Keccak256Read`], these transcripts only perform
+//! arithmetic over the scalar field of the proof, so a recursive verifier whose
+//! native field is that scalar field can reproduce their challenges cheaply.
+//!
+//! The transcript is a duplex sponge of width `T` and rate `RATE`. Its schedule is:
+//!
+//! - The state starts as zeros, except for the first capacity element
+//!   `state[RATE]`, which is set to [`POSEIDON_TRANSCRIPT_DOMAIN`].
+//! - Scalars are absorbed as a single element. Points are absorbed as the
+//!   elements returned by [`encode_point_coordinates`]. No prefixes are absorbed.
+//! - Absorbed elements are buffered. When an element is absorbed while `RATE`
+//!   elements are already buffered, the buffer is added to the rate portion of
+//!   the state, the permutation is applied, and the buffer is cleared.
+//! - To squeeze a challenge, the buffer is padded with zeros to `RATE` elements
+//!   (so an empty buffer becomes `RATE` zeros), added to the state, and the
+//!   permutation is applied. The challenge is `state[0]`, and absorbing resumes
+//!   with an empty buffer.
+//!
+//! This is the schedule of a `halo2_gadgets` Poseidon sponge that absorbs the same
+//! elements, pads them with zeros before each squeeze, and returns to absorbing
+//! after each squeeze, so that the in-circuit sponge and these transcripts
+//! produce the same challenges for the same permutation.
+//!
+//! [`Blake2bRead`]: super::Blake2bRead
+//! [`Keccak256Read`]: super::Keccak256Read
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::iter;
+use std::marker::PhantomData;
+
+use group::ff::PrimeField;
+use halo2curves::{Coordinates, CurveAffine, FieldExt};
+
+use super::{
+    EncodedChallenge, Transcript, TranscriptRead, TranscriptReadBuffer, TranscriptWrite,
+    TranscriptWriterBuffer,
+};
+use crate::arithmetic::{num_limbs, to_limbs};
+
+/// The initial capacity element of a Poseidon transcript, which is the big-endian
+/// integer encoding of `b"Halo2-Transcript"`.
+pub const POSEIDON_TRANSCRIPT_DOMAIN: u128 = u128::from_be_bytes(*b"Halo2-Transcript");
+
+/// A Poseidon permutation of width `T` and rate `RATE` over `F`, used by
+/// [`PoseidonRead`] and [`PoseidonWrite`].
+pub trait PoseidonPermutation<F: FieldExt, const T: usize, const RATE: usize>: fmt::Debug {
+    /// Applies the permutation to `state`.
+    fn permute(&self, state: &mut [F; T]);
+}
+
+/// Encodes the coordinates of `point` as scalar field elements, as they are
+/// absorbed by [`PoseidonRead`] and [`PoseidonWrite`].
+///
+/// The $x$ and $y$ coordinates are each split into little-endian limbs of
+/// [`LIMB_BITS`] bits, in that order, so that every limb fits in the scalar field.
+/// The identity is encoded with both coordinates set to zero. This is the encoding
+/// used by [`KZGAccumulator::to_instances`].
+///
+/// [`LIMB_BITS`]: crate::arithmetic::LIMB_BITS
+/// [`KZGAccumulator::to_instances`]: crate::poly::kzg::accumulator::KZGAccumulator::to_instances
+pub fn encode_point_coordinates<C: CurveAffine>(point: &C) -> Vec<C::Scalar> {
+    let coordinates: Option<Coordinates<C>> = point.coordinates().into();
+    let (x, y) = coordinates
+        .map(|coordinates| (*coordinates.x(), *coordinates.y()))
+        .unwrap_or_default();
+    let num_limbs = num_limbs::<C>();
+    [x.to_repr(), y.to_repr()]
+        .iter()
+        .flat_map(|coordinate| to_limbs(coordinate.as_ref(), num_limbs).collect::<Vec<_>>())
+        .collect()
+}
+
+/// A challenge squeezed directly as a scalar field element.
+#[derive(Copy, Clone, Debug)]
+pub struct ScalarChallenge<C: CurveAffine>(C::Scalar);
+
+impl<C: CurveAffine> EncodedChallenge<C> for ScalarChallenge<C> {
+    type Input = C::Scalar;
+
+    fn new(challenge_input: &C::Scalar) -> Self {
+        ScalarChallenge(*challenge_input)
+    }
+
+    fn get_scalar(&self) -> C::Scalar {
+        self.0
+    }
+}
+
+/// The duplex sponge shared by [`PoseidonRead`] and [`PoseidonWrite`].
+#[derive(Clone, Debug)]
+struct PoseidonSponge<F: FieldExt, P, const T: usize, const RATE: usize> {
+    permutation: P,
+    state: [F; T],
+    buffer: Vec<F>,
+}
+
+impl<F: FieldExt, P: PoseidonPermutation<F, T, RATE>, const T: usize, const RATE: usize>
+    PoseidonSponge<F, P, T, RATE>
+{
+    fn new(permutation: P) -> Self {
+        let mut state = [F::zero(); T];
+        state[RATE] = F::from_u128(POSEIDON_TRANSCRIPT_DOMAIN);
+        PoseidonSponge {
+            permutation,
+            state,
+            buffer: Vec::with_capacity(RATE),
+        }
+    }
+
+    /// Adds the zero-padded buffer to the state and permutes it.
+    fn permute_buffer(&mut self) {
+        let block = self.buffer.drain(..).chain(iter::repeat(F::zero()));
+        for (word, value) in self.state.iter_mut().take(RATE).zip(block) {
+            *word += value;
+        }
+        self.permutation.permute(&mut self.state);
+    }
+
+    fn absorb(&mut self, value: F) {
+        if self.buffer.len() == RATE {
+            self.permute_buffer();
+        }
+        self.buffer.push(value);
+    }
+
+    fn squeeze(&mut self) -> F {
+        self.permute_buffer();
+        self.state[0]
+    }
+}
+
+/// A transcript reader that hashes the proof with a Poseidon sponge.
+#[derive(Debug, Clone)]
+pub struct PoseidonRead<R: Read, C: CurveAffine, P, const T: usize, const RATE: usize> {
+    sponge: PoseidonSponge<C::Scalar, P, T, RATE>,
+    reader: R,
+    _marker: PhantomData<C>,
+}
+
+impl<R: Read, C: CurveAffine, P, const T: usize, const RATE: usize> PoseidonRead<R, C, P, T, RATE>
+where
+    P: PoseidonPermutation<C::Scalar, T, RATE>,
+{
+    /// Initializes a transcript with the given permutation and input buffer.
+    pub fn with_permutation(reader: R, permutation: P) -> Self {
+        PoseidonRead {
+            sponge: PoseidonSponge::new(permutation),
+            reader,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Read, C: CurveAffine, P, const T: usize, const RATE: usize>
+    TranscriptReadBuffer<R, C, ScalarChallenge<C>> for PoseidonRead<R, C, P, T, RATE>
+where
+    P: PoseidonPermutation<C::Scalar, T, RATE> + Default,
+{
+    /// Initialize a transcript given an input buffer.
+    fn init(reader: R) -> Self {
+        Self::with_permutation(reader, P::default())
+    }
+}
+
+impl<R: Read, C: CurveAffine, P, const T: usize, const RATE: usize>
+    TranscriptRead<C, ScalarChallenge<C>> for PoseidonRead<R, C, P, T, RATE>
+where
+    P: PoseidonPermutation<C::Scalar, T, RATE>,
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let mut compressed = C::Repr::default();
+        self.reader.read_exact(compressed.as_mut())?;
+        let point: C = Option::from(C::from_bytes(&compressed)).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof")
+        })?;
+        self.common_point(point)?;
+
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let mut data = <C::Scalar as PrimeField>::Repr::default();
+        self.reader.read_exact(data.as_mut())?;
+        let scalar: C::Scalar = Option::from(C::Scalar::from_repr(data)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "invalid field element encoding in proof",
+            )
+        })?;
+        self.common_scalar(scalar)?;
+
+        Ok(scalar)
+    }
+}
+
+impl<R: Read, C: CurveAffine, P, const T: usize, const RATE: usize>
+    Transcript<C, ScalarChallenge<C>> for PoseidonRead<R, C, P, T, RATE>
+where
+    P: PoseidonPermutation<C::Scalar, T, RATE>,
+{
+    fn squeeze_challenge(&mut self) -> ScalarChallenge<C> {
+        ScalarChallenge::new(&self.sponge.squeeze())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        for value in encode_point_coordinates(&point) {
+            self.sponge.absorb(value);
+        }
+
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.sponge.absorb(scalar);
+
+        Ok(())
+    }
+}
+
+/// A transcript writer that hashes the proof with a Poseidon sponge.
+#[derive(Debug, Clone)]
+pub struct PoseidonWrite<W: Write, C: CurveAffine, P, const T: usize, const RATE: usize> {
+    sponge: PoseidonSponge<C::Scalar, P, T, RATE>,
+    writer: W,
+    _marker: PhantomData<C>,
+}
+
+impl<W: Write, C: CurveAffine, P, const T: usize, const RATE: usize> PoseidonWrite<W, C, P, T, RATE>
+where
+    P: PoseidonPermutation<C::Scalar, T, RATE>,
+{
+    /// Initializes a transcript with the given permutation and output buffer.
+    pub fn with_permutation(writer: W, permutation: P) -> Self {
+        PoseidonWrite {
+            sponge: PoseidonSponge::new(permutation),
+            writer,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<W: Write, C: CurveAffine, P, const T: usize, const RATE: usize>
+    TranscriptWriterBuffer<W, C, ScalarChallenge<C>> for PoseidonWrite<W, C, P, T, RATE>
+where
+    P: PoseidonPermutation<C::Scalar, T, RATE> + Default,
+{
+    /// Initialize a transcript given an output buffer.
+    fn init(writer: W) -> Self {
+        Self::with_permutation(writer, P::default())
+    }
+
+    /// Conclude the interaction and return the output buffer (writer).
+    fn finalize(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write, C: CurveAffine, P, const T: usize, const RATE: usize>
+    TranscriptWrite<C, ScalarChallenge<C>> for PoseidonWrite<W, C, P, T, RATE>
+where
+    P: PoseidonPermutation<C::Scalar, T, RATE>,
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.common_point(point)?;
+        let compressed = point.to_bytes();
+        self.writer.write_all(compressed.as_ref())
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        let data = scalar.to_repr();
+        self.writer.write_all(data.as_ref())
+    }
+}
+
+impl<W: Write, C: CurveAffine, P, const T: usize, const RATE: usize>
+    Transcript<C, ScalarChallenge<C>> for PoseidonWrite<W, C, P, T, RATE>
+where
+    P: PoseidonPermutation<C::Scalar, T, RATE>,
+{
+    fn squeeze_challenge(&mut self) -> ScalarChallenge<C> {
+        ScalarChallenge::new(&self.sponge.squeeze())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        for value in encode_point_coordinates(&point) {
+            self.sponge.absorb(value);
+        }
+
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.sponge.absorb(scalar);
+
+        Ok(())
+    }
+}
@@ -6,17 +6,18 @@ use halo2_proofs::arithmetic::{Field, FieldExt};
 use halo2_proofs::circuit::{Cell, Layouter, SimpleFloorPlanner, Value};
 use halo2_proofs::dev::MockProver;
 use halo2_proofs::plonk::{
-    create_proof as create_plonk_proof, keygen_pk, keygen_vk, verify_proof as verify_plonk_proof,
-    Advice, Assigned, Circuit, Column, ConstraintSystem, Error, Fixed, ProvingKey, TableColumn,
-    VerifyingKey,
+    create_proof as create_plonk_proof, keygen_pk, keygen_vk, minimum_k,
+    verify_proof as verify_plonk_proof, Advice, Assigned, BatchAccumulator, BatchVerifier, Circuit,
+    Column, ConstraintSystem, Error, Fixed, ProvingKey, Selector, TableColumn, VerifyingKey,
 };
-use halo2_proofs::poly::commitment::{CommitmentScheme, ParamsProver, Prover, Verifier};
+use halo2_proofs::poly::commitment::{CommitmentScheme, Params, ParamsProver, Prover, Verifier};
 use halo2_proofs::poly::Rotation;
 use halo2_proofs::poly::VerificationStrategy;
 use halo2_proofs::transcript::{
     Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, TranscriptReadBuffer,
     TranscriptWriterBuffer,
 };
+use halo2_proofs::SerdeFormat;
 use rand_core::{OsRng, RngCore};
 use std::marker::PhantomData;
 
@@ -318,6 +319,11 @@ fn plonk_api() {
                 vec![(a_, sl)]
             });
 
+            meta.lookup_logup("logup", |meta| {
+                let a_ = meta.query_any(a, Rotation::cur());
+                (vec![vec![a_]], vec![sl])
+            });
+
             meta.create_gate("Combined add-mult", |meta| {
                 let d = meta.query_advice(d, Rotation::next());
                 let a = meta.query_advice(a, Rotation::cur());
@@ -442,6 +448,14 @@ fn plonk_api() {
                     current_k,
                 }) if current_k == K - 1
             );
+
+            // Check that the minimum k for the circuit is the smallest one that keygen
+            // accepts, and that larger params can be downsized to it.
+            let k = minimum_k(&empty_circuit, &[1])
+                .expect("measuring the circuit should not fail");
+            assert_eq!(k, K);
+            let params = <$scheme as CommitmentScheme>::ParamsProver::new(K + 1).downsized(k);
+            keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
         }};
     }
 
@@ -532,6 +546,68 @@ fn plonk_api() {
         assert!(strategy.finalize());
     }
 
+    fn verify_proof_batch<'params, Scheme: CommitmentScheme, V: Verifier<'params, Scheme>>(
+        params_verifier: &'params Scheme::ParamsVerifier,
+        vk: &VerifyingKey<Scheme::Curve>,
+        proof: &[u8],
+    ) where
+        Scheme::ParamsVerifier: Sync,
+        V::MSMAccumulator: BatchAccumulator<'params, Scheme, Guard = V::Guard>,
+    {
+        let (_, instance, _) = common!(Scheme);
+        let instances = vec![vec![vec![instance]], vec![vec![instance]]];
+
+        let mut batch = BatchVerifier::<Scheme>::new();
+        batch.add_proof(instances.clone(), proof.to_vec());
+        batch.add_proof(instances.clone(), proof.to_vec());
+        assert!(batch.finalize::<V, _, Blake2bRead<_, _, Challenge255<_>>>(params_verifier, vk));
+
+        // A single corrupted proof makes the whole batch fail.
+        let mut bad_proof = proof.to_vec();
+        let last = bad_proof.len() - 1;
+        bad_proof[last] ^= 1;
+        batch.add_proof(instances.clone(), bad_proof);
+        batch.add_proof(instances.clone(), proof.to_vec());
+        assert!(!batch.finalize::<V, _, Blake2bRead<_, _, Challenge255<_>>>(params_verifier, vk));
+
+        let report = batch
+            .finalize_with_report::<V, _, Blake2bRead<_, _, Challenge255<_>>>(params_verifier, vk);
+        assert!(!report.is_valid());
+        assert_eq!(report.invalid_proofs(), vec![2]);
+
+        // A proof checked against the wrong instance is read in full, but fails the
+        // final check.
+        let wrong_instances = vec![
+            vec![vec![instance]],
+            vec![vec![instance + <Scheme as CommitmentScheme>::Scalar::one()]],
+        ];
+        let mut batch = BatchVerifier::<Scheme>::new();
+        batch.add_proof(instances.clone(), proof.to_vec());
+        batch.add_proof(wrong_instances, proof.to_vec());
+        batch.add_proof(instances.clone(), proof.to_vec());
+        assert!(!batch.finalize::<V, _, Blake2bRead<_, _, Challenge255<_>>>(params_verifier, vk));
+
+        let report = batch
+            .finalize_with_report::<V, _, Blake2bRead<_, _, Challenge255<_>>>(params_verifier, vk);
+        assert!(report.rejected.is_empty());
+        assert_eq!(report.failed, vec![1]);
+
+        // A truncated proof cannot be read, and is rejected before the final check.
+        let mut batch = BatchVerifier::<Scheme>::new();
+        batch.add_proof(instances.clone(), proof.to_vec());
+        batch.add_proof(instances.clone(), proof[..proof.len() / 2].to_vec());
+        batch.add_proof(instances, proof.to_vec());
+        assert!(!batch.finalize::<V, _, Blake2bRead<_, _, Challenge255<_>>>(params_verifier, vk));
+
+        let report = batch
+            .finalize_with_report::<V, _, Blake2bRead<_, _, Challenge255<_>>>(params_verifier, vk);
+        assert_eq!(
+            report.rejected.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert!(report.failed.is_empty());
+    }
+
     fn test_plonk_api_gwc() {
         use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
         use halo2_proofs::poly::kzg::multiopen::{ProverGWC, VerifierGWC};
@@ -559,6 +635,49 @@ fn plonk_api() {
             Blake2bRead<_, _, Challenge255<_>>,
             AccumulatorStrategy<_>,
         >(verifier_params, pk.get_vk(), &proof[..]);
+
+        verify_proof_batch::<_, VerifierGWC<_>>(verifier_params, pk.get_vk(), &proof[..]);
+
+        // Check that accumulators exported from verified proofs can be folded, with
+        // the pairing check deferred until the end.
+        {
+            use halo2_proofs::poly::kzg::accumulator::{decide, fold_accumulators, KZGAccumulator};
+
+            let (_, instance, _) = common!(Scheme);
+            let pubinputs = vec![instance];
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+            let accumulator = verify_plonk_proof::<_, VerifierGWC<_>, _, _, _>(
+                verifier_params,
+                pk.get_vk(),
+                AccumulatorStrategy::new(verifier_params),
+                &[&[&pubinputs[..]], &[&pubinputs[..]]],
+                &mut transcript,
+            )
+            .unwrap()
+            .extract_accumulator();
+            assert!(decide(verifier_params, &accumulator));
+            assert_eq!(
+                KZGAccumulator::from_instances(&accumulator.to_instances()),
+                Some(accumulator)
+            );
+
+            let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+            let folded = fold_accumulators(&[accumulator, accumulator], &mut transcript).unwrap();
+            assert!(decide(verifier_params, &folded));
+
+            // A single invalid accumulator makes the folded one invalid.
+            let invalid = KZGAccumulator {
+                lhs: accumulator.rhs,
+                rhs: accumulator.lhs,
+            };
+            let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+            let folded = fold_accumulators(&[accumulator, invalid], &mut transcript).unwrap();
+            assert!(!decide(verifier_params, &folded));
+
+            // Folding nothing is rejected rather than giving the identity pair.
+            let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+            assert!(fold_accumulators::<Bn256, _, _>(&[], &mut transcript).is_err());
+        }
     }
 
     fn test_plonk_api_shplonk() {
@@ -588,6 +707,8 @@ fn plonk_api() {
             Blake2bRead<_, _, Challenge255<_>>,
             AccumulatorStrategy<_>,
         >(verifier_params, pk.get_vk(), &proof[..]);
+
+        verify_proof_batch::<_, VerifierSHPLONK<_>>(verifier_params, pk.get_vk(), &proof[..]);
     }
 
     fn test_plonk_api_ipa() {
@@ -618,6 +739,74 @@ fn plonk_api() {
             AccumulatorStrategy<_>,
         >(verifier_params, pk.get_vk(), &proof[..]);
 
+        verify_proof_batch::<_, VerifierIPA<_>>(verifier_params, pk.get_vk(), &proof[..]);
+
+        // Check that a verifying key read without the circuit type still verifies
+        {
+            let bytes = pk
+                .get_vk()
+                .to_bytes_with_cs(SerdeFormat::RawBytes)
+                .expect("writing the verifying key should not fail");
+            let vk = VerifyingKey::<EqAffine>::from_bytes_with_cs(&bytes, SerdeFormat::RawBytes)
+                .expect("reading the verifying key should not fail");
+            assert_eq!(
+                format!("{:?}", vk.pinned()),
+                format!("{:?}", pk.get_vk().pinned())
+            );
+
+            verify_proof::<
+                _,
+                VerifierIPA<_>,
+                _,
+                Blake2bRead<_, _, Challenge255<_>>,
+                AccumulatorStrategy<_>,
+            >(verifier_params, &vk, &proof[..]);
+
+            // Malformed domains are rejected rather than panicking
+            let read_with_header = |k: u32, j: u32| {
+                let mut bytes = bytes.clone();
+                bytes[..4].copy_from_slice(&k.to_be_bytes());
+                bytes[4..8].copy_from_slice(&j.to_be_bytes());
+                VerifyingKey::<EqAffine>::from_bytes_with_cs(&bytes, SerdeFormat::RawBytes)
+            };
+            let k = u32::from_be_bytes(bytes[..4].try_into().unwrap());
+            let j = u32::from_be_bytes(bytes[4..8].try_into().unwrap());
+            assert!(read_with_header(k, j).is_ok());
+            for (k, j) in [(u32::MAX, j), (64, j), (k, j + 1), (k, 1), (k, u32::MAX)] {
+                assert_matches!(
+                    read_with_header(k, j),
+                    Err(Error::VerifyingKeySerialization(e))
+                        if e.kind() == std::io::ErrorKind::InvalidData
+                );
+            }
+        }
+
+        // Check that a proving key backed by a file produces valid proofs
+        {
+            let path = std::env::temp_dir().join(format!("plonk_api_{}.pk", std::process::id()));
+            let mut writer = std::io::BufWriter::new(std::fs::File::create(&path).unwrap());
+            pk.write(&mut writer, SerdeFormat::RawBytes).unwrap();
+            drop(writer);
+
+            let file_pk = ProvingKey::<EqAffine>::read_file_backed::<MyCircuit<_>>(
+                &path,
+                SerdeFormat::RawBytes,
+            )
+            .expect("opening the proving key should not fail");
+            let proof = create_proof::<_, ProverIPA<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                rng, &params, &file_pk,
+            );
+            std::fs::remove_file(&path).unwrap();
+
+            verify_proof::<
+                _,
+                VerifierIPA<_>,
+                _,
+                Blake2bRead<_, _, Challenge255<_>>,
+                AccumulatorStrategy<_>,
+            >(verifier_params, pk.get_vk(), &proof[..]);
+        }
+
         // Check that the verification key has not changed unexpectedly
         {
             //panic!("{:#?}", pk.get_vk().pinned());
@@ -988,6 +1177,30 @@ fn plonk_api() {
                 ],
             },
         ],
+        logups: [
+            Argument {
+                input_expressions: [
+                    [
+                        Advice {
+                            query_index: 0,
+                            column_index: 1,
+                            rotation: Rotation(
+                                0,
+                            ),
+                        },
+                    ],
+                ],
+                table_expressions: [
+                    Fixed {
+                        query_index: 0,
+                        column_index: 6,
+                        rotation: Rotation(
+                            0,
+                        ),
+                    },
+                ],
+            },
+        ],
         constants: [],
         minimum_degree: None,
     },
@@ -1025,3 +1238,507 @@ fn plonk_api() {
     test_plonk_api_gwc();
     test_plonk_api_shplonk();
 }
+
+/// Proves `circuit` with SHPLONK over BN254, and returns whether the proof verifies.
+fn prove_and_verify<C: Circuit<halo2curves::bn256::Fr>>(k: u32, circuit: C) -> Result<bool, Error> {
+    use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+    use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+    use halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
+    use halo2curves::bn256::Bn256;
+
+    let params = ParamsKZG::<Bn256>::new(k);
+    let vk = keygen_vk(&params, &circuit)?;
+    let pk = keygen_pk(&params, vk, &circuit)?;
+
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_plonk_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+        &params,
+        &pk,
+        &[circuit],
+        &[&[]],
+        OsRng,
+        &mut transcript,
+    )?;
+    let proof = transcript.finalize();
+
+    let verifier_params = params.verifier_params();
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    Ok(verify_plonk_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+        verifier_params,
+        pk.get_vk(),
+        AccumulatorStrategy::new(verifier_params),
+        &[&[]],
+        &mut transcript,
+    )
+    .map(|strategy| strategy.finalize())
+    .unwrap_or_default())
+}
+
+#[test]
+fn plonk_api_shuffle() {
+    use halo2curves::bn256::Fr;
+
+    const K: u32 = 4;
+
+    #[derive(Clone)]
+    struct ShuffleConfig {
+        input: Column<Advice>,
+        shuffle: Column<Advice>,
+        q: Selector,
+    }
+
+    struct ShuffleCircuit {
+        input: Vec<u64>,
+        shuffle: Vec<u64>,
+    }
+
+    impl Circuit<Fr> for ShuffleCircuit {
+        type Config = ShuffleConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                input: vec![0; self.input.len()],
+                shuffle: vec![0; self.shuffle.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> ShuffleConfig {
+            let input = meta.advice_column();
+            let shuffle = meta.advice_column();
+            let q = meta.complex_selector();
+
+            meta.shuffle("shuffle", |meta| {
+                let input = meta.query_advice(input, Rotation::cur());
+                let shuffle = meta.query_advice(shuffle, Rotation::cur());
+                let q = meta.query_selector(q);
+
+                vec![(q.clone() * input, q * shuffle)]
+            });
+
+            ShuffleConfig { input, shuffle, q }
+        }
+
+        fn synthesize(
+            &self,
+            config: ShuffleConfig,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "shuffle",
+                |mut region| {
+                    for (offset, (input, shuffle)) in
+                        self.input.iter().zip(self.shuffle.iter()).enumerate()
+                    {
+                        config.q.enable(&mut region, offset)?;
+                        region.assign_advice(
+                            || "input",
+                            config.input,
+                            offset,
+                            || Value::known(Fr::from(*input)),
+                        )?;
+                        region.assign_advice(
+                            || "shuffle",
+                            config.shuffle,
+                            offset,
+                            || Value::known(Fr::from(*shuffle)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let circuit = ShuffleCircuit {
+        input: vec![1, 2, 3, 4, 2],
+        shuffle: vec![2, 4, 1, 2, 3],
+    };
+    assert_eq!(
+        MockProver::run(K, &circuit, vec![]).unwrap().verify(),
+        Ok(())
+    );
+    assert_matches!(prove_and_verify(K, circuit), Ok(true));
+
+    // The prover only asserts the grand product when sanity checks are enabled.
+    #[cfg(not(feature = "sanity-checks"))]
+    {
+        // 5 appears in the input but not in the shuffle, and 2 only once.
+        let circuit = ShuffleCircuit {
+            input: vec![1, 2, 3, 4, 5],
+            shuffle: vec![2, 4, 1, 2, 3],
+        };
+        assert!(MockProver::run(K, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .is_err());
+        assert_matches!(prove_and_verify(K, circuit), Ok(false));
+    }
+}
+
+#[test]
+fn plonk_api_dynamic_table() {
+    use halo2_proofs::plonk::{ArgumentKind, DynamicTable};
+    use halo2curves::bn256::Fr;
+
+    const K: u32 = 5;
+
+    #[derive(Clone)]
+    struct MemoryConfig {
+        addr: Column<Advice>,
+        value: Column<Advice>,
+        q: Selector,
+        memory: DynamicTable,
+    }
+
+    struct MemoryCircuit {
+        memory: Vec<(u64, u64)>,
+        // Rows written to the memory columns without being added to the table.
+        untagged: Vec<(u64, u64)>,
+        reads: Vec<(u64, u64)>,
+    }
+
+    impl Circuit<Fr> for MemoryCircuit {
+        type Config = MemoryConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                memory: vec![(0, 0); self.memory.len()],
+                untagged: vec![(0, 0); self.untagged.len()],
+                reads: vec![(0, 0); self.reads.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> MemoryConfig {
+            let addr = meta.advice_column();
+            let value = meta.advice_column();
+            let q = meta.complex_selector();
+
+            let memory_addr = meta.advice_column();
+            let memory_value = meta.advice_column();
+            let memory = meta.dynamic_table(&[memory_addr, memory_value]);
+
+            meta.lookup_dynamic("memory read", &memory, |meta| {
+                let addr = meta.query_advice(addr, Rotation::cur());
+                let value = meta.query_advice(value, Rotation::cur());
+                let q = meta.query_selector(q);
+
+                (q, vec![(addr, memory_addr), (value, memory_value)])
+            });
+
+            MemoryConfig {
+                addr,
+                value,
+                q,
+                memory,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: MemoryConfig,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let rows: Vec<_> = self
+                .memory
+                .iter()
+                .map(|&(addr, value)| {
+                    vec![Value::known(Fr::from(addr)), Value::known(Fr::from(value))]
+                })
+                .collect();
+            layouter.assign_dynamic_table(|| "memory", &config.memory, &rows)?;
+
+            layouter.assign_region(
+                || "untagged",
+                |mut region| {
+                    for (offset, &(addr, value)) in self.untagged.iter().enumerate() {
+                        let columns = config.memory.columns();
+                        region.assign_advice(
+                            || "addr",
+                            columns[0],
+                            offset,
+                            || Value::known(Fr::from(addr)),
+                        )?;
+                        region.assign_advice(
+                            || "value",
+                            columns[1],
+                            offset,
+                            || Value::known(Fr::from(value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+
+            layouter.assign_region(
+                || "reads",
+                |mut region| {
+                    for (offset, &(addr, value)) in self.reads.iter().enumerate() {
+                        config.q.enable(&mut region, offset)?;
+                        region.assign_advice(
+                            || "addr",
+                            config.addr,
+                            offset,
+                            || Value::known(Fr::from(addr)),
+                        )?;
+                        region.assign_advice(
+                            || "value",
+                            config.value,
+                            offset,
+                            || Value::known(Fr::from(value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let memory = vec![(0, 10), (1, 20), (2, 30)];
+
+    let circuit = MemoryCircuit {
+        memory: memory.clone(),
+        untagged: vec![(3, 40)],
+        reads: vec![(1, 20), (2, 30), (1, 20), (0, 10)],
+    };
+    assert_eq!(
+        MockProver::run(K, &circuit, vec![]).unwrap().verify(),
+        Ok(())
+    );
+    assert_matches!(prove_and_verify(K, circuit), Ok(true));
+
+    // (1, 30) mixes the address of one row with the value of another.
+    let circuit = MemoryCircuit {
+        memory: memory.clone(),
+        untagged: vec![(3, 40)],
+        reads: vec![(1, 20), (1, 30)],
+    };
+    assert!(MockProver::run(K, &circuit, vec![])
+        .unwrap()
+        .verify()
+        .is_err());
+    assert_matches!(
+        prove_and_verify(K, circuit),
+        Err(Error::ConstraintSystemFailure(ArgumentKind::Lookup(0)))
+    );
+
+    // (3, 40) is present in the memory columns, but was never added to the table.
+    let circuit = MemoryCircuit {
+        memory,
+        untagged: vec![(3, 40)],
+        reads: vec![(1, 20), (3, 40)],
+    };
+    assert!(MockProver::run(K, &circuit, vec![])
+        .unwrap()
+        .verify()
+        .is_err());
+    assert_matches!(
+        prove_and_verify(K, circuit),
+        Err(Error::ConstraintSystemFailure(ArgumentKind::Lookup(0)))
+    );
+}
+
+#[test]
+fn plonk_api_errors() {
+    use halo2_proofs::plonk::ArgumentKind;
+    use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+    use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+    use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+    use halo2curves::bn256::{Bn256, Fr};
+
+    const K: u32 = 5;
+
+    #[derive(Clone)]
+    struct ArgumentsConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        c: Column<Advice>,
+        q: Selector,
+        table: TableColumn,
+    }
+
+    /// Uses every argument of the proof system on the values `a`: a gate that
+    /// doubles them into `b`, a lookup and a LogUp lookup into a range table, a
+    /// shuffle into `c`, and a copy constraint between the first and last values.
+    struct ArgumentsCircuit {
+        a: Vec<Value<Fr>>,
+        b: Vec<Value<Fr>>,
+    }
+
+    impl Circuit<Fr> for ArgumentsCircuit {
+        type Config = ArgumentsConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                a: vec![Value::unknown(); self.a.len()],
+                b: vec![Value::unknown(); self.b.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> ArgumentsConfig {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let c = meta.advice_column();
+            let q = meta.complex_selector();
+            let table = meta.lookup_table_column();
+            meta.enable_equality(a);
+
+            meta.create_gate("double", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let q = meta.query_selector(q);
+                vec![q * (b - a.clone() - a)]
+            });
+            meta.lookup("range", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let q = meta.query_selector(q);
+                vec![(q * a, table)]
+            });
+            meta.lookup_logup("range", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let q = meta.query_selector(q);
+                (vec![vec![q * a]], vec![table])
+            });
+            meta.shuffle("reverse", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let c = meta.query_advice(c, Rotation::cur());
+                let q = meta.query_selector(q);
+                vec![(q.clone() * a, q * c)]
+            });
+
+            ArgumentsConfig { a, b, c, q, table }
+        }
+
+        fn synthesize(
+            &self,
+            config: ArgumentsConfig,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_table(
+                || "range",
+                |mut table| {
+                    for value in 0..8 {
+                        table.assign_cell(
+                            || "range",
+                            config.table,
+                            value,
+                            || Value::known(Fr::from(value as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+
+            layouter.assign_region(
+                || "values",
+                |mut region| {
+                    let mut cells = vec![];
+                    for (offset, ((a, b), c)) in self
+                        .a
+                        .iter()
+                        .zip(self.b.iter())
+                        .zip(self.a.iter().rev())
+                        .enumerate()
+                    {
+                        config.q.enable(&mut region, offset)?;
+                        cells.push(region.assign_advice(|| "a", config.a, offset, || *a)?);
+                        region.assign_advice(|| "b", config.b, offset, || *b)?;
+                        region.assign_advice(|| "c", config.c, offset, || *c)?;
+                    }
+                    region.constrain_equal(cells[0].cell(), cells[cells.len() - 1].cell())
+                },
+            )
+        }
+    }
+
+    let circuit = |a: [u64; 4], b: [u64; 4]| ArgumentsCircuit {
+        a: a.iter().map(|a| Value::known(Fr::from(*a))).collect(),
+        b: b.iter().map(|b| Value::known(Fr::from(*b))).collect(),
+    };
+
+    let params = ParamsKZG::<Bn256>::new(K);
+    let verifier_params = params.verifier_params();
+    let empty_circuit = circuit([0; 4], [0; 4]).without_witnesses();
+    let vk = keygen_vk(&params, &empty_circuit).unwrap();
+    let pk = keygen_pk(&params, vk, &empty_circuit).unwrap();
+
+    let prove = |circuit: ArgumentsCircuit| -> Result<Vec<u8>, Error> {
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_plonk_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[]],
+            OsRng,
+            &mut transcript,
+        )?;
+        Ok(transcript.finalize())
+    };
+    let verify = |proof: &[u8]| -> Result<(), Error> {
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+        verify_plonk_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            verifier_params,
+            pk.get_vk(),
+            SingleStrategy::new(verifier_params),
+            &[&[]],
+            &mut transcript,
+        )
+    };
+
+    // A missing witness is reported with the region it belongs to.
+    let mut missing = circuit([1, 2, 3, 1], [2, 4, 6, 2]);
+    missing.b[2] = Value::unknown();
+    assert_matches!(
+        prove(missing),
+        Err(Error::SynthesisAt { region: Some(region), row, .. })
+            if region == "values" && row == 2
+    );
+
+    let proof = prove(circuit([1, 2, 3, 1], [2, 4, 6, 2])).unwrap();
+    assert_matches!(verify(&proof), Ok(()));
+
+    // Truncating the proof makes the verifier fail while it reads the part of the
+    // proof that belongs to each argument in turn.
+    let mut arguments = vec![];
+    for len in (0..proof.len()).step_by(32) {
+        match verify(&proof[..len]) {
+            Err(Error::ArgumentTranscript { argument, source }) => {
+                assert_eq!(source.kind(), std::io::ErrorKind::UnexpectedEof);
+                if !arguments.contains(&argument) {
+                    arguments.push(argument);
+                }
+            }
+            // The multi-opening verifiers do not return the transcript error.
+            Err(Error::Opening(ArgumentKind::Multiopen)) => {
+                if !arguments.contains(&ArgumentKind::Multiopen) {
+                    arguments.push(ArgumentKind::Multiopen);
+                }
+            }
+            Err(Error::Transcript(_)) => (),
+            other => panic!(
+                "unexpected result {:?} for a proof of length {}",
+                other, len
+            ),
+        }
+    }
+    assert_eq!(
+        arguments,
+        vec![
+            ArgumentKind::Lookup(0),
+            ArgumentKind::LogUp(0),
+            ArgumentKind::Permutation,
+            ArgumentKind::Shuffle(0),
+            ArgumentKind::Vanishing,
+            ArgumentKind::Multiopen,
+        ]
+    );
+
+    // The prover does not check the gates, but the final check of the proof fails.
+    let proof = prove(circuit([1, 2, 3, 1], [2, 4, 7, 2])).unwrap();
+    assert_matches!(
+        verify(&proof),
+        Err(Error::ConstraintSystemFailure(ArgumentKind::Multiopen))
+    );
+}
@@ -0,0 +1,178 @@
+//! Checks that proving with a file-backed proving key never holds all of its
+//! extended-domain polynomials in memory at once.
+//!
+//! This is a separate test binary because it measures the memory allocated by
+//! the whole process.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::plonk::{
+    create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column, ConstraintSystem,
+    Error, Fixed, ProvingKey, Selector,
+};
+use halo2_proofs::poly::commitment::ParamsProver;
+use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+use halo2_proofs::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
+use halo2_proofs::poly::ipa::strategy::SingleStrategy;
+use halo2_proofs::poly::{Rotation, VerificationStrategy};
+use halo2_proofs::transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+};
+use halo2_proofs::SerdeFormat;
+use halo2curves::pasta::{EqAffine, Fp};
+use rand_core::OsRng;
+
+/// Tracks the number of bytes currently allocated, and the peak since the last
+/// call to [`reset_peak`].
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn reset_peak() -> usize {
+    let allocated = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(allocated, Ordering::SeqCst);
+    allocated
+}
+
+const K: u32 = 8;
+const NUM_CONSTANTS: usize = 48;
+
+#[derive(Clone, Debug)]
+struct WideConfig {
+    a: Column<Advice>,
+    s: Selector,
+    constants: Vec<Column<Fixed>>,
+}
+
+/// Copies many fixed columns into a single advice column, so that most of the
+/// proving key is made up of fixed and permutation polynomials.
+#[derive(Clone, Default)]
+struct WideCircuit;
+
+impl Circuit<Fp> for WideCircuit {
+    type Config = WideConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> WideConfig {
+        let a = meta.advice_column();
+        meta.enable_equality(a);
+        let s = meta.selector();
+        let constants: Vec<_> = (0..NUM_CONSTANTS)
+            .map(|_| {
+                let column = meta.fixed_column();
+                meta.enable_equality(column);
+                column
+            })
+            .collect();
+
+        // A degree 5 gate lets the permutation argument group three columns
+        // into each of its grand products.
+        meta.create_gate("quartic", |meta| {
+            let s = meta.query_selector(s);
+            let a = meta.query_advice(a, Rotation::cur());
+            vec![s * a.clone() * a.clone() * a.clone() * a]
+        });
+
+        WideConfig { a, s, constants }
+    }
+
+    fn synthesize(&self, config: WideConfig, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        layouter.assign_region(
+            || "constants",
+            |mut region| {
+                config.s.enable(&mut region, 0)?;
+                for (row, column) in config.constants.iter().enumerate() {
+                    let value = Value::known(Fp::from(row as u64));
+                    let fixed = region.assign_fixed(|| "constant", *column, 0, || value)?;
+                    let advice = region.assign_advice(|| "copy", config.a, row, || value)?;
+                    region.constrain_equal(fixed.cell(), advice.cell())?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[test]
+fn file_backed_proving_key_memory() {
+    let params = ParamsIPA::<EqAffine>::new(K);
+    let pk = {
+        let vk = keygen_vk(&params, &WideCircuit).expect("keygen_vk should not fail");
+        keygen_pk(&params, vk, &WideCircuit).expect("keygen_pk should not fail")
+    };
+    let vk = pk.get_vk().clone();
+
+    // The fixed and permutation polynomials of the key in the extended domain.
+    let num_fixed_columns = vk.cs().num_fixed_columns();
+    let num_permutation_columns = vk.cs().permutation().get_columns().len();
+    let cosets_len = (num_fixed_columns + num_permutation_columns)
+        * vk.get_domain().extended_len()
+        * std::mem::size_of::<Fp>();
+
+    let path = std::env::temp_dir().join(format!("proving_key_memory_{}.pk", std::process::id()));
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(&path).unwrap());
+    pk.write(&mut writer, SerdeFormat::RawBytes).unwrap();
+    drop(writer);
+    drop(pk);
+    let pk = ProvingKey::<EqAffine>::read_file_backed::<WideCircuit>(&path, SerdeFormat::RawBytes)
+        .expect("opening the proving key should not fail");
+
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    let baseline = reset_peak();
+    create_proof::<IPACommitmentScheme<_>, ProverIPA<_>, _, _, _, _>(
+        &params,
+        &pk,
+        &[WideCircuit],
+        &[&[]],
+        OsRng,
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let peak = PEAK.load(Ordering::SeqCst) - baseline;
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(
+        peak < cosets_len,
+        "proving used {} bytes, but the fixed and permutation cosets alone take {} bytes",
+        peak,
+        cosets_len
+    );
+
+    let proof = transcript.finalize();
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    let strategy = SingleStrategy::new(&params);
+    verify_proof::<IPACommitmentScheme<_>, VerifierIPA<_>, _, _, _>(
+        &params,
+        &vk,
+        strategy,
+        &[&[]],
+        &mut transcript,
+    )
+    .expect("verification should not fail");
+}